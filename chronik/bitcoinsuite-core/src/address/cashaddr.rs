@@ -0,0 +1,429 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{
+    address::AddressType,
+    hash::{Hashed, ShaRmd160},
+    script::{Script, ScriptVariant},
+};
+
+/// CashAddr prefix for eCash mainnet.
+pub const ECASH_PREFIX: &str = "ecash";
+/// CashAddr prefix for eCash mainnet, used for token wallets.
+pub const ETOKEN_PREFIX: &str = "etoken";
+/// CashAddr prefix for eCash testnet.
+pub const ECTEST_PREFIX: &str = "ectest";
+/// CashAddr prefix for eCash regtest.
+pub const ECREGTEST_PREFIX: &str = "ecregtest";
+
+/// Prefixes tried when parsing a CashAddr without a prefix.
+pub const KNOWN_PREFIXES: &[&str] =
+    &[ECASH_PREFIX, ETOKEN_PREFIX, ECTEST_PREFIX, ECREGTEST_PREFIX];
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const CHECKSUM_LEN: usize = 8;
+/// Size bits of the version byte for 160-bit hashes, the only size we support.
+const SIZE_160: u8 = 0;
+
+/// CashAddr address, encoding a P2PKH or P2SH script with a human-readable
+/// prefix (e.g. "ecash") and a checksum.
+///
+/// ```
+/// # use bitcoinsuite_core::{
+/// #     address::{AddressType, CashAddress},
+/// #     hash::ShaRmd160,
+/// # };
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let address = "ecash:qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqs7ratqfx"
+///     .parse::<CashAddress>()?;
+/// assert_eq!(address.prefix(), "ecash");
+/// assert_eq!(address.addr_type(), AddressType::P2PKH);
+/// assert_eq!(address.hash(), &ShaRmd160([0; 20]));
+/// assert!(!address.is_token_aware());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CashAddress {
+    prefix: String,
+    addr_type: AddressType,
+    hash: ShaRmd160,
+    is_token_aware: bool,
+}
+
+/// Errors indicating a CashAddr couldn't be parsed or created.
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum CashAddressError {
+    /// Address mixes upper and lower case characters.
+    #[error("Address mixes upper and lower case: {0}")]
+    MixedCase(String),
+
+    /// Address contains a character not in the CashAddr charset.
+    #[error("Invalid character {0:?} in address")]
+    InvalidChar(char),
+
+    /// Checksum doesn't match, e.g. because of a typo.
+    #[error("Invalid checksum for address {0}")]
+    InvalidChecksum(String),
+
+    /// Address has no prefix and none of the [`KNOWN_PREFIXES`] matches.
+    #[error("Address {0} has no prefix, and none of {KNOWN_PREFIXES:?} match")]
+    UnknownPrefix(String),
+
+    /// Payload isn't padded with zero bits.
+    #[error("Invalid padding in address payload")]
+    InvalidPadding,
+
+    /// Version byte has an unsupported type or size.
+    #[error("Unsupported version byte 0x{0:02x}")]
+    UnsupportedVersion(u8),
+
+    /// Payload has an invalid length for a 160-bit hash.
+    #[error("Invalid payload length, expected 21 bytes but got {0}")]
+    InvalidPayloadLength(usize),
+
+    /// Script can't be encoded as CashAddr, only P2PKH and P2SH can.
+    #[error("Script can't be encoded as CashAddr: {0:?}")]
+    UnsupportedScript(ScriptVariant),
+}
+
+use self::CashAddressError::*;
+
+impl CashAddress {
+    /// Create a new [`CashAddress`] with the given prefix, type and hash.
+    pub fn new(
+        prefix: impl Into<String>,
+        addr_type: AddressType,
+        hash: ShaRmd160,
+    ) -> Self {
+        CashAddress {
+            prefix: prefix.into(),
+            addr_type,
+            hash,
+            is_token_aware: false,
+        }
+    }
+
+    /// Make the address token-aware, i.e. signal that the receiver's wallet
+    /// supports tokens, which is encoded in the version byte.
+    pub fn with_token_aware(mut self, is_token_aware: bool) -> Self {
+        self.is_token_aware = is_token_aware;
+        self
+    }
+
+    /// Create a [`CashAddress`] for the given [`ScriptVariant`], which must be
+    /// either P2PKH or P2SH.
+    ///
+    /// ```
+    /// # use bitcoinsuite_core::{
+    /// #     address::CashAddress,
+    /// #     hash::ShaRmd160,
+    /// #     script::ScriptVariant,
+    /// # };
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let variant = ScriptVariant::P2SH(ShaRmd160([0; 20]));
+    /// let address = CashAddress::from_script_variant("ecash", &variant)?;
+    /// assert_eq!(
+    ///     address.to_string(),
+    ///     "ecash:pqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq8m7jvrjm",
+    /// );
+    /// assert_eq!(address.to_script_variant(), variant);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_script_variant(
+        prefix: impl Into<String>,
+        variant: &ScriptVariant,
+    ) -> Result<Self, CashAddressError> {
+        match *variant {
+            ScriptVariant::P2PKH(hash) => {
+                Ok(CashAddress::new(prefix, AddressType::P2PKH, hash))
+            }
+            ScriptVariant::P2SH(hash) => {
+                Ok(CashAddress::new(prefix, AddressType::P2SH, hash))
+            }
+            _ => Err(UnsupportedScript(variant.clone())),
+        }
+    }
+
+    /// Parse a CashAddr with or without prefix. If the prefix is omitted, the
+    /// [`KNOWN_PREFIXES`] are tried.
+    pub fn parse(address: &str) -> Result<Self, CashAddressError> {
+        let has_lower = address.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = address.chars().any(|c| c.is_ascii_uppercase());
+        if has_lower && has_upper {
+            return Err(MixedCase(address.to_string()));
+        }
+        let address_lower = address.to_ascii_lowercase();
+        match address_lower.split_once(':') {
+            Some((prefix, payload)) => Self::parse_payload(prefix, payload),
+            None => KNOWN_PREFIXES
+                .iter()
+                .find_map(|prefix| {
+                    Self::parse_payload(prefix, &address_lower).ok()
+                })
+                .ok_or_else(|| UnknownPrefix(address.to_string())),
+        }
+    }
+
+    fn parse_payload(
+        prefix: &str,
+        payload: &str,
+    ) -> Result<Self, CashAddressError> {
+        let values = payload
+            .chars()
+            .map(|c| {
+                CHARSET
+                    .iter()
+                    .position(|&charset_c| charset_c as char == c)
+                    .map(|value| value as u8)
+                    .ok_or(InvalidChar(c))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.len() < CHECKSUM_LEN || polymod(prefix, &values) != 0 {
+            return Err(InvalidChecksum(format!("{prefix}:{payload}")));
+        }
+        let data = convert_bits(&values[..values.len() - CHECKSUM_LEN], 5, 8)
+            .ok_or(InvalidPadding)?;
+        if data.len() != 1 + ShaRmd160::SIZE {
+            return Err(InvalidPayloadLength(data.len()));
+        }
+        let version = data[0];
+        if version & 0x87 != SIZE_160 {
+            return Err(UnsupportedVersion(version));
+        }
+        let (addr_type, is_token_aware) = match version >> 3 {
+            0 => (AddressType::P2PKH, false),
+            1 => (AddressType::P2SH, false),
+            2 => (AddressType::P2PKH, true),
+            3 => (AddressType::P2SH, true),
+            _ => return Err(UnsupportedVersion(version)),
+        };
+        let hash = ShaRmd160(data[1..].try_into().unwrap());
+        Ok(CashAddress {
+            prefix: prefix.to_string(),
+            addr_type,
+            hash,
+            is_token_aware,
+        })
+    }
+
+    /// Human-readable prefix of the address, e.g. "ecash".
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Whether the address encodes a P2PKH or P2SH script.
+    pub fn addr_type(&self) -> AddressType {
+        self.addr_type
+    }
+
+    /// Hash of the pubkey or script of the address.
+    pub fn hash(&self) -> &ShaRmd160 {
+        &self.hash
+    }
+
+    /// Whether the address signals that the receiver supports tokens.
+    pub fn is_token_aware(&self) -> bool {
+        self.is_token_aware
+    }
+
+    /// The [`ScriptVariant`] encoded by this address.
+    pub fn to_script_variant(&self) -> ScriptVariant {
+        self.addr_type.to_script_variant(self.hash)
+    }
+
+    /// The [`Script`] encoded by this address.
+    pub fn to_script(&self) -> Script {
+        self.to_script_variant().to_script()
+    }
+
+    fn version_byte(&self) -> u8 {
+        let type_bits = match (self.addr_type, self.is_token_aware) {
+            (AddressType::P2PKH, false) => 0,
+            (AddressType::P2SH, false) => 1,
+            (AddressType::P2PKH, true) => 2,
+            (AddressType::P2SH, true) => 3,
+        };
+        (type_bits << 3) | SIZE_160
+    }
+}
+
+impl FromStr for CashAddress {
+    type Err = CashAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CashAddress::parse(s)
+    }
+}
+
+impl Display for CashAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut data = Vec::with_capacity(1 + ShaRmd160::SIZE);
+        data.push(self.version_byte());
+        data.extend_from_slice(self.hash.as_ref());
+        let mut values = convert_bits(&data, 8, 5).expect("Padding allowed");
+        values.extend_from_slice(&[0; CHECKSUM_LEN]);
+        let checksum = polymod(&self.prefix, &values);
+        let num_values = values.len();
+        for (idx, value) in
+            values[num_values - CHECKSUM_LEN..].iter_mut().enumerate()
+        {
+            *value =
+                ((checksum >> (5 * (CHECKSUM_LEN - 1 - idx))) & 0x1f) as u8;
+        }
+        write!(f, "{}:", self.prefix)?;
+        for value in values {
+            write!(f, "{}", CHARSET[value as usize] as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// BCH checksum over the prefix and the 5-bit values, as defined by the
+/// CashAddr spec. Returns 0 for values with a valid checksum.
+fn polymod(prefix: &str, values: &[u8]) -> u64 {
+    const GENERATORS: [u64; 5] = [
+        0x98f2bc8e61,
+        0x79b76d99e2,
+        0xf33e5fb3c4,
+        0xae2eabe2a8,
+        0x1e4f43e470,
+    ];
+    let prefix_values = prefix.bytes().map(|b| b & 0x1f);
+    let mut checksum = 1u64;
+    for value in prefix_values.chain([0]).chain(values.iter().copied()) {
+        let top = checksum >> 35;
+        checksum = ((checksum & 0x07_ffff_ffff) << 5) ^ u64::from(value);
+        for (bit, generator) in GENERATORS.iter().enumerate() {
+            if (top >> bit) & 1 != 0 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum ^ 1
+}
+
+/// Regroup the bits of `data` from `from_bits` to `to_bits` per item.
+/// When converting to a larger group size, the leftover bits must be zero
+/// padding, otherwise [`None`] is returned.
+fn convert_bits(data: &[u8], from_bits: u32, to_bits: u32) -> Option<Vec<u8>> {
+    let max_value = (1u32 << to_bits) - 1;
+    let mut acc = 0u32;
+    let mut num_bits = 0;
+    let mut result = Vec::with_capacity(data.len() * from_bits as usize / 2);
+    for &value in data {
+        acc = (acc << from_bits) | u32::from(value);
+        num_bits += from_bits;
+        while num_bits >= to_bits {
+            num_bits -= to_bits;
+            result.push(((acc >> num_bits) & max_value) as u8);
+        }
+    }
+    if from_bits > to_bits {
+        if num_bits > 0 {
+            result.push(((acc << (to_bits - num_bits)) & max_value) as u8);
+        }
+    } else if num_bits >= from_bits
+        || (acc << (to_bits - num_bits)) & max_value != 0
+    {
+        return None;
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use crate::{
+        address::{
+            AddressType, CashAddress, CashAddressError, ECREGTEST_PREFIX,
+        },
+        hash::ShaRmd160,
+        script::ScriptVariant,
+    };
+
+    #[test]
+    fn test_cash_address() -> Result<(), CashAddressError> {
+        // Vectors from the CashAddr spec
+        let hash = ShaRmd160(hex!("f5bf48b397dae70be82b3cca4793f8eb2b6cdac9"));
+        let address = CashAddress::new("bitcoincash", AddressType::P2PKH, hash);
+        let address_str =
+            "bitcoincash:qr6m7j9njldwwzlg9v7v53unlr4jkmx6eylep8ekg2";
+        assert_eq!(address.to_string(), address_str);
+        assert_eq!(address_str.parse::<CashAddress>()?, address);
+        assert_eq!(
+            address_str.to_ascii_uppercase().parse::<CashAddress>()?,
+            address,
+        );
+        let hash = ShaRmd160(hex!("76a04053bda0a88bda5177b86a15c3b29f559873"));
+        let address = CashAddress::new("bitcoincash", AddressType::P2SH, hash);
+        let address_str =
+            "bitcoincash:ppm2qsznhks23z7629mms6s4cwef74vcwvn0h829pq";
+        assert_eq!(address.to_string(), address_str);
+        assert_eq!(address_str.parse::<CashAddress>()?, address);
+
+        // P2SH OP_TRUE address used in the functional tests, without prefix
+        let address = "prdpw30fk4ym6zl6rftfjuw806arpn26fvkgfu97xt"
+            .parse::<CashAddress>()?;
+        assert_eq!(address.prefix(), ECREGTEST_PREFIX);
+        assert_eq!(
+            address.to_script_variant(),
+            ScriptVariant::P2SH(ShaRmd160(hex!(
+                "da1745e9b549bd0bfa1a569971c77eba30cd5a4b"
+            ))),
+        );
+
+        // Token-aware addresses use a different version byte
+        let token_address = address.clone().with_token_aware(true);
+        let token_address_str = token_address.to_string();
+        assert_ne!(token_address_str, address.to_string());
+        let parsed = token_address_str.parse::<CashAddress>()?;
+        assert!(parsed.is_token_aware());
+        assert_eq!(parsed, token_address);
+        assert_eq!(parsed.to_script(), address.to_script());
+
+        // Errors
+        assert_eq!(
+            "ecash:qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqs7ratqfX"
+                .parse::<CashAddress>(),
+            Err(CashAddressError::MixedCase(
+                "ecash:qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqs7ratqfX".to_string(),
+            )),
+        );
+        assert_eq!(
+            "ecash:qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqs7ratqfb"
+                .parse::<CashAddress>(),
+            Err(CashAddressError::InvalidChar('b')),
+        );
+        assert_eq!(
+            "ectest:qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqs7ratqfx"
+                .parse::<CashAddress>(),
+            Err(CashAddressError::InvalidChecksum(
+                "ectest:qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqs7ratqfx".to_string(),
+            )),
+        );
+        assert_eq!(
+            "qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqs7ratqfq".parse::<CashAddress>(),
+            Err(CashAddressError::UnknownPrefix(
+                "qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqs7ratqfq".to_string(),
+            )),
+        );
+        assert_eq!(
+            CashAddress::from_script_variant(
+                "ecash",
+                &ScriptVariant::Other(Default::default()),
+            ),
+            Err(CashAddressError::UnsupportedScript(ScriptVariant::Other(
+                Default::default(),
+            ))),
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{
+    address::AddressType,
+    hash::{Hashed, Sha256d, ShaRmd160},
+    script::{Script, ScriptVariant},
+};
+
+const CHECKSUM_LEN: usize = 4;
+
+/// Network of a [`LegacyAddress`], determining its version byte.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LegacyNet {
+    /// Mainnet, P2PKH addresses start with "1" and P2SH with "3".
+    Mainnet,
+    /// Testnet and regtest, P2PKH addresses start with "m" or "n" and P2SH
+    /// with "2".
+    Testnet,
+}
+
+/// Legacy Base58Check address, encoding a P2PKH or P2SH script.
+///
+/// ```
+/// # use bitcoinsuite_core::{
+/// #     address::{AddressType, LegacyAddress, LegacyNet},
+/// #     hash::ShaRmd160,
+/// # };
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let address = "1111111111111111111114oLvT2".parse::<LegacyAddress>()?;
+/// assert_eq!(
+///     address,
+///     LegacyAddress::new(
+///         LegacyNet::Mainnet,
+///         AddressType::P2PKH,
+///         ShaRmd160([0; 20]),
+///     ),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LegacyAddress {
+    net: LegacyNet,
+    addr_type: AddressType,
+    hash: ShaRmd160,
+}
+
+/// Errors indicating a legacy address couldn't be parsed or created.
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum LegacyAddressError {
+    /// Address isn't valid Base58.
+    #[error("Invalid Base58: {0}")]
+    InvalidBase58(bs58::decode::Error),
+
+    /// Decoded address has an invalid length.
+    #[error("Invalid length, expected 25 bytes but got {0}")]
+    InvalidLength(usize),
+
+    /// Checksum doesn't match, e.g. because of a typo.
+    #[error("Invalid checksum for address {0}")]
+    InvalidChecksum(String),
+
+    /// Version byte doesn't correspond to a known network and address type.
+    #[error("Unsupported version byte 0x{0:02x}")]
+    UnsupportedVersion(u8),
+
+    /// Script can't be encoded as legacy address, only P2PKH and P2SH can.
+    #[error("Script can't be encoded as legacy address: {0:?}")]
+    UnsupportedScript(ScriptVariant),
+}
+
+use self::LegacyAddressError::*;
+
+impl LegacyAddress {
+    /// Create a new [`LegacyAddress`] with the given network, type and hash.
+    pub fn new(
+        net: LegacyNet,
+        addr_type: AddressType,
+        hash: ShaRmd160,
+    ) -> Self {
+        LegacyAddress {
+            net,
+            addr_type,
+            hash,
+        }
+    }
+
+    /// Create a [`LegacyAddress`] for the given [`ScriptVariant`], which must
+    /// be either P2PKH or P2SH.
+    ///
+    /// ```
+    /// # use bitcoinsuite_core::{
+    /// #     address::{LegacyAddress, LegacyNet},
+    /// #     hash::ShaRmd160,
+    /// #     script::ScriptVariant,
+    /// # };
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let variant = ScriptVariant::P2SH(ShaRmd160([0; 20]));
+    /// let address =
+    ///     LegacyAddress::from_script_variant(LegacyNet::Mainnet, &variant)?;
+    /// assert_eq!(address.to_string(), "31h1vYVSYuKP6AhS86fbRdMw9XHieotbST");
+    /// assert_eq!(address.to_script_variant(), variant);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_script_variant(
+        net: LegacyNet,
+        variant: &ScriptVariant,
+    ) -> Result<Self, LegacyAddressError> {
+        match *variant {
+            ScriptVariant::P2PKH(hash) => {
+                Ok(LegacyAddress::new(net, AddressType::P2PKH, hash))
+            }
+            ScriptVariant::P2SH(hash) => {
+                Ok(LegacyAddress::new(net, AddressType::P2SH, hash))
+            }
+            _ => Err(UnsupportedScript(variant.clone())),
+        }
+    }
+
+    /// Network of the address.
+    pub fn net(&self) -> LegacyNet {
+        self.net
+    }
+
+    /// Whether the address encodes a P2PKH or P2SH script.
+    pub fn addr_type(&self) -> AddressType {
+        self.addr_type
+    }
+
+    /// Hash of the pubkey or script of the address.
+    pub fn hash(&self) -> &ShaRmd160 {
+        &self.hash
+    }
+
+    /// The [`ScriptVariant`] encoded by this address.
+    pub fn to_script_variant(&self) -> ScriptVariant {
+        self.addr_type.to_script_variant(self.hash)
+    }
+
+    /// The [`Script`] encoded by this address.
+    pub fn to_script(&self) -> Script {
+        self.to_script_variant().to_script()
+    }
+
+    fn version_byte(&self) -> u8 {
+        match (self.net, self.addr_type) {
+            (LegacyNet::Mainnet, AddressType::P2PKH) => 0x00,
+            (LegacyNet::Mainnet, AddressType::P2SH) => 0x05,
+            (LegacyNet::Testnet, AddressType::P2PKH) => 0x6f,
+            (LegacyNet::Testnet, AddressType::P2SH) => 0xc4,
+        }
+    }
+}
+
+impl FromStr for LegacyAddress {
+    type Err = LegacyAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = bs58::decode(s).into_vec().map_err(InvalidBase58)?;
+        if data.len() != 1 + ShaRmd160::SIZE + CHECKSUM_LEN {
+            return Err(InvalidLength(data.len()));
+        }
+        let (payload, checksum) = data.split_at(1 + ShaRmd160::SIZE);
+        if Sha256d::digest(payload).as_le_bytes()[..CHECKSUM_LEN] != *checksum {
+            return Err(InvalidChecksum(s.to_string()));
+        }
+        let (net, addr_type) = match payload[0] {
+            0x00 => (LegacyNet::Mainnet, AddressType::P2PKH),
+            0x05 => (LegacyNet::Mainnet, AddressType::P2SH),
+            0x6f => (LegacyNet::Testnet, AddressType::P2PKH),
+            0xc4 => (LegacyNet::Testnet, AddressType::P2SH),
+            version => return Err(UnsupportedVersion(version)),
+        };
+        let hash = ShaRmd160(payload[1..].try_into().unwrap());
+        Ok(LegacyAddress::new(net, addr_type, hash))
+    }
+}
+
+impl Display for LegacyAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut data = Vec::with_capacity(1 + ShaRmd160::SIZE + CHECKSUM_LEN);
+        data.push(self.version_byte());
+        data.extend_from_slice(self.hash.as_ref());
+        let checksum = Sha256d::digest(&data);
+        data.extend_from_slice(&checksum.as_le_bytes()[..CHECKSUM_LEN]);
+        write!(f, "{}", bs58::encode(data).into_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use crate::{
+        address::{AddressType, LegacyAddress, LegacyAddressError, LegacyNet},
+        hash::ShaRmd160,
+        script::ScriptVariant,
+    };
+
+    #[test]
+    fn test_legacy_address() -> Result<(), LegacyAddressError> {
+        let hash = ShaRmd160(hex!("f5bf48b397dae70be82b3cca4793f8eb2b6cdac9"));
+        for (net, addr_type, hash, address_str) in [
+            (
+                LegacyNet::Mainnet,
+                AddressType::P2PKH,
+                ShaRmd160([0; 20]),
+                "1111111111111111111114oLvT2",
+            ),
+            (
+                LegacyNet::Testnet,
+                AddressType::P2PKH,
+                ShaRmd160([0; 20]),
+                "mfWxJ45yp2SFn7UciZyNpvDKrzbhyfKrY8",
+            ),
+            (
+                LegacyNet::Testnet,
+                AddressType::P2SH,
+                ShaRmd160([0; 20]),
+                "2MsFDzHRUAMpjHxKyoEHU3aMCMsVtMqs1PV",
+            ),
+            (
+                LegacyNet::Mainnet,
+                AddressType::P2PKH,
+                hash,
+                "1PQPheJQSauxRPTxzNMUco1XmoCyPoEJCp",
+            ),
+        ] {
+            let address = LegacyAddress::new(net, addr_type, hash);
+            assert_eq!(address.to_string(), address_str);
+            assert_eq!(address_str.parse::<LegacyAddress>()?, address);
+        }
+
+        assert_eq!(
+            "1111111111111111111114oLvT3".parse::<LegacyAddress>(),
+            Err(LegacyAddressError::InvalidChecksum(
+                "1111111111111111111114oLvT3".to_string(),
+            )),
+        );
+        assert_eq!(
+            "111111".parse::<LegacyAddress>(),
+            Err(LegacyAddressError::InvalidLength(6)),
+        );
+        assert!(matches!(
+            "10".parse::<LegacyAddress>(),
+            Err(LegacyAddressError::InvalidBase58(_)),
+        ));
+        assert_eq!(
+            LegacyAddress::from_script_variant(
+                LegacyNet::Mainnet,
+                &ScriptVariant::Other(Default::default()),
+            ),
+            Err(LegacyAddressError::UnsupportedScript(ScriptVariant::Other(
+                Default::default(),
+            ))),
+        );
+        Ok(())
+    }
+}
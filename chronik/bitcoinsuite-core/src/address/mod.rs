@@ -0,0 +1,46 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for addresses, i.e. human-readable encodings of scripts, e.g.
+//! [`CashAddress`] and [`LegacyAddress`].
+
+mod cashaddr;
+mod legacy;
+
+pub use self::cashaddr::*;
+pub use self::legacy::*;
+use crate::{hash::ShaRmd160, script::ScriptVariant};
+
+/// Type of script an address encodes.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AddressType {
+    /// Pay-to-public-key-hash.
+    /// Script: `OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG`
+    P2PKH,
+    /// Pay-to-script-hash.
+    /// Script: `OP_HASH160 <hash> OP_EQUAL`
+    P2SH,
+}
+
+impl AddressType {
+    /// The [`ScriptVariant`] of this address type with the given hash.
+    ///
+    /// ```
+    /// # use bitcoinsuite_core::{
+    /// #     address::AddressType,
+    /// #     hash::ShaRmd160,
+    /// #     script::ScriptVariant,
+    /// # };
+    /// assert_eq!(
+    ///     AddressType::P2SH.to_script_variant(ShaRmd160([1; 20])),
+    ///     ScriptVariant::P2SH(ShaRmd160([1; 20])),
+    /// );
+    /// ```
+    pub fn to_script_variant(self, hash: ShaRmd160) -> ScriptVariant {
+        match self {
+            AddressType::P2PKH => ScriptVariant::P2PKH(hash),
+            AddressType::P2SH => ScriptVariant::P2SH(hash),
+        }
+    }
+}
@@ -3,8 +3,10 @@
 // file COPYING or http://www.opensource.org/licenses/mit-license.php.
 
 use crate::{
+    bytes::read_array,
     error::DataError,
     hash::{Hashed, Sha256d},
+    ser::{BitcoinSer, BitcoinSerializer},
 };
 
 /// Wraps a block hash's [`Sha256d`], to avoid mixing different kinds of hashes.
@@ -91,6 +93,16 @@ impl AsRef<[u8]> for BlockHash {
     }
 }
 
+impl BitcoinSer for BlockHash {
+    fn ser_to<S: BitcoinSerializer>(&self, bytes: &mut S) {
+        bytes.put(self.0.as_le_bytes())
+    }
+
+    fn deser(data: &mut bytes::Bytes) -> Result<Self, DataError> {
+        Ok(BlockHash(Sha256d(read_array(data)?)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use hex_literal::hex;
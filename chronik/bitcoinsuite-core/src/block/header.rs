@@ -0,0 +1,126 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use crate::{
+    block::BlockHash,
+    error::DataError,
+    hash::{Hashed, Sha256d},
+    ser::{BitcoinSer, BitcoinSerializer},
+};
+
+/// Header of a block, which is hashed to get the [`BlockHash`].
+///
+/// ```
+/// # use bitcoinsuite_core::{
+/// #     block::BlockHeader,
+/// #     hash::{Hashed, Sha256d},
+/// #     ser::BitcoinSer,
+/// # };
+/// let header = BlockHeader {
+///     version: 1,
+///     timestamp: 1231006505,
+///     ..Default::default()
+/// };
+/// assert_eq!(header.ser().len(), BlockHeader::SIZE);
+/// assert_eq!(header.hash(), Sha256d::digest(header.ser()).into());
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BlockHeader {
+    /// Block version, used to signal soft forks.
+    pub version: i32,
+    /// Hash of the previous block.
+    pub prev_hash: BlockHash,
+    /// Root of the merkle tree of the txids of the block, see
+    /// [`crate::merkle`].
+    pub merkle_root: Sha256d,
+    /// Timestamp of the block, in seconds since the UNIX epoch.
+    pub timestamp: u32,
+    /// Compact encoding of the target the block hash must be below.
+    pub bits: u32,
+    /// Nonce to make the block hash fall below the target.
+    pub nonce: u32,
+}
+
+impl BlockHeader {
+    /// Number of bytes of a serialized [`BlockHeader`].
+    pub const SIZE: usize = 80;
+
+    /// Hash the header to get the [`BlockHash`] of the block.
+    pub fn hash(&self) -> BlockHash {
+        BlockHash::from(Sha256d::digest(self.ser()))
+    }
+}
+
+impl BitcoinSer for BlockHeader {
+    fn ser_to<S: BitcoinSerializer>(&self, bytes: &mut S) {
+        self.version.ser_to(bytes);
+        self.prev_hash.ser_to(bytes);
+        self.merkle_root.as_le_bytes().ser_to(bytes);
+        self.timestamp.ser_to(bytes);
+        self.bits.ser_to(bytes);
+        self.nonce.ser_to(bytes);
+    }
+
+    fn deser(data: &mut bytes::Bytes) -> Result<Self, DataError> {
+        Ok(BlockHeader {
+            version: BitcoinSer::deser(data)?,
+            prev_hash: BitcoinSer::deser(data)?,
+            merkle_root: Sha256d(BitcoinSer::deser(data)?),
+            timestamp: BitcoinSer::deser(data)?,
+            bits: BitcoinSer::deser(data)?,
+            nonce: BitcoinSer::deser(data)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use hex_literal::hex;
+
+    use crate::{
+        block::{BlockHash, BlockHeader},
+        error::DataError,
+        hash::{Hashed, Sha256d},
+        ser::BitcoinSer,
+    };
+
+    #[test]
+    fn test_genesis_header() -> Result<(), DataError> {
+        let genesis_ser = hex!(
+            "0100000000000000000000000000000000000000000000000000000000000000"
+            "000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa"
+            "4b1e5e4a29ab5f49ffff001d1dac2b7c"
+        );
+        let genesis = BlockHeader {
+            version: 1,
+            prev_hash: BlockHash::default(),
+            merkle_root: Sha256d::from_be_hex(
+                "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+            )?,
+            timestamp: 1231006505,
+            bits: 0x1d00ffff,
+            nonce: 2083236893,
+        };
+        assert_eq!(genesis.ser().as_ref(), genesis_ser);
+        assert_eq!(genesis.ser_len(), BlockHeader::SIZE);
+        assert_eq!(
+            BlockHeader::deser(&mut Bytes::copy_from_slice(&genesis_ser))?,
+            genesis,
+        );
+        assert_eq!(
+            genesis.hash().to_string(),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+        );
+        // Too short
+        assert_eq!(
+            BlockHeader::deser(&mut Bytes::copy_from_slice(&genesis_ser[..79])),
+            Err(DataError::InvalidLength {
+                expected: 4,
+                actual: 3,
+            }),
+        );
+        Ok(())
+    }
+}
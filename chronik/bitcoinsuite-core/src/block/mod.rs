@@ -2,8 +2,11 @@
 // Distributed under the MIT software license, see the accompanying
 // file COPYING or http://www.opensource.org/licenses/mit-license.php.
 
-//! Module for data refering to blocks, e.g. [`BlockHash`].
+//! Module for data refering to blocks, e.g. [`BlockHash`] and
+//! [`BlockHeader`].
 
 mod block_hash;
+mod header;
 
 pub use self::block_hash::*;
+pub use self::header::*;
@@ -8,10 +8,12 @@
 //! usage in Chronik, an indexer for Bitcoin ABC.
 
 abc_rust_lint::lint! {
+    pub mod address;
     pub mod block;
     pub mod bytes;
     pub mod error;
     pub mod hash;
+    pub mod merkle;
     pub mod script;
     pub mod ser;
     pub mod tx;
@@ -0,0 +1,181 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for computing merkle roots and branches of the txids of a block.
+//!
+//! Each level of the tree hashes pairs of nodes with [`Sha256d`], duplicating
+//! the last node if a level has an odd number of nodes, like the node does.
+
+use crate::{
+    block::BlockHeader,
+    hash::{Hashed, Sha256d},
+    tx::TxId,
+};
+
+/// Compute the merkle root of the given txids, as stored in the
+/// [`crate::block::BlockHeader`]. Returns the null hash if there are no txids.
+///
+/// ```
+/// # use bitcoinsuite_core::{
+/// #     hash::{Hashed, Sha256d},
+/// #     merkle::merkle_root,
+/// #     tx::TxId,
+/// # };
+/// assert_eq!(merkle_root(&[]), Sha256d::default());
+/// // A single txid is its own merkle root
+/// assert_eq!(merkle_root(&[TxId::from([1; 32])]), Sha256d([1; 32]));
+/// ```
+pub fn merkle_root(txids: &[TxId]) -> Sha256d {
+    let mut level = txids
+        .iter()
+        .map(|txid| Sha256d(txid.to_bytes()))
+        .collect::<Vec<_>>();
+    if level.is_empty() {
+        return Sha256d::default();
+    }
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Compute the merkle branch of the tx at `tx_idx`, i.e. the sibling hashes
+/// from the bottom of the tree to the top, which, together with the txid,
+/// are enough to compute the merkle root.
+///
+/// Returns [`None`] if `tx_idx` is out of bounds.
+pub fn merkle_branch(txids: &[TxId], tx_idx: usize) -> Option<Vec<Sha256d>> {
+    if tx_idx >= txids.len() {
+        return None;
+    }
+    let mut level = txids
+        .iter()
+        .map(|txid| Sha256d(txid.to_bytes()))
+        .collect::<Vec<_>>();
+    let mut idx = tx_idx;
+    let mut branch = Vec::new();
+    while level.len() > 1 {
+        let sibling_idx = (idx ^ 1).min(level.len() - 1);
+        branch.push(level[sibling_idx]);
+        level = next_level(&level);
+        idx /= 2;
+    }
+    Some(branch)
+}
+
+/// Compute the merkle root from a txid, its index in the block and its
+/// merkle branch, as returned by [`merkle_branch`].
+pub fn merkle_root_from_branch(
+    txid: &TxId,
+    tx_idx: usize,
+    branch: &[Sha256d],
+) -> Sha256d {
+    let mut hash = Sha256d(txid.to_bytes());
+    let mut idx = tx_idx;
+    for sibling in branch {
+        hash = if idx % 2 == 0 {
+            hash_pair(&hash, sibling)
+        } else {
+            hash_pair(sibling, &hash)
+        };
+        idx /= 2;
+    }
+    hash
+}
+
+/// Verify that the tx with the given txid is at `tx_idx` of the block with the
+/// given header, using its merkle branch.
+///
+/// This only checks the inclusion in the block; it's up to the caller to
+/// check the header is part of the chain with the most work.
+pub fn verify_merkle_proof(
+    txid: &TxId,
+    tx_idx: usize,
+    branch: &[Sha256d],
+    header: &BlockHeader,
+) -> bool {
+    merkle_root_from_branch(txid, tx_idx, branch) == header.merkle_root
+}
+
+fn next_level(level: &[Sha256d]) -> Vec<Sha256d> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_pair(left, right),
+            [single] => hash_pair(single, single),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+fn hash_pair(left: &Sha256d, right: &Sha256d) -> Sha256d {
+    Sha256d::digest([left.as_le_bytes().as_ref(), right.as_le_bytes()].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        block::BlockHeader,
+        error::DataError,
+        hash::{Hashed, Sha256d},
+        merkle::{
+            merkle_branch, merkle_root, merkle_root_from_branch,
+            verify_merkle_proof,
+        },
+        tx::TxId,
+    };
+
+    #[test]
+    fn test_merkle_root() -> Result<(), DataError> {
+        // Txs of Bitcoin block 100000
+        let txids = [
+            "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+            "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+            "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+            "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+        ]
+        .into_iter()
+        .map(|txid| txid.parse::<TxId>())
+        .collect::<Result<Vec<_>, _>>()?;
+        let root = Sha256d::from_be_hex(
+            "f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766",
+        )?;
+        assert_eq!(merkle_root(&txids), root);
+        for (tx_idx, txid) in txids.iter().enumerate() {
+            let branch = merkle_branch(&txids, tx_idx).unwrap();
+            assert_eq!(branch.len(), 2);
+            assert_eq!(merkle_root_from_branch(txid, tx_idx, &branch), root);
+        }
+        assert_eq!(merkle_branch(&txids, 4), None);
+
+        let header = BlockHeader {
+            merkle_root: root,
+            ..Default::default()
+        };
+        let branch = merkle_branch(&txids, 2).unwrap();
+        assert!(verify_merkle_proof(&txids[2], 2, &branch, &header));
+        // Wrong txid, index or branch
+        assert!(!verify_merkle_proof(&txids[1], 2, &branch, &header));
+        assert!(!verify_merkle_proof(&txids[2], 3, &branch, &header));
+        assert!(!verify_merkle_proof(&txids[2], 2, &branch[..1], &header));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_branch_odd() {
+        for num_txs in 1..=9u8 {
+            let txids = (1..=num_txs)
+                .map(|num| TxId::from([num; 32]))
+                .collect::<Vec<_>>();
+            let root = merkle_root(&txids);
+            for (tx_idx, txid) in txids.iter().enumerate() {
+                let branch = merkle_branch(&txids, tx_idx).unwrap();
+                assert_eq!(
+                    merkle_root_from_branch(txid, tx_idx, &branch),
+                    root,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use bitcoinsuite_core::{script::Script, ser::BitcoinSer};
+use bytes::{BufMut, Bytes, BytesMut};
+use thiserror::Error;
+
+use crate::{
+    alp::{AlpMintData, AlpSection, AlpTxType, ALP_LOKAD_ID, MAX_ALP_AMOUNT},
+    empp,
+    structs::Amount,
+    token_id::TokenId,
+};
+
+/// Errors when building an ALP section failed.
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum BuildError {
+    /// Amount doesn't fit in 48 bits
+    #[error("Amount {0} exceeds the maximum of {MAX_ALP_AMOUNT}")]
+    AmountTooBig(Amount),
+
+    /// Number of amounts doesn't fit in 1 byte
+    #[error("Too many amounts, got {0} but can only encode 255")]
+    TooManyAmounts(usize),
+
+    /// Number of mint batons doesn't fit in 1 byte
+    #[error("Too many mint batons, got {0} but can only encode 255")]
+    TooManyBatons(usize),
+}
+
+use self::BuildError::*;
+
+/// Serialize the section as eMPP pushdata, the inverse of
+/// [`crate::alp::parse_section`].
+///
+/// The document hash of GENESIS sections is ignored, as ALP doesn't have one.
+pub fn build_section(section: &AlpSection) -> Result<Bytes, BuildError> {
+    let mut data = BytesMut::new();
+    data.put_slice(&ALP_LOKAD_ID);
+    data.put_u8(section.token_type.to_u8());
+    match &section.tx_type {
+        AlpTxType::Genesis(genesis) => {
+            let info = &genesis.info;
+            Bytes::from_static(b"GENESIS").ser_to(&mut data);
+            info.token_ticker.ser_to(&mut data);
+            info.token_name.ser_to(&mut data);
+            info.url.ser_to(&mut data);
+            info.data.clone().unwrap_or_default().ser_to(&mut data);
+            info.auth_pubkey
+                .clone()
+                .unwrap_or_default()
+                .ser_to(&mut data);
+            data.put_u8(info.decimals);
+            put_mint_data(&mut data, &genesis.mint_data)?;
+        }
+        AlpTxType::Mint(mint) => {
+            Bytes::from_static(b"MINT").ser_to(&mut data);
+            put_token_id(&mut data, &mint.token_id);
+            put_mint_data(&mut data, &mint.mint_data)?;
+        }
+        AlpTxType::Send(send) => {
+            Bytes::from_static(b"SEND").ser_to(&mut data);
+            put_token_id(&mut data, &send.token_id);
+            put_amounts(&mut data, &send.amounts)?;
+        }
+        AlpTxType::Burn(burn) => {
+            Bytes::from_static(b"BURN").ser_to(&mut data);
+            put_token_id(&mut data, &burn.token_id);
+            put_amount(&mut data, burn.burn_amount)?;
+        }
+    }
+    Ok(data.freeze())
+}
+
+/// Build an eMPP OP_RETURN script with one pushdata for each section.
+pub fn sections_script(sections: &[AlpSection]) -> Result<Script, BuildError> {
+    let pushdata = sections
+        .iter()
+        .map(build_section)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(empp::build(&pushdata))
+}
+
+fn put_mint_data(
+    data: &mut BytesMut,
+    mint_data: &AlpMintData,
+) -> Result<(), BuildError> {
+    put_amounts(data, &mint_data.amounts)?;
+    let num_batons = u8::try_from(mint_data.num_batons)
+        .map_err(|_| TooManyBatons(mint_data.num_batons))?;
+    data.put_u8(num_batons);
+    Ok(())
+}
+
+fn put_token_id(data: &mut BytesMut, token_id: &TokenId) {
+    data.put_slice(token_id.txid().as_bytes());
+}
+
+fn put_amounts(
+    data: &mut BytesMut,
+    amounts: &[Amount],
+) -> Result<(), BuildError> {
+    let num_amounts = u8::try_from(amounts.len())
+        .map_err(|_| TooManyAmounts(amounts.len()))?;
+    data.put_u8(num_amounts);
+    for &amount in amounts {
+        put_amount(data, amount)?;
+    }
+    Ok(())
+}
+
+fn put_amount(data: &mut BytesMut, amount: Amount) -> Result<(), BuildError> {
+    if amount > MAX_ALP_AMOUNT {
+        return Err(AmountTooBig(amount));
+    }
+    data.put_slice(&amount.to_le_bytes()[..6]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::tx::TxId;
+    use bytes::Bytes;
+
+    use crate::{
+        alp::{
+            build_section, parse_section, parse_sections, sections_script,
+            AlpBurn, AlpGenesis, AlpMint, AlpMintData, AlpSection, AlpSend,
+            AlpTokenType, AlpTxType, BuildError, MAX_ALP_AMOUNT,
+        },
+        empp,
+        structs::GenesisInfo,
+        token_id::TokenId,
+    };
+
+    fn section(tx_type: AlpTxType) -> AlpSection {
+        AlpSection {
+            token_type: AlpTokenType::Standard,
+            tx_type,
+        }
+    }
+
+    #[test]
+    fn test_alp_build_roundtrip() -> Result<(), BuildError> {
+        let token_id = TokenId::new(TxId::from([7; 32]));
+        let sections = vec![
+            section(AlpTxType::Genesis(Box::new(AlpGenesis {
+                info: GenesisInfo {
+                    token_ticker: Bytes::from_static(b"ALP"),
+                    token_name: Bytes::from_static(b"ALP token"),
+                    url: Bytes::from_static(b"https://example.com"),
+                    hash: None,
+                    data: Some(Bytes::from(vec![0xda; 300])),
+                    auth_pubkey: Some(Bytes::from_static(&[3; 33])),
+                    decimals: 9,
+                },
+                mint_data: AlpMintData {
+                    amounts: vec![0, MAX_ALP_AMOUNT],
+                    num_batons: 255,
+                },
+            }))),
+            section(AlpTxType::Mint(AlpMint {
+                token_id,
+                mint_data: AlpMintData {
+                    amounts: vec![],
+                    num_batons: 1,
+                },
+            })),
+            section(AlpTxType::Send(AlpSend {
+                token_id,
+                amounts: vec![1, 2, 3],
+            })),
+            section(AlpTxType::Burn(AlpBurn {
+                token_id,
+                burn_amount: 0x0102_0304_0506,
+            })),
+        ];
+        for section in &sections {
+            assert_eq!(
+                parse_section(&build_section(section)?),
+                Ok(Some(section.clone()))
+            );
+        }
+        let script = sections_script(&sections)?;
+        let empp_data = empp::parse(&script).unwrap();
+        assert_eq!(
+            parse_sections(&empp_data),
+            sections.into_iter().map(Ok).collect::<Vec<_>>(),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_alp_build_errors() {
+        let token_id = TokenId::new(TxId::from([7; 32]));
+        assert_eq!(
+            build_section(&section(AlpTxType::Burn(AlpBurn {
+                token_id,
+                burn_amount: MAX_ALP_AMOUNT + 1,
+            }))),
+            Err(BuildError::AmountTooBig(MAX_ALP_AMOUNT + 1)),
+        );
+        assert_eq!(
+            build_section(&section(AlpTxType::Send(AlpSend {
+                token_id,
+                amounts: vec![1; 256],
+            }))),
+            Err(BuildError::TooManyAmounts(256)),
+        );
+        assert_eq!(
+            build_section(&section(AlpTxType::Mint(AlpMint {
+                token_id,
+                mint_data: AlpMintData {
+                    amounts: vec![],
+                    num_batons: 256,
+                },
+            }))),
+            Err(BuildError::TooManyBatons(256)),
+        );
+    }
+}
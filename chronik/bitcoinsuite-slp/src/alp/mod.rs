@@ -0,0 +1,45 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! # ALP (Augmented Ledger Protocol).
+//!
+//! Tokens encoded in eMPP pushdata, see [`crate::empp`]. A tx can have
+//! multiple ALP sections, one per eMPP pushdata.
+//!
+//! ## Specification
+//!
+//! An ALP section is an eMPP pushdata that looks like this:
+//!
+//! 1. The LOKAD ID `b"SLP2"`; pushdata with a different prefix are not ALP
+//! sections and are ignored.
+//! 2. The token type, 1 byte (0 = standard)
+//! 3. The tx type, size-prefixed, one of `b"GENESIS"`, `b"MINT"`, `b"SEND"`
+//! or `b"BURN"`
+//! 4. The fields of the tx type (see below)
+//!
+//! Sizes are encoded as Bitcoin compact size. Amounts are 6 bytes (48 bits)
+//! little-endian. Token IDs are 32 bytes little-endian (like txids in txs).
+//!
+//! The fields by tx type are:
+//!
+//! - GENESIS: size-prefixed token ticker, token name, URL, data and auth
+//! pubkey, decimals (1 byte, 0-9), mint data.
+//! - MINT: token ID, mint data.
+//! - SEND: token ID, number of amounts (1 byte), the amounts for outputs 1,
+//! 2, ...
+//! - BURN: token ID, burn amount.
+//!
+//! Mint data is: number of amounts (1 byte), the amounts for outputs 1, 2,
+//! ..., number of mint batons (1 byte), which are put at the outputs following
+//! the amounts.
+//!
+//! Sections must not have any bytes left over after the fields.
+
+mod build;
+mod parse;
+mod structs;
+
+pub use self::build::*;
+pub use self::parse::*;
+pub use self::structs::*;
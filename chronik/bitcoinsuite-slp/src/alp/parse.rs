@@ -0,0 +1,308 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use bitcoinsuite_core::{
+    bytes::read_array, error::DataError, ser::BitcoinSer, tx::TxId,
+};
+use bytes::Bytes;
+use thiserror::Error;
+
+use crate::{
+    alp::{
+        AlpBurn, AlpGenesis, AlpMint, AlpMintData, AlpSection, AlpSend,
+        AlpTokenType, AlpTxType, ALP_LOKAD_ID,
+    },
+    structs::{Amount, GenesisInfo},
+    token_id::TokenId,
+};
+
+/// Errors when parsing an ALP section failed.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum ParseError {
+    /// Section ended prematurely
+    #[error("Failed parsing section: {0}")]
+    DataError(#[from] DataError),
+
+    /// Token type is not one of the supported ones
+    #[error("Unknown token type: {0}")]
+    UnknownTokenType(u8),
+
+    /// Tx type is not GENESIS, MINT, SEND or BURN
+    #[error("Unknown tx type: {}", hex::encode(.0))]
+    UnknownTxType(Bytes),
+
+    /// Decimals must be 0-9
+    #[error("Decimals must be at most 9, but got {0}")]
+    InvalidDecimals(u8),
+
+    /// Section has bytes after the last field
+    #[error("Leftover bytes: {}", hex::encode(.0))]
+    LeftoverBytes(Bytes),
+}
+
+use self::ParseError::*;
+
+/// Parse the eMPP pushdata as an ALP section, or return [`None`] if it
+/// doesn't have the ALP LOKAD ID.
+///
+/// See [`crate::alp`] for details.
+pub fn parse_section(
+    pushdata: &Bytes,
+) -> Result<Option<AlpSection>, ParseError> {
+    if !pushdata.starts_with(&ALP_LOKAD_ID) {
+        return Ok(None);
+    }
+    let mut data = pushdata.slice(ALP_LOKAD_ID.len()..);
+    let [token_type] = read_array::<1>(&mut data)?;
+    let token_type = AlpTokenType::from_u8(token_type)
+        .ok_or(UnknownTokenType(token_type))?;
+    let tx_type = Bytes::deser(&mut data)?;
+    let tx_type = match tx_type.as_ref() {
+        b"GENESIS" => parse_genesis(&mut data)?,
+        b"MINT" => parse_mint(&mut data)?,
+        b"SEND" => parse_send(&mut data)?,
+        b"BURN" => parse_burn(&mut data)?,
+        _ => return Err(UnknownTxType(tx_type)),
+    };
+    if !data.is_empty() {
+        return Err(LeftoverBytes(data));
+    }
+    Ok(Some(AlpSection {
+        token_type,
+        tx_type,
+    }))
+}
+
+/// Parse all the ALP sections of the eMPP pushdata, skipping pushdata that
+/// don't have the ALP LOKAD ID.
+pub fn parse_sections(
+    empp_data: &[Bytes],
+) -> Vec<Result<AlpSection, ParseError>> {
+    empp_data
+        .iter()
+        .filter_map(|pushdata| parse_section(pushdata).transpose())
+        .collect()
+}
+
+fn parse_genesis(data: &mut Bytes) -> Result<AlpTxType, ParseError> {
+    let token_ticker = Bytes::deser(data)?;
+    let token_name = Bytes::deser(data)?;
+    let url = Bytes::deser(data)?;
+    let genesis_data = Bytes::deser(data)?;
+    let auth_pubkey = Bytes::deser(data)?;
+    let [decimals] = read_array::<1>(data)?;
+    if decimals > 9 {
+        return Err(InvalidDecimals(decimals));
+    }
+    let mint_data = parse_mint_data(data)?;
+    Ok(AlpTxType::Genesis(Box::new(AlpGenesis {
+        info: GenesisInfo {
+            token_ticker,
+            token_name,
+            url,
+            hash: None,
+            data: Some(genesis_data),
+            auth_pubkey: Some(auth_pubkey),
+            decimals,
+        },
+        mint_data,
+    })))
+}
+
+fn parse_mint(data: &mut Bytes) -> Result<AlpTxType, ParseError> {
+    Ok(AlpTxType::Mint(AlpMint {
+        token_id: parse_token_id(data)?,
+        mint_data: parse_mint_data(data)?,
+    }))
+}
+
+fn parse_send(data: &mut Bytes) -> Result<AlpTxType, ParseError> {
+    Ok(AlpTxType::Send(AlpSend {
+        token_id: parse_token_id(data)?,
+        amounts: parse_amounts(data)?,
+    }))
+}
+
+fn parse_burn(data: &mut Bytes) -> Result<AlpTxType, ParseError> {
+    Ok(AlpTxType::Burn(AlpBurn {
+        token_id: parse_token_id(data)?,
+        burn_amount: parse_amount(data)?,
+    }))
+}
+
+fn parse_mint_data(data: &mut Bytes) -> Result<AlpMintData, ParseError> {
+    let amounts = parse_amounts(data)?;
+    let [num_batons] = read_array::<1>(data)?;
+    Ok(AlpMintData {
+        amounts,
+        num_batons: num_batons.into(),
+    })
+}
+
+fn parse_token_id(data: &mut Bytes) -> Result<TokenId, ParseError> {
+    Ok(TokenId::new(TxId::from(read_array::<32>(data)?)))
+}
+
+fn parse_amounts(data: &mut Bytes) -> Result<Vec<Amount>, ParseError> {
+    let [num_amounts] = read_array::<1>(data)?;
+    (0..num_amounts).map(|_| parse_amount(data)).collect()
+}
+
+fn parse_amount(data: &mut Bytes) -> Result<Amount, ParseError> {
+    let [a0, a1, a2, a3, a4, a5] = read_array::<6>(data)?;
+    Ok(Amount::from_le_bytes([a0, a1, a2, a3, a4, a5, 0, 0]))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::{error::DataError, tx::TxId};
+    use bytes::Bytes;
+
+    use crate::{
+        alp::{
+            parse_section, parse_sections, AlpBurn, AlpGenesis, AlpMint,
+            AlpMintData, AlpSection, AlpSend, AlpTokenType, AlpTxType,
+            ParseError,
+        },
+        structs::GenesisInfo,
+        token_id::TokenId,
+    };
+
+    fn parse(data: &[u8]) -> Result<Option<AlpSection>, ParseError> {
+        parse_section(&Bytes::copy_from_slice(data))
+    }
+
+    fn standard(tx_type: AlpTxType) -> Option<AlpSection> {
+        Some(AlpSection {
+            token_type: AlpTokenType::Standard,
+            tx_type,
+        })
+    }
+
+    #[test]
+    fn test_parse_alp_section() {
+        let token_id = TokenId::new(TxId::from([7; 32]));
+
+        // Not ALP
+        assert_eq!(parse(b""), Ok(None));
+        assert_eq!(parse(b"SLP"), Ok(None));
+        assert_eq!(parse(b"SLP\0\x01"), Ok(None));
+
+        assert_eq!(
+            parse(b"SLP2"),
+            Err(ParseError::DataError(DataError::InvalidLength {
+                expected: 1,
+                actual: 0,
+            })),
+        );
+        assert_eq!(parse(b"SLP2\x01"), Err(ParseError::UnknownTokenType(1)));
+        assert_eq!(
+            parse(b"SLP2\0\x04SWAP"),
+            Err(ParseError::UnknownTxType(Bytes::from_static(b"SWAP"))),
+        );
+
+        let genesis = [
+            b"SLP2\0\x07GENESIS".as_ref(),
+            b"\x03ALP\x04Name\x00\x02\xda\xda\x01\x03",
+            &[4],
+            &[2, 1, 0, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            &[1],
+        ]
+        .concat();
+        assert_eq!(
+            parse(&genesis),
+            Ok(standard(AlpTxType::Genesis(Box::new(AlpGenesis {
+                info: GenesisInfo {
+                    token_ticker: Bytes::from_static(b"ALP"),
+                    token_name: Bytes::from_static(b"Name"),
+                    url: Bytes::new(),
+                    hash: None,
+                    data: Some(Bytes::from_static(&[0xda, 0xda])),
+                    auth_pubkey: Some(Bytes::from_static(&[3])),
+                    decimals: 4,
+                },
+                mint_data: AlpMintData {
+                    amounts: vec![1, 0xffff_ffff_ffff],
+                    num_batons: 1,
+                },
+            })))),
+        );
+        let mut bad_decimals = genesis.clone();
+        bad_decimals[28] = 10;
+        assert_eq!(parse(&bad_decimals), Err(ParseError::InvalidDecimals(10)));
+        assert_eq!(
+            parse(&[genesis.as_slice(), &[0xaa][..]].concat()),
+            Err(ParseError::LeftoverBytes(Bytes::from_static(&[0xaa]))),
+        );
+
+        let mint = [b"SLP2\0\x04MINT".as_ref(), &[7; 32], &[0, 2]].concat();
+        assert_eq!(
+            parse(&mint),
+            Ok(standard(AlpTxType::Mint(AlpMint {
+                token_id,
+                mint_data: AlpMintData {
+                    amounts: vec![],
+                    num_batons: 2,
+                },
+            }))),
+        );
+        assert_eq!(
+            parse(&mint[..mint.len() - 1]),
+            Err(ParseError::DataError(DataError::InvalidLength {
+                expected: 1,
+                actual: 0,
+            })),
+        );
+
+        let send = [
+            b"SLP2\0\x04SEND".as_ref(),
+            &[7; 32],
+            &[2, 5, 0, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0],
+        ]
+        .concat();
+        assert_eq!(
+            parse(&send),
+            Ok(standard(AlpTxType::Send(AlpSend {
+                token_id,
+                amounts: vec![5, 6],
+            }))),
+        );
+        assert_eq!(
+            parse(&send[..send.len() - 1]),
+            Err(ParseError::DataError(DataError::InvalidLength {
+                expected: 6,
+                actual: 5,
+            })),
+        );
+
+        let burn = [b"SLP2\0\x04BURN".as_ref(), &[7; 32], &[1, 2, 3, 4, 5, 6]]
+            .concat();
+        assert_eq!(
+            parse(&burn),
+            Ok(standard(AlpTxType::Burn(AlpBurn {
+                token_id,
+                burn_amount: 0x0605_0403_0201,
+            }))),
+        );
+
+        // Non-ALP pushdata are skipped
+        assert_eq!(
+            parse_sections(&[
+                Bytes::from_static(b"OTHR"),
+                Bytes::from(burn),
+                Bytes::from_static(b"SLP2\x01"),
+            ]),
+            vec![
+                Ok(AlpSection {
+                    token_type: AlpTokenType::Standard,
+                    tx_type: AlpTxType::Burn(AlpBurn {
+                        token_id,
+                        burn_amount: 0x0605_0403_0201,
+                    }),
+                }),
+                Err(ParseError::UnknownTokenType(1)),
+            ],
+        );
+    }
+}
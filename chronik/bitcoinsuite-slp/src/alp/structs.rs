@@ -0,0 +1,118 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use crate::{
+    structs::{Amount, GenesisInfo},
+    token_id::TokenId,
+};
+
+/// LOKAD ID of ALP, the prefix of every ALP section.
+pub const ALP_LOKAD_ID: [u8; 4] = *b"SLP2";
+
+/// Largest amount that can be encoded in ALP (48 bits).
+pub const MAX_ALP_AMOUNT: Amount = 0xffff_ffff_ffff;
+
+/// Token type of an ALP section.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AlpTokenType {
+    /// Standard token, token type 0.
+    Standard,
+}
+
+/// Parsed ALP section.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct AlpSection {
+    /// Token type of the section.
+    pub token_type: AlpTokenType,
+    /// Tx type, including the parsed fields.
+    pub tx_type: AlpTxType,
+}
+
+/// Tx type of an ALP section, with the fields specific to it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum AlpTxType {
+    /// Create a new token.
+    Genesis(Box<AlpGenesis>),
+    /// Create more tokens of an existing token.
+    Mint(AlpMint),
+    /// Move tokens to other outputs.
+    Send(AlpSend),
+    /// Burn an exact amount of tokens.
+    Burn(AlpBurn),
+}
+
+/// Tokens and mint batons created by a GENESIS or MINT section.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AlpMintData {
+    /// Amount of tokens created at outputs 1, 2, ...
+    pub amounts: Vec<Amount>,
+    /// Number of mint batons, at the outputs following the amounts.
+    pub num_batons: usize,
+}
+
+/// Fields of an ALP GENESIS section.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AlpGenesis {
+    /// Info the token is created with.
+    pub info: GenesisInfo,
+    /// Tokens and mint batons created.
+    pub mint_data: AlpMintData,
+}
+
+/// Fields of an ALP MINT section.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AlpMint {
+    /// Token ID of the tokens to mint.
+    pub token_id: TokenId,
+    /// Tokens and mint batons created.
+    pub mint_data: AlpMintData,
+}
+
+/// Fields of an ALP SEND section.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AlpSend {
+    /// Token ID of the tokens to send.
+    pub token_id: TokenId,
+    /// Amount of tokens sent to outputs 1, 2, ...
+    pub amounts: Vec<Amount>,
+}
+
+/// Fields of an ALP BURN section.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AlpBurn {
+    /// Token ID of the tokens to burn.
+    pub token_id: TokenId,
+    /// Amount of tokens burned.
+    pub burn_amount: Amount,
+}
+
+impl AlpTokenType {
+    /// Number of the token type as encoded in the section.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            AlpTokenType::Standard => 0,
+        }
+    }
+
+    /// Token type for the number as encoded in the section, or [`None`] if
+    /// it's unknown.
+    pub fn from_u8(token_type: u8) -> Option<Self> {
+        match token_type {
+            0 => Some(AlpTokenType::Standard),
+            _ => None,
+        }
+    }
+}
+
+impl AlpTxType {
+    /// Token ID of the section, or [`None`] for GENESIS, where it's the txid.
+    pub fn token_id(&self) -> Option<&TokenId> {
+        match self {
+            AlpTxType::Genesis(_) => None,
+            AlpTxType::Mint(mint) => Some(&mint.token_id),
+            AlpTxType::Send(send) => Some(&send.token_id),
+            AlpTxType::Burn(burn) => Some(&burn.token_id),
+        }
+    }
+}
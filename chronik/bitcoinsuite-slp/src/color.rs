@@ -0,0 +1,1124 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`ColoredTx`], which validates the tokens of a tx.
+
+use bitcoinsuite_core::{
+    script::{opcode::*, Op, Script},
+    tx::Tx,
+};
+use thiserror::Error;
+
+use crate::{
+    alp::{self, AlpMintData, AlpTxType},
+    empp,
+    slp::{self, SlpTokenType, SlpTxType, SLP_LOKAD_ID},
+    structs::{Amount, GenesisInfo, Token, TokenType, TokenVariant, TxType},
+    token_id::TokenId,
+};
+
+/// Tokens of a tx, after validating its OP_RETURN against the tokens spent by
+/// its inputs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ColoredTx {
+    /// Tokens involved in the tx, in the order they appear in the OP_RETURN,
+    /// followed by the tokens that are only spent by the inputs.
+    pub entries: Vec<TokenTxEntry>,
+    /// Token of each output, [`None`] for outputs that don't carry any tokens.
+    pub outputs: Vec<Option<ColoredOutput>>,
+    /// Errors when parsing the OP_RETURN. For SLP, this means no tokens are
+    /// created; for ALP, all sections after the failing one are ignored.
+    pub failed_parsings: Vec<FailedParsing>,
+}
+
+/// Token carried by an output of a [`ColoredTx`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ColoredOutput {
+    /// Index into [`ColoredTx::entries`] of the token of this output.
+    pub entry_idx: usize,
+    /// Amount or mint baton carried by this output.
+    pub variant: TokenVariant,
+}
+
+/// How a tx affects one token, including burns and why it's invalid, if so.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenTxEntry {
+    /// Token ID of the token.
+    pub token_id: TokenId,
+    /// Token type of the token.
+    pub token_type: TokenType,
+    /// Tx type of the OP_RETURN or section for this token, or [`None`] if
+    /// the token is only spent by the inputs.
+    pub tx_type: Option<TxType>,
+    /// Info of the token created, for GENESIS txs.
+    pub genesis_info: Option<GenesisInfo>,
+    /// Sum of the amounts of this token spent by the inputs.
+    pub input_sum: u128,
+    /// Amount the OP_RETURN explicitly burns, e.g. using a BURN tx.
+    pub intentional_burn: u128,
+    /// Amount actually burned by the tx, i.e. spent but not sent to outputs.
+    pub actual_burn: u128,
+    /// Whether mint batons of this token have been spent without a MINT.
+    pub burns_mint_batons: bool,
+    /// Why this entry is invalid; an empty list means it's valid.
+    pub failures: Vec<ColorError>,
+}
+
+/// Error when parsing the OP_RETURN of a tx.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum FailedParsing {
+    /// OP_RETURN has the SLP LOKAD ID, but is invalid.
+    #[error("Invalid SLP: {0}")]
+    Slp(slp::ParseError),
+
+    /// OP_RETURN starts like eMPP, but is invalid.
+    #[error("Invalid eMPP: {0}")]
+    Empp(empp::ParseError),
+
+    /// eMPP pushdata has the ALP LOKAD ID, but is an invalid section.
+    #[error("Invalid ALP at pushdata idx {pushdata_idx}: {error}")]
+    Alp {
+        /// Index of the eMPP pushdata of the section.
+        pushdata_idx: usize,
+        /// Why the section is invalid.
+        error: alp::ParseError,
+    },
+}
+
+/// Why the OP_RETURN or section of a token is invalid given the tx.
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum ColorError {
+    /// Amounts or mint batons assigned to outputs that don't exist.
+    #[error("Too few outputs, expected {expected} but got {actual}")]
+    TooFewOutputs {
+        /// Number of outputs required by the OP_RETURN.
+        expected: usize,
+        /// Number of outputs of the tx.
+        actual: usize,
+    },
+
+    /// Output already carries tokens of a previous ALP section.
+    #[error("Output {0} is already colored by a previous section")]
+    OverlappingOutput(usize),
+
+    /// ALP GENESIS section not in the first position.
+    #[error("GENESIS must be the first ALP section")]
+    GenesisMustBeFirst,
+
+    /// More than one GENESIS, MINT or SEND section for the same token.
+    #[error("Duplicate section for the same token ID")]
+    DuplicateTokenId,
+
+    /// MINT without spending a mint baton of the token.
+    #[error("Missing MINT baton")]
+    MissingMintBaton,
+
+    /// NFT1 child GENESIS without spending an NFT1 group token at input 0.
+    #[error(
+        "NFT1 child GENESIS requires spending an NFT1 group token at input 0"
+    )]
+    MissingNft1Group,
+
+    /// SEND spends fewer tokens than it sends to its outputs.
+    #[error("Insufficient input sum, required {required} but got {actual}")]
+    InsufficientInputSum {
+        /// Sum of the amounts sent to outputs.
+        required: u128,
+        /// Sum of the amounts spent by the inputs.
+        actual: u128,
+    },
+}
+
+use self::ColorError::*;
+
+enum Protocol {
+    Slp,
+    Alp,
+}
+
+struct Coloring<'a> {
+    tx: &'a Tx,
+    colored: ColoredTx,
+    /// Outputs each entry assigns tokens to, before validating.
+    claims: Vec<Vec<(usize, TokenVariant)>>,
+}
+
+impl ColoredTx {
+    /// Color the outputs of the tx, given the tokens of the outputs spent by
+    /// its inputs (one for each input, [`None`] if it doesn't carry tokens).
+    ///
+    /// Outputs of invalid OP_RETURNs or sections don't carry tokens, and all
+    /// spent tokens that don't end up in an output are counted as burned.
+    pub fn color(tx: &Tx, spent_tokens: &[Option<Token>]) -> Self {
+        let mut coloring = Coloring {
+            tx,
+            colored: ColoredTx {
+                outputs: vec![None; tx.outputs.len()],
+                ..Default::default()
+            },
+            claims: vec![],
+        };
+        if let Some(output) = tx.outputs.first() {
+            match detect_protocol(&output.script) {
+                Some(Protocol::Slp) => coloring.color_slp(&output.script),
+                Some(Protocol::Alp) => coloring.color_alp(&output.script),
+                None => {}
+            }
+        }
+        coloring.verify(spent_tokens);
+        coloring.colored
+    }
+
+    /// Whether the tx has anything to do with tokens, i.e. has token entries
+    /// or a token OP_RETURN that failed parsing.
+    pub fn has_any(&self) -> bool {
+        !self.entries.is_empty() || !self.failed_parsings.is_empty()
+    }
+
+    /// Token of the output at `out_idx`, or [`None`] if it doesn't carry any.
+    pub fn output_token(&self, out_idx: usize) -> Option<Token> {
+        let output = (*self.outputs.get(out_idx)?)?;
+        let entry = &self.entries[output.entry_idx];
+        Some(Token {
+            token_id: entry.token_id,
+            token_type: entry.token_type,
+            variant: output.variant,
+        })
+    }
+}
+
+impl TokenTxEntry {
+    /// Whether the OP_RETURN or section of this token is valid.
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Amount burned that hasn't been burned explicitly.
+    pub fn unintentional_burn(&self) -> u128 {
+        self.actual_burn.saturating_sub(self.intentional_burn)
+    }
+}
+
+fn detect_protocol(script: &Script) -> Option<Protocol> {
+    let mut ops = script.iter_ops();
+    if !matches!(ops.next(), Some(Ok(Op::Code(OP_RETURN)))) {
+        return None;
+    }
+    match ops.next()? {
+        Ok(Op::Code(OP_RESERVED)) => Some(Protocol::Alp),
+        Ok(Op::Push(_, lokad_id)) if lokad_id.as_ref() == SLP_LOKAD_ID => {
+            Some(Protocol::Slp)
+        }
+        _ => None,
+    }
+}
+
+impl Coloring<'_> {
+    fn color_slp(&mut self, script: &Script) {
+        let slp_data = match slp::parse(script) {
+            Ok(slp_data) => slp_data,
+            Err(err) => {
+                self.colored.failed_parsings.push(FailedParsing::Slp(err));
+                return;
+            }
+        };
+        let token_type = TokenType::Slp(slp_data.token_type);
+        match slp_data.tx_type {
+            SlpTxType::Genesis(genesis) => {
+                let token_id = TokenId::new(self.tx.txid());
+                let idx = self.add_entry(token_id, token_type, TxType::Genesis);
+                self.colored.entries[idx].genesis_info = Some(genesis.info);
+                self.claim_slp(
+                    idx,
+                    genesis.initial_quantity,
+                    genesis.mint_baton_out_idx,
+                );
+            }
+            SlpTxType::Mint(mint) => {
+                let idx =
+                    self.add_entry(mint.token_id, token_type, TxType::Mint);
+                self.claim_slp(
+                    idx,
+                    mint.additional_quantity,
+                    mint.mint_baton_out_idx,
+                );
+            }
+            SlpTxType::Send(send) => {
+                let idx =
+                    self.add_entry(send.token_id, token_type, TxType::Send);
+                self.claim_amounts(idx, &send.output_quantities);
+            }
+            SlpTxType::Burn(burn) => {
+                let idx =
+                    self.add_entry(burn.token_id, token_type, TxType::Burn);
+                self.colored.entries[idx].intentional_burn =
+                    burn.burn_quantity.into();
+            }
+        }
+    }
+
+    /// SLP GENESIS and MINT quietly drop tokens and mint batons assigned to
+    /// outputs that don't exist.
+    fn claim_slp(
+        &mut self,
+        entry_idx: usize,
+        quantity: Amount,
+        mint_baton_out_idx: Option<u8>,
+    ) {
+        let num_outputs = self.tx.outputs.len();
+        let claims = &mut self.claims[entry_idx];
+        if quantity > 0 && num_outputs > 1 {
+            claims.push((1, TokenVariant::Amount(quantity)));
+        }
+        if let Some(out_idx) = mint_baton_out_idx {
+            if usize::from(out_idx) < num_outputs {
+                claims.push((out_idx.into(), TokenVariant::MintBaton));
+            }
+        }
+    }
+
+    fn color_alp(&mut self, script: &Script) {
+        let empp_data = match empp::parse(script) {
+            Ok(empp_data) => empp_data,
+            Err(err) => {
+                self.colored.failed_parsings.push(FailedParsing::Empp(err));
+                return;
+            }
+        };
+        let mut num_sections = 0;
+        for (pushdata_idx, pushdata) in empp_data.iter().enumerate() {
+            let section = match alp::parse_section(pushdata) {
+                Ok(Some(section)) => section,
+                Ok(None) => continue,
+                Err(error) => {
+                    self.colored.failed_parsings.push(FailedParsing::Alp {
+                        pushdata_idx,
+                        error,
+                    });
+                    break;
+                }
+            };
+            let token_type = TokenType::Alp(section.token_type);
+            match section.tx_type {
+                AlpTxType::Genesis(genesis) => {
+                    let token_id = TokenId::new(self.tx.txid());
+                    let idx =
+                        self.add_entry(token_id, token_type, TxType::Genesis);
+                    let entry = &mut self.colored.entries[idx];
+                    entry.genesis_info = Some(genesis.info);
+                    if num_sections > 0 {
+                        entry.failures.push(GenesisMustBeFirst);
+                    }
+                    self.claim_alp_mint_data(idx, &genesis.mint_data);
+                }
+                AlpTxType::Mint(mint) => {
+                    if let Some(idx) = self.add_alp_entry(
+                        mint.token_id,
+                        token_type,
+                        TxType::Mint,
+                    ) {
+                        self.claim_alp_mint_data(idx, &mint.mint_data);
+                    }
+                }
+                AlpTxType::Send(send) => {
+                    if let Some(idx) = self.add_alp_entry(
+                        send.token_id,
+                        token_type,
+                        TxType::Send,
+                    ) {
+                        self.claim_amounts(idx, &send.amounts);
+                    }
+                }
+                AlpTxType::Burn(burn) => {
+                    let idx = match self.entry_idx(&burn.token_id, token_type) {
+                        Some(idx) => idx,
+                        None => self.add_entry(
+                            burn.token_id,
+                            token_type,
+                            TxType::Burn,
+                        ),
+                    };
+                    self.colored.entries[idx].intentional_burn +=
+                        u128::from(burn.burn_amount);
+                }
+            }
+            num_sections += 1;
+        }
+    }
+
+    /// Add an entry for a MINT or SEND section. If there's already an entry
+    /// for the token, a BURN entry is upgraded to the new tx type, otherwise
+    /// the existing entry is marked as invalid and [`None`] is returned.
+    fn add_alp_entry(
+        &mut self,
+        token_id: TokenId,
+        token_type: TokenType,
+        tx_type: TxType,
+    ) -> Option<usize> {
+        let idx = match self.entry_idx(&token_id, token_type) {
+            Some(idx) => idx,
+            None => return Some(self.add_entry(token_id, token_type, tx_type)),
+        };
+        let entry = &mut self.colored.entries[idx];
+        if entry.tx_type == Some(TxType::Burn) {
+            entry.tx_type = Some(tx_type);
+            return Some(idx);
+        }
+        if !entry.failures.contains(&DuplicateTokenId) {
+            entry.failures.push(DuplicateTokenId);
+        }
+        None
+    }
+
+    fn claim_alp_mint_data(
+        &mut self,
+        entry_idx: usize,
+        mint_data: &AlpMintData,
+    ) {
+        self.claim_amounts(entry_idx, &mint_data.amounts);
+        let first_baton_idx = mint_data.amounts.len() + 1;
+        let baton_idxs =
+            first_baton_idx..first_baton_idx + mint_data.num_batons;
+        self.check_num_outputs(entry_idx, baton_idxs.end);
+        self.claims[entry_idx].extend(
+            baton_idxs.map(|out_idx| (out_idx, TokenVariant::MintBaton)),
+        );
+    }
+
+    /// Assign the amounts to outputs 1, 2, ..., skipping amounts of 0.
+    fn claim_amounts(&mut self, entry_idx: usize, amounts: &[Amount]) {
+        self.check_num_outputs(entry_idx, amounts.len() + 1);
+        self.claims[entry_idx].extend(
+            amounts
+                .iter()
+                .enumerate()
+                .filter(|(_, &amount)| amount > 0)
+                .map(|(idx, &amount)| (idx + 1, TokenVariant::Amount(amount))),
+        );
+    }
+
+    fn check_num_outputs(&mut self, entry_idx: usize, expected: usize) {
+        let actual = self.tx.outputs.len();
+        if expected > actual {
+            self.colored.entries[entry_idx]
+                .failures
+                .push(TooFewOutputs { expected, actual });
+        }
+    }
+
+    fn entry_idx(
+        &self,
+        token_id: &TokenId,
+        token_type: TokenType,
+    ) -> Option<usize> {
+        self.colored.entries.iter().position(|entry| {
+            entry.token_id == *token_id && entry.token_type == token_type
+        })
+    }
+
+    fn add_entry(
+        &mut self,
+        token_id: TokenId,
+        token_type: TokenType,
+        tx_type: TxType,
+    ) -> usize {
+        self.push_entry(token_id, token_type, Some(tx_type))
+    }
+
+    fn push_entry(
+        &mut self,
+        token_id: TokenId,
+        token_type: TokenType,
+        tx_type: Option<TxType>,
+    ) -> usize {
+        self.colored.entries.push(TokenTxEntry {
+            token_id,
+            token_type,
+            tx_type,
+            genesis_info: None,
+            input_sum: 0,
+            intentional_burn: 0,
+            actual_burn: 0,
+            burns_mint_batons: false,
+            failures: vec![],
+        });
+        self.claims.push(vec![]);
+        self.colored.entries.len() - 1
+    }
+
+    fn verify(&mut self, spent_tokens: &[Option<Token>]) {
+        // Sum up the spent tokens, adding entries for tokens only in inputs
+        let mut has_mint_baton = vec![false; self.colored.entries.len()];
+        for token in spent_tokens.iter().flatten() {
+            let idx = match self.entry_idx(&token.token_id, token.token_type) {
+                Some(idx) => idx,
+                None => {
+                    has_mint_baton.push(false);
+                    self.push_entry(token.token_id, token.token_type, None)
+                }
+            };
+            match token.variant {
+                TokenVariant::Amount(amount) => {
+                    self.colored.entries[idx].input_sum += u128::from(amount);
+                }
+                TokenVariant::MintBaton => has_mint_baton[idx] = true,
+            }
+        }
+
+        // Check the entries against the spent tokens
+        for (idx, &has_mint_baton) in has_mint_baton.iter().enumerate() {
+            let entry = &self.colored.entries[idx];
+            match entry.tx_type {
+                Some(TxType::Genesis)
+                    if entry.token_type
+                        == TokenType::Slp(SlpTokenType::Nft1Child) =>
+                {
+                    self.verify_nft1_child_genesis(idx, spent_tokens);
+                }
+                Some(TxType::Mint) if !has_mint_baton => {
+                    self.colored.entries[idx].failures.push(MissingMintBaton);
+                }
+                Some(TxType::Send) => {
+                    let required = claims_sum(&self.claims[idx]);
+                    let entry = &mut self.colored.entries[idx];
+                    if entry.input_sum < required {
+                        entry.failures.push(InsufficientInputSum {
+                            required,
+                            actual: entry.input_sum,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Assign the claimed outputs of the valid entries, in order
+        for idx in 0..self.colored.entries.len() {
+            if !self.colored.entries[idx].is_valid() {
+                continue;
+            }
+            let overlap = self.claims[idx]
+                .iter()
+                .find(|&&(out_idx, _)| self.colored.outputs[out_idx].is_some());
+            if let Some(&(out_idx, _)) = overlap {
+                self.colored.entries[idx]
+                    .failures
+                    .push(OverlappingOutput(out_idx));
+                continue;
+            }
+            for &(out_idx, variant) in &self.claims[idx] {
+                self.colored.outputs[out_idx] = Some(ColoredOutput {
+                    entry_idx: idx,
+                    variant,
+                });
+            }
+        }
+
+        // Whatever is spent but not sent is burned
+        for (idx, entry) in self.colored.entries.iter_mut().enumerate() {
+            let is_valid = entry.failures.is_empty();
+            let is_send = entry.tx_type == Some(TxType::Send);
+            let is_mint = entry.tx_type == Some(TxType::Mint);
+            let sent = if is_valid && is_send {
+                claims_sum(&self.claims[idx])
+            } else {
+                0
+            };
+            entry.actual_burn = entry.input_sum - sent;
+            entry.burns_mint_batons =
+                has_mint_baton[idx] && !(is_valid && is_mint);
+        }
+    }
+
+    /// NFT1 child GENESIS consumes the NFT1 group token at input 0.
+    fn verify_nft1_child_genesis(
+        &mut self,
+        entry_idx: usize,
+        spent_tokens: &[Option<Token>],
+    ) {
+        let group_token = match spent_tokens.first() {
+            Some(Some(
+                token @ Token {
+                    token_type: TokenType::Slp(SlpTokenType::Nft1Group),
+                    variant: TokenVariant::Amount(amount),
+                    ..
+                },
+            )) if *amount > 0 => token,
+            _ => {
+                self.colored.entries[entry_idx]
+                    .failures
+                    .push(MissingNft1Group);
+                return;
+            }
+        };
+        if let Some(group_idx) =
+            self.entry_idx(&group_token.token_id, group_token.token_type)
+        {
+            self.colored.entries[group_idx].intentional_burn += 1;
+        }
+    }
+}
+
+fn claims_sum(claims: &[(usize, TokenVariant)]) -> u128 {
+    claims
+        .iter()
+        .map(|&(_, variant)| u128::from(variant.amount()))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::{
+        script::{opcode::*, Script, ScriptMut},
+        tx::{Tx, TxId, TxMut, TxOutput},
+    };
+    use bytes::Bytes;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        alp::{
+            sections_script, AlpBurn, AlpGenesis, AlpMint, AlpMintData,
+            AlpSection, AlpSend, AlpTokenType, AlpTxType,
+        },
+        color::{
+            ColorError, ColoredOutput, ColoredTx, FailedParsing, TokenTxEntry,
+        },
+        slp::{self, SlpTokenType},
+        structs::{GenesisInfo, Token, TokenType, TokenVariant, TxType},
+        token_id::TokenId,
+    };
+
+    const FUNGIBLE: TokenType = TokenType::Slp(SlpTokenType::Fungible);
+    const ALP: TokenType = TokenType::Alp(AlpTokenType::Standard);
+
+    fn slp_script(pushes: &[&[u8]]) -> Script {
+        let mut script = ScriptMut::default();
+        script.put_opcodes([OP_RETURN]);
+        for push in pushes {
+            if push.is_empty() {
+                script.put_opcodes([OP_PUSHDATA1]);
+            }
+            script.put_bytecode(&[push.len() as u8]);
+            script.put_bytecode(push);
+        }
+        script.freeze()
+    }
+
+    fn make_tx(script: Script, num_outputs: usize) -> Tx {
+        let mut outputs = vec![TxOutput::default(); num_outputs];
+        outputs[0].script = script;
+        Tx::with_txid(
+            TxId::from([1; 32]),
+            TxMut {
+                outputs,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn token(token_id: TokenId, token_type: TokenType, amount: u64) -> Token {
+        Token {
+            token_id,
+            token_type,
+            variant: TokenVariant::Amount(amount),
+        }
+    }
+
+    fn baton(token_id: TokenId, token_type: TokenType) -> Token {
+        Token {
+            token_id,
+            token_type,
+            variant: TokenVariant::MintBaton,
+        }
+    }
+
+    fn entry(
+        token_id: TokenId,
+        token_type: TokenType,
+        tx_type: Option<TxType>,
+    ) -> TokenTxEntry {
+        TokenTxEntry {
+            token_id,
+            token_type,
+            tx_type,
+            genesis_info: None,
+            input_sum: 0,
+            intentional_burn: 0,
+            actual_burn: 0,
+            burns_mint_batons: false,
+            failures: vec![],
+        }
+    }
+
+    fn amount(entry_idx: usize, amount: u64) -> Option<ColoredOutput> {
+        Some(ColoredOutput {
+            entry_idx,
+            variant: TokenVariant::Amount(amount),
+        })
+    }
+
+    fn mint_baton(entry_idx: usize) -> Option<ColoredOutput> {
+        Some(ColoredOutput {
+            entry_idx,
+            variant: TokenVariant::MintBaton,
+        })
+    }
+
+    #[test]
+    fn test_color_slp() {
+        let genesis_id = TokenId::new(TxId::from([1; 32]));
+        let token_id = TokenId::new(TxId::from([7; 32]));
+        let group_id = TokenId::new(TxId::from([8; 32]));
+        let qty = |qty: u64| qty.to_be_bytes();
+
+        // Non-token OP_RETURN
+        let tx = make_tx(slp_script(&[b"TEST", &[1]]), 2);
+        let colored = ColoredTx::color(&tx, &[]);
+        assert_eq!(
+            colored,
+            ColoredTx {
+                outputs: vec![None, None],
+                ..Default::default()
+            }
+        );
+        assert!(!colored.has_any());
+
+        // GENESIS with mint baton at output 2
+        let tx = make_tx(
+            slp_script(&[
+                b"SLP\0",
+                &[1],
+                b"GENESIS",
+                b"TKN",
+                b"Token",
+                b"",
+                b"",
+                &[2],
+                &[2],
+                &qty(1000),
+            ]),
+            3,
+        );
+        let colored = ColoredTx::color(&tx, &[]);
+        assert_eq!(
+            colored.entries,
+            vec![TokenTxEntry {
+                genesis_info: Some(GenesisInfo {
+                    token_ticker: Bytes::from_static(b"TKN"),
+                    token_name: Bytes::from_static(b"Token"),
+                    decimals: 2,
+                    ..Default::default()
+                }),
+                ..entry(genesis_id, FUNGIBLE, Some(TxType::Genesis))
+            }]
+        );
+        assert_eq!(colored.outputs, vec![None, amount(0, 1000), mint_baton(0)]);
+        assert_eq!(
+            colored.output_token(1),
+            Some(token(genesis_id, FUNGIBLE, 1000)),
+        );
+        assert_eq!(colored.output_token(0), None);
+        assert_eq!(colored.output_token(3), None);
+
+        // SEND, with 100 tokens and an unrelated token burned
+        let send_script = slp_script(&[
+            b"SLP\0",
+            &[1],
+            b"SEND",
+            &[7; 32],
+            &qty(400),
+            &qty(0),
+            &qty(600),
+        ]);
+        let spent_tokens = [
+            Some(token(token_id, FUNGIBLE, 600)),
+            None,
+            Some(token(group_id, FUNGIBLE, 20)),
+            Some(token(token_id, FUNGIBLE, 500)),
+        ];
+        let colored =
+            ColoredTx::color(&make_tx(send_script.clone(), 4), &spent_tokens);
+        assert_eq!(
+            colored.entries,
+            vec![
+                TokenTxEntry {
+                    input_sum: 1100,
+                    actual_burn: 100,
+                    ..entry(token_id, FUNGIBLE, Some(TxType::Send))
+                },
+                TokenTxEntry {
+                    input_sum: 20,
+                    actual_burn: 20,
+                    ..entry(group_id, FUNGIBLE, None)
+                },
+            ]
+        );
+        assert_eq!(colored.entries[0].unintentional_burn(), 100);
+        assert_eq!(
+            colored.outputs,
+            vec![None, amount(0, 400), None, amount(0, 600)]
+        );
+
+        // SEND with too few outputs or insufficient inputs burns everything
+        let colored =
+            ColoredTx::color(&make_tx(send_script.clone(), 3), &spent_tokens);
+        assert_eq!(
+            colored.entries[0].failures,
+            vec![ColorError::TooFewOutputs {
+                expected: 4,
+                actual: 3,
+            }]
+        );
+        assert_eq!(colored.entries[0].actual_burn, 1100);
+        assert_eq!(colored.outputs, vec![None; 3]);
+        let colored =
+            ColoredTx::color(&make_tx(send_script, 4), &spent_tokens[..2]);
+        assert_eq!(
+            colored.entries,
+            vec![TokenTxEntry {
+                input_sum: 600,
+                actual_burn: 600,
+                failures: vec![ColorError::InsufficientInputSum {
+                    required: 1000,
+                    actual: 600,
+                }],
+                ..entry(token_id, FUNGIBLE, Some(TxType::Send))
+            }]
+        );
+        assert_eq!(colored.outputs, vec![None; 4]);
+
+        // MINT requires a mint baton
+        let mint_script =
+            slp_script(&[b"SLP\0", &[1], b"MINT", &[7; 32], b"", &qty(50)]);
+        let colored = ColoredTx::color(
+            &make_tx(mint_script.clone(), 2),
+            &[Some(token(token_id, FUNGIBLE, 30))],
+        );
+        assert_eq!(
+            colored.entries,
+            vec![TokenTxEntry {
+                input_sum: 30,
+                actual_burn: 30,
+                failures: vec![ColorError::MissingMintBaton],
+                ..entry(token_id, FUNGIBLE, Some(TxType::Mint))
+            }]
+        );
+        assert_eq!(colored.outputs, vec![None, None]);
+        let colored = ColoredTx::color(
+            &make_tx(mint_script.clone(), 2),
+            &[Some(baton(token_id, FUNGIBLE))],
+        );
+        assert_eq!(
+            colored.entries,
+            vec![entry(token_id, FUNGIBLE, Some(TxType::Mint))]
+        );
+        assert_eq!(colored.outputs, vec![None, amount(0, 50)]);
+
+        // Mint baton of a different token type doesn't count, and is burned
+        let colored = ColoredTx::color(
+            &make_tx(mint_script, 2),
+            &[Some(baton(token_id, ALP))],
+        );
+        assert_eq!(
+            colored.entries[0].failures,
+            vec![ColorError::MissingMintBaton]
+        );
+        assert_eq!(
+            colored.entries[1],
+            TokenTxEntry {
+                burns_mint_batons: true,
+                ..entry(token_id, ALP, None)
+            }
+        );
+
+        // BURN
+        let colored = ColoredTx::color(
+            &make_tx(
+                slp_script(&[b"SLP\0", &[1], b"BURN", &[7; 32], &qty(100)]),
+                1,
+            ),
+            &[Some(token(token_id, FUNGIBLE, 150))],
+        );
+        assert_eq!(
+            colored.entries,
+            vec![TokenTxEntry {
+                input_sum: 150,
+                intentional_burn: 100,
+                actual_burn: 150,
+                ..entry(token_id, FUNGIBLE, Some(TxType::Burn))
+            }]
+        );
+        assert_eq!(colored.entries[0].unintentional_burn(), 50);
+
+        // NFT1 child GENESIS consumes an NFT1 group token at input 0
+        let nft1_group = TokenType::Slp(SlpTokenType::Nft1Group);
+        let nft1_child = TokenType::Slp(SlpTokenType::Nft1Child);
+        let child_genesis = make_tx(
+            slp_script(&[
+                b"SLP\0",
+                &[0x41],
+                b"GENESIS",
+                b"",
+                b"",
+                b"",
+                b"",
+                &[0],
+                b"",
+                &qty(1),
+            ]),
+            2,
+        );
+        let colored = ColoredTx::color(
+            &child_genesis,
+            &[Some(token(group_id, nft1_group, 1))],
+        );
+        assert_eq!(
+            colored.entries,
+            vec![
+                TokenTxEntry {
+                    genesis_info: Some(GenesisInfo::default()),
+                    ..entry(genesis_id, nft1_child, Some(TxType::Genesis))
+                },
+                TokenTxEntry {
+                    input_sum: 1,
+                    intentional_burn: 1,
+                    actual_burn: 1,
+                    ..entry(group_id, nft1_group, None)
+                },
+            ]
+        );
+        assert_eq!(colored.outputs, vec![None, amount(0, 1)]);
+        let colored = ColoredTx::color(
+            &child_genesis,
+            &[None, Some(token(group_id, nft1_group, 1))],
+        );
+        assert_eq!(
+            colored.entries[0].failures,
+            vec![ColorError::MissingNft1Group]
+        );
+        assert_eq!(colored.outputs, vec![None, None]);
+
+        // Invalid SLP burns all tokens
+        let colored = ColoredTx::color(
+            &make_tx(slp_script(&[b"SLP\0", &[1], b"SWAP"]), 2),
+            &[Some(token(token_id, FUNGIBLE, 10))],
+        );
+        assert_eq!(
+            colored,
+            ColoredTx {
+                entries: vec![TokenTxEntry {
+                    input_sum: 10,
+                    actual_burn: 10,
+                    ..entry(token_id, FUNGIBLE, None)
+                }],
+                outputs: vec![None, None],
+                failed_parsings: vec![FailedParsing::Slp(
+                    slp::ParseError::UnknownTxType(Bytes::from_static(b"SWAP")),
+                )],
+            }
+        );
+    }
+
+    #[test]
+    fn test_color_alp() {
+        let genesis_id = TokenId::new(TxId::from([1; 32]));
+        let token1 = TokenId::new(TxId::from([7; 32]));
+        let token2 = TokenId::new(TxId::from([8; 32]));
+        let section = |tx_type: AlpTxType| AlpSection {
+            token_type: AlpTokenType::Standard,
+            tx_type,
+        };
+        let genesis = |amounts: Vec<u64>, num_batons: usize| {
+            section(AlpTxType::Genesis(Box::new(AlpGenesis {
+                info: GenesisInfo::default(),
+                mint_data: AlpMintData {
+                    amounts,
+                    num_batons,
+                },
+            })))
+        };
+        let mint = |token_id: TokenId, amounts: Vec<u64>| {
+            section(AlpTxType::Mint(AlpMint {
+                token_id,
+                mint_data: AlpMintData {
+                    amounts,
+                    num_batons: 0,
+                },
+            }))
+        };
+        let send = |token_id: TokenId, amounts: Vec<u64>| {
+            section(AlpTxType::Send(AlpSend { token_id, amounts }))
+        };
+        let burn = |token_id: TokenId, burn_amount: u64| {
+            section(AlpTxType::Burn(AlpBurn {
+                token_id,
+                burn_amount,
+            }))
+        };
+        let color = |sections: &[AlpSection],
+                     num_outputs: usize,
+                     spent_tokens: &[Option<Token>]| {
+            let script = sections_script(sections).unwrap();
+            ColoredTx::color(&make_tx(script, num_outputs), spent_tokens)
+        };
+        let genesis_entry = TokenTxEntry {
+            genesis_info: Some(GenesisInfo {
+                data: Some(Bytes::new()),
+                auth_pubkey: Some(Bytes::new()),
+                ..Default::default()
+            }),
+            ..entry(genesis_id, ALP, Some(TxType::Genesis))
+        };
+
+        // GENESIS, MINT, SEND and BURN in one tx
+        let spent_tokens = [
+            Some(token(token1, ALP, 100)),
+            Some(baton(token1, ALP)),
+            Some(token(token2, ALP, 70)),
+        ];
+        let colored = color(
+            &[
+                genesis(vec![10], 1),
+                mint(token1, vec![0, 0, 5]),
+                send(token2, vec![0, 0, 0, 40, 20]),
+                burn(token2, 10),
+            ],
+            6,
+            &spent_tokens,
+        );
+        assert_eq!(
+            colored,
+            ColoredTx {
+                entries: vec![
+                    genesis_entry.clone(),
+                    TokenTxEntry {
+                        input_sum: 100,
+                        actual_burn: 100,
+                        ..entry(token1, ALP, Some(TxType::Mint))
+                    },
+                    TokenTxEntry {
+                        input_sum: 70,
+                        intentional_burn: 10,
+                        actual_burn: 10,
+                        ..entry(token2, ALP, Some(TxType::Send))
+                    },
+                ],
+                outputs: vec![
+                    None,
+                    amount(0, 10),
+                    mint_baton(0),
+                    amount(1, 5),
+                    amount(2, 40),
+                    amount(2, 20),
+                ],
+                failed_parsings: vec![],
+            }
+        );
+
+        // Overlapping outputs invalidate the later section
+        let colored = color(
+            &[genesis(vec![10], 0), send(token1, vec![30, 20])],
+            4,
+            &spent_tokens,
+        );
+        assert_eq!(
+            colored.entries[1].failures,
+            vec![ColorError::OverlappingOutput(1)]
+        );
+        assert_eq!(colored.entries[1].actual_burn, 100);
+        assert!(colored.entries[1].burns_mint_batons);
+        assert_eq!(colored.outputs, vec![None, amount(0, 10), None, None]);
+
+        // Duplicate sections and GENESIS not first are invalid, and too few
+        // outputs for mint batons
+        let colored = color(
+            &[
+                burn(token1, 10),
+                send(token1, vec![40]),
+                send(token1, vec![0, 50]),
+                genesis(vec![], 3),
+            ],
+            3,
+            &spent_tokens,
+        );
+        assert_eq!(
+            colored.entries,
+            vec![
+                TokenTxEntry {
+                    input_sum: 100,
+                    intentional_burn: 10,
+                    actual_burn: 100,
+                    burns_mint_batons: true,
+                    failures: vec![ColorError::DuplicateTokenId],
+                    ..entry(token1, ALP, Some(TxType::Send))
+                },
+                TokenTxEntry {
+                    failures: vec![
+                        ColorError::GenesisMustBeFirst,
+                        ColorError::TooFewOutputs {
+                            expected: 4,
+                            actual: 3,
+                        },
+                    ],
+                    ..genesis_entry.clone()
+                },
+                TokenTxEntry {
+                    input_sum: 70,
+                    actual_burn: 70,
+                    ..entry(token2, ALP, None)
+                },
+            ]
+        );
+        assert_eq!(colored.outputs, vec![None; 3]);
+
+        // Sections after a failed parsing are ignored
+        let mut script = ScriptMut::default();
+        script.put_bytecode(
+            sections_script(&[send(token2, vec![70])])
+                .unwrap()
+                .bytecode(),
+        );
+        script.put_bytecode(&[0x05]);
+        script.put_bytecode(b"SLP2\x01");
+        script.put_bytecode(
+            &sections_script(&[send(token1, vec![0, 100])])
+                .unwrap()
+                .bytecode()[2..],
+        );
+        let colored =
+            ColoredTx::color(&make_tx(script.freeze(), 3), &spent_tokens);
+        assert_eq!(
+            colored.failed_parsings,
+            vec![FailedParsing::Alp {
+                pushdata_idx: 1,
+                error: crate::alp::ParseError::UnknownTokenType(1),
+            }]
+        );
+        assert_eq!(
+            colored
+                .entries
+                .iter()
+                .map(|entry| entry.tx_type)
+                .collect::<Vec<_>>(),
+            vec![Some(TxType::Send), None],
+        );
+        assert_eq!(colored.outputs, vec![None, amount(0, 70), None]);
+
+        // Invalid eMPP
+        let colored = ColoredTx::color(
+            &make_tx(
+                Script::new(vec![OP_RETURN::N, OP_RESERVED::N, OP_1::N].into()),
+                1,
+            ),
+            &[],
+        );
+        assert_eq!(
+            colored.failed_parsings,
+            vec![FailedParsing::Empp(
+                crate::empp::ParseError::InvalidPushOpcode(OP_1),
+            )]
+        );
+        assert!(colored.has_any());
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use bitcoinsuite_core::script::{opcode::*, Script, ScriptMut};
+use bytes::Bytes;
+
+/// Build an eMPP OP_RETURN script with the given pushdata, the inverse of
+/// [`crate::empp::parse`].
+///
+/// Each pushdata is encoded with the smallest push opcode possible. Note that
+/// eMPP doesn't allow empty pushdata, so these should be non-empty.
+pub fn build(pushdata: &[Bytes]) -> Script {
+    let payload_len = pushdata.iter().map(|data| data.len() + 5).sum::<usize>();
+    let mut script = ScriptMut::with_capacity(2 + payload_len);
+    script.put_opcodes([OP_RETURN, OP_RESERVED]);
+    for data in pushdata {
+        match data.len() {
+            len @ 0..=0x4b => script.put_bytecode(&[len as u8]),
+            len @ 0x4c..=0xff => {
+                script.put_opcodes([OP_PUSHDATA1]);
+                script.put_bytecode(&[len as u8]);
+            }
+            len @ 0x100..=0xffff => {
+                script.put_opcodes([OP_PUSHDATA2]);
+                script.put_bytecode(&(len as u16).to_le_bytes());
+            }
+            len => {
+                script.put_opcodes([OP_PUSHDATA4]);
+                script.put_bytecode(&(len as u32).to_le_bytes());
+            }
+        }
+        script.put_bytecode(data);
+    }
+    script.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::script::{opcode::*, Script};
+    use bytes::Bytes;
+
+    use crate::empp::{build, parse};
+
+    #[test]
+    fn test_empp_build() {
+        assert_eq!(
+            build(&[]),
+            Script::new(vec![OP_RETURN::N, OP_RESERVED::N].into()),
+        );
+        assert_eq!(
+            build(&[Bytes::from_static(&[77]), Bytes::from_static(&[88, 99])]),
+            Script::new(
+                vec![OP_RETURN::N, OP_RESERVED::N, 1, 77, 2, 88, 99].into()
+            ),
+        );
+        for (len, prefix) in [
+            (0x4b, vec![0x4b]),
+            (0x4c, vec![OP_PUSHDATA1::N, 0x4c]),
+            (0xff, vec![OP_PUSHDATA1::N, 0xff]),
+            (0x100, vec![OP_PUSHDATA2::N, 0x00, 0x01]),
+            (0xffff, vec![OP_PUSHDATA2::N, 0xff, 0xff]),
+            (0x10000, vec![OP_PUSHDATA4::N, 0x00, 0x00, 0x01, 0x00]),
+        ] {
+            let data = Bytes::from(vec![0x77; len]);
+            let script = build(std::slice::from_ref(&data));
+            let expected_prefix =
+                [[OP_RETURN::N, OP_RESERVED::N].as_slice(), &prefix].concat();
+            assert_eq!(
+                &script.bytecode()[..expected_prefix.len()],
+                expected_prefix.as_slice(),
+            );
+            assert_eq!(parse(&script), Ok(vec![data]));
+        }
+    }
+}
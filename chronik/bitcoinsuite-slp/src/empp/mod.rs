@@ -48,6 +48,8 @@
 //! All of these are considered valid for pushing the payload 0x77:
 //! 0x0177, 0x4c0177, 0x4d010077, 0x4e0100000077.
 
+mod build;
 mod parse;
 
+pub use self::build::*;
 pub use self::parse::*;
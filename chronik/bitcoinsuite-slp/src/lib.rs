@@ -3,5 +3,10 @@
 // file COPYING or http://www.opensource.org/licenses/mit-license.php.
 
 abc_rust_lint::lint! {
+    pub mod alp;
+    pub mod color;
     pub mod empp;
+    pub mod slp;
+    pub mod structs;
+    pub mod token_id;
 }
@@ -0,0 +1,47 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! # SLP (Simple Ledger Protocol) v1.
+//!
+//! Tokens encoded in the OP_RETURN of the first output of a tx.
+//!
+//! ## Specification
+//!
+//! A valid SLP script looks like this:
+//!
+//! 1. OP_RETURN (0x6a)
+//! 2. The LOKAD ID `b"SLP\0"`
+//! 3. The token type, 1 or 2 bytes big-endian (1 = fungible, 0x81 = NFT1
+//! group, 0x41 = NFT1 child)
+//! 4. The tx type, one of `b"GENESIS"`, `b"MINT"`, `b"SEND"` or `b"BURN"`
+//! 5. The fields of the tx type (see below)
+//!
+//! All pushes must be encoded the following way:
+//!
+//! 1. Any non-push opcode (e.g. OP_CHECKSIG) makes the script invalid.
+//!
+//! 2. Single-byte push opcodes (i.e. OP_0, OP_RESERVED, OP_1NEGATE, OP_1, ...,
+//! OP_16) make the script invalid. Empty fields have to be pushed using
+//! OP_PUSHDATA1, OP_PUSHDATA2 or OP_PUSHDATA4.
+//!
+//! 3. Amounts are 8 bytes big-endian, token IDs are 32 bytes big-endian.
+//!
+//! The fields by tx type are:
+//!
+//! - GENESIS: token ticker, token name, URL, document hash (0 or 32 bytes),
+//! decimals (1 byte, 0-9), mint baton output index (0 or 1 byte, at least 2),
+//! initial quantity.
+//! - MINT: token ID, mint baton output index (0 or 1 byte, at least 2),
+//! additional quantity.
+//! - SEND: token ID, 1 to 19 output quantities, for outputs 1 to 19.
+//! - BURN: token ID, burn quantity.
+//!
+//! NFT1 child tokens must have 0 decimals, no mint baton and an initial
+//! quantity of 1 and can't be minted.
+
+mod parse;
+mod structs;
+
+pub use self::parse::*;
+pub use self::structs::*;
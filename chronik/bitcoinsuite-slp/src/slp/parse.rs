@@ -0,0 +1,652 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use bitcoinsuite_core::{
+    error::DataError,
+    script::{opcode::*, Op, Script},
+};
+use bytes::Bytes;
+use thiserror::Error;
+
+use crate::{
+    slp::{
+        SlpBurn, SlpData, SlpGenesis, SlpMint, SlpSend, SlpTokenType,
+        SlpTxType, MAX_SEND_OUTPUTS, SLP_LOKAD_ID,
+    },
+    structs::{Amount, GenesisInfo},
+    token_id::TokenId,
+};
+
+/// Errors when parsing an SLP tx failed.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum ParseError {
+    /// Script doesn't parse
+    #[error("Failed parsing script: {0}")]
+    DataError(#[from] DataError),
+
+    /// Script empty
+    #[error("Empty script")]
+    EmptyScript,
+
+    /// Script not empty, but didn't get OP_RETURN
+    #[error("Expected OP_RETURN, but got {0:?}")]
+    MissingOpReturn(Op),
+
+    /// Cannot use single-byte push opcodes in SLP
+    #[error(
+        "Invalid push opcode {0}: OP_0, OP_1NEGATE, OP_RESERVED, OP_1, .. \
+         OP_16 not allowed"
+    )]
+    InvalidPushOpcode(Opcode),
+
+    /// Got a non-push opcode, which is not allowed
+    #[error("Invalid non-push opcode {0}")]
+    InvalidNonPushOpcode(Opcode),
+
+    /// Got OP_RETURN, but then nothing followed
+    #[error("Missing LOKAD ID")]
+    MissingLokadId,
+
+    /// First push isn't the SLP LOKAD ID
+    #[error("Invalid LOKAD ID: {}", hex::encode(.0))]
+    InvalidLokadId(Bytes),
+
+    /// Script ended before the token type
+    #[error("Missing token type")]
+    MissingTokenType,
+
+    /// Token type is not one of the supported ones
+    #[error("Unknown token type: {0:#x}")]
+    UnknownTokenType(u16),
+
+    /// Script ended before the tx type
+    #[error("Missing tx type")]
+    MissingTxType,
+
+    /// Tx type is not GENESIS, MINT, SEND or BURN
+    #[error("Unknown tx type: {}", hex::encode(.0))]
+    UnknownTxType(Bytes),
+
+    /// Fewer pushes than required for the tx type
+    #[error("Too few pushes, expected at least {expected} but got {actual}")]
+    TooFewPushes {
+        /// Minimum number of pushes after the OP_RETURN for the tx type
+        expected: usize,
+        /// Number of pushes in the script after the OP_RETURN
+        actual: usize,
+    },
+
+    /// More pushes than allowed for the tx type
+    #[error("Too many pushes, expected at most {expected} but got {actual}")]
+    TooManyPushes {
+        /// Maximum number of pushes after the OP_RETURN for the tx type
+        expected: usize,
+        /// Number of pushes in the script after the OP_RETURN
+        actual: usize,
+    },
+
+    /// A field has a size not allowed for it
+    #[error(
+        "Field {field_name} has invalid size {actual}, expected one of \
+         {expected:?}"
+    )]
+    InvalidFieldSize {
+        /// Name of the field
+        field_name: &'static str,
+        /// Allowed sizes of the field
+        expected: &'static [usize],
+        /// Actual size of the field
+        actual: usize,
+    },
+
+    /// Decimals must be 0-9
+    #[error("Decimals must be at most 9, but got {0}")]
+    InvalidDecimals(u8),
+
+    /// Mint baton can't be at the OP_RETURN or output 1
+    #[error("Mint baton output index must be at least 2, but got {0}")]
+    InvalidMintBatonIdx(u8),
+
+    /// NFT1 child GENESIS must have 0 decimals
+    #[error("NFT1 child GENESIS must have 0 decimals, but got {0}")]
+    Nft1ChildInvalidDecimals(u8),
+
+    /// NFT1 child GENESIS can't have a mint baton
+    #[error("NFT1 child GENESIS can't have a mint baton, but got {0}")]
+    Nft1ChildInvalidMintBaton(u8),
+
+    /// NFT1 child GENESIS must create exactly 1 token
+    #[error("NFT1 child GENESIS must have initial quantity 1, but got {0}")]
+    Nft1ChildInvalidInitialQuantity(Amount),
+
+    /// NFT1 child tokens can't be minted
+    #[error("NFT1 child tokens can't be minted")]
+    Nft1ChildCannotMint,
+}
+
+use self::ParseError::*;
+
+/// Parse the given script according to the SLP (Simple Ledger Protocol) v1.
+///
+/// See [`crate::slp`] for details.
+pub fn parse(script: &Script) -> Result<SlpData, ParseError> {
+    let mut ops = script.iter_ops();
+    let op_return = ops.next().ok_or(EmptyScript)??;
+    if !matches!(op_return, Op::Code(OP_RETURN)) {
+        return Err(MissingOpReturn(op_return));
+    }
+    let mut pushes = Vec::new();
+    for pushop in ops {
+        let payload = match pushop? {
+            Op::Code(opcode @ Opcode(OP_0::N | OP_1NEGATE::N..=OP_16::N)) => {
+                return Err(InvalidPushOpcode(opcode))
+            }
+            Op::Code(opcode) => return Err(InvalidNonPushOpcode(opcode)),
+            Op::Push(_, payload) => payload,
+        };
+        pushes.push(payload);
+    }
+    let lokad_id = pushes.first().ok_or(MissingLokadId)?;
+    if lokad_id.as_ref() != SLP_LOKAD_ID {
+        return Err(InvalidLokadId(lokad_id.clone()));
+    }
+    let token_type = pushes.get(1).ok_or(MissingTokenType)?;
+    let token_type = parse_token_type(token_type)?;
+    let tx_type = pushes.get(2).ok_or(MissingTxType)?;
+    let tx_type = match tx_type.as_ref() {
+        b"GENESIS" => parse_genesis(token_type, &pushes)?,
+        b"MINT" => parse_mint(token_type, &pushes)?,
+        b"SEND" => parse_send(&pushes)?,
+        b"BURN" => parse_burn(&pushes)?,
+        _ => return Err(UnknownTxType(tx_type.clone())),
+    };
+    Ok(SlpData {
+        token_type,
+        tx_type,
+    })
+}
+
+fn parse_token_type(push: &Bytes) -> Result<SlpTokenType, ParseError> {
+    let token_type = match push[..] {
+        [token_type] => token_type as u16,
+        [hi, lo] => u16::from_be_bytes([hi, lo]),
+        _ => {
+            return Err(InvalidFieldSize {
+                field_name: "token_type",
+                expected: &[1, 2],
+                actual: push.len(),
+            })
+        }
+    };
+    SlpTokenType::from_u16(token_type).ok_or(UnknownTokenType(token_type))
+}
+
+fn parse_genesis(
+    token_type: SlpTokenType,
+    pushes: &[Bytes],
+) -> Result<SlpTxType, ParseError> {
+    check_num_pushes(pushes, 10, 10)?;
+    let hash = match pushes[6].len() {
+        0 => None,
+        _ => Some(parse_array::<32>("hash", &[0, 32], &pushes[6])?),
+    };
+    let [decimals] = parse_array::<1>("decimals", &[1], &pushes[7])?;
+    if decimals > 9 {
+        return Err(InvalidDecimals(decimals));
+    }
+    let mint_baton_out_idx = parse_mint_baton_out_idx(&pushes[8])?;
+    let initial_quantity = parse_amount("initial_quantity", &pushes[9])?;
+    if token_type == SlpTokenType::Nft1Child {
+        if decimals != 0 {
+            return Err(Nft1ChildInvalidDecimals(decimals));
+        }
+        if let Some(out_idx) = mint_baton_out_idx {
+            return Err(Nft1ChildInvalidMintBaton(out_idx));
+        }
+        if initial_quantity != 1 {
+            return Err(Nft1ChildInvalidInitialQuantity(initial_quantity));
+        }
+    }
+    Ok(SlpTxType::Genesis(Box::new(SlpGenesis {
+        info: GenesisInfo {
+            token_ticker: pushes[3].clone(),
+            token_name: pushes[4].clone(),
+            url: pushes[5].clone(),
+            hash,
+            data: None,
+            auth_pubkey: None,
+            decimals,
+        },
+        mint_baton_out_idx,
+        initial_quantity,
+    })))
+}
+
+fn parse_mint(
+    token_type: SlpTokenType,
+    pushes: &[Bytes],
+) -> Result<SlpTxType, ParseError> {
+    check_num_pushes(pushes, 6, 6)?;
+    if token_type == SlpTokenType::Nft1Child {
+        return Err(Nft1ChildCannotMint);
+    }
+    Ok(SlpTxType::Mint(SlpMint {
+        token_id: parse_token_id(&pushes[3])?,
+        mint_baton_out_idx: parse_mint_baton_out_idx(&pushes[4])?,
+        additional_quantity: parse_amount("additional_quantity", &pushes[5])?,
+    }))
+}
+
+fn parse_send(pushes: &[Bytes]) -> Result<SlpTxType, ParseError> {
+    check_num_pushes(pushes, 5, 4 + MAX_SEND_OUTPUTS)?;
+    Ok(SlpTxType::Send(SlpSend {
+        token_id: parse_token_id(&pushes[3])?,
+        output_quantities: pushes[4..]
+            .iter()
+            .map(|push| parse_amount("output_quantity", push))
+            .collect::<Result<Vec<_>, _>>()?,
+    }))
+}
+
+fn parse_burn(pushes: &[Bytes]) -> Result<SlpTxType, ParseError> {
+    check_num_pushes(pushes, 5, 5)?;
+    Ok(SlpTxType::Burn(SlpBurn {
+        token_id: parse_token_id(&pushes[3])?,
+        burn_quantity: parse_amount("burn_quantity", &pushes[4])?,
+    }))
+}
+
+fn check_num_pushes(
+    pushes: &[Bytes],
+    min: usize,
+    max: usize,
+) -> Result<(), ParseError> {
+    if pushes.len() < min {
+        return Err(TooFewPushes {
+            expected: min,
+            actual: pushes.len(),
+        });
+    }
+    if pushes.len() > max {
+        return Err(TooManyPushes {
+            expected: max,
+            actual: pushes.len(),
+        });
+    }
+    Ok(())
+}
+
+fn parse_array<const N: usize>(
+    field_name: &'static str,
+    expected: &'static [usize],
+    push: &Bytes,
+) -> Result<[u8; N], ParseError> {
+    push.as_ref().try_into().map_err(|_| InvalidFieldSize {
+        field_name,
+        expected,
+        actual: push.len(),
+    })
+}
+
+fn parse_token_id(push: &Bytes) -> Result<TokenId, ParseError> {
+    Ok(TokenId::from_be_bytes(parse_array("token_id", &[32], push)?))
+}
+
+fn parse_amount(
+    field_name: &'static str,
+    push: &Bytes,
+) -> Result<Amount, ParseError> {
+    Ok(Amount::from_be_bytes(parse_array(field_name, &[8], push)?))
+}
+
+fn parse_mint_baton_out_idx(push: &Bytes) -> Result<Option<u8>, ParseError> {
+    if push.is_empty() {
+        return Ok(None);
+    }
+    let [out_idx] = parse_array::<1>("mint_baton_out_idx", &[0, 1], push)?;
+    if out_idx < 2 {
+        return Err(InvalidMintBatonIdx(out_idx));
+    }
+    Ok(Some(out_idx))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::{
+        error::DataError,
+        script::{opcode::*, Op, Script, ScriptMut},
+    };
+    use bytes::Bytes;
+
+    use crate::{
+        slp::{
+            parse, ParseError, SlpBurn, SlpData, SlpGenesis, SlpMint, SlpSend,
+            SlpTokenType, SlpTxType,
+        },
+        structs::GenesisInfo,
+        token_id::TokenId,
+    };
+
+    fn slp_script(pushes: &[&[u8]]) -> Script {
+        let mut script = ScriptMut::default();
+        script.put_opcodes([OP_RETURN]);
+        for push in pushes {
+            if push.is_empty() {
+                script.put_opcodes([OP_PUSHDATA1]);
+            }
+            script.put_bytecode(&[push.len() as u8]);
+            script.put_bytecode(push);
+        }
+        script.freeze()
+    }
+
+    fn amount(amount: u64) -> [u8; 8] {
+        amount.to_be_bytes()
+    }
+
+    #[test]
+    fn test_parse_slp_script_errors() {
+        assert_eq!(
+            parse(&Script::new(vec![].into())),
+            Err(ParseError::EmptyScript),
+        );
+        assert_eq!(
+            parse(&Script::new(vec![OP_CHECKSIG::N].into())),
+            Err(ParseError::MissingOpReturn(Op::Code(OP_CHECKSIG))),
+        );
+        assert_eq!(
+            parse(&Script::new(vec![OP_RETURN::N, 2, 1].into())),
+            Err(ParseError::DataError(DataError::InvalidLength {
+                expected: 2,
+                actual: 1,
+            })),
+        );
+        for opcode in [OP_0, OP_1NEGATE, OP_RESERVED, OP_1, OP_16] {
+            assert_eq!(
+                parse(&Script::new(vec![OP_RETURN::N, opcode.number()].into())),
+                Err(ParseError::InvalidPushOpcode(opcode)),
+            );
+        }
+        assert_eq!(
+            parse(&Script::new(vec![OP_RETURN::N, OP_CHECKSIG::N].into())),
+            Err(ParseError::InvalidNonPushOpcode(OP_CHECKSIG)),
+        );
+        assert_eq!(
+            parse(&slp_script(&[])),
+            Err(ParseError::MissingLokadId),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\x01"])),
+            Err(ParseError::InvalidLokadId(Bytes::from_static(b"SLP\x01"))),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0"])),
+            Err(ParseError::MissingTokenType),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0", &[0, 0, 1]])),
+            Err(ParseError::InvalidFieldSize {
+                field_name: "token_type",
+                expected: &[1, 2],
+                actual: 3,
+            }),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0", &[2]])),
+            Err(ParseError::UnknownTokenType(2)),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0", &[1, 1]])),
+            Err(ParseError::UnknownTokenType(0x101)),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0", &[1]])),
+            Err(ParseError::MissingTxType),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0", &[1], b"SWAP"])),
+            Err(ParseError::UnknownTxType(Bytes::from_static(b"SWAP"))),
+        );
+    }
+
+    #[test]
+    fn test_parse_slp_genesis() {
+        let genesis = |token_type: u8,
+                       hash: &[u8],
+                       decimals: &[u8],
+                       baton: &[u8],
+                       quantity: &[u8]| {
+            parse(&slp_script(&[
+                b"SLP\0",
+                &[token_type],
+                b"GENESIS",
+                b"TKN",
+                b"Token",
+                b"https://example.com",
+                hash,
+                decimals,
+                baton,
+                quantity,
+            ]))
+        };
+        assert_eq!(
+            genesis(1, &[], &[4], &[2], &amount(1000)),
+            Ok(SlpData {
+                token_type: SlpTokenType::Fungible,
+                tx_type: SlpTxType::Genesis(Box::new(SlpGenesis {
+                    info: GenesisInfo {
+                        token_ticker: Bytes::from_static(b"TKN"),
+                        token_name: Bytes::from_static(b"Token"),
+                        url: Bytes::from_static(b"https://example.com"),
+                        hash: None,
+                        decimals: 4,
+                        ..Default::default()
+                    },
+                    mint_baton_out_idx: Some(2),
+                    initial_quantity: 1000,
+                })),
+            }),
+        );
+        assert_eq!(
+            genesis(0x41, &[7; 32], &[0], &[], &amount(1)),
+            Ok(SlpData {
+                token_type: SlpTokenType::Nft1Child,
+                tx_type: SlpTxType::Genesis(Box::new(SlpGenesis {
+                    info: GenesisInfo {
+                        token_ticker: Bytes::from_static(b"TKN"),
+                        token_name: Bytes::from_static(b"Token"),
+                        url: Bytes::from_static(b"https://example.com"),
+                        hash: Some([7; 32]),
+                        decimals: 0,
+                        ..Default::default()
+                    },
+                    mint_baton_out_idx: None,
+                    initial_quantity: 1,
+                })),
+            }),
+        );
+        assert_eq!(
+            genesis(1, &[7; 31], &[0], &[], &amount(1)),
+            Err(ParseError::InvalidFieldSize {
+                field_name: "hash",
+                expected: &[0, 32],
+                actual: 31,
+            }),
+        );
+        assert_eq!(
+            genesis(1, &[], &[], &[], &amount(1)),
+            Err(ParseError::InvalidFieldSize {
+                field_name: "decimals",
+                expected: &[1],
+                actual: 0,
+            }),
+        );
+        assert_eq!(
+            genesis(1, &[], &[10], &[], &amount(1)),
+            Err(ParseError::InvalidDecimals(10)),
+        );
+        assert_eq!(
+            genesis(1, &[], &[0], &[2, 3], &amount(1)),
+            Err(ParseError::InvalidFieldSize {
+                field_name: "mint_baton_out_idx",
+                expected: &[0, 1],
+                actual: 2,
+            }),
+        );
+        assert_eq!(
+            genesis(1, &[], &[0], &[1], &amount(1)),
+            Err(ParseError::InvalidMintBatonIdx(1)),
+        );
+        assert_eq!(
+            genesis(1, &[], &[0], &[], &[1; 7]),
+            Err(ParseError::InvalidFieldSize {
+                field_name: "initial_quantity",
+                expected: &[8],
+                actual: 7,
+            }),
+        );
+        assert_eq!(
+            genesis(0x41, &[], &[1], &[], &amount(1)),
+            Err(ParseError::Nft1ChildInvalidDecimals(1)),
+        );
+        assert_eq!(
+            genesis(0x41, &[], &[0], &[2], &amount(1)),
+            Err(ParseError::Nft1ChildInvalidMintBaton(2)),
+        );
+        assert_eq!(
+            genesis(0x41, &[], &[0], &[], &amount(2)),
+            Err(ParseError::Nft1ChildInvalidInitialQuantity(2)),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0", &[1], b"GENESIS", b"", b""])),
+            Err(ParseError::TooFewPushes {
+                expected: 10,
+                actual: 5,
+            }),
+        );
+        assert_eq!(
+            parse(&slp_script(&[
+                b"SLP\0",
+                &[1],
+                b"GENESIS",
+                b"",
+                b"",
+                b"",
+                b"",
+                &[0],
+                b"",
+                &amount(1),
+                b"",
+            ])),
+            Err(ParseError::TooManyPushes {
+                expected: 10,
+                actual: 11,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_parse_slp_mint_send_burn() {
+        let token_id = TokenId::from_be_bytes([7; 32]);
+        assert_eq!(
+            parse(&slp_script(&[
+                b"SLP\0",
+                &[0x81],
+                b"MINT",
+                &[7; 32],
+                &[],
+                &amount(55),
+            ])),
+            Ok(SlpData {
+                token_type: SlpTokenType::Nft1Group,
+                tx_type: SlpTxType::Mint(SlpMint {
+                    token_id,
+                    mint_baton_out_idx: None,
+                    additional_quantity: 55,
+                }),
+            }),
+        );
+        assert_eq!(
+            parse(&slp_script(&[
+                b"SLP\0",
+                &[0x41],
+                b"MINT",
+                &[7; 32],
+                &[],
+                &amount(55),
+            ])),
+            Err(ParseError::Nft1ChildCannotMint),
+        );
+        assert_eq!(
+            parse(&slp_script(&[
+                b"SLP\0",
+                &[1],
+                b"MINT",
+                &[7; 33],
+                &[2],
+                &amount(55),
+            ])),
+            Err(ParseError::InvalidFieldSize {
+                field_name: "token_id",
+                expected: &[32],
+                actual: 33,
+            }),
+        );
+        assert_eq!(
+            parse(&slp_script(&[
+                b"SLP\0",
+                &[0, 1],
+                b"SEND",
+                &[7; 32],
+                &amount(10),
+                &amount(0),
+                &amount(u64::MAX),
+            ])),
+            Ok(SlpData {
+                token_type: SlpTokenType::Fungible,
+                tx_type: SlpTxType::Send(SlpSend {
+                    token_id,
+                    output_quantities: vec![10, 0, u64::MAX],
+                }),
+            }),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0", &[1], b"SEND", &[7; 32]])),
+            Err(ParseError::TooFewPushes {
+                expected: 5,
+                actual: 4,
+            }),
+        );
+        let mut pushes: Vec<&[u8]> = vec![b"SLP\0", &[1], b"SEND", &[7; 32]];
+        let amount = amount(1);
+        pushes.extend(std::iter::repeat(amount.as_ref()).take(19));
+        assert!(parse(&slp_script(&pushes)).is_ok());
+        pushes.push(&amount);
+        assert_eq!(
+            parse(&slp_script(&pushes)),
+            Err(ParseError::TooManyPushes {
+                expected: 23,
+                actual: 24,
+            }),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0", &[1], b"BURN", &[7; 32], &amount])),
+            Ok(SlpData {
+                token_type: SlpTokenType::Fungible,
+                tx_type: SlpTxType::Burn(SlpBurn {
+                    token_id,
+                    burn_quantity: 1,
+                }),
+            }),
+        );
+        assert_eq!(
+            parse(&slp_script(&[b"SLP\0", &[1], b"BURN", &[7; 32], &[1; 9]])),
+            Err(ParseError::InvalidFieldSize {
+                field_name: "burn_quantity",
+                expected: &[8],
+                actual: 9,
+            }),
+        );
+    }
+}
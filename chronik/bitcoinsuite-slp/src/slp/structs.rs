@@ -0,0 +1,121 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use crate::{
+    structs::{Amount, GenesisInfo},
+    token_id::TokenId,
+};
+
+/// LOKAD ID of SLP, the first push after the OP_RETURN.
+pub const SLP_LOKAD_ID: [u8; 4] = *b"SLP\0";
+
+/// Maximum number of output quantities in a SEND tx.
+pub const MAX_SEND_OUTPUTS: usize = 19;
+
+/// Token type of an SLP tx.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SlpTokenType {
+    /// Fungible token, token type 1.
+    Fungible,
+    /// Group token for NFT1 children, token type 0x81.
+    Nft1Group,
+    /// NFT1 child token, token type 0x41.
+    Nft1Child,
+}
+
+/// Parsed SLP OP_RETURN script.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SlpData {
+    /// Token type of the tx.
+    pub token_type: SlpTokenType,
+    /// Tx type, including the parsed fields.
+    pub tx_type: SlpTxType,
+}
+
+/// Tx type of an SLP tx, with the fields specific to it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SlpTxType {
+    /// Create a new token.
+    Genesis(Box<SlpGenesis>),
+    /// Create more tokens of an existing token.
+    Mint(SlpMint),
+    /// Move tokens to other outputs.
+    Send(SlpSend),
+    /// Burn an exact amount of tokens.
+    Burn(SlpBurn),
+}
+
+/// Fields of an SLP GENESIS tx.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SlpGenesis {
+    /// Info the token is created with.
+    pub info: GenesisInfo,
+    /// Output index of the mint baton, or [`None`] if there's none.
+    pub mint_baton_out_idx: Option<u8>,
+    /// Amount of tokens created at output 1.
+    pub initial_quantity: Amount,
+}
+
+/// Fields of an SLP MINT tx.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SlpMint {
+    /// Token ID of the tokens to mint.
+    pub token_id: TokenId,
+    /// Output index of the new mint baton, or [`None`] to destroy the baton.
+    pub mint_baton_out_idx: Option<u8>,
+    /// Amount of tokens created at output 1.
+    pub additional_quantity: Amount,
+}
+
+/// Fields of an SLP SEND tx.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SlpSend {
+    /// Token ID of the tokens to send.
+    pub token_id: TokenId,
+    /// Amount of tokens sent to outputs 1, 2, ...
+    pub output_quantities: Vec<Amount>,
+}
+
+/// Fields of an SLP BURN tx.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SlpBurn {
+    /// Token ID of the tokens to burn.
+    pub token_id: TokenId,
+    /// Amount of tokens burned.
+    pub burn_quantity: Amount,
+}
+
+impl SlpTokenType {
+    /// Number of the token type as encoded in the OP_RETURN.
+    pub fn to_u16(self) -> u16 {
+        match self {
+            SlpTokenType::Fungible => 0x01,
+            SlpTokenType::Nft1Group => 0x81,
+            SlpTokenType::Nft1Child => 0x41,
+        }
+    }
+
+    /// Token type for the number as encoded in the OP_RETURN, or [`None`] if
+    /// it's unknown.
+    pub fn from_u16(token_type: u16) -> Option<Self> {
+        match token_type {
+            0x01 => Some(SlpTokenType::Fungible),
+            0x81 => Some(SlpTokenType::Nft1Group),
+            0x41 => Some(SlpTokenType::Nft1Child),
+            _ => None,
+        }
+    }
+}
+
+impl SlpTxType {
+    /// Token ID of the tx, or [`None`] for GENESIS txs, where it's the txid.
+    pub fn token_id(&self) -> Option<&TokenId> {
+        match self {
+            SlpTxType::Genesis(_) => None,
+            SlpTxType::Mint(mint) => Some(&mint.token_id),
+            SlpTxType::Send(send) => Some(&send.token_id),
+            SlpTxType::Burn(burn) => Some(&burn.token_id),
+        }
+    }
+}
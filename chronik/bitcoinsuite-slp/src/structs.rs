@@ -0,0 +1,88 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Structs shared between the token protocols.
+
+use bytes::Bytes;
+
+use crate::{alp::AlpTokenType, slp::SlpTokenType, token_id::TokenId};
+
+/// Amount of tokens, in base units (i.e. ignoring decimals).
+pub type Amount = u64;
+
+/// Info a token has been created with in its GENESIS tx.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct GenesisInfo {
+    /// Short ticker of the token, e.g. "USDT".
+    pub token_ticker: Bytes,
+    /// Display name of the token, e.g. "Tether".
+    pub token_name: Bytes,
+    /// URL for more info about the token, e.g. a website.
+    pub url: Bytes,
+    /// Hash of the token document, if any (SLP only).
+    pub hash: Option<[u8; 32]>,
+    /// Arbitrary data attached to the token (ALP only).
+    pub data: Option<Bytes>,
+    /// Public key of the token's issuer (ALP only).
+    pub auth_pubkey: Option<Bytes>,
+    /// Number of decimal places to display amounts with.
+    pub decimals: u8,
+}
+
+/// Token type of a token, by protocol.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TokenType {
+    /// Token of the SLP protocol.
+    Slp(SlpTokenType),
+    /// Token of the ALP protocol.
+    Alp(AlpTokenType),
+}
+
+/// Tx type of a token tx or ALP section, independent of the protocol.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TxType {
+    /// Create a new token.
+    Genesis,
+    /// Create more tokens of an existing token.
+    Mint,
+    /// Move tokens to other outputs.
+    Send,
+    /// Burn an exact amount of tokens.
+    Burn,
+}
+
+/// What a token output carries: either an amount of tokens or a mint baton.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TokenVariant {
+    /// Amount of tokens, in base units.
+    Amount(Amount),
+    /// Mint baton, which allows creating more tokens in a MINT tx.
+    MintBaton,
+}
+
+/// Token carried by a tx output.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Token {
+    /// Token ID of the token.
+    pub token_id: TokenId,
+    /// Token type of the token.
+    pub token_type: TokenType,
+    /// Amount or mint baton carried by the output.
+    pub variant: TokenVariant,
+}
+
+impl TokenVariant {
+    /// Amount of tokens carried, which is 0 for mint batons.
+    pub fn amount(self) -> Amount {
+        match self {
+            TokenVariant::Amount(amount) => amount,
+            TokenVariant::MintBaton => 0,
+        }
+    }
+
+    /// Whether this is a mint baton.
+    pub fn is_mint_baton(self) -> bool {
+        self == TokenVariant::MintBaton
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`TokenId`].
+
+use bitcoinsuite_core::{
+    error::DataError,
+    hash::{Hashed, Sha256d},
+    tx::TxId,
+};
+
+/// ID of a token, which is the [`TxId`] of the GENESIS tx that created it.
+///
+/// Like txids, token IDs are displayed as big-endian hex strings, and stored
+/// in little-endian byte order. Note that token protocols encode token IDs in
+/// big-endian byte order in the OP_RETURN.
+#[derive(Clone, Copy, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TokenId(TxId);
+
+impl std::fmt::Debug for TokenId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TokenId({})", self.0)
+    }
+}
+
+impl std::fmt::Display for TokenId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl TokenId {
+    /// Create a new [`TokenId`] from the [`TxId`] of the GENESIS tx.
+    pub fn new(txid: TxId) -> Self {
+        TokenId(txid)
+    }
+
+    /// [`TxId`] of the GENESIS tx of this token.
+    pub fn txid(&self) -> &TxId {
+        &self.0
+    }
+
+    /// Create a [`TokenId`] from big-endian bytes, as they're encoded in the
+    /// OP_RETURN of token txs.
+    ///
+    /// ```
+    /// # use bitcoinsuite_slp::token_id::TokenId;
+    /// let mut bytes = [0; 32];
+    /// bytes[0] = 0xab;
+    /// let token_id = TokenId::from_be_bytes(bytes);
+    /// assert_eq!(token_id.to_be_bytes(), bytes);
+    /// assert_eq!(token_id.txid().as_bytes()[31], 0xab);
+    /// assert_eq!(token_id.to_string(), format!("ab{}", "0".repeat(62)));
+    /// ```
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        TokenId(TxId::from(Sha256d::from_be_bytes(bytes)))
+    }
+
+    /// Return the token ID in big-endian byte order, as it's encoded in the
+    /// OP_RETURN of token txs.
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = self.0.to_bytes();
+        bytes.reverse();
+        bytes
+    }
+}
+
+impl From<TxId> for TokenId {
+    fn from(txid: TxId) -> Self {
+        TokenId(txid)
+    }
+}
+
+impl std::str::FromStr for TokenId {
+    type Err = DataError;
+
+    /// Parse a [`TokenId`] from a big-endian hex string.
+    ///
+    /// ```
+    /// # use bitcoinsuite_slp::token_id::TokenId;
+    /// # use bitcoinsuite_core::error::DataError;
+    /// let hex = format!("10{}02", "0".repeat(60));
+    /// let token_id = hex.parse::<TokenId>().unwrap();
+    /// assert_eq!(token_id.to_string(), hex);
+    /// assert_eq!(token_id.to_be_bytes()[0], 0x10);
+    /// assert_eq!(
+    ///     "abcd".parse::<TokenId>(),
+    ///     Err(DataError::InvalidLength {
+    ///         expected: 32,
+    ///         actual: 2,
+    ///     }),
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(TokenId(s.parse::<TxId>()?))
+    }
+}
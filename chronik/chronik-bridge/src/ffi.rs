@@ -54,6 +54,8 @@ mod ffi_inner {
         pub undo_pos: u32,
         /// Serialized size of the block
         pub size: u64,
+        /// Serialized 80-byte header of the block
+        pub header: Vec<u8>,
         /// Txs of this block, including positions within the block/undo files.
         pub txs: Vec<BlockTx>,
     }
@@ -230,6 +232,16 @@ mod ffi_inner {
         /// Load the CTransaction from disk and serialize it.
         fn load_raw_tx(file_num: u32, data_pos: u32) -> Result<Vec<u8>>;
 
+        /// Load the CBlockHeader from disk and serialize it.
+        fn load_raw_block_header(
+            file_num: u32,
+            data_pos: u32,
+        ) -> Result<Vec<u8>>;
+
+        /// Serialize the CBlockHeader of this CBlockIndex, without reading
+        /// the block from disk.
+        fn get_block_header(block_index: &CBlockIndex) -> Vec<u8>;
+
         /// Get a BlockInfo for this CBlockIndex.
         fn get_block_info(block_index: &CBlockIndex) -> BlockInfo;
 
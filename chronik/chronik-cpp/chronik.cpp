@@ -29,7 +29,12 @@ bool Start(const Config &config, const node::NodeContext &node, bool fWipe) {
                                                  ? gArgs.GetArgs("-chronikbind")
                                                  : DEFAULT_BINDS),
             .default_port = BaseParams().ChronikPort(),
+            .electrum_hosts =
+                ToRustVec<rust::String>(gArgs.GetArgs("-chronikelectrumbind")),
+            .electrum_default_port = DEFAULT_ELECTRUM_PORT,
             .wipe_db = fWipe,
+            .persist_mempool = gArgs.GetBoolArg("-chronikpersistmempool",
+                                                DEFAULT_PERSIST_MEMPOOL),
         },
         config, node);
 }
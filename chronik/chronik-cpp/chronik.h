@@ -5,6 +5,7 @@
 #ifndef BITCOIN_CHRONIK_CPP_CHRONIK_H
 #define BITCOIN_CHRONIK_CPP_CHRONIK_H
 
+#include <cstdint>
 #include <string>
 #include <vector>
 
@@ -16,6 +17,8 @@ struct NodeContext;
 namespace chronik {
 
 static const std::vector<std::string> DEFAULT_BINDS = {"127.0.0.1", "::1"};
+static const bool DEFAULT_PERSIST_MEMPOOL = false;
+static const uint16_t DEFAULT_ELECTRUM_PORT = 50001;
 
 // Registers Chronik indexer as ValidationInterface, listens to HTTP queries
 bool Start(const Config &config, const node::NodeContext &node, bool fWipe);
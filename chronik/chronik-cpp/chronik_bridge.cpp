@@ -143,6 +143,9 @@ chronik_bridge::Block BridgeBlock(const CBlock &block,
         }
     }
 
+    CDataStream raw_header{SER_NETWORK, PROTOCOL_VERSION};
+    raw_header << block.GetBlockHeader();
+
     return {.hash = chronik::util::HashToArray(block.GetHash()),
             .prev_hash = chronik::util::HashToArray(block.hashPrevBlock),
             .n_bits = block.nBits,
@@ -152,6 +155,7 @@ chronik_bridge::Block BridgeBlock(const CBlock &block,
             .data_pos = bindex.nDataPos,
             .undo_pos = bindex.nUndoPos,
             .size = ::GetSerializeSize(block),
+            .header = chronik::util::ToRustVec<uint8_t>(raw_header),
             .txs = bridged_txs};
 }
 
@@ -290,6 +294,30 @@ rust::Vec<uint8_t> load_raw_tx(uint32_t file_num, uint32_t data_pos) {
     return chronik::util::ToRustVec<uint8_t>(raw_tx);
 }
 
+rust::Vec<uint8_t> load_raw_block_header(uint32_t file_num,
+                                         uint32_t data_pos) {
+    CAutoFile filein(node::OpenBlockFile(FlatFilePos(file_num, data_pos), true),
+                     SER_DISK, CLIENT_VERSION);
+    if (filein.IsNull()) {
+        throw std::runtime_error("Opening block file failed");
+    }
+    CBlockHeader header;
+    try {
+        filein >> header;
+    } catch (const std::exception &e) {
+        throw std::runtime_error("Reading block header from disk failed");
+    }
+    CDataStream raw_header{SER_NETWORK, PROTOCOL_VERSION};
+    raw_header << header;
+    return chronik::util::ToRustVec<uint8_t>(raw_header);
+}
+
+rust::Vec<uint8_t> get_block_header(const CBlockIndex &bindex) {
+    CDataStream raw_header{SER_NETWORK, PROTOCOL_VERSION};
+    raw_header << bindex.GetBlockHeader();
+    return chronik::util::ToRustVec<uint8_t>(raw_header);
+}
+
 BlockInfo get_block_info(const CBlockIndex &bindex) {
     return {
         .hash = chronik::util::HashToArray(bindex.GetBlockHash()),
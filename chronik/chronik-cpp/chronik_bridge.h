@@ -75,6 +75,10 @@ Block bridge_block(const CBlock &block, const CBlockIndex &bindex);
 
 Tx load_tx(uint32_t file_num, uint32_t data_pos, uint32_t undo_pos);
 rust::Vec<uint8_t> load_raw_tx(uint32_t file_num, uint32_t data_pos);
+rust::Vec<uint8_t> load_raw_block_header(uint32_t file_num,
+                                         uint32_t data_pos);
+
+rust::Vec<uint8_t> get_block_header(const CBlockIndex &index);
 
 BlockInfo get_block_info(const CBlockIndex &index);
 
@@ -14,9 +14,14 @@ use rocksdb::{ColumnFamilyDescriptor, IteratorMode};
 use thiserror::Error;
 
 use crate::{
-    groups::{ScriptHistoryWriter, ScriptUtxoWriter},
+    groups::{
+        LokadIdHistoryWriter, ScriptHashHistoryWriter, ScriptHashUtxoWriter,
+        ScriptHistoryWriter, ScriptUtxoWriter, TokenIdHistoryWriter,
+        TokenIdUtxoWriter,
+    },
     io::{
-        BlockStatsWriter, BlockWriter, MetadataWriter, SpentByWriter, TxWriter,
+        BlockStatsWriter, BlockWriter, MetadataWriter, SpentByWriter,
+        TokenInfoWriter, TokenTxWriter, TxWriter,
     },
 };
 
@@ -31,18 +36,39 @@ pub const CF_BLK_STATS: &str = "blk_stats";
 /// Column family for the block height of the first tx_num of that block. Used
 /// to get the block height of a tx.
 pub const CF_FIRST_TX_BY_BLK: &str = "first_tx_by_blk";
+/// Column family to store tx history by LOKAD ID.
+pub const CF_LOKAD_ID_HISTORY: &str = "lokad_id_history";
 /// Column family to lookup a block by its hash.
 pub const CF_LOOKUP_BLK_BY_HASH: &str = "lookup_blk_by_hash";
 /// Column family to lookup a tx by its hash.
 pub const CF_LOOKUP_TX_BY_HASH: &str = "lookup_tx_by_hash";
 /// Column family name for db metadata.
 pub const CF_META: &str = "meta";
+/// Column family for the balance of scripts, i.e. the sum and number of their
+/// UTXOs.
+pub const CF_SCRIPT_BALANCE: &str = "script_balance";
+/// Column family for the balance of script hashes, i.e. the sum and number
+/// of their UTXOs.
+pub const CF_SCRIPT_HASH_BALANCE: &str = "script_hash_balance";
+/// Column family to store tx history by script hash.
+pub const CF_SCRIPT_HASH_HISTORY: &str = "script_hash_history";
+/// Column family for utxos by script hash.
+pub const CF_SCRIPT_HASH_UTXO: &str = "script_hash_utxo";
 /// Column family to store tx history by script.
 pub const CF_SCRIPT_HISTORY: &str = "script_history";
 /// Column family for utxos by script.
 pub const CF_SCRIPT_UTXO: &str = "script_utxo";
 /// Column family to store which outputs have been spent by which tx inputs.
 pub const CF_SPENT_BY: &str = "spent_by";
+/// Column family to store tx history by token ID.
+pub const CF_TOKEN_ID_HISTORY: &str = "token_id_history";
+/// Column family for utxos by token ID.
+pub const CF_TOKEN_ID_UTXO: &str = "token_id_utxo";
+/// Column family for info about tokens, like their GENESIS info and
+/// circulating supply, by token ID.
+pub const CF_TOKEN_INFO: &str = "token_info";
+/// Column family for the token data of txs, by tx_num.
+pub const CF_TOKEN_TX: &str = "token_tx";
 /// Column family for the tx data.
 pub const CF_TX: &str = "tx";
 
@@ -77,11 +103,18 @@ impl Db {
         let mut cfs = Vec::new();
         BlockWriter::add_cfs(&mut cfs);
         BlockStatsWriter::add_cfs(&mut cfs);
+        LokadIdHistoryWriter::add_cfs(&mut cfs);
         MetadataWriter::add_cfs(&mut cfs);
         TxWriter::add_cfs(&mut cfs);
+        ScriptHashHistoryWriter::add_cfs(&mut cfs);
+        ScriptHashUtxoWriter::add_cfs(&mut cfs);
         ScriptHistoryWriter::add_cfs(&mut cfs);
         ScriptUtxoWriter::add_cfs(&mut cfs);
         SpentByWriter::add_cfs(&mut cfs);
+        TokenIdHistoryWriter::add_cfs(&mut cfs);
+        TokenIdUtxoWriter::add_cfs(&mut cfs);
+        TokenInfoWriter::add_cfs(&mut cfs);
+        TokenTxWriter::add_cfs(&mut cfs);
         Self::open_with_cfs(path, cfs)
     }
 
@@ -0,0 +1,72 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for calculating the fees and fee rates of txs.
+
+use bitcoinsuite_core::tx::TxMut;
+
+/// Fee paid by the tx in satoshis, i.e. the sum of the values of the coins
+/// spent by the inputs minus the sum of the values of the outputs.
+///
+/// Returns [`None`] if any of the spent coins is unknown, e.g. for coinbase
+/// txs.
+pub fn tx_fee(tx: &TxMut) -> Option<i64> {
+    let mut sum_input_sats = 0;
+    for input in &tx.inputs {
+        sum_input_sats += input.coin.as_ref()?.output.value;
+    }
+    let sum_output_sats =
+        tx.outputs.iter().map(|output| output.value).sum::<i64>();
+    Some(sum_input_sats - sum_output_sats)
+}
+
+/// Fee rate in sats/kB of a tx paying `fee` sats with the given serialized
+/// size in bytes.
+pub fn fee_rate(fee: i64, size: usize) -> i64 {
+    fee.saturating_mul(1000) / size.max(1) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::tx::{Coin, TxInput, TxMut, TxOutput};
+
+    use crate::fee::{fee_rate, tx_fee};
+
+    #[test]
+    fn test_tx_fee() {
+        let input = |value: Option<i64>| TxInput {
+            coin: value.map(|value| Coin {
+                output: TxOutput {
+                    value,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let output = |value: i64| TxOutput {
+            value,
+            ..Default::default()
+        };
+        let tx = |inputs, outputs| TxMut {
+            inputs,
+            outputs,
+            ..Default::default()
+        };
+        assert_eq!(tx_fee(&tx(vec![], vec![])), Some(0));
+        assert_eq!(
+            tx_fee(&tx(
+                vec![input(Some(70)), input(Some(30))],
+                vec![output(90)]
+            )),
+            Some(10),
+        );
+        assert_eq!(
+            tx_fee(&tx(vec![input(Some(70)), input(None)], vec![output(50)])),
+            None,
+        );
+        assert_eq!(fee_rate(10, 250), 40);
+        assert_eq!(fee_rate(10, 0), 10_000);
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use bitcoinsuite_core::script::{Op, Script};
+use bitcoinsuite_slp::empp;
+
+use crate::{
+    db::CF_LOKAD_ID_HISTORY,
+    group::{Group, GroupQuery, MemberItem},
+    io::{GroupHistoryConf, GroupHistoryReader, GroupHistoryWriter},
+    mem::MempoolGroupHistory,
+};
+
+/// Index the mempool tx history of LOKAD IDs
+pub type MempoolLokadIdHistory = MempoolGroupHistory<LokadIdGroup>;
+/// Index the tx history of LOKAD IDs in the DB
+pub type LokadIdHistoryWriter<'a> = GroupHistoryWriter<'a, LokadIdGroup>;
+/// Read the tx history of LOKAD IDs in the DB
+pub type LokadIdHistoryReader<'a> = GroupHistoryReader<'a, LokadIdGroup>;
+
+/// LOKAD ID, the 4-byte protocol prefix of an OP_RETURN protocol, e.g.
+/// `b"SLP\0"` for SLP or `b"SLP2"` for ALP.
+pub type LokadId = [u8; 4];
+
+/// Group txs by the LOKAD IDs in their OP_RETURN outputs.
+///
+/// For an eMPP script, the LOKAD ID is the first 4 bytes of each pushdata.
+/// Otherwise, it's the first pushdata after the OP_RETURN, if it has exactly 4
+/// bytes.
+///
+/// Only the tx history is indexed, as OP_RETURN outputs can't be spent, which
+/// is why this doesn't implement [`UtxoGroup`](crate::group::UtxoGroup).
+#[derive(Clone, Debug, Default)]
+pub struct LokadIdGroup;
+
+impl Group for LokadIdGroup {
+    type Aux = ();
+    type Iter<'a> = Vec<MemberItem<LokadId>>;
+    type Member<'a> = LokadId;
+    type MemberSer<'a> = LokadId;
+
+    fn input_members<'a>(
+        &self,
+        _query: GroupQuery<'a>,
+        _aux: &(),
+    ) -> Self::Iter<'a> {
+        vec![]
+    }
+
+    fn output_members<'a>(
+        &self,
+        query: GroupQuery<'a>,
+        _aux: &(),
+    ) -> Self::Iter<'a> {
+        let mut members = Vec::new();
+        for (idx, output) in query.tx.outputs.iter().enumerate() {
+            for member in lokad_ids(&output.script) {
+                members.push(MemberItem { idx, member });
+            }
+        }
+        members
+    }
+
+    fn ser_member<'a>(&self, member: &Self::Member<'a>) -> Self::MemberSer<'a> {
+        *member
+    }
+
+    fn tx_history_conf() -> GroupHistoryConf {
+        GroupHistoryConf {
+            cf_name: CF_LOKAD_ID_HISTORY,
+            page_size: 1000,
+        }
+    }
+}
+
+/// Extract the LOKAD IDs of the given output script.
+pub fn lokad_ids(script: &Script) -> Vec<LokadId> {
+    if !script.is_opreturn() {
+        return vec![];
+    }
+    if let Ok(pushdata) = empp::parse(script) {
+        return pushdata
+            .iter()
+            .filter_map(|pushdata| pushdata.get(..4)?.try_into().ok())
+            .collect();
+    }
+    match script.iter_ops().nth(1) {
+        Some(Ok(Op::Push(_, pushdata))) => {
+            pushdata.as_ref().try_into().ok().into_iter().collect()
+        }
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::{
+        script::{opcode::*, Script},
+        tx::{Tx, TxId, TxMut, TxOutput},
+    };
+    use bitcoinsuite_slp::empp;
+    use bytes::Bytes;
+
+    use crate::{
+        group::{tx_members_for_group, Group, GroupQuery},
+        groups::{lokad_ids, LokadId, LokadIdGroup},
+    };
+
+    const NONE: [LokadId; 0] = [];
+
+    fn script(bytecode: Vec<u8>) -> Script {
+        Script::new(bytecode.into())
+    }
+
+    #[test]
+    fn test_lokad_ids() {
+        // Not OP_RETURN
+        assert_eq!(lokad_ids(&script(vec![0x04, b'S', b'L', b'P', 0])), NONE);
+        // No pushdata
+        assert_eq!(lokad_ids(&script(vec![OP_RETURN::N])), NONE);
+        // Pushdata not 4 bytes
+        assert_eq!(
+            lokad_ids(&script(vec![OP_RETURN::N, 0x03, b'S', b'L', b'P'])),
+            NONE,
+        );
+        // Invalid pushdata
+        assert_eq!(lokad_ids(&script(vec![OP_RETURN::N, 0x04, 1])), NONE);
+        // Pushdata of 4 bytes
+        assert_eq!(
+            lokad_ids(&script(vec![
+                OP_RETURN::N,
+                0x04,
+                b'S',
+                b'L',
+                b'P',
+                0,
+                0x01,
+                1,
+            ])),
+            [*b"SLP\0"],
+        );
+        // eMPP: first 4 bytes of each pushdata, if long enough
+        assert_eq!(
+            lokad_ids(&empp::build(&[
+                Bytes::from_static(b"SLP2\x00\x07GENESIS"),
+                Bytes::from_static(b"abc"),
+                Bytes::from_static(b"TEST"),
+            ])),
+            [*b"SLP2", *b"TEST"],
+        );
+    }
+
+    #[test]
+    fn test_lokad_id_group() {
+        let group = LokadIdGroup;
+        let tx = Tx::with_txid(
+            TxId::from([0; 32]),
+            TxMut {
+                outputs: vec![
+                    TxOutput {
+                        script: script(vec![OP_RETURN::N, 0x04, 1, 2, 3, 4]),
+                        ..Default::default()
+                    },
+                    TxOutput {
+                        script: script(vec![OP_RETURN::N, 0x04, 1, 2, 3, 4]),
+                        ..Default::default()
+                    },
+                    TxOutput {
+                        script: script(vec![OP_RETURN::N, 0x04, 5, 6, 7, 8]),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        let query = GroupQuery {
+            is_coinbase: false,
+            tx: &tx,
+        };
+        assert_eq!(
+            group
+                .output_members(query, &())
+                .into_iter()
+                .map(|item| (item.idx, item.member))
+                .collect::<Vec<_>>(),
+            vec![(0, [1, 2, 3, 4]), (1, [1, 2, 3, 4]), (2, [5, 6, 7, 8])],
+        );
+        assert_eq!(
+            tx_members_for_group(&group, query, &()).collect::<Vec<_>>(),
+            vec![[1, 2, 3, 4], [1, 2, 3, 4], [5, 6, 7, 8]],
+        );
+    }
+}
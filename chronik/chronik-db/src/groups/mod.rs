@@ -4,6 +4,12 @@
 
 //! Collection of group implementations to group transactions by when indexing.
 
+mod lokad_id;
 mod script;
+mod script_hash;
+mod token_id;
 
+pub use self::lokad_id::*;
 pub use self::script::*;
+pub use self::script_hash::*;
+pub use self::token_id::*;
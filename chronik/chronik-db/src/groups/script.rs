@@ -5,8 +5,8 @@
 use bitcoinsuite_core::script::Script;
 
 use crate::{
-    db::{CF_SCRIPT_HISTORY, CF_SCRIPT_UTXO},
-    group::{Group, GroupQuery, MemberItem},
+    db::{CF_SCRIPT_BALANCE, CF_SCRIPT_HISTORY, CF_SCRIPT_UTXO},
+    group::{Group, GroupQuery, MemberItem, UtxoGroup},
     io::{
         GroupHistoryConf, GroupHistoryReader, GroupHistoryWriter,
         GroupUtxoConf, GroupUtxoReader, GroupUtxoWriter,
@@ -37,11 +37,16 @@ pub struct ScriptGroup {
 }
 
 impl Group for ScriptGroup {
+    type Aux = ();
     type Iter<'a> = Vec<MemberItem<&'a Script>>;
     type Member<'a> = &'a Script;
     type MemberSer<'a> = Vec<u8>;
 
-    fn input_members<'a>(&self, query: GroupQuery<'a>) -> Self::Iter<'a> {
+    fn input_members<'a>(
+        &self,
+        query: GroupQuery<'a>,
+        _aux: &(),
+    ) -> Self::Iter<'a> {
         if query.is_coinbase {
             return vec![];
         }
@@ -57,7 +62,11 @@ impl Group for ScriptGroup {
         input_scripts
     }
 
-    fn output_members<'a>(&self, query: GroupQuery<'a>) -> Self::Iter<'a> {
+    fn output_members<'a>(
+        &self,
+        query: GroupQuery<'a>,
+        _aux: &(),
+    ) -> Self::Iter<'a> {
         let mut output_scripts = Vec::with_capacity(query.tx.outputs.len());
         for (idx, output) in query.tx.outputs.iter().enumerate() {
             if !output.script.is_opreturn() {
@@ -80,10 +89,13 @@ impl Group for ScriptGroup {
             page_size: 1000,
         }
     }
+}
 
+impl UtxoGroup for ScriptGroup {
     fn utxo_conf() -> GroupUtxoConf {
         GroupUtxoConf {
             cf_name: CF_SCRIPT_UTXO,
+            balance_cf_name: Some(CF_SCRIPT_BALANCE),
         }
     }
 }
@@ -162,7 +174,7 @@ mod tests {
             tx: &tx,
         };
         assert_eq!(
-            tx_members_for_group(&script_group, query).collect::<Vec<_>>(),
+            tx_members_for_group(&script_group, query, &()).collect::<Vec<_>>(),
             vec![
                 &make_script(vec![0x51]),
                 &make_script(vec![0x52]),
@@ -171,14 +183,14 @@ mod tests {
             ],
         );
         assert_eq!(
-            script_group.input_members(query),
+            script_group.input_members(query, &()),
             vec![
                 make_member_item(0, &make_script(vec![0x51])),
                 make_member_item(1, &make_script(vec![0x52])),
             ],
         );
         assert_eq!(
-            script_group.output_members(query),
+            script_group.output_members(query, &()),
             vec![
                 make_member_item(0, &make_script(vec![0x53])),
                 make_member_item(1, &make_script(vec![0x51])),
@@ -190,15 +202,15 @@ mod tests {
             tx: &tx,
         };
         assert_eq!(
-            tx_members_for_group(&script_group, query).collect::<Vec<_>>(),
+            tx_members_for_group(&script_group, query, &()).collect::<Vec<_>>(),
             vec![
                 &Script::new(vec![0x53].into()),
                 &Script::new(vec![0x51].into()),
             ],
         );
-        assert_eq!(script_group.input_members(query), vec![]);
+        assert_eq!(script_group.input_members(query, &()), vec![]);
         assert_eq!(
-            script_group.output_members(query),
+            script_group.output_members(query, &()),
             vec![
                 make_member_item(0, &make_script(vec![0x53])),
                 make_member_item(1, &make_script(vec![0x51])),
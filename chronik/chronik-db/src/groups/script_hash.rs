@@ -0,0 +1,187 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use bitcoinsuite_core::{
+    hash::{Hashed, Sha256},
+    script::Script,
+};
+
+use crate::{
+    db::{CF_SCRIPT_HASH_BALANCE, CF_SCRIPT_HASH_HISTORY, CF_SCRIPT_HASH_UTXO},
+    group::{Group, GroupQuery, MemberItem, UtxoGroup},
+    io::{
+        GroupHistoryConf, GroupHistoryReader, GroupHistoryWriter,
+        GroupUtxoConf, GroupUtxoReader, GroupUtxoWriter,
+    },
+    mem::{MempoolGroupHistory, MempoolGroupUtxos},
+};
+
+/// Index the mempool tx history of script hashes
+pub type MempoolScriptHashHistory = MempoolGroupHistory<ScriptHashGroup>;
+/// Index the mempool UTXOs of script hashes
+pub type MempoolScriptHashUtxos = MempoolGroupUtxos<ScriptHashGroup>;
+/// Index the tx history of script hashes in the DB
+pub type ScriptHashHistoryWriter<'a> = GroupHistoryWriter<'a, ScriptHashGroup>;
+/// Read the tx history of script hashes in the DB
+pub type ScriptHashHistoryReader<'a> = GroupHistoryReader<'a, ScriptHashGroup>;
+/// Index the UTXOs of script hashes in the DB
+pub type ScriptHashUtxoWriter<'a> = GroupUtxoWriter<'a, ScriptHashGroup>;
+/// Read the UTXOs of script hashes in the DB
+pub type ScriptHashUtxoReader<'a> = GroupUtxoReader<'a, ScriptHashGroup>;
+
+/// Group txs by the SHA-256 hash of their input/output scripts.
+///
+/// This is how the Electrum protocol identifies scripts, so wallets can query
+/// a script without revealing it, and without Chronik having to know how to
+/// compress it.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptHashGroup;
+
+impl Group for ScriptHashGroup {
+    type Aux = ();
+    type Iter<'a> = Vec<MemberItem<Sha256>>;
+    type Member<'a> = Sha256;
+    type MemberSer<'a> = [u8; 32];
+
+    fn input_members<'a>(
+        &self,
+        query: GroupQuery<'a>,
+        _aux: &(),
+    ) -> Self::Iter<'a> {
+        if query.is_coinbase {
+            return vec![];
+        }
+        let mut members = Vec::with_capacity(query.tx.inputs.len());
+        for (idx, input) in query.tx.inputs.iter().enumerate() {
+            if let Some(coin) = &input.coin {
+                members.push(MemberItem {
+                    idx,
+                    member: script_hash(&coin.output.script),
+                });
+            }
+        }
+        members
+    }
+
+    fn output_members<'a>(
+        &self,
+        query: GroupQuery<'a>,
+        _aux: &(),
+    ) -> Self::Iter<'a> {
+        let mut members = Vec::with_capacity(query.tx.outputs.len());
+        for (idx, output) in query.tx.outputs.iter().enumerate() {
+            if !output.script.is_opreturn() {
+                members.push(MemberItem {
+                    idx,
+                    member: script_hash(&output.script),
+                });
+            }
+        }
+        members
+    }
+
+    fn ser_member<'a>(&self, member: &Self::Member<'a>) -> Self::MemberSer<'a> {
+        member.to_le_bytes()
+    }
+
+    fn tx_history_conf() -> GroupHistoryConf {
+        GroupHistoryConf {
+            cf_name: CF_SCRIPT_HASH_HISTORY,
+            page_size: 1000,
+        }
+    }
+}
+
+impl UtxoGroup for ScriptHashGroup {
+    fn utxo_conf() -> GroupUtxoConf {
+        GroupUtxoConf {
+            cf_name: CF_SCRIPT_HASH_UTXO,
+            balance_cf_name: Some(CF_SCRIPT_HASH_BALANCE),
+        }
+    }
+}
+
+/// Hash the script the way the Electrum protocol does, i.e. a single SHA-256
+/// of the serialized script, without length prefix.
+pub fn script_hash(script: &Script) -> Sha256 {
+    Sha256::digest(script.bytecode())
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::{
+        hash::{Hashed, Sha256},
+        script::Script,
+        tx::{Coin, Tx, TxId, TxInput, TxMut, TxOutput},
+    };
+
+    use crate::{
+        group::{tx_members_for_group, Group, GroupQuery, MemberItem},
+        groups::{script_hash, ScriptHashGroup},
+    };
+
+    #[test]
+    fn test_script_hash_group() {
+        let make_script = |script: &[u8]| Script::new(script.to_vec().into());
+        let tx = Tx::with_txid(
+            TxId::from([0; 32]),
+            TxMut {
+                inputs: vec![TxInput {
+                    coin: Some(Coin {
+                        output: TxOutput {
+                            script: make_script(&[0x51]),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                outputs: [[0x6a, 0x01].as_ref(), &[0x52]]
+                    .into_iter()
+                    .map(|script| TxOutput {
+                        script: make_script(script),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+        );
+        let hash_51 = script_hash(&make_script(&[0x51]));
+        let hash_52 = script_hash(&make_script(&[0x52]));
+        assert_eq!(hash_51, Sha256::digest([0x51]));
+
+        let query = GroupQuery {
+            is_coinbase: false,
+            tx: &tx,
+        };
+        assert_eq!(
+            tx_members_for_group(&ScriptHashGroup, query, &())
+                .collect::<Vec<_>>(),
+            vec![hash_51, hash_52],
+        );
+        assert_eq!(
+            ScriptHashGroup.input_members(query, &()),
+            vec![MemberItem {
+                idx: 0,
+                member: hash_51,
+            }],
+        );
+        // OP_RETURN outputs are skipped
+        assert_eq!(
+            ScriptHashGroup.output_members(query, &()),
+            vec![MemberItem {
+                idx: 1,
+                member: hash_52,
+            }],
+        );
+
+        let query = GroupQuery {
+            is_coinbase: true,
+            tx: &tx,
+        };
+        assert_eq!(ScriptHashGroup.input_members(query, &()), vec![]);
+
+        assert_eq!(ScriptHashGroup.ser_member(&hash_52), hash_52.0);
+    }
+}
@@ -0,0 +1,490 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use abc_rust_error::Result;
+use bitcoinsuite_core::tx::{Tx, TxId};
+use bitcoinsuite_slp::{
+    color::ColoredTx,
+    structs::{Token, TokenVariant},
+    token_id::TokenId,
+};
+
+use crate::{
+    db::{Db, CF_TOKEN_ID_HISTORY, CF_TOKEN_ID_UTXO},
+    group::{Group, GroupQuery, MemberItem, UtxoGroup},
+    index_tx::IndexTx,
+    io::{
+        DbTokenTx, GroupHistoryConf, GroupHistoryReader, GroupHistoryWriter,
+        GroupUtxoConf, GroupUtxoReader, GroupUtxoWriter, TokenTxReader, TxNum,
+    },
+    mem::{MempoolGroupHistory, MempoolGroupUtxos},
+};
+
+/// Index the mempool tx history of token IDs
+pub type MempoolTokenIdHistory = MempoolGroupHistory<TokenIdGroup>;
+/// Index the mempool UTXOs of token IDs
+pub type MempoolTokenIdUtxos = MempoolGroupUtxos<TokenIdGroup>;
+/// Index the tx history of token IDs in the DB
+pub type TokenIdHistoryWriter<'a> = GroupHistoryWriter<'a, TokenIdGroup>;
+/// Read the tx history of token IDs in the DB
+pub type TokenIdHistoryReader<'a> = GroupHistoryReader<'a, TokenIdGroup>;
+/// Index the UTXOs of token IDs in the DB
+pub type TokenIdUtxoWriter<'a> = GroupUtxoWriter<'a, TokenIdGroup>;
+/// Read the UTXOs of token IDs in the DB
+pub type TokenIdUtxoReader<'a> = GroupUtxoReader<'a, TokenIdGroup>;
+
+/// Group txs by the token IDs of the tokens in their inputs and outputs.
+///
+/// Which inputs and outputs carry tokens can't be derived from the tx alone,
+/// so this requires a [`TokenIdGroupAux`] with the colored txs.
+#[derive(Clone, Debug, Default)]
+pub struct TokenIdGroup;
+
+/// Tokens of a tx: the tokens spent by its inputs and the [`ColoredTx`]
+/// validated against them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TxTokens {
+    /// Tokens of the outputs spent by the inputs of the tx, [`None`] for
+    /// inputs that don't spend any tokens.
+    pub spent_tokens: Vec<Option<Token>>,
+    /// Tokens of the tx, validated against `spent_tokens`.
+    pub colored: ColoredTx,
+}
+
+/// Auxillary data for [`TokenIdGroup`], with the [`TxTokens`] of the txs
+/// being grouped. Txs not involving any tokens can be left out.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TokenIdGroupAux {
+    /// Tokens of the txs, by txid.
+    pub txs: HashMap<TxId, TxTokens>,
+}
+
+impl Group for TokenIdGroup {
+    type Aux = TokenIdGroupAux;
+    type Iter<'a> = Vec<MemberItem<TokenId>>;
+    type Member<'a> = TokenId;
+    type MemberSer<'a> = [u8; 32];
+
+    fn input_members<'a>(
+        &self,
+        query: GroupQuery<'a>,
+        aux: &TokenIdGroupAux,
+    ) -> Self::Iter<'a> {
+        if query.is_coinbase {
+            return vec![];
+        }
+        match aux.txs.get(query.tx.txid_ref()) {
+            Some(tx_tokens) => members(&tx_tokens.spent_tokens),
+            None => vec![],
+        }
+    }
+
+    fn output_members<'a>(
+        &self,
+        query: GroupQuery<'a>,
+        aux: &TokenIdGroupAux,
+    ) -> Self::Iter<'a> {
+        let tx_tokens = match aux.txs.get(query.tx.txid_ref()) {
+            Some(tx_tokens) => tx_tokens,
+            None => return vec![],
+        };
+        (0..tx_tokens.colored.outputs.len())
+            .filter_map(|idx| {
+                Some(MemberItem {
+                    idx,
+                    member: tx_tokens.colored.output_token(idx)?.token_id,
+                })
+            })
+            .collect()
+    }
+
+    fn ser_member<'a>(&self, member: &Self::Member<'a>) -> Self::MemberSer<'a> {
+        member.txid().to_bytes()
+    }
+
+    fn tx_history_conf() -> GroupHistoryConf {
+        GroupHistoryConf {
+            cf_name: CF_TOKEN_ID_HISTORY,
+            page_size: 1000,
+        }
+    }
+}
+
+impl UtxoGroup for TokenIdGroup {
+    fn utxo_conf() -> GroupUtxoConf {
+        GroupUtxoConf {
+            cf_name: CF_TOKEN_ID_UTXO,
+            balance_cf_name: None,
+        }
+    }
+}
+
+fn members(tokens: &[Option<Token>]) -> Vec<MemberItem<TokenId>> {
+    tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, token)| {
+            Some(MemberItem {
+                idx,
+                member: token.as_ref()?.token_id,
+            })
+        })
+        .collect()
+}
+
+impl TxTokens {
+    /// Color the tx given the tokens spent by its inputs.
+    pub fn color(tx: &Tx, spent_tokens: Vec<Option<Token>>) -> Self {
+        let colored = ColoredTx::color(tx, &spent_tokens);
+        TxTokens {
+            spent_tokens,
+            colored,
+        }
+    }
+
+    /// By how much the tx changes the supply of each token it involves, i.e.
+    /// the amount of its outputs minus the amount spent by its inputs.
+    pub fn supply_deltas(&self) -> HashMap<TokenId, i128> {
+        let mut deltas = HashMap::<TokenId, i128>::new();
+        for token in self.spent_tokens.iter().flatten() {
+            if let TokenVariant::Amount(amount) = token.variant {
+                *deltas.entry(token.token_id).or_default() -= amount as i128;
+            }
+        }
+        for out_idx in 0..self.colored.outputs.len() {
+            let token = self.colored.output_token(out_idx);
+            if let Some(Token {
+                token_id,
+                variant: TokenVariant::Amount(amount),
+                ..
+            }) = token
+            {
+                *deltas.entry(token_id).or_default() += amount as i128;
+            }
+        }
+        deltas
+    }
+}
+
+impl TokenIdGroupAux {
+    /// Color the txs of a block and build the [`TokenIdGroupAux`] for them.
+    ///
+    /// Spent tokens are taken from the other txs of the block, or from the DB
+    /// for txs in previous blocks.
+    pub fn from_db(db: &Db, txs: &[IndexTx<'_>]) -> Result<Self> {
+        let token_tx_reader = TokenTxReader::new(db)?;
+        let block_tx_idxs = txs
+            .iter()
+            .enumerate()
+            .map(|(idx, index_tx)| (index_tx.tx_num, idx))
+            .collect::<HashMap<_, _>>();
+        let mut db_token_txs = HashMap::<TxNum, Option<DbTokenTx>>::new();
+        let mut is_colored = vec![false; txs.len()];
+        let mut aux = TokenIdGroupAux::default();
+        // Txs within a block are not in topological order, so we have to
+        // color the parents of a tx within the block before the tx itself.
+        let mut stack = Vec::new();
+        for start_idx in 0..txs.len() {
+            stack.push(start_idx);
+            while let Some(&tx_idx) = stack.last() {
+                if is_colored[tx_idx] {
+                    stack.pop();
+                    continue;
+                }
+                let index_tx = &txs[tx_idx];
+                let uncolored_parents = index_tx
+                    .input_nums
+                    .iter()
+                    .filter_map(|input_num| block_tx_idxs.get(input_num))
+                    .filter(|&&parent_idx| !is_colored[parent_idx])
+                    .copied()
+                    .collect::<Vec<_>>();
+                if !uncolored_parents.is_empty() {
+                    stack.extend(uncolored_parents);
+                    continue;
+                }
+                let mut spent_tokens = vec![None; index_tx.tx.inputs.len()];
+                for ((input, &input_num), spent_token) in index_tx
+                    .tx
+                    .inputs
+                    .iter()
+                    .zip(&index_tx.input_nums)
+                    .zip(&mut spent_tokens)
+                {
+                    let out_idx = input.prev_out.out_idx;
+                    *spent_token = match block_tx_idxs.get(&input_num) {
+                        Some(_) => aux.txs.get(&input.prev_out.txid).and_then(
+                            |parent| {
+                                parent.colored.output_token(out_idx as usize)
+                            },
+                        ),
+                        None => {
+                            let db_token_tx =
+                                match db_token_txs.entry(input_num) {
+                                    Entry::Occupied(entry) => entry.into_mut(),
+                                    Entry::Vacant(entry) => entry.insert(
+                                        token_tx_reader.by_tx_num(input_num)?,
+                                    ),
+                                };
+                            db_token_tx
+                                .as_ref()
+                                .and_then(|tx| tx.output_token(out_idx))
+                        }
+                    };
+                }
+                let tx_tokens = TxTokens::color(index_tx.tx, spent_tokens);
+                if tx_tokens.colored.has_any() {
+                    aux.txs.insert(index_tx.tx.txid(), tx_tokens);
+                }
+                is_colored[tx_idx] = true;
+                stack.pop();
+            }
+        }
+        Ok(aux)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use abc_rust_error::Result;
+    use bitcoinsuite_core::tx::{OutPoint, Tx, TxId, TxInput, TxMut, TxOutput};
+    use bitcoinsuite_slp::{
+        alp::{
+            sections_script, AlpGenesis, AlpMintData, AlpSection, AlpSend,
+            AlpTokenType, AlpTxType,
+        },
+        structs::{Token, TokenType, TokenVariant},
+        token_id::TokenId,
+    };
+    use pretty_assertions::assert_eq;
+    use rocksdb::WriteBatch;
+
+    use crate::{
+        db::{Db, CF_TOKEN_ID_UTXO},
+        group::{tx_members_for_group, Group, GroupQuery, MemberItem},
+        groups::{
+            TokenIdGroup, TokenIdGroupAux, TokenIdUtxoReader,
+            TokenIdUtxoWriter, TxTokens,
+        },
+        index_tx::prepare_indexed_txs,
+        io::{
+            BlockTxs, GroupUtxoError, TokenTxReader, TokenTxWriter, TxEntry,
+            TxWriter,
+        },
+    };
+
+    const ALP: TokenType = TokenType::Alp(AlpTokenType::Standard);
+
+    fn token(token_id: TokenId, amount: u64) -> Option<Token> {
+        Some(Token {
+            token_id,
+            token_type: ALP,
+            variant: TokenVariant::Amount(amount),
+        })
+    }
+
+    fn make_tx(
+        txid_num: u8,
+        inputs: &[(u8, u32)],
+        tx_type: Option<AlpTxType>,
+    ) -> Tx {
+        let mut outputs = vec![TxOutput::default(); 4];
+        if let Some(tx_type) = tx_type {
+            outputs[0].script = sections_script(&[AlpSection {
+                token_type: AlpTokenType::Standard,
+                tx_type,
+            }])
+            .unwrap();
+        }
+        Tx::with_txid(
+            TxId::from([txid_num; 32]),
+            TxMut {
+                inputs: inputs
+                    .iter()
+                    .map(|&(txid_num, out_idx)| TxInput {
+                        prev_out: OutPoint {
+                            txid: TxId::from([txid_num; 32]),
+                            out_idx,
+                        },
+                        ..Default::default()
+                    })
+                    .collect(),
+                outputs,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn send(token_id: TokenId, amounts: Vec<u64>) -> Option<AlpTxType> {
+        Some(AlpTxType::Send(AlpSend { token_id, amounts }))
+    }
+
+    #[test]
+    fn test_token_id_group() {
+        let token_group = TokenIdGroup;
+        let token1 = TokenId::new(TxId::from([1; 32]));
+        let token2 = TokenId::new(TxId::from([2; 32]));
+        let tx = make_tx(5, &[(1, 1), (3, 0), (2, 1)], send(token1, vec![10]));
+        let aux = TokenIdGroupAux {
+            txs: HashMap::from([(
+                tx.txid(),
+                TxTokens::color(
+                    &tx,
+                    vec![token(token1, 10), None, token(token2, 20)],
+                ),
+            )]),
+        };
+        let query = GroupQuery {
+            is_coinbase: false,
+            tx: &tx,
+        };
+        assert_eq!(
+            tx_members_for_group(&token_group, query, &aux).collect::<Vec<_>>(),
+            vec![token1, token2, token1],
+        );
+        assert_eq!(
+            token_group.input_members(query, &aux),
+            vec![
+                MemberItem {
+                    idx: 0,
+                    member: token1,
+                },
+                MemberItem {
+                    idx: 2,
+                    member: token2,
+                },
+            ],
+        );
+        assert_eq!(
+            token_group.output_members(query, &aux),
+            vec![MemberItem {
+                idx: 1,
+                member: token1,
+            }],
+        );
+
+        // Coinbase txs have no input members
+        let query = GroupQuery {
+            is_coinbase: true,
+            tx: &tx,
+        };
+        assert_eq!(token_group.input_members(query, &aux), vec![]);
+
+        // Txs not in the aux have no members
+        let query = GroupQuery {
+            is_coinbase: false,
+            tx: &make_tx(6, &[(1, 1)], None),
+        };
+        assert_eq!(
+            tx_members_for_group(&token_group, query, &aux).collect::<Vec<_>>(),
+            vec![],
+        );
+
+        assert_eq!(token_group.ser_member(&token1), [1; 32]);
+    }
+
+    #[test]
+    fn test_token_id_group_aux_from_db() -> Result<()> {
+        abc_rust_error::install();
+        let tempdir = tempdir::TempDir::new("chronik-db--token_id_aux")?;
+        let mut cfs = Vec::new();
+        TxWriter::add_cfs(&mut cfs);
+        TokenTxWriter::add_cfs(&mut cfs);
+        let db = Db::open_with_cfs(tempdir.path(), cfs)?;
+        let tx_writer = TxWriter::new(&db)?;
+        let token_tx_writer = TokenTxWriter::new(&db)?;
+        let token_tx_reader = TokenTxReader::new(&db)?;
+        let connect_block = |txs: &[Tx], block_height| -> Result<_> {
+            let mut batch = WriteBatch::default();
+            let block_txs = BlockTxs {
+                txs: txs
+                    .iter()
+                    .map(|tx| TxEntry {
+                        txid: tx.txid(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                block_height,
+            };
+            let first_tx_num = tx_writer.insert(&mut batch, &block_txs)?;
+            let index_txs = prepare_indexed_txs(&db, first_tx_num, txs)?;
+            let aux = TokenIdGroupAux::from_db(&db, &index_txs)?;
+            token_tx_writer.insert(&mut batch, &index_txs, &aux)?;
+            db.write_batch(batch)?;
+            Ok(aux)
+        };
+
+        let genesis_id = TokenId::new(TxId::from([2; 32]));
+        let block0 = vec![
+            make_tx(1, &[(0, u32::MAX)], None),
+            make_tx(
+                2,
+                &[(1, 0)],
+                Some(AlpTxType::Genesis(Box::new(AlpGenesis {
+                    mint_data: AlpMintData {
+                        amounts: vec![100, 50],
+                        num_batons: 0,
+                    },
+                    ..Default::default()
+                }))),
+            ),
+        ];
+        let aux = connect_block(&block0, 0)?;
+        assert_eq!(aux.txs.len(), 1);
+        let db_token_tx = token_tx_reader.by_tx_num(1)?.unwrap();
+        assert_eq!(db_token_tx.output_token(1), token(genesis_id, 100));
+        assert_eq!(token_tx_reader.by_tx_num(0)?, None);
+
+        // Txs 5 and 4 spend from txs later in the block; tx 4 sends more
+        // than it has, so tx 5 only spends the token from the DB.
+        let block1 = vec![
+            make_tx(3, &[(0, u32::MAX)], None),
+            make_tx(5, &[(4, 1), (6, 1)], send(genesis_id, vec![130])),
+            make_tx(4, &[(2, 2)], send(genesis_id, vec![60])),
+            make_tx(6, &[(2, 1)], send(genesis_id, vec![80])),
+        ];
+        let aux = connect_block(&block1, 1)?;
+        assert_eq!(aux.txs.len(), 3);
+        let tx4 = &aux.txs[&TxId::from([4; 32])];
+        assert_eq!(tx4.spent_tokens, vec![token(genesis_id, 50)]);
+        assert_eq!(tx4.colored.output_token(1), None);
+        let tx5 = &aux.txs[&TxId::from([5; 32])];
+        assert_eq!(tx5.spent_tokens, vec![None, token(genesis_id, 80)]);
+        assert!(!tx5.colored.entries[0].is_valid());
+        assert_eq!(tx5.colored.entries[0].actual_burn, 80);
+        let tx6 = &aux.txs[&TxId::from([6; 32])];
+        assert_eq!(tx6.colored.output_token(1), token(genesis_id, 80));
+        assert_eq!(
+            token_tx_reader.by_tx_num(5)?.unwrap().output_token(1),
+            token(genesis_id, 80),
+        );
+
+        // Txs without any tokens are left out
+        assert!(!aux.txs.contains_key(&TxId::from([3; 32])));
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_id_utxos_no_balance() -> Result<()> {
+        abc_rust_error::install();
+        let tempdir = tempdir::TempDir::new("chronik-db--token_id_balance")?;
+        let mut cfs = Vec::new();
+        TokenIdUtxoWriter::add_cfs(&mut cfs);
+        let db = Db::open_with_cfs(tempdir.path(), cfs)?;
+        let reader = TokenIdUtxoReader::new(&db)?;
+        // Sats of token UTXOs aren't token amounts, so no balance is stored
+        assert_eq!(
+            reader
+                .balance(&[1; 32])
+                .unwrap_err()
+                .downcast::<GroupUtxoError>()?,
+            GroupUtxoError::NoBalanceColumn(CF_TOKEN_ID_UTXO),
+        );
+        Ok(())
+    }
+}
@@ -3,11 +3,13 @@
 // file COPYING or http://www.opensource.org/licenses/mit-license.php.
 
 use abc_rust_error::Result;
+use bitcoinsuite_core::ser::BitcoinSer;
 use rocksdb::ColumnFamilyDescriptor;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     db::{Db, CF, CF_BLK_STATS},
+    fee::{fee_rate, tx_fee},
     index_tx::IndexTx,
     io::{bh_to_bytes, BlockHeight},
     ser::{db_deserialize, db_serialize},
@@ -34,6 +36,15 @@ pub struct BlockStats {
     pub sum_normal_output_sats: i64,
     /// Total number of satoshis burned using OP_RETURN
     pub sum_burned_sats: i64,
+    /// Total fees paid by the non-coinbase txs in this block
+    pub sum_fees: i64,
+    /// Smallest fee rate of the non-coinbase txs in this block, in sats/kB
+    pub min_fee_rate: i64,
+    /// Median fee rate of the non-coinbase txs in this block, in sats/kB.
+    /// For an even number of txs, this is the lower of the two middle ones.
+    pub median_fee_rate: i64,
+    /// Largest fee rate of the non-coinbase txs in this block, in sats/kB
+    pub max_fee_rate: i64,
 }
 
 struct BlockStatsColumn<'a> {
@@ -83,6 +94,8 @@ impl<'a> BlockStatsWriter<'a> {
         let mut sum_normal_output_sats = 0;
         let mut sum_coinbase_output_sats = 0;
         let mut sum_burned_sats = 0;
+        let mut sum_fees = 0;
+        let mut fee_rates = Vec::with_capacity(txs.len());
         for tx in txs {
             for output in &tx.tx.outputs {
                 if output.script.is_opreturn() {
@@ -100,10 +113,15 @@ impl<'a> BlockStatsWriter<'a> {
                         sum_input_sats += coin.output.value;
                     }
                 }
+                if let Some(fee) = tx_fee(tx.tx) {
+                    sum_fees += fee;
+                    fee_rates.push(fee_rate(fee, tx.tx.ser_len()));
+                }
             }
             num_inputs += tx.tx.inputs.len();
             num_outputs += tx.tx.outputs.len();
         }
+        fee_rates.sort_unstable();
         let stats = BlockStats {
             block_size,
             num_txs: txs.len() as u64,
@@ -113,6 +131,13 @@ impl<'a> BlockStatsWriter<'a> {
             sum_coinbase_output_sats,
             sum_normal_output_sats,
             sum_burned_sats,
+            sum_fees,
+            min_fee_rate: fee_rates.first().copied().unwrap_or_default(),
+            median_fee_rate: fee_rates
+                .get(fee_rates.len().saturating_sub(1) / 2)
+                .copied()
+                .unwrap_or_default(),
+            max_fee_rate: fee_rates.last().copied().unwrap_or_default(),
         };
         batch.put_cf(
             self.col.cf,
@@ -173,6 +198,7 @@ mod tests {
     use abc_rust_error::Result;
     use bitcoinsuite_core::{
         script::{opcode::*, ScriptMut},
+        ser::BitcoinSer,
         tx::{Tx, TxId, TxMut, TxOutput},
     };
     use pretty_assertions::assert_eq;
@@ -257,6 +283,10 @@ mod tests {
                 sum_coinbase_output_sats: 70,
                 sum_normal_output_sats: 175,
                 sum_burned_sats: 60,
+                sum_fees: 5,
+                min_fee_rate: 0,
+                median_fee_rate: 0,
+                max_fee_rate: 5000 / block[2].ser_len() as i64,
             }),
         );
 
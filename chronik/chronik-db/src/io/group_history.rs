@@ -124,8 +124,9 @@ impl<'a, G: Group> GroupHistoryWriter<'a, G> {
         &self,
         batch: &mut WriteBatch,
         txs: &[IndexTx<'_>],
+        aux: &G::Aux,
     ) -> Result<()> {
-        let grouped_txs = self.group_txs(txs);
+        let grouped_txs = self.group_txs(txs, aux);
         for (member, mut new_tx_nums) in grouped_txs {
             let member_ser: G::MemberSer<'_> = self.group.ser_member(&member);
             let (mut page_num, mut last_page_tx_nums) = self
@@ -153,8 +154,9 @@ impl<'a, G: Group> GroupHistoryWriter<'a, G> {
         &self,
         batch: &mut WriteBatch,
         txs: &[IndexTx<'_>],
+        aux: &G::Aux,
     ) -> Result<()> {
-        let grouped_txs = self.group_txs(txs);
+        let grouped_txs = self.group_txs(txs, aux);
         for (member, removed_tx_nums) in grouped_txs {
             let member_ser: G::MemberSer<'_> = self.group.ser_member(&member);
             let mut num_remaining_removes = removed_tx_nums.len();
@@ -193,6 +195,7 @@ impl<'a, G: Group> GroupHistoryWriter<'a, G> {
     fn group_txs<'tx>(
         &self,
         txs: &'tx [IndexTx<'tx>],
+        aux: &G::Aux,
     ) -> HashMap<G::Member<'tx>, Vec<TxNum>> {
         let mut group_tx_nums = HashMap::<G::Member<'tx>, Vec<TxNum>>::new();
         for index_tx in txs {
@@ -200,7 +203,7 @@ impl<'a, G: Group> GroupHistoryWriter<'a, G> {
                 is_coinbase: index_tx.is_coinbase,
                 tx: index_tx.tx,
             };
-            for member in tx_members_for_group(&self.group, query) {
+            for member in tx_members_for_group(&self.group, query, aux) {
                 let tx_nums = group_tx_nums.entry(member).or_default();
                 if let Some(&last_tx_num) = tx_nums.last() {
                     if last_tx_num == index_tx.tx_num {
@@ -323,7 +326,7 @@ mod tests {
             *block_height.borrow_mut() += 1;
             let first_tx_num = tx_writer.insert(&mut batch, &txs_batch(txs))?;
             let index_txs = prepare_indexed_txs(&db, first_tx_num, txs)?;
-            group_writer.insert(&mut batch, &index_txs)?;
+            group_writer.insert(&mut batch, &index_txs, &())?;
             db.write_batch(batch)?;
             Ok(())
         };
@@ -331,7 +334,7 @@ mod tests {
             let mut batch = WriteBatch::default();
             let first_tx_num = tx_writer.delete(&mut batch, &txs_batch(txs))?;
             let index_txs = prepare_indexed_txs(&db, first_tx_num, txs)?;
-            group_writer.delete(&mut batch, &index_txs)?;
+            group_writer.delete(&mut batch, &index_txs, &())?;
             db.write_batch(batch)?;
             *block_height.borrow_mut() -= 1;
             Ok(())
@@ -16,7 +16,7 @@ use thiserror::Error;
 
 use crate::{
     db::{Db, CF},
-    group::{Group, GroupQuery},
+    group::{GroupQuery, UtxoGroup},
     index_tx::IndexTx,
     io::TxNum,
     ser::{db_deserialize, db_serialize},
@@ -27,11 +27,16 @@ use crate::{
 pub struct GroupUtxoConf {
     /// Column family to store the group utxos entries.
     pub cf_name: &'static str,
+    /// Column family to store the [`GroupBalance`] of each member, or
+    /// [`None`] if balances are meaningless for the group, e.g. for token IDs,
+    /// where the sats of the UTXOs aren't the token amounts.
+    pub balance_cf_name: Option<&'static str>,
 }
 
 struct GroupUtxoColumn<'a> {
     db: &'a Db,
     cf: &'a CF,
+    cf_balance: Option<&'a CF>,
 }
 
 /// Outpoint in the DB, but with [`TxNum`] instead of `TxId` for the txid.
@@ -72,16 +77,30 @@ pub struct UtxoEntry {
     pub value: i64,
 }
 
+/// Balance of a member of a group, i.e. the sum and number of its UTXOs.
+///
+/// This is updated alongside the UTXOs, so it can be read without reading all
+/// the [`UtxoEntry`]s of the member.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize,
+)]
+pub struct GroupBalance {
+    /// Sum of the values of the UTXOs in satoshis.
+    pub value: i64,
+    /// Number of UTXOs.
+    pub num_utxos: u64,
+}
+
 /// Write UTXOs of a group to the DB.
 #[derive(Debug)]
-pub struct GroupUtxoWriter<'a, G: Group> {
+pub struct GroupUtxoWriter<'a, G: UtxoGroup> {
     col: GroupUtxoColumn<'a>,
     group: G,
 }
 
 /// Read UTXOs of a group from the DB.
 #[derive(Debug)]
-pub struct GroupUtxoReader<'a, G: Group> {
+pub struct GroupUtxoReader<'a, G: UtxoGroup> {
     col: GroupUtxoColumn<'a>,
     phantom: PhantomData<G>,
 }
@@ -98,6 +117,10 @@ pub enum GroupUtxoError {
     /// UTXO already in the DB
     #[error("UTXO doesn't exist: {0:?} is not in the member's UTXOs")]
     UtxoDoesntExist(UtxoOutpoint),
+
+    /// Balance queried for a group that doesn't store balances
+    #[error("Group with UTXOs in {0:?} has no balance column")]
+    NoBalanceColumn(&'static str),
 }
 
 use self::GroupUtxoError::*;
@@ -105,11 +128,15 @@ use self::GroupUtxoError::*;
 impl<'a> GroupUtxoColumn<'a> {
     fn new(db: &'a Db, conf: &GroupUtxoConf) -> Result<Self> {
         let cf = db.cf(conf.cf_name)?;
-        Ok(GroupUtxoColumn { db, cf })
+        let cf_balance = match conf.balance_cf_name {
+            Some(balance_cf_name) => Some(db.cf(balance_cf_name)?),
+            None => None,
+        };
+        Ok(GroupUtxoColumn { db, cf, cf_balance })
     }
 }
 
-impl<'a, G: Group> GroupUtxoWriter<'a, G> {
+impl<'a, G: UtxoGroup> GroupUtxoWriter<'a, G> {
     /// Create a new [`GroupUtxoWriter`].
     pub fn new(db: &'a Db, group: G) -> Result<Self> {
         let conf = G::utxo_conf();
@@ -125,6 +152,7 @@ impl<'a, G: Group> GroupUtxoWriter<'a, G> {
         &self,
         batch: &mut WriteBatch,
         txs: &'tx [IndexTx<'tx>],
+        aux: &G::Aux,
     ) -> Result<()> {
         let mut updated_utxos =
             HashMap::<G::Member<'tx>, Vec<UtxoEntry>>::new();
@@ -133,7 +161,7 @@ impl<'a, G: Group> GroupUtxoWriter<'a, G> {
                 is_coinbase: index_tx.is_coinbase,
                 tx: index_tx.tx,
             };
-            for item in self.group.output_members(query) {
+            for item in self.group.output_members(query, aux) {
                 let entries =
                     self.get_or_fetch(&mut updated_utxos, item.member)?;
                 let new_entry = Self::output_utxo(index_tx, item.idx);
@@ -148,7 +176,7 @@ impl<'a, G: Group> GroupUtxoWriter<'a, G> {
                 is_coinbase: index_tx.is_coinbase,
                 tx: index_tx.tx,
             };
-            for item in self.group.input_members(query) {
+            for item in self.group.input_members(query, aux) {
                 let entries =
                     self.get_or_fetch(&mut updated_utxos, item.member)?;
                 let delete_entry = Self::input_utxo(index_tx, item.idx);
@@ -167,6 +195,7 @@ impl<'a, G: Group> GroupUtxoWriter<'a, G> {
         &self,
         batch: &mut WriteBatch,
         txs: &'tx [IndexTx<'tx>],
+        aux: &G::Aux,
     ) -> Result<()> {
         let mut updated_utxos =
             HashMap::<G::Member<'tx>, Vec<UtxoEntry>>::new();
@@ -178,7 +207,7 @@ impl<'a, G: Group> GroupUtxoWriter<'a, G> {
                 is_coinbase: index_tx.is_coinbase,
                 tx: index_tx.tx,
             };
-            for item in self.group.input_members(query) {
+            for item in self.group.input_members(query, aux) {
                 let entries =
                     self.get_or_fetch(&mut updated_utxos, item.member)?;
                 let new_entry = Self::input_utxo(index_tx, item.idx);
@@ -190,7 +219,7 @@ impl<'a, G: Group> GroupUtxoWriter<'a, G> {
                 is_coinbase: index_tx.is_coinbase,
                 tx: index_tx.tx,
             };
-            for item in self.group.output_members(query) {
+            for item in self.group.output_members(query, aux) {
                 let entries =
                     self.get_or_fetch(&mut updated_utxos, item.member)?;
                 let delete_entry = Self::output_utxo(index_tx, item.idx);
@@ -202,10 +231,17 @@ impl<'a, G: Group> GroupUtxoWriter<'a, G> {
     }
 
     pub(crate) fn add_cfs(columns: &mut Vec<rocksdb::ColumnFamilyDescriptor>) {
+        let conf = G::utxo_conf();
         columns.push(rocksdb::ColumnFamilyDescriptor::new(
-            G::utxo_conf().cf_name,
+            conf.cf_name,
             rocksdb::Options::default(),
         ));
+        if let Some(balance_cf_name) = conf.balance_cf_name {
+            columns.push(rocksdb::ColumnFamilyDescriptor::new(
+                balance_cf_name,
+                rocksdb::Options::default(),
+            ));
+        }
     }
 
     fn output_utxo(index_tx: &IndexTx<'_>, idx: usize) -> UtxoEntry {
@@ -286,19 +322,33 @@ impl<'a, G: Group> GroupUtxoWriter<'a, G> {
             let member_ser = self.group.ser_member(member);
             if utxos.is_empty() {
                 batch.delete_cf(self.col.cf, member_ser.as_ref());
+                if let Some(cf_balance) = self.col.cf_balance {
+                    batch.delete_cf(cf_balance, member_ser.as_ref());
+                }
             } else {
                 batch.put_cf(
                     self.col.cf,
                     member_ser.as_ref(),
                     db_serialize(&utxos)?,
                 );
+                if let Some(cf_balance) = self.col.cf_balance {
+                    let balance = GroupBalance {
+                        value: utxos.iter().map(|utxo| utxo.value).sum(),
+                        num_utxos: utxos.len() as u64,
+                    };
+                    batch.put_cf(
+                        cf_balance,
+                        member_ser.as_ref(),
+                        db_serialize(&balance)?,
+                    );
+                }
             }
         }
         Ok(())
     }
 }
 
-impl<'a, G: Group> GroupUtxoReader<'a, G> {
+impl<'a, G: UtxoGroup> GroupUtxoReader<'a, G> {
     /// Create a new [`GroupUtxoReader`].
     pub fn new(db: &'a Db) -> Result<Self> {
         let conf = G::utxo_conf();
@@ -316,6 +366,19 @@ impl<'a, G: Group> GroupUtxoReader<'a, G> {
             None => Ok(None),
         }
     }
+
+    /// Query the balance of the given member, or [`None`] if it has no UTXOs.
+    /// Errors if the group doesn't store balances.
+    pub fn balance(&self, member: &[u8]) -> Result<Option<GroupBalance>> {
+        let cf_balance = match self.col.cf_balance {
+            Some(cf_balance) => cf_balance,
+            None => return Err(NoBalanceColumn(G::utxo_conf().cf_name).into()),
+        };
+        match self.col.db.get(cf_balance, member)? {
+            Some(entry) => Ok(Some(db_deserialize::<GroupBalance>(&entry)?)),
+            None => Ok(None),
+        }
+    }
 }
 
 impl std::fmt::Debug for GroupUtxoColumn<'_> {
@@ -336,8 +399,8 @@ mod tests {
         db::Db,
         index_tx::prepare_indexed_txs,
         io::{
-            BlockTxs, GroupUtxoReader, GroupUtxoWriter, TxEntry, TxWriter,
-            UtxoEntry, UtxoOutpoint,
+            BlockTxs, GroupBalance, GroupUtxoReader, GroupUtxoWriter, TxEntry,
+            TxWriter, UtxoEntry, UtxoOutpoint,
         },
         test::{make_inputs_tx, ser_value, ValueGroup},
     };
@@ -370,7 +433,7 @@ mod tests {
             *block_height.borrow_mut() += 1;
             let first_tx_num = tx_writer.insert(&mut batch, &txs_batch(txs))?;
             let index_txs = prepare_indexed_txs(&db, first_tx_num, txs)?;
-            group_writer.insert(&mut batch, &index_txs)?;
+            group_writer.insert(&mut batch, &index_txs, &())?;
             db.write_batch(batch)?;
             Ok(())
         };
@@ -378,7 +441,7 @@ mod tests {
             let mut batch = WriteBatch::default();
             let first_tx_num = tx_writer.delete(&mut batch, &txs_batch(txs))?;
             let index_txs = prepare_indexed_txs(&db, first_tx_num, txs)?;
-            group_writer.delete(&mut batch, &index_txs)?;
+            group_writer.delete(&mut batch, &index_txs, &())?;
             db.write_batch(batch)?;
             *block_height.borrow_mut() -= 1;
             Ok(())
@@ -388,12 +451,16 @@ mod tests {
             value,
         };
         let read_utxos = |val: i64| group_reader.utxos(&ser_value(val));
+        let read_balance = |val: i64| group_reader.balance(&ser_value(val));
+        let balance = |value, num_utxos| GroupBalance { value, num_utxos };
 
         let block0 =
             vec![make_inputs_tx(0x01, [(0x00, u32::MAX, 0xffff)], [100, 200])];
         connect_block(&block0)?;
         assert_eq!(read_utxos(100)?, Some(vec![utxo(0, 0, 100)]));
         assert_eq!(read_utxos(200)?, Some(vec![utxo(0, 1, 200)]));
+        assert_eq!(read_balance(100)?, Some(balance(100, 1)));
+        assert_eq!(read_balance(200)?, Some(balance(200, 1)));
 
         let block1 = vec![
             make_inputs_tx(0x02, [(0x00, u32::MAX, 0xffff)], [200]),
@@ -412,6 +479,10 @@ mod tests {
             read_utxos(200)?,
             Some(vec![utxo(1, 0, 200), utxo(3, 0, 200)]),
         );
+        assert_eq!(read_balance(10)?, Some(balance(10, 1)));
+        assert_eq!(read_balance(20)?, None);
+        assert_eq!(read_balance(100)?, None);
+        assert_eq!(read_balance(200)?, Some(balance(400, 2)));
 
         disconnect_block(&block1)?;
         assert_eq!(read_utxos(10)?, None);
@@ -442,9 +513,14 @@ mod tests {
         assert_eq!(read_utxos(100)?, Some(vec![utxo(0, 0, 100)]));
         assert_eq!(read_utxos(200)?, Some(vec![utxo(0, 1, 200)]));
 
+        assert_eq!(read_balance(100)?, Some(balance(100, 1)));
+        assert_eq!(read_balance(200)?, Some(balance(200, 1)));
+
         disconnect_block(&block0)?;
         assert_eq!(read_utxos(100)?, None);
         assert_eq!(read_utxos(200)?, None);
+        assert_eq!(read_balance(100)?, None);
+        assert_eq!(read_balance(200)?, None);
 
         Ok(())
     }
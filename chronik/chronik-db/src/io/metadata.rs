@@ -16,6 +16,9 @@ pub type SchemaVersion = u64;
 /// Field in the `meta` cf storing the schema version.
 pub const FIELD_SCHEMA_VERSION: &[u8] = b"SCHEMA_VERSION";
 
+/// Field in the `meta` cf storing whether script hashes are indexed.
+pub const FIELD_SCRIPT_HASH_INDEX: &[u8] = b"SCRIPT_HASH_INDEX";
+
 /// Write database metadata
 pub struct MetadataWriter<'a> {
     cf: &'a CF,
@@ -48,6 +51,20 @@ impl<'a> MetadataWriter<'a> {
         Ok(())
     }
 
+    /// Update whether script hashes are indexed in the database
+    pub fn update_is_script_hash_index(
+        &self,
+        batch: &mut rocksdb::WriteBatch,
+        is_script_hash_index: bool,
+    ) -> Result<()> {
+        batch.put_cf(
+            self.cf,
+            FIELD_SCRIPT_HASH_INDEX,
+            db_serialize(&is_script_hash_index)?,
+        );
+        Ok(())
+    }
+
     pub(crate) fn add_cfs(columns: &mut Vec<ColumnFamilyDescriptor>) {
         columns.push(ColumnFamilyDescriptor::new(
             CF_META,
@@ -78,6 +95,16 @@ impl<'a> MetadataReader<'a> {
             None => Ok(None),
         }
     }
+
+    /// Read whether script hashes are indexed in the database
+    pub fn is_script_hash_index(&self) -> Result<Option<bool>> {
+        match self.db.get(self.cf, FIELD_SCRIPT_HASH_INDEX)? {
+            Some(ser_is_script_hash_index) => {
+                Ok(Some(db_deserialize(&ser_is_script_hash_index)?))
+            }
+            None => Ok(None),
+        }
+    }
 }
 
 impl std::fmt::Debug for MetadataReader<'_> {
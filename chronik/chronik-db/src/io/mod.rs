@@ -10,6 +10,8 @@ mod group_history;
 mod group_utxos;
 mod metadata;
 mod spent_by;
+mod token_info;
+mod token_txs;
 mod txs;
 
 pub use self::block_stats::*;
@@ -18,4 +20,6 @@ pub use self::group_history::*;
 pub use self::group_utxos::*;
 pub use self::metadata::*;
 pub use self::spent_by::*;
+pub use self::token_info::*;
+pub use self::token_txs::*;
 pub use self::txs::*;
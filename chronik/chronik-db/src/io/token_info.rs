@@ -0,0 +1,428 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use abc_rust_error::Result;
+use bitcoinsuite_slp::{
+    structs::{GenesisInfo, TxType},
+    token_id::TokenId,
+};
+use rocksdb::{ColumnFamilyDescriptor, Options, WriteBatch};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{Db, CF, CF_TOKEN_INFO},
+    groups::TokenIdGroupAux,
+    index_tx::IndexTx,
+    io::{BlockHeight, DbTokenType},
+    ser::{db_deserialize, db_serialize},
+};
+
+/// Info about a token, as stored in the DB.
+///
+/// Written when the GENESIS tx of the token is confirmed, and updated with
+/// every block that mints or burns tokens of it.
+#[derive(
+    Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize,
+)]
+pub struct DbTokenInfo {
+    /// Token type of the token.
+    pub token_type: DbTokenType,
+    /// Info the token has been created with.
+    pub genesis_info: DbGenesisInfo,
+    /// Height of the block the GENESIS tx has been confirmed in.
+    pub block_height: BlockHeight,
+    /// Amount of tokens in confirmed UTXOs, in base units.
+    pub circulating_supply: u128,
+}
+
+/// [`GenesisInfo`] of a token, as stored in the DB.
+#[derive(
+    Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize,
+)]
+pub struct DbGenesisInfo {
+    /// Short ticker of the token.
+    pub token_ticker: Vec<u8>,
+    /// Display name of the token.
+    pub token_name: Vec<u8>,
+    /// URL for more info about the token.
+    pub url: Vec<u8>,
+    /// Hash of the token document, if any (SLP only).
+    pub hash: Option<[u8; 32]>,
+    /// Arbitrary data attached to the token (ALP only).
+    pub data: Option<Vec<u8>>,
+    /// Public key of the token's issuer (ALP only).
+    pub auth_pubkey: Option<Vec<u8>>,
+    /// Number of decimal places to display amounts with.
+    pub decimals: u8,
+}
+
+struct TokenInfoColumn<'a> {
+    db: &'a Db,
+    cf: &'a CF,
+}
+
+/// Write [`DbTokenInfo`]s to the DB.
+#[derive(Debug)]
+pub struct TokenInfoWriter<'a> {
+    col: TokenInfoColumn<'a>,
+}
+
+/// Read [`DbTokenInfo`]s from the DB.
+#[derive(Debug)]
+pub struct TokenInfoReader<'a> {
+    col: TokenInfoColumn<'a>,
+}
+
+fn token_id_key(token_id: &TokenId) -> &[u8; 32] {
+    token_id.txid().as_bytes()
+}
+
+impl From<&GenesisInfo> for DbGenesisInfo {
+    fn from(info: &GenesisInfo) -> Self {
+        DbGenesisInfo {
+            token_ticker: info.token_ticker.to_vec(),
+            token_name: info.token_name.to_vec(),
+            url: info.url.to_vec(),
+            hash: info.hash,
+            data: info.data.as_ref().map(|data| data.to_vec()),
+            auth_pubkey: info.auth_pubkey.as_ref().map(|pk| pk.to_vec()),
+            decimals: info.decimals,
+        }
+    }
+}
+
+impl<'a> TokenInfoColumn<'a> {
+    fn new(db: &'a Db) -> Result<Self> {
+        let cf = db.cf(CF_TOKEN_INFO)?;
+        Ok(TokenInfoColumn { db, cf })
+    }
+
+    fn get(&self, token_id: &TokenId) -> Result<Option<DbTokenInfo>> {
+        match self.db.get(self.cf, token_id_key(token_id))? {
+            Some(ser_token_info) => {
+                Ok(Some(db_deserialize::<DbTokenInfo>(&ser_token_info)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a> TokenInfoWriter<'a> {
+    /// Create a new [`TokenInfoWriter`].
+    pub fn new(db: &'a Db) -> Result<Self> {
+        let col = TokenInfoColumn::new(db)?;
+        Ok(TokenInfoWriter { col })
+    }
+
+    /// Add the tokens created by the txs of the block and update the
+    /// circulating supply of the tokens minted or burned by them.
+    pub fn insert(
+        &self,
+        batch: &mut WriteBatch,
+        block_height: BlockHeight,
+        txs: &[IndexTx<'_>],
+        aux: &TokenIdGroupAux,
+    ) -> Result<()> {
+        let mut infos = HashMap::<TokenId, DbTokenInfo>::new();
+        let mut deltas = HashMap::<TokenId, i128>::new();
+        for index_tx in txs {
+            let tx_tokens = match aux.txs.get(index_tx.tx.txid_ref()) {
+                Some(tx_tokens) => tx_tokens,
+                None => continue,
+            };
+            for entry in &tx_tokens.colored.entries {
+                if entry.tx_type != Some(TxType::Genesis) || !entry.is_valid() {
+                    continue;
+                }
+                let genesis_info = match &entry.genesis_info {
+                    Some(genesis_info) => genesis_info,
+                    None => continue,
+                };
+                infos.insert(
+                    entry.token_id,
+                    DbTokenInfo {
+                        token_type: entry.token_type.into(),
+                        genesis_info: genesis_info.into(),
+                        block_height,
+                        circulating_supply: 0,
+                    },
+                );
+            }
+            for (token_id, delta) in tx_tokens.supply_deltas() {
+                *deltas.entry(token_id).or_default() += delta;
+            }
+        }
+        for (token_id, delta) in deltas {
+            let info = match infos.entry(token_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => match self.col.get(&token_id)? {
+                    Some(info) => entry.insert(info),
+                    None => continue,
+                },
+            };
+            info.circulating_supply =
+                (info.circulating_supply as i128 + delta) as u128;
+        }
+        for (token_id, info) in infos {
+            batch.put_cf(
+                self.col.cf,
+                token_id_key(&token_id),
+                db_serialize(&info)?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Remove the tokens created by the txs of the block and revert the
+    /// circulating supply of the tokens minted or burned by them.
+    pub fn delete(
+        &self,
+        batch: &mut WriteBatch,
+        txs: &[IndexTx<'_>],
+        aux: &TokenIdGroupAux,
+    ) -> Result<()> {
+        let mut deleted = Vec::new();
+        let mut deltas = HashMap::<TokenId, i128>::new();
+        for index_tx in txs {
+            let tx_tokens = match aux.txs.get(index_tx.tx.txid_ref()) {
+                Some(tx_tokens) => tx_tokens,
+                None => continue,
+            };
+            for entry in &tx_tokens.colored.entries {
+                if entry.tx_type == Some(TxType::Genesis) && entry.is_valid() {
+                    deleted.push(entry.token_id);
+                }
+            }
+            for (token_id, delta) in tx_tokens.supply_deltas() {
+                *deltas.entry(token_id).or_default() += delta;
+            }
+        }
+        for token_id in &deleted {
+            batch.delete_cf(self.col.cf, token_id_key(token_id));
+            deltas.remove(token_id);
+        }
+        for (token_id, delta) in deltas {
+            let mut info = match self.col.get(&token_id)? {
+                Some(info) => info,
+                None => continue,
+            };
+            info.circulating_supply =
+                (info.circulating_supply as i128 - delta) as u128;
+            batch.put_cf(
+                self.col.cf,
+                token_id_key(&token_id),
+                db_serialize(&info)?,
+            );
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_cfs(columns: &mut Vec<ColumnFamilyDescriptor>) {
+        columns.push(ColumnFamilyDescriptor::new(
+            CF_TOKEN_INFO,
+            Options::default(),
+        ));
+    }
+}
+
+impl<'a> TokenInfoReader<'a> {
+    /// Create a new [`TokenInfoReader`].
+    pub fn new(db: &'a Db) -> Result<Self> {
+        let col = TokenInfoColumn::new(db)?;
+        Ok(TokenInfoReader { col })
+    }
+
+    /// Read the [`DbTokenInfo`] of the token, or [`None`] if its GENESIS tx
+    /// hasn't been confirmed.
+    pub fn by_token_id(
+        &self,
+        token_id: &TokenId,
+    ) -> Result<Option<DbTokenInfo>> {
+        self.col.get(token_id)
+    }
+}
+
+impl std::fmt::Debug for TokenInfoColumn<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TokenInfoColumn {{ .. }}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use abc_rust_error::Result;
+    use bitcoinsuite_core::tx::{Tx, TxId, TxInput, TxMut, TxOutput};
+    use bitcoinsuite_slp::{
+        alp::{
+            sections_script, AlpGenesis, AlpMint, AlpMintData, AlpSection,
+            AlpSend, AlpTokenType, AlpTxType,
+        },
+        structs::{GenesisInfo, Token, TokenType, TokenVariant},
+        token_id::TokenId,
+    };
+    use pretty_assertions::assert_eq;
+    use rocksdb::WriteBatch;
+
+    use crate::{
+        db::Db,
+        groups::{TokenIdGroupAux, TxTokens},
+        index_tx::IndexTx,
+        io::{
+            DbGenesisInfo, DbTokenInfo, DbTokenType, TokenInfoReader,
+            TokenInfoWriter,
+        },
+    };
+
+    fn make_tx(txid_num: u8, num_inputs: usize, tx_type: AlpTxType) -> Tx {
+        let mut outputs = vec![TxOutput::default(); 4];
+        outputs[0].script = sections_script(&[AlpSection {
+            token_type: AlpTokenType::Standard,
+            tx_type,
+        }])
+        .unwrap();
+        Tx::with_txid(
+            TxId::from([txid_num; 32]),
+            TxMut {
+                inputs: vec![TxInput::default(); num_inputs],
+                outputs,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn make_block<'a>(
+        txs: &'a [(Tx, Vec<Option<Token>>)],
+    ) -> (Vec<IndexTx<'a>>, TokenIdGroupAux) {
+        let index_txs = txs
+            .iter()
+            .map(|(tx, _)| IndexTx {
+                tx,
+                tx_num: 0,
+                is_coinbase: false,
+                input_nums: vec![],
+            })
+            .collect();
+        let aux = TokenIdGroupAux {
+            txs: txs
+                .iter()
+                .map(|(tx, spent_tokens)| {
+                    (tx.txid(), TxTokens::color(tx, spent_tokens.clone()))
+                })
+                .collect::<HashMap<_, _>>(),
+        };
+        (index_txs, aux)
+    }
+
+    #[test]
+    fn test_token_info() -> Result<()> {
+        abc_rust_error::install();
+        let tempdir = tempdir::TempDir::new("chronik-db--token_info")?;
+        let mut cfs = Vec::new();
+        TokenInfoWriter::add_cfs(&mut cfs);
+        let db = Db::open_with_cfs(tempdir.path(), cfs)?;
+        let writer = TokenInfoWriter::new(&db)?;
+        let reader = TokenInfoReader::new(&db)?;
+
+        let token_id = TokenId::new(TxId::from([1; 32]));
+        let token = |variant| {
+            Some(Token {
+                token_id,
+                token_type: TokenType::Alp(AlpTokenType::Standard),
+                variant,
+            })
+        };
+        let info = GenesisInfo {
+            token_ticker: b"TKN".as_ref().into(),
+            decimals: 4,
+            ..Default::default()
+        };
+        let block1 = [(
+            make_tx(
+                1,
+                1,
+                AlpTxType::Genesis(Box::new(AlpGenesis {
+                    info,
+                    mint_data: AlpMintData {
+                        amounts: vec![100, 20],
+                        num_batons: 1,
+                    },
+                })),
+            ),
+            vec![None],
+        )];
+        let block2 = [
+            (
+                make_tx(
+                    2,
+                    1,
+                    AlpTxType::Mint(AlpMint {
+                        token_id,
+                        mint_data: AlpMintData {
+                            amounts: vec![30],
+                            num_batons: 0,
+                        },
+                    }),
+                ),
+                vec![token(TokenVariant::MintBaton)],
+            ),
+            (
+                make_tx(
+                    3,
+                    2,
+                    AlpTxType::Send(AlpSend {
+                        token_id,
+                        amounts: vec![60],
+                    }),
+                ),
+                vec![token(TokenVariant::Amount(100)), None],
+            ),
+        ];
+        let expected_info = |circulating_supply| DbTokenInfo {
+            token_type: DbTokenType::AlpStandard,
+            genesis_info: DbGenesisInfo {
+                token_ticker: b"TKN".to_vec(),
+                data: Some(vec![]),
+                auth_pubkey: Some(vec![]),
+                decimals: 4,
+                ..Default::default()
+            },
+            block_height: 1,
+            circulating_supply,
+        };
+
+        let connect = |block_height, txs| -> Result<()> {
+            let (index_txs, aux) = make_block(txs);
+            let mut batch = WriteBatch::default();
+            writer.insert(&mut batch, block_height, &index_txs, &aux)?;
+            db.write_batch(batch)?;
+            Ok(())
+        };
+        let disconnect = |txs| -> Result<()> {
+            let (index_txs, aux) = make_block(txs);
+            let mut batch = WriteBatch::default();
+            writer.delete(&mut batch, &index_txs, &aux)?;
+            db.write_batch(batch)?;
+            Ok(())
+        };
+
+        connect(1, &block1)?;
+        assert_eq!(reader.by_token_id(&token_id)?, Some(expected_info(120)));
+
+        // Mint 30 and burn 40
+        connect(2, &block2)?;
+        assert_eq!(reader.by_token_id(&token_id)?, Some(expected_info(110)));
+
+        disconnect(&block2)?;
+        assert_eq!(reader.by_token_id(&token_id)?, Some(expected_info(120)));
+
+        disconnect(&block1)?;
+        assert_eq!(reader.by_token_id(&token_id)?, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,461 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use abc_rust_error::Result;
+use bitcoinsuite_core::tx::TxId;
+use bitcoinsuite_slp::{
+    alp::AlpTokenType,
+    color::ColoredTx,
+    slp::SlpTokenType,
+    structs::{Amount, Token, TokenType, TokenVariant, TxType},
+    token_id::TokenId,
+};
+use rocksdb::{ColumnFamilyDescriptor, Options, WriteBatch};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{Db, CF, CF_TOKEN_TX},
+    groups::TokenIdGroupAux,
+    index_tx::IndexTx,
+    io::TxNum,
+    ser::{db_deserialize, db_serialize},
+};
+
+/// Token data of a tx, as stored in the DB.
+///
+/// This is the [`ColoredTx`] of the tx, validated against the tokens of its
+/// inputs. Only txs involving any tokens are stored.
+#[derive(
+    Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize,
+)]
+pub struct DbTokenTx {
+    /// Tokens involved in the tx, see [`ColoredTx::entries`].
+    pub entries: Vec<DbTokenEntry>,
+    /// For each input, the token it spends, or [`None`] if it doesn't spend
+    /// any tokens.
+    pub inputs: Vec<Option<DbToken>>,
+    /// For each output, the token it carries, or [`None`] if it doesn't carry
+    /// any tokens.
+    pub outputs: Vec<Option<DbToken>>,
+    /// Errors when parsing the OP_RETURN, as human-readable messages.
+    pub failed_parsings: Vec<String>,
+}
+
+/// How a tx affects a token, as stored in the DB.
+#[derive(
+    Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize,
+)]
+pub struct DbTokenEntry {
+    /// Token ID, as little-endian bytes.
+    pub token_id: [u8; 32],
+    /// Token type of the token.
+    pub token_type: DbTokenType,
+    /// Tx type of the OP_RETURN or section, or [`None`] if the token is only
+    /// spent by the inputs.
+    pub tx_type: Option<DbTxType>,
+    /// Amount explicitly burned by the OP_RETURN.
+    pub intentional_burn: u128,
+    /// Amount actually burned by the tx.
+    pub actual_burn: u128,
+    /// Whether mint batons of the token have been burned.
+    pub burns_mint_batons: bool,
+    /// Why the OP_RETURN or section is invalid, as human-readable messages.
+    pub failures: Vec<String>,
+}
+
+/// Token carried by an output or spent by an input, as stored in the DB.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct DbToken {
+    /// Index into [`DbTokenTx::entries`] of the token.
+    pub entry_idx: u32,
+    /// Amount of tokens, or [`None`] for mint batons.
+    pub amount: Option<Amount>,
+}
+
+/// Token type, as stored in the DB.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize,
+)]
+pub enum DbTokenType {
+    /// SLP fungible token.
+    #[default]
+    SlpFungible,
+    /// SLP NFT1 group token.
+    SlpNft1Group,
+    /// SLP NFT1 child token.
+    SlpNft1Child,
+    /// ALP standard token.
+    AlpStandard,
+}
+
+/// Tx type, as stored in the DB.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum DbTxType {
+    /// GENESIS tx or section.
+    Genesis,
+    /// MINT tx or section.
+    Mint,
+    /// SEND tx or section.
+    Send,
+    /// BURN tx or section.
+    Burn,
+}
+
+struct TokenTxColumn<'a> {
+    db: &'a Db,
+    cf: &'a CF,
+}
+
+/// Write [`DbTokenTx`]s to the DB.
+#[derive(Debug)]
+pub struct TokenTxWriter<'a> {
+    col: TokenTxColumn<'a>,
+}
+
+/// Read [`DbTokenTx`]s from the DB.
+#[derive(Debug)]
+pub struct TokenTxReader<'a> {
+    col: TokenTxColumn<'a>,
+}
+
+fn ser_tx_num(tx_num: TxNum) -> Result<Vec<u8>> {
+    db_serialize(&tx_num)
+}
+
+impl DbTokenTx {
+    /// Build a [`DbTokenTx`] from the [`ColoredTx`] of a tx and the tokens
+    /// spent by its inputs.
+    pub fn from_colored(
+        colored: &ColoredTx,
+        spent_tokens: &[Option<Token>],
+    ) -> Self {
+        DbTokenTx {
+            entries: colored
+                .entries
+                .iter()
+                .map(|entry| DbTokenEntry {
+                    token_id: entry.token_id.txid().to_bytes(),
+                    token_type: entry.token_type.into(),
+                    tx_type: entry.tx_type.map(Into::into),
+                    intentional_burn: entry.intentional_burn,
+                    actual_burn: entry.actual_burn,
+                    burns_mint_batons: entry.burns_mint_batons,
+                    failures: entry
+                        .failures
+                        .iter()
+                        .map(|failure| failure.to_string())
+                        .collect(),
+                })
+                .collect(),
+            inputs: spent_tokens
+                .iter()
+                .map(|token| {
+                    let token = token.as_ref()?;
+                    // Spent tokens always have an entry
+                    let entry_idx =
+                        colored.entries.iter().position(|entry| {
+                            entry.token_id == token.token_id
+                                && entry.token_type == token.token_type
+                        })?;
+                    Some(DbToken::new(entry_idx, token.variant))
+                })
+                .collect(),
+            outputs: colored
+                .outputs
+                .iter()
+                .map(|output| {
+                    let output = output.as_ref()?;
+                    Some(DbToken::new(output.entry_idx, output.variant))
+                })
+                .collect(),
+            failed_parsings: colored
+                .failed_parsings
+                .iter()
+                .map(|failed_parsing| failed_parsing.to_string())
+                .collect(),
+        }
+    }
+
+    /// Token of the output at `out_idx`, or [`None`] if it doesn't carry any
+    /// tokens.
+    pub fn output_token(&self, out_idx: u32) -> Option<Token> {
+        self.token(self.outputs.get(out_idx as usize)?.as_ref()?)
+    }
+
+    /// Token spent by the input at `input_idx`, or [`None`] if it doesn't
+    /// spend any tokens.
+    pub fn input_token(&self, input_idx: u32) -> Option<Token> {
+        self.token(self.inputs.get(input_idx as usize)?.as_ref()?)
+    }
+
+    /// Resolve the [`DbToken`] against the entries of this tx.
+    pub fn token(&self, db_token: &DbToken) -> Option<Token> {
+        let entry = self.entries.get(db_token.entry_idx as usize)?;
+        Some(Token {
+            token_id: TokenId::new(TxId::from(entry.token_id)),
+            token_type: entry.token_type.into(),
+            variant: db_token.variant(),
+        })
+    }
+}
+
+impl DbToken {
+    fn new(entry_idx: usize, variant: TokenVariant) -> Self {
+        DbToken {
+            entry_idx: entry_idx as u32,
+            amount: match variant {
+                TokenVariant::Amount(amount) => Some(amount),
+                TokenVariant::MintBaton => None,
+            },
+        }
+    }
+
+    /// Amount or mint baton of the token.
+    pub fn variant(&self) -> TokenVariant {
+        match self.amount {
+            Some(amount) => TokenVariant::Amount(amount),
+            None => TokenVariant::MintBaton,
+        }
+    }
+}
+
+impl From<TokenType> for DbTokenType {
+    fn from(token_type: TokenType) -> Self {
+        match token_type {
+            TokenType::Slp(SlpTokenType::Fungible) => DbTokenType::SlpFungible,
+            TokenType::Slp(SlpTokenType::Nft1Group) => {
+                DbTokenType::SlpNft1Group
+            }
+            TokenType::Slp(SlpTokenType::Nft1Child) => {
+                DbTokenType::SlpNft1Child
+            }
+            TokenType::Alp(AlpTokenType::Standard) => DbTokenType::AlpStandard,
+        }
+    }
+}
+
+impl From<DbTokenType> for TokenType {
+    fn from(token_type: DbTokenType) -> Self {
+        match token_type {
+            DbTokenType::SlpFungible => TokenType::Slp(SlpTokenType::Fungible),
+            DbTokenType::SlpNft1Group => {
+                TokenType::Slp(SlpTokenType::Nft1Group)
+            }
+            DbTokenType::SlpNft1Child => {
+                TokenType::Slp(SlpTokenType::Nft1Child)
+            }
+            DbTokenType::AlpStandard => TokenType::Alp(AlpTokenType::Standard),
+        }
+    }
+}
+
+impl From<TxType> for DbTxType {
+    fn from(tx_type: TxType) -> Self {
+        match tx_type {
+            TxType::Genesis => DbTxType::Genesis,
+            TxType::Mint => DbTxType::Mint,
+            TxType::Send => DbTxType::Send,
+            TxType::Burn => DbTxType::Burn,
+        }
+    }
+}
+
+impl From<DbTxType> for TxType {
+    fn from(tx_type: DbTxType) -> Self {
+        match tx_type {
+            DbTxType::Genesis => TxType::Genesis,
+            DbTxType::Mint => TxType::Mint,
+            DbTxType::Send => TxType::Send,
+            DbTxType::Burn => TxType::Burn,
+        }
+    }
+}
+
+impl<'a> TokenTxColumn<'a> {
+    fn new(db: &'a Db) -> Result<Self> {
+        let cf = db.cf(CF_TOKEN_TX)?;
+        Ok(TokenTxColumn { db, cf })
+    }
+}
+
+impl<'a> TokenTxWriter<'a> {
+    /// Create a new [`TokenTxWriter`].
+    pub fn new(db: &'a Db) -> Result<Self> {
+        let col = TokenTxColumn::new(db)?;
+        Ok(TokenTxWriter { col })
+    }
+
+    /// Add the token data of the txs involving tokens, as colored in `aux`.
+    pub fn insert(
+        &self,
+        batch: &mut WriteBatch,
+        txs: &[IndexTx<'_>],
+        aux: &TokenIdGroupAux,
+    ) -> Result<()> {
+        for index_tx in txs {
+            let tx_tokens = match aux.txs.get(index_tx.tx.txid_ref()) {
+                Some(tx_tokens) => tx_tokens,
+                None => continue,
+            };
+            let db_token_tx = DbTokenTx::from_colored(
+                &tx_tokens.colored,
+                &tx_tokens.spent_tokens,
+            );
+            batch.put_cf(
+                self.col.cf,
+                ser_tx_num(index_tx.tx_num)?,
+                db_serialize(&db_token_tx)?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Remove the token data of the txs.
+    pub fn delete(
+        &self,
+        batch: &mut WriteBatch,
+        txs: &[IndexTx<'_>],
+    ) -> Result<()> {
+        for index_tx in txs {
+            batch.delete_cf(self.col.cf, ser_tx_num(index_tx.tx_num)?);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_cfs(columns: &mut Vec<ColumnFamilyDescriptor>) {
+        columns
+            .push(ColumnFamilyDescriptor::new(CF_TOKEN_TX, Options::default()));
+    }
+}
+
+impl<'a> TokenTxReader<'a> {
+    /// Create a new [`TokenTxReader`].
+    pub fn new(db: &'a Db) -> Result<Self> {
+        let col = TokenTxColumn::new(db)?;
+        Ok(TokenTxReader { col })
+    }
+
+    /// Read the [`DbTokenTx`] of the tx with the given [`TxNum`], or [`None`]
+    /// if the tx doesn't involve any tokens.
+    pub fn by_tx_num(&self, tx_num: TxNum) -> Result<Option<DbTokenTx>> {
+        match self.col.db.get(self.col.cf, ser_tx_num(tx_num)?)? {
+            Some(ser_token_tx) => {
+                Ok(Some(db_deserialize::<DbTokenTx>(&ser_token_tx)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl std::fmt::Debug for TokenTxColumn<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TokenTxColumn {{ .. }}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::tx::{Tx, TxId, TxInput, TxMut, TxOutput};
+    use bitcoinsuite_slp::{
+        alp::{sections_script, AlpSection, AlpSend, AlpTokenType, AlpTxType},
+        color::ColoredTx,
+        structs::{Token, TokenType, TokenVariant},
+        token_id::TokenId,
+    };
+    use pretty_assertions::assert_eq;
+
+    use crate::io::{DbToken, DbTokenEntry, DbTokenTx, DbTokenType, DbTxType};
+
+    #[test]
+    fn test_db_token_tx() {
+        let token1 = TokenId::new(TxId::from([1; 32]));
+        let token2 = TokenId::new(TxId::from([2; 32]));
+        let alp = TokenType::Alp(AlpTokenType::Standard);
+        let token = |token_id, variant| Token {
+            token_id,
+            token_type: alp,
+            variant,
+        };
+        let mut outputs = vec![TxOutput::default(); 4];
+        outputs[0].script = sections_script(&[AlpSection {
+            token_type: AlpTokenType::Standard,
+            tx_type: AlpTxType::Send(AlpSend {
+                token_id: token1,
+                amounts: vec![0, 60, 40],
+            }),
+        }])
+        .unwrap();
+        let tx = Tx::with_txid(
+            TxId::from([3; 32]),
+            TxMut {
+                inputs: vec![TxInput::default(); 3],
+                outputs,
+                ..Default::default()
+            },
+        );
+        let spent_tokens = [
+            Some(token(token1, TokenVariant::Amount(100))),
+            Some(token(token2, TokenVariant::MintBaton)),
+            None,
+        ];
+        let colored = ColoredTx::color(&tx, &spent_tokens);
+        let db_token_tx = DbTokenTx::from_colored(&colored, &spent_tokens);
+        assert_eq!(
+            db_token_tx,
+            DbTokenTx {
+                entries: vec![
+                    DbTokenEntry {
+                        token_id: [1; 32],
+                        token_type: DbTokenType::AlpStandard,
+                        tx_type: Some(DbTxType::Send),
+                        ..Default::default()
+                    },
+                    DbTokenEntry {
+                        token_id: [2; 32],
+                        token_type: DbTokenType::AlpStandard,
+                        tx_type: None,
+                        burns_mint_batons: true,
+                        ..Default::default()
+                    },
+                ],
+                inputs: vec![
+                    Some(DbToken {
+                        entry_idx: 0,
+                        amount: Some(100),
+                    }),
+                    Some(DbToken {
+                        entry_idx: 1,
+                        amount: None,
+                    }),
+                    None,
+                ],
+                outputs: vec![
+                    None,
+                    None,
+                    Some(DbToken {
+                        entry_idx: 0,
+                        amount: Some(60),
+                    }),
+                    Some(DbToken {
+                        entry_idx: 0,
+                        amount: Some(40),
+                    }),
+                ],
+                failed_parsings: vec![],
+            }
+        );
+        for out_idx in 0..5 {
+            assert_eq!(
+                db_token_tx.output_token(out_idx as u32),
+                colored.output_token(out_idx),
+            );
+        }
+        for input_idx in 0..4 {
+            assert_eq!(
+                db_token_tx.input_token(input_idx as u32),
+                spent_tokens.get(input_idx).copied().flatten(),
+            );
+        }
+    }
+}
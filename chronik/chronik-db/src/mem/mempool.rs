@@ -4,14 +4,28 @@
 
 //! Module for [`Mempool`], to index mempool txs.
 
-use std::collections::HashMap;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+};
 
 use abc_rust_error::Result;
-use bitcoinsuite_core::tx::{Tx, TxId};
+use bitcoinsuite_core::{
+    ser::BitcoinSer,
+    tx::{Tx, TxId},
+};
 use thiserror::Error;
 
 use crate::{
-    groups::{MempoolScriptHistory, MempoolScriptUtxos, ScriptGroup},
+    db::Db,
+    fee::tx_fee,
+    groups::{
+        LokadIdGroup, MempoolLokadIdHistory, MempoolScriptHashHistory,
+        MempoolScriptHashUtxos, MempoolScriptHistory, MempoolScriptUtxos,
+        MempoolTokenIdHistory, MempoolTokenIdUtxos, ScriptGroup,
+        ScriptHashGroup, TokenIdGroup, TokenIdGroupAux, TxTokens,
+    },
+    io::{TokenTxReader, TxReader},
     mem::MempoolSpentBy,
 };
 
@@ -21,9 +35,16 @@ use crate::{
 #[derive(Debug)]
 pub struct Mempool {
     txs: HashMap<TxId, MempoolTx>,
+    txs_by_time_first_seen: BTreeSet<(Reverse<i64>, TxId)>,
     script_history: MempoolScriptHistory,
     script_utxos: MempoolScriptUtxos,
     spent_by: MempoolSpentBy,
+    tokens: TokenIdGroupAux,
+    token_id_history: MempoolTokenIdHistory,
+    token_id_utxos: MempoolTokenIdUtxos,
+    lokad_id_history: MempoolLokadIdHistory,
+    script_hash_history: Option<MempoolScriptHashHistory>,
+    script_hash_utxos: Option<MempoolScriptHashUtxos>,
 }
 
 /// Transaction in the mempool.
@@ -49,24 +70,79 @@ pub enum MempoolError {
 
 use self::MempoolError::*;
 
+impl MempoolTx {
+    /// Serialized size of the tx in bytes.
+    pub fn size(&self) -> usize {
+        self.tx.ser_len()
+    }
+
+    /// Fee paid by the tx in satoshis, or 0 if any of the spent coins is
+    /// unknown, see [`tx_fee`].
+    pub fn fee(&self) -> i64 {
+        tx_fee(&self.tx).unwrap_or_default()
+    }
+}
+
 impl Mempool {
-    /// Create a new [`Mempool`].
-    pub fn new(script_group: ScriptGroup) -> Self {
+    /// Create a new [`Mempool`]. Script hashes are only indexed if
+    /// `enable_script_hash_index` is set.
+    pub fn new(
+        script_group: ScriptGroup,
+        enable_script_hash_index: bool,
+    ) -> Self {
         Mempool {
             txs: HashMap::new(),
+            txs_by_time_first_seen: BTreeSet::new(),
             script_history: MempoolScriptHistory::new(script_group.clone()),
             script_utxos: MempoolScriptUtxos::new(script_group),
             spent_by: MempoolSpentBy::default(),
+            tokens: TokenIdGroupAux::default(),
+            token_id_history: MempoolTokenIdHistory::new(TokenIdGroup),
+            token_id_utxos: MempoolTokenIdUtxos::new(TokenIdGroup),
+            lokad_id_history: MempoolLokadIdHistory::new(LokadIdGroup),
+            script_hash_history: enable_script_hash_index
+                .then(|| MempoolScriptHashHistory::new(ScriptHashGroup)),
+            script_hash_utxos: enable_script_hash_index
+                .then(|| MempoolScriptHashUtxos::new(ScriptHashGroup)),
         }
     }
 
     /// Insert tx into the mempool.
-    pub fn insert(&mut self, mempool_tx: MempoolTx) -> Result<()> {
+    ///
+    /// The DB is used to look up the tokens of outputs spent by the tx that
+    /// are not in the mempool, to color the tx.
+    pub fn insert(&mut self, db: &Db, mempool_tx: MempoolTx) -> Result<()> {
         let txid = mempool_tx.tx.txid();
-        self.script_history.insert(&mempool_tx);
-        self.script_utxos
-            .insert(&mempool_tx, |txid| self.txs.contains_key(txid))?;
+        let tx_tokens = self.tx_tokens(db, &mempool_tx.tx)?;
+        if tx_tokens.colored.has_any() {
+            self.tokens.txs.insert(txid, tx_tokens);
+        }
+        self.script_history.insert(&mempool_tx, &());
+        self.script_utxos.insert(
+            &mempool_tx,
+            |txid| self.txs.contains_key(txid),
+            &(),
+        )?;
+        self.token_id_history.insert(&mempool_tx, &self.tokens);
+        self.token_id_utxos.insert(
+            &mempool_tx,
+            |txid| self.txs.contains_key(txid),
+            &self.tokens,
+        )?;
+        self.lokad_id_history.insert(&mempool_tx, &());
+        if let Some(script_hash_history) = &mut self.script_hash_history {
+            script_hash_history.insert(&mempool_tx, &());
+        }
+        if let Some(script_hash_utxos) = &mut self.script_hash_utxos {
+            script_hash_utxos.insert(
+                &mempool_tx,
+                |txid| self.txs.contains_key(txid),
+                &(),
+            )?;
+        }
         self.spent_by.insert(&mempool_tx)?;
+        self.txs_by_time_first_seen
+            .insert((Reverse(mempool_tx.time_first_seen), txid));
         if self.txs.insert(txid, mempool_tx).is_some() {
             return Err(DuplicateTx(txid).into());
         }
@@ -79,9 +155,32 @@ impl Mempool {
             Some(mempool_tx) => mempool_tx,
             None => return Err(NoSuchMempoolTx(txid).into()),
         };
-        self.script_history.remove(&mempool_tx);
-        self.script_utxos
-            .remove(&mempool_tx, |txid| self.txs.contains_key(txid))?;
+        self.txs_by_time_first_seen
+            .remove(&(Reverse(mempool_tx.time_first_seen), txid));
+        self.script_history.remove(&mempool_tx, &());
+        self.script_utxos.remove(
+            &mempool_tx,
+            |txid| self.txs.contains_key(txid),
+            &(),
+        )?;
+        self.token_id_history.remove(&mempool_tx, &self.tokens);
+        self.token_id_utxos.remove(
+            &mempool_tx,
+            |txid| self.txs.contains_key(txid),
+            &self.tokens,
+        )?;
+        self.lokad_id_history.remove(&mempool_tx, &());
+        if let Some(script_hash_history) = &mut self.script_hash_history {
+            script_hash_history.remove(&mempool_tx, &());
+        }
+        if let Some(script_hash_utxos) = &mut self.script_hash_utxos {
+            script_hash_utxos.remove(
+                &mempool_tx,
+                |txid| self.txs.contains_key(txid),
+                &(),
+            )?;
+        }
+        self.tokens.txs.remove(&txid);
         self.spent_by.remove(&mempool_tx)?;
         Ok(mempool_tx)
     }
@@ -89,19 +188,98 @@ impl Mempool {
     /// Remove mined tx from the mempool.
     pub fn remove_mined(&mut self, txid: &TxId) -> Result<Option<MempoolTx>> {
         if let Some(mempool_tx) = self.txs.remove(txid) {
-            self.script_history.remove(&mempool_tx);
-            self.script_utxos.remove_mined(&mempool_tx);
+            self.txs_by_time_first_seen
+                .remove(&(Reverse(mempool_tx.time_first_seen), *txid));
+            self.script_history.remove(&mempool_tx, &());
+            self.script_utxos.remove_mined(&mempool_tx, &());
+            self.token_id_history.remove(&mempool_tx, &self.tokens);
+            self.token_id_utxos.remove_mined(&mempool_tx, &self.tokens);
+            self.lokad_id_history.remove(&mempool_tx, &());
+            if let Some(script_hash_history) = &mut self.script_hash_history {
+                script_hash_history.remove(&mempool_tx, &());
+            }
+            if let Some(script_hash_utxos) = &mut self.script_hash_utxos {
+                script_hash_utxos.remove_mined(&mempool_tx, &());
+            }
+            self.tokens.txs.remove(txid);
             self.spent_by.remove(&mempool_tx)?;
             return Ok(Some(mempool_tx));
         }
         Ok(None)
     }
 
+    /// Remove mempool txs that spend outputs that are also spent by the given
+    /// mined txs, i.e. that have been double-spent, including all the
+    /// descendants of those txs in the mempool.
+    ///
+    /// Must be called after the mined txs have been removed via
+    /// [`Mempool::remove_mined`]. Returns the removed txs, descendants before
+    /// their ancestors.
+    pub fn remove_conflicts(
+        &mut self,
+        mined_txs: &[Tx],
+    ) -> Result<Vec<MempoolTx>> {
+        let mut double_spent = Vec::new();
+        for mined_tx in mined_txs {
+            for input in &mined_tx.inputs {
+                let spent_by = self
+                    .spent_by
+                    .outputs_spent(&input.prev_out.txid)
+                    .and_then(|outputs| outputs.get(&input.prev_out.out_idx));
+                if let Some(spent_by) = spent_by {
+                    if spent_by.txid != mined_tx.txid() {
+                        double_spent.push(spent_by.txid);
+                    }
+                }
+            }
+        }
+        let mut removed = Vec::new();
+        for txid in double_spent {
+            self.remove_with_descendants(txid, &mut removed)?;
+        }
+        Ok(removed)
+    }
+
+    fn remove_with_descendants(
+        &mut self,
+        txid: TxId,
+        removed: &mut Vec<MempoolTx>,
+    ) -> Result<()> {
+        if !self.txs.contains_key(&txid) {
+            return Ok(());
+        }
+        // Children spending multiple outputs are only removed once, as they're
+        // not in the mempool anymore after the first time
+        let children = self
+            .spent_by
+            .outputs_spent(&txid)
+            .into_iter()
+            .flat_map(|outputs| outputs.values().map(|spent_by| spent_by.txid))
+            .collect::<Vec<_>>();
+        for child_txid in children {
+            self.remove_with_descendants(child_txid, removed)?;
+        }
+        removed.push(self.remove(txid)?);
+        Ok(())
+    }
+
     /// Get a tx by [`TxId`], or [`None`], if not found.
     pub fn tx(&self, txid: &TxId) -> Option<&MempoolTx> {
         self.txs.get(txid)
     }
 
+    /// All txs in the mempool, by [`TxId`].
+    pub fn txs(&self) -> &HashMap<TxId, MempoolTx> {
+        &self.txs
+    }
+
+    /// TxIds of all txs in the mempool, ordered by the time they have been
+    /// first seen, latest first. Txs first seen at the same time are ordered
+    /// by txid.
+    pub fn txs_by_time_first_seen(&self) -> &BTreeSet<(Reverse<i64>, TxId)> {
+        &self.txs_by_time_first_seen
+    }
+
     /// Tx history of scripts in the mempool.
     pub fn script_history(&self) -> &MempoolScriptHistory {
         &self.script_history
@@ -116,4 +294,56 @@ impl Mempool {
     pub fn spent_by(&self) -> &MempoolSpentBy {
         &self.spent_by
     }
+
+    /// Tx history of token IDs in the mempool.
+    pub fn token_id_history(&self) -> &MempoolTokenIdHistory {
+        &self.token_id_history
+    }
+
+    /// UTXOs of token IDs in the mempool.
+    pub fn token_id_utxos(&self) -> &MempoolTokenIdUtxos {
+        &self.token_id_utxos
+    }
+
+    /// Tx history of LOKAD IDs in the mempool.
+    pub fn lokad_id_history(&self) -> &MempoolLokadIdHistory {
+        &self.lokad_id_history
+    }
+
+    /// Tx history of script hashes in the mempool, if indexed.
+    pub fn script_hash_history(&self) -> Option<&MempoolScriptHashHistory> {
+        self.script_hash_history.as_ref()
+    }
+
+    /// UTXOs of script hashes in the mempool, if indexed.
+    pub fn script_hash_utxos(&self) -> Option<&MempoolScriptHashUtxos> {
+        self.script_hash_utxos.as_ref()
+    }
+
+    /// Colored mempool txs that involve any tokens.
+    pub fn tokens(&self) -> &TokenIdGroupAux {
+        &self.tokens
+    }
+
+    fn tx_tokens(&self, db: &Db, tx: &Tx) -> Result<TxTokens> {
+        let mut spent_tokens = vec![None; tx.inputs.len()];
+        let tx_reader = TxReader::new(db)?;
+        let token_tx_reader = TokenTxReader::new(db)?;
+        for (input, spent_token) in tx.inputs.iter().zip(&mut spent_tokens) {
+            let prev_out = &input.prev_out;
+            if self.txs.contains_key(&prev_out.txid) {
+                *spent_token =
+                    self.tokens.txs.get(&prev_out.txid).and_then(|parent| {
+                        parent.colored.output_token(prev_out.out_idx as usize)
+                    });
+                continue;
+            }
+            if let Some(tx_num) = tx_reader.tx_num_by_txid(&prev_out.txid)? {
+                *spent_token = token_tx_reader
+                    .by_tx_num(tx_num)?
+                    .and_then(|tx| tx.output_token(prev_out.out_idx));
+            }
+        }
+        Ok(TxTokens::color(tx, spent_tokens))
+    }
 }
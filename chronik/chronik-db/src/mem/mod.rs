@@ -7,9 +7,11 @@
 mod group_history;
 mod group_utxos;
 mod mempool;
+mod snapshot;
 mod spent_by;
 
 pub use self::group_history::*;
 pub use self::group_utxos::*;
 pub use self::mempool::*;
+pub use self::snapshot::*;
 pub use self::spent_by::*;
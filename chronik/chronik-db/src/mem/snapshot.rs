@@ -0,0 +1,228 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`MempoolSnapshot`], to persist the mempool across restarts.
+
+use std::{collections::HashMap, path::Path};
+
+use abc_rust_error::{Result, WrapErr};
+use bitcoinsuite_core::tx::TxId;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    mem::Mempool,
+    ser::{db_deserialize, db_serialize},
+};
+
+/// Version of the snapshot format, bump this if [`MempoolSnapshot`] changes.
+pub const MEMPOOL_SNAPSHOT_VERSION: u32 = 1;
+
+/// Snapshot of the mempool txs, written to disk on shutdown and loaded again on
+/// startup, so the time txs have first been seen stays the same across
+/// restarts.
+///
+/// Only txids and first-seen times are stored, the txs themselves are added
+/// again by the node once it reloads its mempool.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MempoolSnapshot {
+    /// Version of the snapshot format, see [`MEMPOOL_SNAPSHOT_VERSION`].
+    pub version: u32,
+    /// Txs that were in the mempool when the snapshot has been taken.
+    pub txs: Vec<MempoolSnapshotTx>,
+}
+
+/// Entry of a [`MempoolSnapshot`].
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+)]
+pub struct MempoolSnapshotTx {
+    /// Txid of the mempool tx.
+    pub txid: [u8; 32],
+    /// Time this tx has first been seen.
+    pub time_first_seen: i64,
+}
+
+/// Errors for [`MempoolSnapshot`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MempoolSnapshotError {
+    /// Reading the snapshot file failed
+    #[error("Reading mempool snapshot {0} failed")]
+    ReadFailed(String),
+
+    /// Writing the snapshot file failed
+    #[error("Writing mempool snapshot {0} failed")]
+    WriteFailed(String),
+
+    /// Snapshot has been written by a different version
+    #[error(
+        "Unsupported mempool snapshot version {0}, expected {}",
+        MEMPOOL_SNAPSHOT_VERSION
+    )]
+    UnsupportedVersion(u32),
+}
+
+use self::MempoolSnapshotError::*;
+
+impl MempoolSnapshot {
+    /// Take a snapshot of the txs currently in the mempool.
+    pub fn from_mempool(mempool: &Mempool) -> Self {
+        let mut txs = mempool
+            .txs()
+            .values()
+            .map(|tx| MempoolSnapshotTx {
+                txid: *tx.tx.txid_ref().as_bytes(),
+                time_first_seen: tx.time_first_seen,
+            })
+            .collect::<Vec<_>>();
+        txs.sort_unstable();
+        MempoolSnapshot {
+            version: MEMPOOL_SNAPSHOT_VERSION,
+            txs,
+        }
+    }
+
+    /// Write the snapshot to the given path. The file is first written to a
+    /// temporary file and then renamed, so a crash never leaves a partially
+    /// written snapshot behind.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        let data = db_serialize(self)?;
+        std::fs::write(&tmp_path, data)
+            .wrap_err_with(|| WriteFailed(tmp_path.display().to_string()))?;
+        std::fs::rename(&tmp_path, path)
+            .wrap_err_with(|| WriteFailed(path.display().to_string()))?;
+        Ok(())
+    }
+
+    /// Load and verify the snapshot from the given path, or [`None`] if there
+    /// is none.
+    ///
+    /// The file is removed after loading, so a snapshot is only used once and
+    /// a stale snapshot isn't loaded again after a crash.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = std::fs::read(path)
+            .wrap_err_with(|| ReadFailed(path.display().to_string()))?;
+        std::fs::remove_file(path)
+            .wrap_err_with(|| ReadFailed(path.display().to_string()))?;
+        let snapshot = db_deserialize::<MempoolSnapshot>(&data)?;
+        if snapshot.version != MEMPOOL_SNAPSHOT_VERSION {
+            return Err(UnsupportedVersion(snapshot.version).into());
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// First-seen times of the txs in the snapshot, by [`TxId`].
+    pub fn times_first_seen(&self) -> HashMap<TxId, i64> {
+        self.txs
+            .iter()
+            .map(|tx| (TxId::from(tx.txid), tx.time_first_seen))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use abc_rust_error::Result;
+    use bitcoinsuite_core::tx::{Tx, TxId, TxMut};
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        db::Db,
+        groups::{prefix_mock_compress, ScriptGroup},
+        mem::{
+            Mempool, MempoolSnapshot, MempoolSnapshotError, MempoolSnapshotTx,
+            MempoolTx, MEMPOOL_SNAPSHOT_VERSION,
+        },
+        ser::db_serialize,
+    };
+
+    #[test]
+    fn test_mempool_snapshot() -> Result<()> {
+        abc_rust_error::install();
+        let tempdir = tempdir::TempDir::new("chronik-db--mempool_snapshot")?;
+        let db = Db::open(tempdir.path().join("db"))?;
+        let path = tempdir.path().join("mempool.dat");
+        let mut mempool =
+            Mempool::new(ScriptGroup::new(prefix_mock_compress), false);
+
+        // No snapshot yet
+        assert_eq!(MempoolSnapshot::load(&path)?, None);
+
+        for (txid_num, time_first_seen) in [(2, 1000), (1, 2000)] {
+            mempool.insert(
+                &db,
+                MempoolTx {
+                    tx: Tx::with_txid(
+                        TxId::from([txid_num; 32]),
+                        TxMut::default(),
+                    ),
+                    time_first_seen,
+                },
+            )?;
+        }
+        let snapshot = MempoolSnapshot::from_mempool(&mempool);
+        assert_eq!(
+            snapshot,
+            MempoolSnapshot {
+                version: MEMPOOL_SNAPSHOT_VERSION,
+                txs: vec![
+                    MempoolSnapshotTx {
+                        txid: [1; 32],
+                        time_first_seen: 2000,
+                    },
+                    MempoolSnapshotTx {
+                        txid: [2; 32],
+                        time_first_seen: 1000,
+                    },
+                ],
+            },
+        );
+        assert_eq!(
+            snapshot.times_first_seen(),
+            [(TxId::from([1; 32]), 2000), (TxId::from([2; 32]), 1000)]
+                .into_iter()
+                .collect(),
+        );
+
+        // Loading removes the snapshot
+        snapshot.save(&path)?;
+        assert_eq!(MempoolSnapshot::load(&path)?, Some(snapshot));
+        assert_eq!(MempoolSnapshot::load(&path)?, None);
+
+        // Unsupported version
+        std::fs::write(
+            &path,
+            db_serialize(&MempoolSnapshot {
+                version: MEMPOOL_SNAPSHOT_VERSION + 1,
+                txs: vec![],
+            })?,
+        )?;
+        assert_eq!(
+            MempoolSnapshot::load(&path)
+                .unwrap_err()
+                .downcast::<MempoolSnapshotError>()?,
+            MempoolSnapshotError::UnsupportedVersion(
+                MEMPOOL_SNAPSHOT_VERSION + 1,
+            ),
+        );
+
+        // Corrupted snapshot
+        std::fs::write(&path, [0xff; 3])?;
+        assert!(MempoolSnapshot::load(&path).is_err());
+
+        Ok(())
+    }
+}
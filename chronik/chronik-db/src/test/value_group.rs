@@ -7,7 +7,7 @@ use bitcoinsuite_core::tx::{
 };
 
 use crate::{
-    group::{Group, GroupQuery, MemberItem},
+    group::{Group, GroupQuery, MemberItem, UtxoGroup},
     io::{GroupHistoryConf, GroupUtxoConf},
 };
 
@@ -20,8 +20,13 @@ impl Group for ValueGroup {
     type Iter<'a> = Vec<MemberItem<i64>>;
     type Member<'a> = i64;
     type MemberSer<'a> = [u8; 8];
+    type Aux = ();
 
-    fn input_members(&self, query: GroupQuery<'_>) -> Self::Iter<'_> {
+    fn input_members(
+        &self,
+        query: GroupQuery<'_>,
+        _aux: &(),
+    ) -> Self::Iter<'_> {
         let mut inputs = Vec::new();
         if !query.is_coinbase {
             for (idx, input) in query.tx.inputs.iter().enumerate() {
@@ -36,7 +41,11 @@ impl Group for ValueGroup {
         inputs
     }
 
-    fn output_members(&self, query: GroupQuery<'_>) -> Self::Iter<'_> {
+    fn output_members(
+        &self,
+        query: GroupQuery<'_>,
+        _aux: &(),
+    ) -> Self::Iter<'_> {
         let mut outputs = Vec::new();
         for (idx, output) in query.tx.outputs.iter().enumerate() {
             outputs.push(MemberItem {
@@ -57,10 +66,13 @@ impl Group for ValueGroup {
             page_size: 4,
         }
     }
+}
 
+impl UtxoGroup for ValueGroup {
     fn utxo_conf() -> GroupUtxoConf {
         GroupUtxoConf {
             cf_name: "value_utxo",
+            balance_cf_name: Some("value_balance"),
         }
     }
 }
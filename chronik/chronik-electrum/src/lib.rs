@@ -0,0 +1,13 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Crate for serving the Electrum protocol (as spoken by ElectrumX and
+//! Fulcrum) from the Chronik index, so existing wallets can use Chronik as
+//! their backend.
+
+abc_rust_lint::lint! {
+    pub mod rpc;
+    pub mod server;
+    pub mod session;
+}
@@ -0,0 +1,344 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for the JSON-RPC 2.0 messages the Electrum protocol is built on.
+
+use abc_rust_error::{parse_error_status, Report};
+use chronik_util::{log, log_chronik};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+/// JSON-RPC error code for requests that are not valid JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code for JSON that is not a valid request.
+pub const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code for unknown methods.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for invalid method params.
+pub const INVALID_PARAMS: i64 = -32602;
+/// Error code used by ElectrumX for valid requests that can't be served, e.g.
+/// because a tx doesn't exist.
+pub const BAD_REQUEST: i64 = 1;
+/// Error code used by ElectrumX for internal errors.
+pub const DAEMON_ERROR: i64 = 2;
+
+/// A JSON-RPC request or notification sent by the client.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpcRequest {
+    /// ID of the request, echoed back in the response. `None` for
+    /// notifications, which don't get a response.
+    pub id: Option<Value>,
+    /// Method to call, e.g. `"blockchain.scripthash.get_history"`.
+    pub method: String,
+    /// Positional params of the method.
+    pub params: Vec<Value>,
+}
+
+/// Errors in the JSON-RPC layer, which have their own error codes.
+#[derive(Debug, Error, PartialEq)]
+pub enum RpcError {
+    /// Request is not valid JSON
+    #[error("Parse error: {0}")]
+    ParseError(String),
+
+    /// JSON is not a valid JSON-RPC request
+    #[error("Invalid request: {0}")]
+    InvalidRequest(&'static str),
+
+    /// Method not known to the server
+    #[error("Unknown method: {0}")]
+    MethodNotFound(String),
+
+    /// Param missing or has the wrong type
+    #[error("Invalid params: {0}")]
+    InvalidParams(String),
+}
+
+use self::RpcError::*;
+
+impl RpcError {
+    /// JSON-RPC error code of this error.
+    pub fn code(&self) -> i64 {
+        match self {
+            ParseError(_) => PARSE_ERROR,
+            InvalidRequest(_) => INVALID_REQUEST,
+            MethodNotFound(_) => METHOD_NOT_FOUND,
+            InvalidParams(_) => INVALID_PARAMS,
+        }
+    }
+}
+
+/// Parse a JSON value into a [`RpcRequest`].
+///
+/// Only positional params are supported, as used by all Electrum clients.
+pub fn parse_request(value: Value) -> Result<RpcRequest, RpcError> {
+    let mut object = match value {
+        Value::Object(object) => object,
+        _ => return Err(InvalidRequest("Request must be an object")),
+    };
+    let method = match object.remove("method") {
+        Some(Value::String(method)) => method,
+        _ => return Err(InvalidRequest("Missing method")),
+    };
+    let params = match object.remove("params") {
+        None | Some(Value::Null) => vec![],
+        Some(Value::Array(params)) => params,
+        Some(_) => return Err(InvalidRequest("Params must be an array")),
+    };
+    Ok(RpcRequest {
+        id: object.remove("id"),
+        method,
+        params,
+    })
+}
+
+/// Get the param at `idx`, erroring if it is missing.
+pub fn param<'a>(
+    params: &'a [Value],
+    idx: usize,
+    name: &str,
+) -> Result<&'a Value, RpcError> {
+    params
+        .get(idx)
+        .ok_or_else(|| InvalidParams(format!("Missing {name}")))
+}
+
+/// Get the string param at `idx`.
+pub fn str_param<'a>(
+    params: &'a [Value],
+    idx: usize,
+    name: &str,
+) -> Result<&'a str, RpcError> {
+    param(params, idx, name)?
+        .as_str()
+        .ok_or_else(|| InvalidParams(format!("{name} must be a string")))
+}
+
+/// Get the non-negative integer param at `idx`.
+pub fn u64_param(
+    params: &[Value],
+    idx: usize,
+    name: &str,
+) -> Result<u64, RpcError> {
+    param(params, idx, name)?.as_u64().ok_or_else(|| {
+        InvalidParams(format!("{name} must be a non-negative integer"))
+    })
+}
+
+/// Get the non-negative integer param at `idx`, or `default` if missing.
+pub fn u64_param_or(
+    params: &[Value],
+    idx: usize,
+    name: &str,
+    default: u64,
+) -> Result<u64, RpcError> {
+    match params.get(idx) {
+        None => Ok(default),
+        Some(_) => u64_param(params, idx, name),
+    }
+}
+
+/// Get the boolean param at `idx`, or `default` if missing.
+pub fn bool_param_or(
+    params: &[Value],
+    idx: usize,
+    name: &str,
+    default: bool,
+) -> Result<bool, RpcError> {
+    match params.get(idx) {
+        None => Ok(default),
+        Some(value) => value
+            .as_bool()
+            .ok_or_else(|| InvalidParams(format!("{name} must be a boolean"))),
+    }
+}
+
+/// Build the response to a successful request.
+pub fn make_response(id: Value, result: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "result": result})
+}
+
+/// Build the response to a failed request.
+///
+/// Like for the HTTP endpoint, errors prefixed with a 4xx status are the
+/// client's fault and are returned as they are. Errors prefixed with a 5xx
+/// status are also returned but logged, and any other error is only logged,
+/// so we don't leak potentially sensitive internals.
+pub fn make_error_response(id: Value, report: &Report) -> Value {
+    let (code, msg) = match report.downcast_ref::<RpcError>() {
+        Some(rpc_error) => (rpc_error.code(), rpc_error.to_string()),
+        None => {
+            let msg = report.to_string();
+            match parse_error_status(&msg) {
+                None => {
+                    log_chronik!("{report:?}\n");
+                    log!(
+                        "Chronik Electrum server got an unknown error: \
+                         {report:#}\n"
+                    );
+                    (DAEMON_ERROR, "Unknown error, contact admins".to_string())
+                }
+                Some(status) if status.is_server_error() => {
+                    log_chronik!("{report:?}\n");
+                    log!(
+                        "Chronik Electrum server got an internal server \
+                         error: {report:#}\n"
+                    );
+                    (DAEMON_ERROR, msg)
+                }
+                Some(_) => (BAD_REQUEST, msg),
+            }
+        }
+    };
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": msg},
+    })
+}
+
+/// Build a notification sent by the server, e.g. for subscriptions.
+pub fn make_notification(method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "method": method, "params": params})
+}
+
+#[cfg(test)]
+mod tests {
+    use abc_rust_error::Report;
+    use serde_json::{json, Value};
+    use thiserror::Error;
+
+    use crate::rpc::{
+        bool_param_or, make_error_response, make_notification, make_response,
+        parse_request, str_param, u64_param, u64_param_or, RpcError,
+        RpcRequest,
+    };
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(
+            parse_request(json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "server.ping",
+            })),
+            Ok(RpcRequest {
+                id: Some(json!(7)),
+                method: "server.ping".to_string(),
+                params: vec![],
+            }),
+        );
+        assert_eq!(
+            parse_request(json!({
+                "method": "blockchain.block.header",
+                "params": [5, 0],
+            })),
+            Ok(RpcRequest {
+                id: None,
+                method: "blockchain.block.header".to_string(),
+                params: vec![json!(5), json!(0)],
+            }),
+        );
+        assert_eq!(
+            parse_request(json!([])),
+            Err(RpcError::InvalidRequest("Request must be an object")),
+        );
+        assert_eq!(
+            parse_request(json!({"id": 1})),
+            Err(RpcError::InvalidRequest("Missing method")),
+        );
+        assert_eq!(
+            parse_request(json!({"method": "x", "params": {"a": 1}})),
+            Err(RpcError::InvalidRequest("Params must be an array")),
+        );
+    }
+
+    #[test]
+    fn test_params() {
+        let params = [json!("abc"), json!(3), json!(true)];
+        assert_eq!(str_param(&params, 0, "a"), Ok("abc"));
+        assert_eq!(
+            str_param(&params, 1, "b"),
+            Err(RpcError::InvalidParams("b must be a string".to_string())),
+        );
+        assert_eq!(
+            str_param(&params, 3, "d"),
+            Err(RpcError::InvalidParams("Missing d".to_string())),
+        );
+        assert_eq!(u64_param(&params, 1, "b"), Ok(3));
+        assert_eq!(
+            u64_param(&params, 3, "d"),
+            Err(RpcError::InvalidParams("Missing d".to_string())),
+        );
+        assert_eq!(u64_param_or(&params, 1, "b", 0), Ok(3));
+        assert_eq!(u64_param_or(&params, 3, "d", 9), Ok(9));
+        assert_eq!(
+            u64_param_or(&params, 0, "a", 0),
+            Err(RpcError::InvalidParams(
+                "a must be a non-negative integer".to_string()
+            )),
+        );
+        assert_eq!(bool_param_or(&params, 2, "c", false), Ok(true));
+        assert_eq!(bool_param_or(&params, 3, "d", false), Ok(false));
+        assert_eq!(
+            bool_param_or(&params, 1, "b", false),
+            Err(RpcError::InvalidParams("b must be a boolean".to_string())),
+        );
+    }
+
+    #[test]
+    fn test_responses() {
+        #[derive(Debug, Error)]
+        enum TestError {
+            #[error("Something obscure")]
+            Obscure,
+            #[error("500: Cable eaten by cat")]
+            CableEaten,
+            #[error("404: Tx not found")]
+            NotFound,
+        }
+
+        assert_eq!(
+            make_response(json!(1), json!([1, 2])),
+            json!({"jsonrpc": "2.0", "id": 1, "result": [1, 2]}),
+        );
+        assert_eq!(
+            make_notification("blockchain.headers.subscribe", json!([{}])),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "blockchain.headers.subscribe",
+                "params": [{}],
+            }),
+        );
+
+        let error = |id: Value, report: Report| {
+            let response = make_error_response(id, &report);
+            (
+                response["id"].clone(),
+                response["error"]["code"].as_i64().unwrap(),
+                response["error"]["message"].as_str().unwrap().to_string(),
+            )
+        };
+        assert_eq!(
+            error(json!(1), RpcError::MethodNotFound("x".to_string()).into()),
+            (json!(1), -32601, "Unknown method: x".to_string()),
+        );
+        assert_eq!(
+            error(Value::Null, RpcError::ParseError("eof".to_string()).into()),
+            (Value::Null, -32700, "Parse error: eof".to_string()),
+        );
+        assert_eq!(
+            error(json!("a"), TestError::NotFound.into()),
+            (json!("a"), 1, "404: Tx not found".to_string()),
+        );
+        assert_eq!(
+            error(json!(2), TestError::CableEaten.into()),
+            (json!(2), 2, "500: Cable eaten by cat".to_string()),
+        );
+        assert_eq!(
+            error(json!(3), TestError::Obscure.into()),
+            (json!(3), 2, "Unknown error, contact admins".to_string()),
+        );
+    }
+}
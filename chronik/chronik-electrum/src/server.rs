@@ -0,0 +1,327 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`ElectrumServer`].
+
+use std::{net::SocketAddr, sync::Arc};
+
+use abc_rust_error::Result;
+use chronik_indexer::indexer::{ChronikIndexer, Node};
+use chronik_util::{log, log_chronik};
+use serde_json::Value;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::RwLock,
+};
+
+use crate::{
+    rpc::{make_error_response, make_response, parse_request, RpcError},
+    session::{ElectrumSession, SessionEvent},
+};
+
+/// Ref-counted indexer with read or write access
+pub type ChronikIndexerRef = Arc<RwLock<ChronikIndexer>>;
+/// Ref-counted access to the bitcoind node
+pub type NodeRef = Arc<Node>;
+
+/// Port used if a host is given without one, same as ElectrumX's TCP port.
+pub const DEFAULT_ELECTRUM_PORT: u16 = 50001;
+/// Maximum size of a single request line, large enough for broadcasting the
+/// hex of a max-size tx.
+pub const MAX_REQUEST_SIZE: usize = 4_000_000;
+
+/// Params defining where to serve [`ElectrumServer`].
+#[derive(Clone, Debug)]
+pub struct ElectrumServerParams {
+    /// Host addresses (port + IP) where to serve the Electrum protocol at.
+    pub hosts: Vec<SocketAddr>,
+    /// Indexer to read data from
+    pub indexer: ChronikIndexerRef,
+    /// Access to the bitcoind node
+    pub node: NodeRef,
+}
+
+/// Electrum protocol server, serving newline-delimited JSON-RPC over TCP.
+#[derive(Debug)]
+pub struct ElectrumServer {
+    listeners: Vec<std::net::TcpListener>,
+    indexer: ChronikIndexerRef,
+    node: NodeRef,
+}
+
+/// Errors for [`ElectrumServer`].
+#[derive(Debug, Eq, Error, PartialEq)]
+pub enum ElectrumServerError {
+    /// Binding to host address failed
+    #[error("Chronik Electrum failed binding to {0}: {1}")]
+    FailedBindingAddress(SocketAddr, String),
+
+    /// Listening on a bound address failed
+    #[error("Chronik Electrum failed listening: {0}")]
+    FailedListening(String),
+
+    /// Client sent a line that's too long
+    #[error("Request too large, may not be above {} bytes", MAX_REQUEST_SIZE)]
+    RequestTooLarge,
+}
+
+use self::ElectrumServerError::*;
+
+impl ElectrumServer {
+    /// Binds the Electrum server on the given hosts
+    pub fn setup(params: ElectrumServerParams) -> Result<Self> {
+        let listeners = params
+            .hosts
+            .into_iter()
+            .map(|host| {
+                let listener =
+                    std::net::TcpListener::bind(host).map_err(|err| {
+                        FailedBindingAddress(host, err.to_string())
+                    })?;
+                listener.set_nonblocking(true).map_err(|err| {
+                    FailedBindingAddress(host, err.to_string())
+                })?;
+                Ok(listener)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ElectrumServer {
+            listeners,
+            indexer: params.indexer,
+            node: params.node,
+        })
+    }
+
+    /// Addresses the server is bound to, e.g. to find out which port has been
+    /// picked when binding to port 0.
+    pub fn local_addrs(&self) -> Result<Vec<SocketAddr>> {
+        self.listeners
+            .iter()
+            .map(|listener| {
+                listener
+                    .local_addr()
+                    .map_err(|err| FailedListening(err.to_string()).into())
+            })
+            .collect()
+    }
+
+    /// Serve the Electrum protocol on the bound hosts.
+    pub async fn serve(self) -> Result<()> {
+        let listeners = self
+            .listeners
+            .into_iter()
+            .map(|listener| {
+                TcpListener::from_std(listener)
+                    .map_err(|err| FailedListening(err.to_string()).into())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let servers = listeners.into_iter().map(|listener| {
+            let indexer = Arc::clone(&self.indexer);
+            let node = Arc::clone(&self.node);
+            Box::pin(Self::accept_loop(listener, indexer, node))
+        });
+        let (result, _, _) = futures::future::select_all(servers).await;
+        result
+    }
+
+    async fn accept_loop(
+        listener: TcpListener,
+        indexer: ChronikIndexerRef,
+        node: NodeRef,
+    ) -> Result<()> {
+        loop {
+            let (stream, addr) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    // E.g. too many open files, keep serving the others
+                    log!("Chronik Electrum failed accepting: {err}\n");
+                    continue;
+                }
+            };
+            log_chronik!("Electrum connection from {addr}\n");
+            let indexer = Arc::clone(&indexer);
+            let node = Arc::clone(&node);
+            tokio::spawn(async move {
+                if let Err(report) =
+                    handle_connection(stream, &indexer, &node).await
+                {
+                    log_chronik!(
+                        "Electrum connection {addr} failed: {report}\n"
+                    );
+                }
+            });
+        }
+    }
+}
+
+enum ConnectionEvent {
+    Line(std::io::Result<usize>),
+    Session(SessionEvent),
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    indexer: &ChronikIndexerRef,
+    node: &NodeRef,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut session = ElectrumSession::default();
+    let mut line = Vec::new();
+    let result = async {
+        loop {
+            let event = tokio::select! {
+                num_read = read_line(&mut reader, &mut line) => {
+                    ConnectionEvent::Line(num_read)
+                }
+                event = session.recv_event() => {
+                    ConnectionEvent::Session(event)
+                }
+            };
+            let message = match event {
+                ConnectionEvent::Line(num_read) => {
+                    if num_read? == 0 {
+                        // Connection closed
+                        return Ok(());
+                    }
+                    if !line.ends_with(b"\n") && line.len() > MAX_REQUEST_SIZE {
+                        return Err(RequestTooLarge.into());
+                    }
+                    let message =
+                        handle_line(&line, &mut session, indexer, node).await;
+                    line.clear();
+                    message
+                }
+                ConnectionEvent::Session(event) => {
+                    session.handle_event(event, indexer).await?
+                }
+            };
+            if let Some(message) = message {
+                let mut bytes = serde_json::to_vec(&message)?;
+                bytes.push(b'\n');
+                writer.write_all(&bytes).await?;
+            }
+        }
+    }
+    .await;
+    session.unsubscribe_all(indexer).await;
+    result
+}
+
+/// Read until the next newline, but no more than [`MAX_REQUEST_SIZE`] bytes.
+///
+/// This is cancel safe, partially read lines stay in `line`.
+async fn read_line<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    line: &mut Vec<u8>,
+) -> std::io::Result<usize> {
+    let limit = (MAX_REQUEST_SIZE + 1).saturating_sub(line.len());
+    let num_read = reader.take(limit as u64).read_until(b'\n', line).await?;
+    Ok(num_read)
+}
+
+/// Handle a line sent by the client, which is either a single request or a
+/// batch of requests. Returns the response, if any.
+async fn handle_line(
+    line: &[u8],
+    session: &mut ElectrumSession,
+    indexer: &ChronikIndexerRef,
+    node: &NodeRef,
+) -> Option<Value> {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return None;
+    }
+    let value = match serde_json::from_slice::<Value>(line) {
+        Ok(value) => value,
+        Err(err) => {
+            let err = RpcError::ParseError(err.to_string());
+            return Some(make_error_response(Value::Null, &err.into()));
+        }
+    };
+    match value {
+        Value::Array(values) if values.is_empty() => {
+            let err = RpcError::InvalidRequest("Empty batch");
+            Some(make_error_response(Value::Null, &err.into()))
+        }
+        Value::Array(values) => {
+            let mut responses = Vec::with_capacity(values.len());
+            for value in values {
+                if let Some(response) =
+                    handle_value(value, session, indexer, node).await
+                {
+                    responses.push(response);
+                }
+            }
+            match responses.is_empty() {
+                true => None,
+                false => Some(Value::Array(responses)),
+            }
+        }
+        value => handle_value(value, session, indexer, node).await,
+    }
+}
+
+async fn handle_value(
+    value: Value,
+    session: &mut ElectrumSession,
+    indexer: &ChronikIndexerRef,
+    node: &NodeRef,
+) -> Option<Value> {
+    let id = value.get("id").cloned().unwrap_or(Value::Null);
+    let request = match parse_request(value) {
+        Ok(request) => request,
+        Err(err) => return Some(make_error_response(id, &err.into())),
+    };
+    let result = session.handle_request(&request, indexer, node).await;
+    // Notifications don't get a response, not even errors
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => make_response(id, result),
+        Err(report) => make_error_response(id, &report),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use abc_rust_error::Result;
+    use tokio::io::BufReader;
+
+    use crate::server::{read_line, MAX_REQUEST_SIZE};
+
+    #[tokio::test]
+    async fn test_read_line() -> Result<()> {
+        let data = b"{\"a\": 1}\n\n{\"b\": 2}".as_ref();
+        let mut reader = BufReader::new(data);
+        let mut line = Vec::new();
+        assert_eq!(read_line(&mut reader, &mut line).await?, 9);
+        assert_eq!(line, b"{\"a\": 1}\n");
+        line.clear();
+        assert_eq!(read_line(&mut reader, &mut line).await?, 1);
+        assert_eq!(line, b"\n");
+        line.clear();
+        // Last line without newline
+        assert_eq!(read_line(&mut reader, &mut line).await?, 8);
+        assert_eq!(line, b"{\"b\": 2}");
+        line.clear();
+        assert_eq!(read_line(&mut reader, &mut line).await?, 0);
+
+        // Lines longer than MAX_REQUEST_SIZE are cut off after one more byte
+        let data = vec![b'x'; MAX_REQUEST_SIZE + 10];
+        let mut reader = BufReader::new(data.as_slice());
+        let mut line = Vec::new();
+        assert_eq!(
+            read_line(&mut reader, &mut line).await?,
+            MAX_REQUEST_SIZE + 1,
+        );
+        assert!(!line.ends_with(b"\n"));
+
+        // Partially read lines are continued
+        let mut reader = BufReader::new(b"23]\n".as_ref());
+        let mut line = b"[1, ".to_vec();
+        assert_eq!(read_line(&mut reader, &mut line).await?, 4);
+        assert_eq!(line, b"[1, 23]\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,552 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`ElectrumSession`], serving the requests of one connection.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use abc_rust_error::Result;
+use bitcoinsuite_core::{
+    hash::{Hashed, Sha256},
+    tx::TxId,
+};
+use chronik_db::io::BlockHeight;
+use chronik_indexer::{
+    indexer::{ChronikIndexer, Node},
+    query::{QueryBroadcast, QueryGroupHistoryError},
+    subs::{BlockMsg, BlockMsgType},
+    subs_group::TxMsg,
+};
+use chronik_proto::proto;
+use chronik_util::{log, log_chronik};
+use futures::future::select_all;
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{
+    rpc::{
+        bool_param_or, make_notification, str_param, u64_param, u64_param_or,
+        RpcError, RpcRequest,
+    },
+    server::{ChronikIndexerRef, NodeRef},
+};
+
+/// Server software and version, reported by `server.version`.
+pub const SERVER_VERSION: &str = concat!("Chronik ", env!("CARGO_PKG_VERSION"));
+/// Version of the Electrum protocol spoken by the server.
+pub const PROTOCOL_VERSION: &str = "1.4";
+/// Maximum number of txs returned by `blockchain.scripthash.get_history`.
+/// The whole history is hashed for the status of a script hash, so we reject
+/// script hashes with huge histories, like ElectrumX does.
+pub const MAX_HISTORY_TXS: usize = 10_000;
+
+/// Errors serving requests of an [`ElectrumSession`].
+#[derive(Debug, Error, PartialEq)]
+pub enum ElectrumSessionError {
+    /// Script hash is not 32 bytes of hex
+    #[error("400: Invalid script hash: {0}")]
+    InvalidScriptHash(String),
+
+    /// Txid is not 32 bytes of hex
+    #[error("400: Invalid txid: {0}")]
+    InvalidTxId(String),
+
+    /// Raw tx is not hex
+    #[error("400: Invalid raw tx hex: {0}")]
+    InvalidRawTxHex(String),
+
+    /// Script hash has too many txs to return its history
+    #[error(
+        "400: History of {0} too large, may not be above {} txs but got {1}",
+        MAX_HISTORY_TXS
+    )]
+    HistoryTooLarge(String, usize),
+
+    /// Only raw txs can be returned
+    #[error("400: Verbose txs are not supported")]
+    VerboseTxNotSupported,
+
+    /// Merkle proofs to a checkpoint are not supported
+    #[error("400: Checkpoint heights are not supported")]
+    CheckpointNotSupported,
+
+    /// No block at the requested height
+    #[error("404: Block not found: {0}")]
+    BlockNotFound(u64),
+
+    /// Chronik hasn't indexed any blocks yet
+    #[error("404: No blocks indexed yet")]
+    NoBlocks,
+}
+
+use self::ElectrumSessionError::*;
+
+/// Something happened to a subscription of an [`ElectrumSession`], see
+/// [`ElectrumSession::recv_event`].
+#[derive(Debug)]
+pub enum SessionEvent {
+    /// A block has been connected or disconnected etc.
+    Block(BlockMsg),
+    /// A tx involving the script hash has been added or removed, or we missed
+    /// some messages.
+    ScriptHash(Sha256),
+    /// The subscription has been closed, nothing to do.
+    Nothing,
+}
+
+/// State of a connection, i.e. its subscriptions.
+#[derive(Debug, Default)]
+pub struct ElectrumSession {
+    headers: Option<broadcast::Receiver<BlockMsg>>,
+    script_hashes: HashMap<Sha256, ScriptHashSub>,
+}
+
+#[derive(Debug)]
+struct ScriptHashSub {
+    recv: broadcast::Receiver<TxMsg>,
+    /// Last status sent to the client, to only notify about changes.
+    status: Option<String>,
+}
+
+/// Entry of the history of a script hash.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HistoryEntry {
+    /// Hex of the txid, in big-endian display order.
+    pub tx_hash: String,
+    /// Height of the block the tx has been mined in. For mempool txs, this is
+    /// 0 if all its parents are mined and -1 otherwise.
+    pub height: BlockHeight,
+    /// Fee of the tx, only given for mempool txs.
+    pub fee: Option<i64>,
+}
+
+impl ElectrumSession {
+    /// Serve the request and return its result.
+    pub async fn handle_request(
+        &mut self,
+        request: &RpcRequest,
+        indexer: &ChronikIndexerRef,
+        node: &NodeRef,
+    ) -> Result<Value> {
+        let params = request.params.as_slice();
+        match request.method.as_str() {
+            "server.version" => Ok(json!([SERVER_VERSION, PROTOCOL_VERSION])),
+            "server.ping" => Ok(Value::Null),
+            "blockchain.block.header" => {
+                let height = u64_param(params, 0, "height")?;
+                if u64_param_or(params, 1, "cp_height", 0)? != 0 {
+                    return Err(CheckpointNotSupported.into());
+                }
+                let indexer = indexer.read().await;
+                Ok(json!(block_header_hex(&indexer, node, height)?))
+            }
+            "blockchain.headers.subscribe" => {
+                let indexer = indexer.read().await;
+                if self.headers.is_none() {
+                    log_chronik!("Electrum subscribe to headers\n");
+                    let subs = indexer.subs().read().await;
+                    self.headers = Some(subs.sub_to_block_msgs());
+                }
+                let tip_height = indexer.blocks().blockchain_info()?.tip_height;
+                if tip_height < 0 {
+                    return Err(NoBlocks.into());
+                }
+                let tip_height = tip_height as u64;
+                Ok(json!({
+                    "hex": block_header_hex(&indexer, node, tip_height)?,
+                    "height": tip_height,
+                }))
+            }
+            "blockchain.scripthash.get_balance" => {
+                let script_hash = script_hash_param(params)?;
+                let indexer = indexer.read().await;
+                let balance =
+                    indexer.script_hash_balance()?.balance(script_hash)?;
+                Ok(json!({
+                    "confirmed": balance.confirmed_value,
+                    "unconfirmed": balance.unconfirmed_delta,
+                }))
+            }
+            "blockchain.scripthash.get_history" => {
+                let script_hash = script_hash_param(params)?;
+                let indexer = indexer.read().await;
+                let history = script_hash_history(&indexer, script_hash)?;
+                Ok(Value::Array(
+                    history.iter().map(history_entry_json).collect(),
+                ))
+            }
+            "blockchain.scripthash.listunspent" => {
+                let script_hash = script_hash_param(params)?;
+                let indexer = indexer.read().await;
+                let utxos = indexer.script_hash_utxos()?.utxos(script_hash)?;
+                Ok(Value::Array(utxos.iter().map(utxo_json).collect()))
+            }
+            "blockchain.scripthash.subscribe" => {
+                let script_hash = script_hash_param(params)?;
+                let indexer = indexer.read().await;
+                if let Entry::Vacant(entry) =
+                    self.script_hashes.entry(script_hash)
+                {
+                    log_chronik!(
+                        "Electrum subscribe to script hash {}\n",
+                        script_hash.hex_be(),
+                    );
+                    let mut subs = indexer.subs().write().await;
+                    let recv = subs
+                        .subs_script_hash_mut()
+                        .subscribe_to_member(&script_hash);
+                    entry.insert(ScriptHashSub { recv, status: None });
+                }
+                // Subscribed before computing the status, so we don't miss
+                // any changes in between
+                let status = script_hash_status(&indexer, script_hash)?;
+                if let Some(sub) = self.script_hashes.get_mut(&script_hash) {
+                    sub.status = status.clone();
+                }
+                Ok(json!(status))
+            }
+            "blockchain.scripthash.unsubscribe" => {
+                let script_hash = script_hash_param(params)?;
+                let indexer = indexer.read().await;
+                let was_subscribed =
+                    self.script_hashes.contains_key(&script_hash);
+                self.unsubscribe_script_hash(&indexer, &script_hash).await;
+                Ok(json!(was_subscribed))
+            }
+            "blockchain.transaction.broadcast" => {
+                let raw_tx_hex = str_param(params, 0, "raw_tx")?;
+                let raw_tx = hex::decode(raw_tx_hex)
+                    .map_err(|_| InvalidRawTxHex(raw_tx_hex.to_string()))?;
+                let query = QueryBroadcast {
+                    broadcaster: node.as_ref(),
+                };
+                let txid = query.broadcast_tx(&raw_tx)?;
+                Ok(json!(txid.to_string()))
+            }
+            "blockchain.transaction.get" => {
+                let txid_hex = str_param(params, 0, "tx_hash")?;
+                let txid = txid_hex
+                    .parse::<TxId>()
+                    .map_err(|_| InvalidTxId(txid_hex.to_string()))?;
+                if bool_param_or(params, 1, "verbose", false)? {
+                    return Err(VerboseTxNotSupported.into());
+                }
+                let indexer = indexer.read().await;
+                let raw_tx = indexer.txs().raw_tx_by_id(&txid)?;
+                Ok(json!(hex::encode(raw_tx.raw_tx)))
+            }
+            method => Err(RpcError::MethodNotFound(method.to_string()).into()),
+        }
+    }
+
+    /// Wait until something happens to one of the subscriptions.
+    ///
+    /// This is cancel safe, so it can be used in `tokio::select!`; handle the
+    /// event afterwards using [`ElectrumSession::handle_event`].
+    pub async fn recv_event(&mut self) -> SessionEvent {
+        tokio::select! {
+            event = Self::recv_headers(&mut self.headers) => event,
+            event = Self::recv_script_hashes(&mut self.script_hashes) => event,
+        }
+    }
+
+    async fn recv_headers(
+        headers: &mut Option<broadcast::Receiver<BlockMsg>>,
+    ) -> SessionEvent {
+        let recv = match headers {
+            Some(recv) => recv,
+            None => return futures::future::pending().await,
+        };
+        match recv.recv().await {
+            Ok(block_msg) => SessionEvent::Block(block_msg),
+            // Only the latest tip is sent, so missed blocks don't matter
+            Err(RecvError::Lagged(_)) => SessionEvent::Nothing,
+            Err(RecvError::Closed) => {
+                *headers = None;
+                SessionEvent::Nothing
+            }
+        }
+    }
+
+    async fn recv_script_hashes(
+        script_hashes: &mut HashMap<Sha256, ScriptHashSub>,
+    ) -> SessionEvent {
+        if script_hashes.is_empty() {
+            return futures::future::pending().await;
+        }
+        let receivers =
+            select_all(script_hashes.iter_mut().map(|(script_hash, sub)| {
+                Box::pin(async move { (*script_hash, sub.recv.recv().await) })
+            }));
+        let ((script_hash, result), _, _) = receivers.await;
+        match result {
+            // If we lagged behind, the status may still have changed
+            Ok(_) | Err(RecvError::Lagged(_)) => {
+                SessionEvent::ScriptHash(script_hash)
+            }
+            Err(RecvError::Closed) => {
+                script_hashes.remove(&script_hash);
+                SessionEvent::Nothing
+            }
+        }
+    }
+
+    /// Handle an event returned by [`ElectrumSession::recv_event`], and
+    /// return the notification to send to the client, if any.
+    ///
+    /// If the status of a script hash can't be computed anymore, e.g. because
+    /// its history grew above [`MAX_HISTORY_TXS`], only that subscription is
+    /// dropped.
+    pub async fn handle_event(
+        &mut self,
+        event: SessionEvent,
+        indexer: &ChronikIndexerRef,
+    ) -> Result<Option<Value>> {
+        match event {
+            SessionEvent::Block(block_msg) => {
+                // Electrum only notifies about new tips; if a block got
+                // disconnected, the next connected block becomes the new tip.
+                if block_msg.msg_type != BlockMsgType::Connected {
+                    return Ok(None);
+                }
+                Ok(Some(make_notification(
+                    "blockchain.headers.subscribe",
+                    json!([{
+                        "hex": hex::encode(&block_msg.header),
+                        "height": block_msg.height,
+                    }]),
+                )))
+            }
+            SessionEvent::ScriptHash(script_hash) => {
+                let indexer = indexer.read().await;
+                let status = match script_hash_status(&indexer, script_hash) {
+                    Ok(status) => status,
+                    Err(report) => {
+                        // E.g. the history grew too large. Only drop this
+                        // subscription, the others of the session stay intact.
+                        log!(
+                            "Chronik Electrum dropping subscription to script \
+                             hash {}: {report}\n",
+                            script_hash.hex_be(),
+                        );
+                        self.unsubscribe_script_hash(&indexer, &script_hash)
+                            .await;
+                        return Ok(None);
+                    }
+                };
+                let sub = match self.script_hashes.get_mut(&script_hash) {
+                    Some(sub) => sub,
+                    None => return Ok(None),
+                };
+                if sub.status == status {
+                    return Ok(None);
+                }
+                sub.status = status.clone();
+                Ok(Some(make_notification(
+                    "blockchain.scripthash.subscribe",
+                    json!([script_hash.hex_be(), status]),
+                )))
+            }
+            SessionEvent::Nothing => Ok(None),
+        }
+    }
+
+    /// Cleanly unsubscribe from everything, e.g. when the connection closed.
+    pub async fn unsubscribe_all(&mut self, indexer: &ChronikIndexerRef) {
+        let indexer = indexer.read().await;
+        self.headers = None;
+        let script_hashes =
+            self.script_hashes.keys().copied().collect::<Vec<_>>();
+        for script_hash in script_hashes {
+            self.unsubscribe_script_hash(&indexer, &script_hash).await;
+        }
+    }
+
+    async fn unsubscribe_script_hash(
+        &mut self,
+        indexer: &ChronikIndexer,
+        script_hash: &Sha256,
+    ) {
+        if self.script_hashes.remove(script_hash).is_some() {
+            log_chronik!(
+                "Electrum unsubscribe from script hash {}\n",
+                script_hash.hex_be(),
+            );
+            let mut subs = indexer.subs().write().await;
+            subs.subs_script_hash_mut()
+                .unsubscribe_from_member(script_hash);
+        }
+    }
+}
+
+/// Parse a script hash like Electrum encodes them, i.e. as the hex of the
+/// SHA-256 of the script in reversed byte order.
+pub fn parse_script_hash(script_hash_hex: &str) -> Result<Sha256> {
+    Ok(Sha256::from_be_hex(script_hash_hex)
+        .map_err(|_| InvalidScriptHash(script_hash_hex.to_string()))?)
+}
+
+fn script_hash_param(params: &[Value]) -> Result<Sha256> {
+    parse_script_hash(str_param(params, 0, "scripthash")?)
+}
+
+fn block_header_hex(
+    indexer: &ChronikIndexer,
+    node: &Node,
+    height: u64,
+) -> Result<String> {
+    let block_height = match BlockHeight::try_from(height) {
+        Ok(block_height) => block_height,
+        Err(_) => return Err(BlockNotFound(height).into()),
+    };
+    let headers =
+        indexer
+            .blocks()
+            .headers_by_range(node, block_height, block_height)?;
+    match headers.headers.first() {
+        Some(header) => Ok(hex::encode(header)),
+        None => Err(BlockNotFound(height).into()),
+    }
+}
+
+/// Hex of a txid from a protobuf message, in big-endian display order.
+fn txid_hex(txid_le: &[u8]) -> String {
+    let mut txid = txid_le.to_vec();
+    txid.reverse();
+    hex::encode(txid)
+}
+
+/// Query the history of the script hash, as returned by
+/// `blockchain.scripthash.get_history`: mined txs in blockchain order,
+/// followed by mempool txs.
+///
+/// This only reads the index and the mempool, but doesn't load any txs from
+/// disk, so it's cheap enough to recompute the status on every update.
+pub fn script_hash_history(
+    indexer: &ChronikIndexer,
+    script_hash: Sha256,
+) -> Result<Vec<HistoryEntry>> {
+    let query = indexer.script_hash_history()?;
+    let num_txs = query.num_confirmed_txs(script_hash)?;
+    if num_txs > MAX_HISTORY_TXS {
+        return Err(HistoryTooLarge(script_hash.hex_be(), num_txs).into());
+    }
+    let mut history = query
+        .confirmed_txids(script_hash)?
+        .into_iter()
+        .map(|(txid, height)| HistoryEntry {
+            tx_hash: txid.to_string(),
+            height,
+            fee: None,
+        })
+        .collect::<Vec<_>>();
+    let mempool = indexer.mempool().mempool;
+    for txid in query.unconfirmed_txids(script_hash) {
+        let mempool_tx = mempool
+            .tx(&txid)
+            .ok_or(QueryGroupHistoryError::MissingMempoolTx(txid))?;
+        let has_unconfirmed_parent = mempool_tx
+            .tx
+            .inputs
+            .iter()
+            .any(|input| mempool.tx(&input.prev_out.txid).is_some());
+        history.push(HistoryEntry {
+            tx_hash: txid.to_string(),
+            height: match has_unconfirmed_parent {
+                true => -1,
+                false => 0,
+            },
+            fee: Some(mempool_tx.fee()),
+        });
+    }
+    Ok(history)
+}
+
+/// Status of the script hash as defined by the Electrum protocol, i.e. the
+/// SHA-256 of the concatenated `"tx_hash:height:"` of its history, or `None`
+/// if it has no history.
+pub fn script_hash_status(
+    indexer: &ChronikIndexer,
+    script_hash: Sha256,
+) -> Result<Option<String>> {
+    Ok(history_status(&script_hash_history(indexer, script_hash)?))
+}
+
+/// Status of the given history, see [`script_hash_status`].
+pub fn history_status(history: &[HistoryEntry]) -> Option<String> {
+    if history.is_empty() {
+        return None;
+    }
+    let status = history
+        .iter()
+        .map(|entry| format!("{}:{}:", entry.tx_hash, entry.height))
+        .collect::<String>();
+    Some(Sha256::digest(status).hex_le())
+}
+
+fn history_entry_json(entry: &HistoryEntry) -> Value {
+    match entry.fee {
+        Some(fee) => json!({
+            "tx_hash": entry.tx_hash,
+            "height": entry.height,
+            "fee": fee,
+        }),
+        None => json!({"tx_hash": entry.tx_hash, "height": entry.height}),
+    }
+}
+
+fn utxo_json(utxo: &proto::ScriptUtxo) -> Value {
+    let outpoint = utxo.outpoint.clone().unwrap_or_default();
+    json!({
+        "tx_hash": txid_hex(&outpoint.txid),
+        "tx_pos": outpoint.out_idx,
+        // Mempool UTXOs have height -1 in Chronik, but 0 in Electrum
+        "height": utxo.block_height.max(0),
+        "value": utxo.value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::hash::{Hashed, Sha256};
+
+    use crate::session::{history_status, parse_script_hash, HistoryEntry};
+
+    #[test]
+    fn test_parse_script_hash() {
+        let script_hash = Sha256::digest([0x51]);
+        assert_eq!(
+            parse_script_hash(&script_hash.hex_be()).ok(),
+            Some(script_hash)
+        );
+        assert_eq!(
+            parse_script_hash("abcd").unwrap_err().to_string(),
+            "400: Invalid script hash: abcd",
+        );
+    }
+
+    #[test]
+    fn test_history_status() {
+        assert_eq!(history_status(&[]), None);
+        let history = [
+            HistoryEntry {
+                tx_hash: "01".repeat(32),
+                height: 10,
+                fee: None,
+            },
+            HistoryEntry {
+                tx_hash: "02".repeat(32),
+                height: 0,
+                fee: Some(300),
+            },
+        ];
+        let expected = Sha256::digest(format!(
+            "{}:10:{}:0:",
+            "01".repeat(32),
+            "02".repeat(32),
+        ));
+        assert_eq!(history_status(&history), Some(expected.hex_le()));
+    }
+}
@@ -10,7 +10,7 @@ use chronik_proto::proto;
 use chronik_util::{log, log_chronik};
 use hyper::StatusCode;
 
-use crate::{protobuf::Protobuf, server::ChronikServerError};
+use crate::{json::ProtobufOrJson, server::ChronikServerError};
 
 /// Wrapper around [`Report`] which can be converted into a [`Response`].
 #[derive(Debug)]
@@ -64,7 +64,7 @@ impl IntoResponse for ReportError {
     fn into_response(self) -> Response {
         let ReportError(report) = self;
         let (code, proto_response) = report_status_error(report);
-        (code, Protobuf(proto_response)).into_response()
+        (code, ProtobufOrJson(proto_response)).into_response()
     }
 }
 
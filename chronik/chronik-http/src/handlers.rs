@@ -2,13 +2,35 @@
 
 use std::{collections::HashMap, fmt::Display, str::FromStr};
 
-use abc_rust_error::{Report, Result};
-use chronik_indexer::indexer::ChronikIndexer;
+use abc_rust_error::{Report, Result, WrapErr};
+use bitcoinsuite_core::{script::Script, tx::TxId};
+use bitcoinsuite_slp::token_id::TokenId;
+use chronik_indexer::{
+    indexer::ChronikIndexer,
+    query::{
+        HistoryCursor, HistoryCursorRequest, QueryBroadcast, TxBroadcaster,
+        DEFAULT_TX_GRAPH_DEPTH,
+    },
+};
 use chronik_proto::proto;
 use hyper::Uri;
 use thiserror::Error;
 
-use crate::{error::ReportError, parse::parse_script_variant_hex};
+use crate::{
+    error::ReportError,
+    parse::{
+        parse_address, parse_lokad_id_hex, parse_script_variant,
+        parse_script_variant_hex,
+    },
+};
+
+/// Maximum number of scripts that can be queried at once in the `/scripts/...`
+/// routes.
+pub const MAX_BATCH_SCRIPTS: usize = 100;
+
+/// Maximum number of txs that can be requested at once in the
+/// `/scripts/history` route, i.e. number of scripts times the page size.
+pub const MAX_BATCH_TXS: usize = 2000;
 
 /// Errors for HTTP handlers.
 #[derive(Debug, Error, PartialEq)]
@@ -27,6 +49,29 @@ pub enum ChronikHandlerError {
         /// Human-readable error message.
         msg: String,
     },
+
+    /// Query is not a token ID
+    #[error("400: Not a token ID: {0}")]
+    NotTokenId(String),
+
+    /// Both a `before` and an `after` cursor have been given
+    #[error("400: Only one of the params before and after can be given")]
+    BeforeAndAfterCursor,
+
+    /// Too many scripts requested in a batch query
+    #[error(
+        "400: Too many scripts requested: {0}, maximum is {}",
+        MAX_BATCH_SCRIPTS
+    )]
+    TooManyScripts(usize),
+
+    /// Too many txs requested in a batch query
+    #[error(
+        "400: Too many txs requested: {0} scripts with page size {1}, maximum \
+         is {} txs in total",
+        MAX_BATCH_TXS
+    )]
+    TooManyBatchTxs(usize, usize),
 }
 
 use self::ChronikHandlerError::*;
@@ -46,11 +91,53 @@ where
     })?))
 }
 
+/// Parse the `before` or `after` cursor param of history queries, if any.
+fn get_cursor_request(
+    params: &HashMap<String, String>,
+) -> Result<Option<HistoryCursorRequest>> {
+    let get_cursor = |param_name: &str| -> Result<Option<HistoryCursor>> {
+        Ok(params
+            .get(param_name)
+            .map(|cursor| cursor.parse())
+            .transpose()?)
+    };
+    match (get_cursor("before")?, get_cursor("after")?) {
+        (Some(_), Some(_)) => Err(BeforeAndAfterCursor.into()),
+        (Some(cursor), None) => Ok(Some(HistoryCursorRequest::Before(cursor))),
+        (None, Some(cursor)) => Ok(Some(HistoryCursorRequest::After(cursor))),
+        (None, None) => Ok(None),
+    }
+}
+
 /// Fallback route that returns a 404 response
 pub async fn handle_not_found(uri: Uri) -> Result<(), ReportError> {
     Err(Report::from(RouteNotFound(uri)).into())
 }
 
+/// Broadcast a single tx to the network, returning its txid.
+pub async fn handle_broadcast_tx(
+    request: proto::BroadcastTxRequest,
+    broadcaster: &dyn TxBroadcaster,
+) -> Result<proto::BroadcastTxResponse> {
+    let query = QueryBroadcast { broadcaster };
+    let txid = query.broadcast_tx(&request.raw_tx)?;
+    Ok(proto::BroadcastTxResponse {
+        txid: txid.to_vec(),
+    })
+}
+
+/// Broadcast multiple txs to the network, returning their txids.
+pub async fn handle_broadcast_txs(
+    request: proto::BroadcastTxsRequest,
+    broadcaster: &dyn TxBroadcaster,
+) -> Result<proto::BroadcastTxsResponse> {
+    let query = QueryBroadcast { broadcaster };
+    let txids = query.broadcast_txs(&request.raw_txs)?;
+    Ok(proto::BroadcastTxsResponse {
+        txids: txids.iter().map(|txid| txid.to_vec()).collect(),
+    })
+}
+
 /// Return a page of the txs of a block.
 pub async fn handle_block_txs(
     hash_or_height: String,
@@ -63,8 +150,32 @@ pub async fn handle_block_txs(
     blocks.block_txs(hash_or_height, page_num as usize, page_size as usize)
 }
 
+/// Return the ancestors of the given tx, up to the `depth` query param.
+pub async fn handle_tx_ancestors(
+    txid: &TxId,
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TxGraph> {
+    let depth: usize =
+        get_param(query_params, "depth")?.unwrap_or(DEFAULT_TX_GRAPH_DEPTH);
+    indexer.tx_graph().ancestors(txid, depth)
+}
+
+/// Return the descendants of the given tx, up to the `depth` query param.
+pub async fn handle_tx_descendants(
+    txid: &TxId,
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TxGraph> {
+    let depth: usize =
+        get_param(query_params, "depth")?.unwrap_or(DEFAULT_TX_GRAPH_DEPTH);
+    indexer.tx_graph().descendants(txid, depth)
+}
+
 /// Return a page of the confirmed txs of the given script.
 /// Scripts are identified by script_type and payload.
+/// If a `before` or `after` cursor is given, the page is relative to it
+/// instead of the `page` number.
 pub async fn handle_script_confirmed_txs(
     script_type: &str,
     payload: &str,
@@ -72,16 +183,14 @@ pub async fn handle_script_confirmed_txs(
     indexer: &ChronikIndexer,
 ) -> Result<proto::TxHistoryPage> {
     let script_variant = parse_script_variant_hex(script_type, payload)?;
-    let script_history = indexer.script_history()?;
-    let page_num: u32 = get_param(query_params, "page")?.unwrap_or(0);
-    let page_size: u32 = get_param(query_params, "page_size")?.unwrap_or(25);
-    let script = script_variant.to_script();
-    script_history.confirmed_txs(&script, page_num as usize, page_size as usize)
+    script_confirmed_txs(&script_variant.to_script(), query_params, indexer)
 }
 
 /// Return a page of the tx history of the given script, in reverse
 /// chronological order, i.e. the latest transaction first and then going back
 /// in time. Scripts are identified by script_type and payload.
+/// If a `before` or `after` cursor is given, the page is relative to it
+/// instead of the `page` number.
 pub async fn handle_script_history(
     script_type: &str,
     payload: &str,
@@ -89,11 +198,7 @@ pub async fn handle_script_history(
     indexer: &ChronikIndexer,
 ) -> Result<proto::TxHistoryPage> {
     let script_variant = parse_script_variant_hex(script_type, payload)?;
-    let script_history = indexer.script_history()?;
-    let page_num: u32 = get_param(query_params, "page")?.unwrap_or(0);
-    let page_size: u32 = get_param(query_params, "page_size")?.unwrap_or(25);
-    let script = script_variant.to_script();
-    script_history.rev_history(&script, page_num as usize, page_size as usize)
+    script_rev_history(&script_variant.to_script(), query_params, indexer)
 }
 
 /// Return a page of the confirmed txs of the given script.
@@ -105,8 +210,7 @@ pub async fn handle_script_unconfirmed_txs(
 ) -> Result<proto::TxHistoryPage> {
     let script_variant = parse_script_variant_hex(script_type, payload)?;
     let script_history = indexer.script_history()?;
-    let script = script_variant.to_script();
-    script_history.unconfirmed_txs(&script)
+    script_history.unconfirmed_txs(&script_variant.to_script())
 }
 
 /// Return the UTXOs of the given script.
@@ -117,11 +221,307 @@ pub async fn handle_script_utxos(
     indexer: &ChronikIndexer,
 ) -> Result<proto::ScriptUtxos> {
     let script_variant = parse_script_variant_hex(script_type, payload)?;
+    script_utxos(&script_variant.to_script(), indexer)
+}
+
+/// Return the balance of the given script.
+/// Scripts are identified by script_type and payload.
+pub async fn handle_script_balance(
+    script_type: &str,
+    payload: &str,
+    indexer: &ChronikIndexer,
+) -> Result<proto::ScriptBalance> {
+    let script_variant = parse_script_variant_hex(script_type, payload)?;
+    script_balance(&script_variant.to_script(), indexer)
+}
+
+/// Return a page of the confirmed txs of the script of the given address,
+/// either CashAddr or legacy.
+/// If a `before` or `after` cursor is given, the page is relative to it
+/// instead of the `page` number.
+pub async fn handle_address_confirmed_txs(
+    address: &str,
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TxHistoryPage> {
+    let script_variant = parse_address(address)?;
+    script_confirmed_txs(&script_variant.to_script(), query_params, indexer)
+}
+
+/// Return a page of the tx history of the script of the given address, in
+/// reverse chronological order.
+/// If a `before` or `after` cursor is given, the page is relative to it
+/// instead of the `page` number.
+pub async fn handle_address_history(
+    address: &str,
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TxHistoryPage> {
+    let script_variant = parse_address(address)?;
+    script_rev_history(&script_variant.to_script(), query_params, indexer)
+}
+
+/// Return the unconfirmed txs of the script of the given address.
+pub async fn handle_address_unconfirmed_txs(
+    address: &str,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TxHistoryPage> {
+    let script_variant = parse_address(address)?;
+    let script_history = indexer.script_history()?;
+    script_history.unconfirmed_txs(&script_variant.to_script())
+}
+
+/// Return the UTXOs of the script of the given address.
+pub async fn handle_address_utxos(
+    address: &str,
+    indexer: &ChronikIndexer,
+) -> Result<proto::ScriptUtxos> {
+    let script_variant = parse_address(address)?;
+    script_utxos(&script_variant.to_script(), indexer)
+}
+
+/// Return the balance of the script of the given address.
+pub async fn handle_address_balance(
+    address: &str,
+    indexer: &ChronikIndexer,
+) -> Result<proto::ScriptBalance> {
+    let script_variant = parse_address(address)?;
+    script_balance(&script_variant.to_script(), indexer)
+}
+
+fn script_confirmed_txs(
+    script: &Script,
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TxHistoryPage> {
+    let script_history = indexer.script_history()?;
+    let page_num: u32 = get_param(query_params, "page")?.unwrap_or(0);
+    let page_size: u32 = get_param(query_params, "page_size")?.unwrap_or(25);
+    match get_cursor_request(query_params)? {
+        Some(request) => script_history.confirmed_txs_by_cursor(
+            script,
+            request,
+            page_size as usize,
+        ),
+        None => script_history.confirmed_txs(
+            script,
+            page_num as usize,
+            page_size as usize,
+        ),
+    }
+}
+
+fn script_rev_history(
+    script: &Script,
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TxHistoryPage> {
+    let script_history = indexer.script_history()?;
+    let page_num: u32 = get_param(query_params, "page")?.unwrap_or(0);
+    let page_size: u32 = get_param(query_params, "page_size")?.unwrap_or(25);
+    match get_cursor_request(query_params)? {
+        Some(request) => script_history.rev_history_by_cursor(
+            script,
+            request,
+            page_size as usize,
+        ),
+        None => script_history.rev_history(
+            script,
+            page_num as usize,
+            page_size as usize,
+        ),
+    }
+}
+
+fn script_utxos(
+    script: &Script,
+    indexer: &ChronikIndexer,
+) -> Result<proto::ScriptUtxos> {
     let script_utxos = indexer.script_utxos()?;
-    let script = script_variant.to_script();
-    let utxos = script_utxos.utxos(&script)?;
+    let utxos = script_utxos.utxos(script)?;
     Ok(proto::ScriptUtxos {
         script: script.bytecode().to_vec(),
         utxos,
     })
 }
+
+fn script_balance(
+    script: &Script,
+    indexer: &ChronikIndexer,
+) -> Result<proto::ScriptBalance> {
+    let script_balance = indexer.script_balance()?;
+    Ok(proto::ScriptBalance {
+        script: script.bytecode().to_vec(),
+        ..script_balance.balance(script)?
+    })
+}
+
+/// Return the UTXOs of multiple scripts at once, in the order of the request.
+pub async fn handle_scripts_utxos(
+    request: proto::ScriptsUtxosRequest,
+    indexer: &ChronikIndexer,
+) -> Result<proto::ScriptsUtxos> {
+    check_num_batch_scripts(&request.scripts)?;
+    let script_utxos = indexer.script_utxos()?;
+    let scripts = request
+        .scripts
+        .iter()
+        .map(|script_request| {
+            let script = parse_script_variant(
+                &script_request.script_type,
+                &script_request.payload,
+            )?
+            .to_script();
+            let utxos = script_utxos.utxos(&script)?;
+            Ok(proto::ScriptUtxos {
+                script: script.bytecode().to_vec(),
+                utxos,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(proto::ScriptsUtxos { scripts })
+}
+
+/// Return a page of the tx history of multiple scripts at once, in the order
+/// of the request. Each page is in reverse chronological order.
+pub async fn handle_scripts_history(
+    request: proto::ScriptsHistoryRequest,
+    indexer: &ChronikIndexer,
+) -> Result<proto::ScriptsHistory> {
+    check_num_batch_scripts(&request.scripts)?;
+    let page_size = request.page_size as usize;
+    if request.scripts.len().saturating_mul(page_size) > MAX_BATCH_TXS {
+        return Err(TooManyBatchTxs(request.scripts.len(), page_size).into());
+    }
+    let script_history = indexer.script_history()?;
+    let pages = request
+        .scripts
+        .iter()
+        .map(|script_request| {
+            let script = parse_script_variant(
+                &script_request.script_type,
+                &script_request.payload,
+            )?
+            .to_script();
+            script_history.rev_history(
+                &script,
+                request.page as usize,
+                page_size,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(proto::ScriptsHistory { pages })
+}
+
+fn check_num_batch_scripts(scripts: &[proto::ScriptRequest]) -> Result<()> {
+    if scripts.len() > MAX_BATCH_SCRIPTS {
+        return Err(TooManyScripts(scripts.len()).into());
+    }
+    Ok(())
+}
+
+/// Return a page of the tx history of the given token ID, in reverse
+/// chronological order, i.e. the latest transaction first and then going back
+/// in time.
+/// If a `before` or `after` cursor is given, the page is relative to it
+/// instead of the `page` number.
+pub async fn handle_token_id_history(
+    token_id_hex: &str,
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TxHistoryPage> {
+    let token_id = parse_token_id(token_id_hex)?;
+    let token_id_history = indexer.token_id_history()?;
+    let page_num: u32 = get_param(query_params, "page")?.unwrap_or(0);
+    let page_size: u32 = get_param(query_params, "page_size")?.unwrap_or(25);
+    match get_cursor_request(query_params)? {
+        Some(request) => token_id_history.rev_history_by_cursor(
+            token_id,
+            request,
+            page_size as usize,
+        ),
+        None => token_id_history.rev_history(
+            token_id,
+            page_num as usize,
+            page_size as usize,
+        ),
+    }
+}
+
+/// Return the UTXOs of the given token ID.
+pub async fn handle_token_id_utxos(
+    token_id_hex: &str,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TokenIdUtxos> {
+    let token_id = parse_token_id(token_id_hex)?;
+    let token_id_utxos = indexer.token_id_utxos()?;
+    let utxos = token_id_utxos.utxos(token_id)?;
+    Ok(proto::TokenIdUtxos {
+        token_id: token_id.txid().to_vec(),
+        utxos,
+    })
+}
+
+/// Return a page of the txs in the mempool, latest first.
+pub async fn handle_mempool(
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::MempoolTxs> {
+    let page_num: u32 = get_param(query_params, "page")?.unwrap_or(0);
+    let page_size: u32 = get_param(query_params, "page_size")?.unwrap_or(25);
+    indexer.mempool().txs(page_num as usize, page_size as usize)
+}
+
+/// Estimate the fee rate required for a tx to be mined within the next
+/// `blocks` blocks (default 1).
+pub async fn handle_fee_estimate(
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::FeeEstimate> {
+    let num_blocks: u32 = get_param(query_params, "blocks")?.unwrap_or(1);
+    indexer.fee_estimate().fee_estimate(num_blocks)
+}
+
+/// Return a page of the tx history of the given LOKAD ID, in reverse
+/// chronological order, i.e. the latest transaction first and then going back
+/// in time.
+/// If a `before` or `after` cursor is given, the page is relative to it
+/// instead of the `page` number.
+pub async fn handle_lokad_id_history(
+    lokad_id_hex: &str,
+    query_params: &HashMap<String, String>,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TxHistoryPage> {
+    let lokad_id = parse_lokad_id_hex(lokad_id_hex)?;
+    let lokad_id_history = indexer.lokad_id_history()?;
+    let page_num: u32 = get_param(query_params, "page")?.unwrap_or(0);
+    let page_size: u32 = get_param(query_params, "page_size")?.unwrap_or(25);
+    match get_cursor_request(query_params)? {
+        Some(request) => lokad_id_history.rev_history_by_cursor(
+            lokad_id,
+            request,
+            page_size as usize,
+        ),
+        None => lokad_id_history.rev_history(
+            lokad_id,
+            page_num as usize,
+            page_size as usize,
+        ),
+    }
+}
+
+/// Return the info of the given token ID, like its GENESIS info and
+/// circulating supply.
+pub async fn handle_token_info(
+    token_id_hex: &str,
+    indexer: &ChronikIndexer,
+) -> Result<proto::TokenInfo> {
+    let token_id = parse_token_id(token_id_hex)?;
+    indexer.tokens().token_info(&token_id)
+}
+
+fn parse_token_id(token_id_hex: &str) -> Result<TokenId> {
+    token_id_hex
+        .parse::<TokenId>()
+        .wrap_err_with(|| NotTokenId(token_id_hex.to_string()))
+}
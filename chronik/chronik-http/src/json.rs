@@ -0,0 +1,465 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`Json`], [`ProtobufOrJson`] and [`negotiate_json`].
+
+use std::sync::Arc;
+
+use abc_rust_error::Report;
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequest,
+    http::{HeaderMap, HeaderValue, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hyper::{
+    body::to_bytes,
+    header::{ACCEPT, CONTENT_TYPE},
+    Body,
+};
+use prost::Message;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::{
+    error::{report_status_error, ReportError},
+    protobuf::{make_protobuf_response, Protobuf, CONTENT_TYPE_PROTOBUF},
+    validation::{check_content_type, media_type},
+};
+
+/// Struct for en-/decoding a specific message `P` as JSON.
+///
+/// Used as:
+/// 1. Extractor for JSON requests of a specific message type, and fail
+///    otherwise.
+/// 2. Response type to return a JSON encoded message.
+///
+/// Protobuf messages of `chronik_proto` are encoded as JSON with camelCase
+/// field names, hashes as hex in big-endian display order, other byte strings
+/// as hex, 64-bit integers as decimal strings and enums by name.
+#[derive(Debug)]
+pub struct Json<P>(pub P);
+
+/// Struct for en-/decoding a specific protobuf message `P` either as protobuf
+/// or as JSON:
+///
+/// 1. As extractor, decodes the request as JSON if the media type of the
+///    `Content-Type` is `application/json`, and as protobuf otherwise.
+/// 2. As response, encodes the message as protobuf, or as JSON if the request
+///    prefers it in its `Accept` header. This requires the [`negotiate_json`]
+///    middleware.
+#[derive(Debug)]
+pub struct ProtobufOrJson<P>(pub P);
+
+/// 'Content-Type' header for JSON requests/responses
+pub const CONTENT_TYPE_JSON: &str = "application/json";
+
+/// Errors indicating something went wrong with [`Json`].
+#[derive(Debug, Error)]
+pub enum ChronikJsonError {
+    /// Couldn't read body from request
+    #[error("400: Invalid body: {0}")]
+    InvalidBody(String),
+
+    /// Couldn't decode request body as JSON
+    #[error("400: Bad JSON: {0}")]
+    BadJson(String),
+
+    /// Couldn't encode response as JSON
+    #[error("500: Encoding JSON failed: {0}")]
+    EncodeJsonFailed(String),
+}
+
+use self::ChronikJsonError::*;
+
+type EncodeJsonFn = dyn Fn() -> serde_json::Result<Vec<u8>> + Send + Sync;
+
+/// Response extension to re-encode a [`ProtobufOrJson`] response as JSON.
+#[derive(Clone)]
+struct JsonEncoder(Arc<EncodeJsonFn>);
+
+#[async_trait]
+impl<P: DeserializeOwned, S: Send + Sync> FromRequest<S, Body> for Json<P> {
+    type Rejection = ReportError;
+
+    async fn from_request(
+        req: Request<Body>,
+        _: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let headers = req.headers();
+        check_content_type(headers, CONTENT_TYPE_JSON)?;
+        let body_bytes = to_bytes(&mut req.into_body())
+            .await
+            .map_err(|err| Report::from(InvalidBody(err.to_string())))?;
+        let msg = serde_json::from_slice(&body_bytes)
+            .map_err(|err| Report::from(BadJson(err.to_string())))?;
+        Ok(Json(msg))
+    }
+}
+
+impl<P: Serialize> IntoResponse for Json<P> {
+    fn into_response(self) -> Response {
+        match serde_json::to_vec(&self.0) {
+            Ok(json) => make_json_response(json),
+            Err(err) => encode_json_failed_response(err),
+        }
+    }
+}
+
+#[async_trait]
+impl<P, S> FromRequest<S, Body> for ProtobufOrJson<P>
+where
+    P: Message + Default + DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ReportError;
+
+    async fn from_request(
+        req: Request<Body>,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let content_type = req.headers().get(CONTENT_TYPE);
+        let is_json = content_type
+            .and_then(|value| value.to_str().ok())
+            .map_or(false, |value| {
+                media_type(value).eq_ignore_ascii_case(CONTENT_TYPE_JSON)
+            });
+        if is_json {
+            let Json(msg) = Json::from_request(req, state).await?;
+            Ok(ProtobufOrJson(msg))
+        } else {
+            let Protobuf(msg) = Protobuf::from_request(req, state).await?;
+            Ok(ProtobufOrJson(msg))
+        }
+    }
+}
+
+impl<P> IntoResponse for ProtobufOrJson<P>
+where
+    P: Message + Default + Serialize + Send + Sync + 'static,
+{
+    fn into_response(self) -> Response {
+        let msg = Arc::new(self.0);
+        let mut response = make_protobuf_response(msg.encode_to_vec());
+        response
+            .extensions_mut()
+            .insert(JsonEncoder(Arc::new(move || serde_json::to_vec(&*msg))));
+        response
+    }
+}
+
+/// Middleware which re-encodes [`ProtobufOrJson`] responses (including errors)
+/// as JSON if the request prefers `application/json` over
+/// `application/x-protobuf` in its `Accept` header.
+///
+/// The one with the highest quality value (`q=`, 1 by default) is preferred,
+/// and the first one listed on ties. Media types with `q=0` are not acceptable.
+/// Protobuf is returned if neither is acceptable.
+pub async fn negotiate_json<B>(req: Request<B>, next: Next<B>) -> Response {
+    let prefers_json = prefers_json(req.headers());
+    let mut response = next.run(req).await;
+    if !prefers_json {
+        return response;
+    }
+    let encoder = match response.extensions_mut().remove::<JsonEncoder>() {
+        Some(encoder) => encoder,
+        None => return response,
+    };
+    match (encoder.0)() {
+        Ok(json) => {
+            (response.status(), make_json_response(json)).into_response()
+        }
+        Err(err) => encode_json_failed_response(err),
+    }
+}
+
+fn prefers_json(headers: &HeaderMap) -> bool {
+    let accept = match headers.get(ACCEPT).map(|accept| accept.to_str()) {
+        Some(Ok(accept)) => accept,
+        _ => return false,
+    };
+    let mut preferred: Option<(&str, f32)> = None;
+    for entry in accept.split(',') {
+        let media_type = media_type(entry);
+        if !media_type.eq_ignore_ascii_case(CONTENT_TYPE_JSON)
+            && !media_type.eq_ignore_ascii_case(CONTENT_TYPE_PROTOBUF)
+        {
+            continue;
+        }
+        let quality = entry
+            .split(';')
+            .skip(1)
+            .find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                if !name.trim().eq_ignore_ascii_case("q") {
+                    return None;
+                }
+                value.trim().parse::<f32>().ok()
+            })
+            .unwrap_or(1.0);
+        let is_preferred = match preferred {
+            Some((_, preferred_quality)) => quality > preferred_quality,
+            None => quality > 0.0,
+        };
+        if is_preferred {
+            preferred = Some((media_type, quality));
+        }
+    }
+    preferred.map_or(false, |(media_type, _)| {
+        media_type.eq_ignore_ascii_case(CONTENT_TYPE_JSON)
+    })
+}
+
+fn encode_json_failed_response(err: serde_json::Error) -> Response {
+    let (status, error) =
+        report_status_error(EncodeJsonFailed(err.to_string()).into());
+    (status, Json(error)).into_response()
+}
+
+fn make_json_response(json: Vec<u8>) -> Response {
+    let mut response = Response::builder()
+        .body(axum::body::boxed(Body::from(json)))
+        .unwrap();
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE_JSON));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use abc_rust_error::Result;
+    use axum::{middleware, routing::post, Router};
+    use chronik_proto::proto;
+    use hyper::{
+        body::to_bytes,
+        header::{HeaderValue, ACCEPT, CONTENT_TYPE},
+        service::Service,
+        Body, HeaderMap, Request, StatusCode,
+    };
+    use prost::Message;
+    use serde_json::json;
+
+    use crate::{
+        json::{
+            negotiate_json, prefers_json, ProtobufOrJson, CONTENT_TYPE_JSON,
+        },
+        protobuf::CONTENT_TYPE_PROTOBUF,
+    };
+
+    #[test]
+    fn test_json_encoding() -> Result<()> {
+        let msg = proto::WsMsg {
+            msg_type: Some(proto::ws_msg::MsgType::Tx(proto::MsgTx {
+                msg_type: proto::TxMsgType::TxConfirmed as i32,
+                txid: vec![1, 2, 3],
+            })),
+        };
+        assert_eq!(
+            serde_json::to_value(&msg)?,
+            json!({"tx": {"msgType": "TX_CONFIRMED", "txid": "030201"}}),
+        );
+        assert_eq!(
+            serde_json::from_value::<proto::WsMsg>(serde_json::to_value(
+                &msg
+            )?)?,
+            msg,
+        );
+
+        let output = proto::TxOutput {
+            value: 1234,
+            output_script: vec![0x51],
+            spent_by: None,
+            token: Some(proto::Token {
+                token_id: vec![4, 5],
+                token_type: Some(proto::TokenType {
+                    token_type: Some(proto::token_type::TokenType::Slp(
+                        proto::SlpTokenType::Fungible as i32,
+                    )),
+                }),
+                entry_idx: 0,
+                amount: 1000,
+                is_mint_baton: false,
+            }),
+        };
+        let output_json = json!({
+            "value": "1234",
+            "outputScript": "51",
+            "spentBy": null,
+            "token": {
+                "tokenId": "0504",
+                "tokenType": {"slp": "SLP_TOKEN_TYPE_FUNGIBLE"},
+                "entryIdx": 0,
+                "amount": "1000",
+                "isMintBaton": false,
+            },
+        });
+        assert_eq!(serde_json::to_value(&output)?, output_json);
+        assert_eq!(
+            serde_json::from_value::<proto::TxOutput>(output_json)?,
+            output
+        );
+
+        // Defaults can be omitted and 64-bit ints can be numbers
+        assert_eq!(
+            serde_json::from_value::<proto::TxOutput>(json!({"value": 7}))?,
+            proto::TxOutput {
+                value: 7,
+                ..Default::default()
+            },
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_protobuf_or_json() -> Result<()> {
+        async fn handle_broadcast_tx(
+            ProtobufOrJson(request): ProtobufOrJson<proto::BroadcastTxRequest>,
+        ) -> ProtobufOrJson<proto::BroadcastTxResponse> {
+            ProtobufOrJson(proto::BroadcastTxResponse {
+                txid: request.raw_tx,
+            })
+        }
+
+        let mut router = Router::<()>::new()
+            .route("/", post(handle_broadcast_tx))
+            .layer(middleware::from_fn(negotiate_json));
+
+        // JSON request and response
+        let response = router
+            .call(
+                Request::post("/")
+                    .header(CONTENT_TYPE, CONTENT_TYPE_JSON)
+                    .header(ACCEPT, CONTENT_TYPE_JSON)
+                    .body(Body::from(r#"{"rawTx": "abcd"}"#))?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], CONTENT_TYPE_JSON);
+        let body = to_bytes(response.into_body()).await?;
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body)?,
+            json!({"txid": "cdab"}),
+        );
+
+        // Parameters of the Content-Type are ignored
+        let response = router
+            .call(
+                Request::post("/")
+                    .header(CONTENT_TYPE, "Application/JSON; charset=utf-8")
+                    .header(ACCEPT, CONTENT_TYPE_JSON)
+                    .body(Body::from(r#"{"rawTx": "abcd"}"#))?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body()).await?;
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body)?,
+            json!({"txid": "cdab"}),
+        );
+
+        // Errors are also returned as JSON
+        let response = router
+            .call(
+                Request::post("/")
+                    .header(CONTENT_TYPE, CONTENT_TYPE_JSON)
+                    .header(ACCEPT, "text/html, application/json; q=0.9")
+                    .body(Body::from(r#"{"rawTx": "xyz"}"#))?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[CONTENT_TYPE], CONTENT_TYPE_JSON);
+        let body = to_bytes(response.into_body()).await?;
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body)?,
+            json!({
+                "msg": "400: Bad JSON: Odd number of digits at line 1 column 16",
+            }),
+        );
+
+        // Protobuf request, protobuf is preferred for the response
+        let request = proto::BroadcastTxRequest {
+            raw_tx: vec![0xab, 0xcd],
+        };
+        let response = router
+            .call(
+                Request::post("/")
+                    .header(CONTENT_TYPE, CONTENT_TYPE_PROTOBUF)
+                    .header(ACCEPT, "application/x-protobuf, application/json")
+                    .body(Body::from(request.encode_to_vec()))?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], CONTENT_TYPE_PROTOBUF);
+        let body = to_bytes(response.into_body()).await?;
+        assert_eq!(
+            proto::BroadcastTxResponse::decode(body)?,
+            proto::BroadcastTxResponse {
+                txid: vec![0xab, 0xcd],
+            },
+        );
+
+        // Protobuf request, JSON has a higher quality value
+        let response = router
+            .call(
+                Request::post("/")
+                    .header(CONTENT_TYPE, CONTENT_TYPE_PROTOBUF)
+                    .header(
+                        ACCEPT,
+                        "application/x-protobuf;q=0.1, application/json",
+                    )
+                    .body(Body::from(request.encode_to_vec()))?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], CONTENT_TYPE_JSON);
+        let body = to_bytes(response.into_body()).await?;
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body)?,
+            json!({"txid": "cdab"}),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefers_json() {
+        let prefers = |accept: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, HeaderValue::from_str(accept).unwrap());
+            prefers_json(&headers)
+        };
+        assert!(!prefers_json(&HeaderMap::new()));
+        assert!(!prefers("*/*"));
+        assert!(prefers("application/json"));
+        assert!(prefers("text/html, Application/JSON"));
+        assert!(!prefers("application/x-protobuf"));
+        // Ties go to the first one listed
+        assert!(prefers("application/json, application/x-protobuf"));
+        assert!(!prefers("application/x-protobuf, application/json"));
+        assert!(prefers(
+            "application/json;q=0.5, application/x-protobuf;q=0.5"
+        ));
+        // Highest quality value wins
+        assert!(prefers("application/x-protobuf;q=0.1, application/json"));
+        assert!(prefers(
+            "application/x-protobuf; q=0.8, application/json; q=0.9"
+        ));
+        assert!(!prefers(
+            "application/json;q=0.3, application/x-protobuf;q=0.4"
+        ));
+        // q=0 means not acceptable, falling back to protobuf
+        assert!(!prefers("application/json;q=0"));
+        assert!(!prefers(
+            "application/json; q=0.0, application/x-protobuf;q=0"
+        ));
+        assert!(prefers(
+            "application/x-protobuf;q=0, application/json;q=0.1"
+        ));
+        // Invalid quality values count as the default
+        assert!(prefers("application/json;q=abc, application/x-protobuf"));
+    }
+}
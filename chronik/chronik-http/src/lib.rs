@@ -7,6 +7,7 @@
 abc_rust_lint::lint! {
     pub mod error;
     pub mod handlers;
+    pub mod json;
     pub mod parse;
     pub mod protobuf;
     pub mod server;
@@ -6,9 +6,11 @@
 
 use abc_rust_error::Result;
 use bitcoinsuite_core::{
+    address::{CashAddress, CashAddressError, LegacyAddress},
     error::DataError,
     script::{ScriptType, ScriptTypeError, ScriptVariant},
 };
+use chronik_db::groups::LokadId;
 use thiserror::Error;
 
 /// Errors indicating parsing failed.
@@ -25,6 +27,14 @@ pub enum ChronikParseError {
     /// Script payload invalid for script_type
     #[error("400: Invalid payload for {0:?}: {1}")]
     InvalidScriptPayload(ScriptType, DataError),
+
+    /// Address is neither a valid CashAddr nor a legacy address
+    #[error("400: Invalid address {0}: {1}")]
+    InvalidAddress(String, CashAddressError),
+
+    /// LOKAD ID must be 4 bytes
+    #[error("400: Invalid LOKAD ID length: expected 4 bytes but got {0}")]
+    InvalidLokadIdLength(usize),
 }
 
 use self::ChronikParseError::*;
@@ -53,3 +63,27 @@ pub fn parse_script_variant(
     Ok(ScriptVariant::from_type_and_payload(script_type, payload)
         .map_err(|err| InvalidScriptPayload(script_type, err))?)
 }
+
+/// Parse the [`ScriptVariant`] of a CashAddr or legacy address (e.g. from
+/// URL). CashAddrs may omit the prefix.
+pub fn parse_address(address: &str) -> Result<ScriptVariant> {
+    match address.parse::<CashAddress>() {
+        Ok(address) => Ok(address.to_script_variant()),
+        Err(err) => match address.parse::<LegacyAddress>() {
+            Ok(address) => Ok(address.to_script_variant()),
+            Err(_) => Err(InvalidAddress(address.to_string(), err).into()),
+        },
+    }
+}
+
+/// Parse the [`LokadId`] from hex (e.g. from URL).
+pub fn parse_lokad_id_hex(lokad_id_hex: &str) -> Result<LokadId> {
+    parse_lokad_id(&parse_hex(lokad_id_hex)?)
+}
+
+/// Parse the [`LokadId`] from bytes (e.g. from protobuf).
+pub fn parse_lokad_id(lokad_id: &[u8]) -> Result<LokadId> {
+    Ok(lokad_id
+        .try_into()
+        .map_err(|_| InvalidLokadIdLength(lokad_id.len()))?)
+}
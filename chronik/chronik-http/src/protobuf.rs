@@ -86,17 +86,21 @@ impl<P: Message + Default, S: Send + Sync> FromRequest<S, Body>
 
 impl<P: Message + Default> IntoResponse for Protobuf<P> {
     fn into_response(self) -> Response {
-        let mut response = Response::builder()
-            .body(axum::body::boxed(Body::from(self.0.encode_to_vec())))
-            .unwrap();
-        response.headers_mut().insert(
-            CONTENT_TYPE,
-            HeaderValue::from_static(CONTENT_TYPE_PROTOBUF),
-        );
-        response
+        make_protobuf_response(self.0.encode_to_vec())
     }
 }
 
+pub(crate) fn make_protobuf_response(body: Vec<u8>) -> Response {
+    let mut response = Response::builder()
+        .body(axum::body::boxed(Body::from(body)))
+        .unwrap();
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(CONTENT_TYPE_PROTOBUF),
+    );
+    response
+}
+
 #[cfg(test)]
 mod tests {
     use abc_rust_error::Result;
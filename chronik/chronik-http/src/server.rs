@@ -10,10 +10,11 @@ use std::{net::SocketAddr, sync::Arc};
 use abc_rust_error::{Result, WrapErr};
 use axum::{
     extract::{Path, Query, WebSocketUpgrade},
+    middleware,
     response::IntoResponse,
     routing, Extension, Router,
 };
-use bitcoinsuite_core::tx::TxId;
+use bitcoinsuite_core::tx::{OutPoint, TxId};
 use chronik_indexer::indexer::{ChronikIndexer, Node};
 use chronik_proto::proto;
 use hyper::server::conn::AddrIncoming;
@@ -21,7 +22,9 @@ use thiserror::Error;
 use tokio::sync::RwLock;
 
 use crate::{
-    error::ReportError, handlers, protobuf::Protobuf,
+    error::ReportError,
+    handlers,
+    json::{negotiate_json, ProtobufOrJson},
     ws::handle_subscribe_socket,
 };
 
@@ -69,6 +72,10 @@ pub enum ChronikServerError {
     #[error("400: Not a txid: {0}")]
     NotTxId(String),
 
+    /// Query is not a valid output index
+    #[error("400: Not an output index: {0}")]
+    NotOutIdx(String),
+
     /// Block not found in DB
     #[error("404: Block not found: {0}")]
     BlockNotFound(String),
@@ -121,8 +128,18 @@ impl ChronikServer {
             .route("/block/:hash_or_height", routing::get(handle_block))
             .route("/block-txs/:hash_or_height", routing::get(handle_block_txs))
             .route("/blocks/:start/:end", routing::get(handle_block_range))
+            .route(
+                "/block-headers/:start/:end",
+                routing::get(handle_block_headers),
+            )
             .route("/tx/:txid", routing::get(handle_tx))
+            .route("/tx/:txid/merkle-proof", routing::get(handle_merkle_proof))
+            .route("/tx/:txid/ancestors", routing::get(handle_tx_ancestors))
+            .route("/tx/:txid/descendants", routing::get(handle_tx_descendants))
             .route("/raw-tx/:txid", routing::get(handle_raw_tx))
+            .route("/outpoint/:txid/:out_idx", routing::get(handle_outpoint))
+            .route("/broadcast-tx", routing::post(handle_broadcast_tx))
+            .route("/broadcast-txs", routing::post(handle_broadcast_txs))
             .route(
                 "/script/:type/:payload/confirmed-txs",
                 routing::get(handle_script_confirmed_txs),
@@ -139,46 +156,103 @@ impl ChronikServer {
                 "/script/:type/:payload/utxos",
                 routing::get(handle_script_utxos),
             )
+            .route(
+                "/script/:type/:payload/balance",
+                routing::get(handle_script_balance),
+            )
+            .route(
+                "/address/:address/confirmed-txs",
+                routing::get(handle_address_confirmed_txs),
+            )
+            .route(
+                "/address/:address/history",
+                routing::get(handle_address_history),
+            )
+            .route(
+                "/address/:address/unconfirmed-txs",
+                routing::get(handle_address_unconfirmed_txs),
+            )
+            .route(
+                "/address/:address/utxos",
+                routing::get(handle_address_utxos),
+            )
+            .route(
+                "/address/:address/balance",
+                routing::get(handle_address_balance),
+            )
+            .route("/scripts/utxos", routing::post(handle_scripts_utxos))
+            .route("/scripts/history", routing::post(handle_scripts_history))
+            .route(
+                "/token-id/:token_id/history",
+                routing::get(handle_token_id_history),
+            )
+            .route(
+                "/token-id/:token_id/utxos",
+                routing::get(handle_token_id_utxos),
+            )
+            .route("/token/:token_id", routing::get(handle_token_info))
+            .route(
+                "/lokad-id/:lokad_id/history",
+                routing::get(handle_lokad_id_history),
+            )
+            .route("/mempool", routing::get(handle_mempool))
+            .route("/mempool/info", routing::get(handle_mempool_info))
+            .route("/fee-estimate", routing::get(handle_fee_estimate))
             .route("/ws", routing::get(handle_ws))
             .fallback(handlers::handle_not_found)
             .layer(Extension(indexer))
             .layer(Extension(node))
+            .layer(middleware::from_fn(negotiate_json))
     }
 }
 
 async fn handle_blockchain_info(
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::BlockchainInfo>, ReportError> {
+) -> Result<ProtobufOrJson<proto::BlockchainInfo>, ReportError> {
     let indexer = indexer.read().await;
     let blocks = indexer.blocks();
-    Ok(Protobuf(blocks.blockchain_info()?))
+    Ok(ProtobufOrJson(blocks.blockchain_info()?))
 }
 
 async fn handle_block_range(
     Path((start_height, end_height)): Path<(i32, i32)>,
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::Blocks>, ReportError> {
+) -> Result<ProtobufOrJson<proto::Blocks>, ReportError> {
+    let indexer = indexer.read().await;
+    let blocks = indexer.blocks();
+    Ok(ProtobufOrJson(blocks.by_range(start_height, end_height)?))
+}
+
+async fn handle_block_headers(
+    Path((start_height, end_height)): Path<(i32, i32)>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+    Extension(node): Extension<NodeRef>,
+) -> Result<ProtobufOrJson<proto::BlockHeaders>, ReportError> {
     let indexer = indexer.read().await;
     let blocks = indexer.blocks();
-    Ok(Protobuf(blocks.by_range(start_height, end_height)?))
+    Ok(ProtobufOrJson(blocks.headers_by_range(
+        &node,
+        start_height,
+        end_height,
+    )?))
 }
 
 async fn handle_block(
     Path(hash_or_height): Path<String>,
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::Block>, ReportError> {
+) -> Result<ProtobufOrJson<proto::Block>, ReportError> {
     let indexer = indexer.read().await;
     let blocks = indexer.blocks();
-    Ok(Protobuf(blocks.by_hash_or_height(hash_or_height)?))
+    Ok(ProtobufOrJson(blocks.by_hash_or_height(hash_or_height)?))
 }
 
 async fn handle_block_txs(
     Path(hash_or_height): Path<String>,
     Query(query_params): Query<HashMap<String, String>>,
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::TxHistoryPage>, ReportError> {
+) -> Result<ProtobufOrJson<proto::TxHistoryPage>, ReportError> {
     let indexer = indexer.read().await;
-    Ok(Protobuf(
+    Ok(ProtobufOrJson(
         handlers::handle_block_txs(hash_or_height, &query_params, &indexer)
             .await?,
     ))
@@ -187,28 +261,90 @@ async fn handle_block_txs(
 async fn handle_tx(
     Path(txid): Path<String>,
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::Tx>, ReportError> {
+) -> Result<ProtobufOrJson<proto::Tx>, ReportError> {
     let indexer = indexer.read().await;
     let txid = txid.parse::<TxId>().wrap_err(NotTxId(txid))?;
-    Ok(Protobuf(indexer.txs().tx_by_id(txid)?))
+    Ok(ProtobufOrJson(indexer.txs().tx_by_id(txid)?))
+}
+
+async fn handle_tx_ancestors(
+    Path(txid): Path<String>,
+    Query(query_params): Query<HashMap<String, String>>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TxGraph>, ReportError> {
+    let indexer = indexer.read().await;
+    let txid = txid.parse::<TxId>().wrap_err(NotTxId(txid))?;
+    Ok(ProtobufOrJson(
+        handlers::handle_tx_ancestors(&txid, &query_params, &indexer).await?,
+    ))
+}
+
+async fn handle_tx_descendants(
+    Path(txid): Path<String>,
+    Query(query_params): Query<HashMap<String, String>>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TxGraph>, ReportError> {
+    let indexer = indexer.read().await;
+    let txid = txid.parse::<TxId>().wrap_err(NotTxId(txid))?;
+    Ok(ProtobufOrJson(
+        handlers::handle_tx_descendants(&txid, &query_params, &indexer).await?,
+    ))
 }
 
 async fn handle_raw_tx(
     Path(txid): Path<String>,
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::RawTx>, ReportError> {
+) -> Result<ProtobufOrJson<proto::RawTx>, ReportError> {
     let indexer = indexer.read().await;
     let txid = txid.parse::<TxId>().wrap_err(NotTxId(txid))?;
-    Ok(Protobuf(indexer.txs().raw_tx_by_id(&txid)?))
+    Ok(ProtobufOrJson(indexer.txs().raw_tx_by_id(&txid)?))
+}
+
+async fn handle_merkle_proof(
+    Path(txid): Path<String>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TxMerkleProof>, ReportError> {
+    let indexer = indexer.read().await;
+    let txid = txid.parse::<TxId>().wrap_err(NotTxId(txid))?;
+    Ok(ProtobufOrJson(indexer.txs().merkle_proof(&txid)?))
+}
+
+async fn handle_outpoint(
+    Path((txid, out_idx)): Path<(String, String)>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::OutpointInfo>, ReportError> {
+    let indexer = indexer.read().await;
+    let txid = txid.parse::<TxId>().wrap_err(NotTxId(txid))?;
+    let out_idx = out_idx.parse::<u32>().wrap_err(NotOutIdx(out_idx))?;
+    let outpoint = OutPoint { txid, out_idx };
+    Ok(ProtobufOrJson(indexer.txs().outpoint(&outpoint)?))
+}
+
+async fn handle_broadcast_tx(
+    Extension(node): Extension<NodeRef>,
+    ProtobufOrJson(request): ProtobufOrJson<proto::BroadcastTxRequest>,
+) -> Result<ProtobufOrJson<proto::BroadcastTxResponse>, ReportError> {
+    Ok(ProtobufOrJson(
+        handlers::handle_broadcast_tx(request, node.as_ref()).await?,
+    ))
+}
+
+async fn handle_broadcast_txs(
+    Extension(node): Extension<NodeRef>,
+    ProtobufOrJson(request): ProtobufOrJson<proto::BroadcastTxsRequest>,
+) -> Result<ProtobufOrJson<proto::BroadcastTxsResponse>, ReportError> {
+    Ok(ProtobufOrJson(
+        handlers::handle_broadcast_txs(request, node.as_ref()).await?,
+    ))
 }
 
 async fn handle_script_confirmed_txs(
     Path((script_type, payload)): Path<(String, String)>,
     Query(query_params): Query<HashMap<String, String>>,
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::TxHistoryPage>, ReportError> {
+) -> Result<ProtobufOrJson<proto::TxHistoryPage>, ReportError> {
     let indexer = indexer.read().await;
-    Ok(Protobuf(
+    Ok(ProtobufOrJson(
         handlers::handle_script_confirmed_txs(
             &script_type,
             &payload,
@@ -223,9 +359,9 @@ async fn handle_script_history(
     Path((script_type, payload)): Path<(String, String)>,
     Query(query_params): Query<HashMap<String, String>>,
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::TxHistoryPage>, ReportError> {
+) -> Result<ProtobufOrJson<proto::TxHistoryPage>, ReportError> {
     let indexer = indexer.read().await;
-    Ok(Protobuf(
+    Ok(ProtobufOrJson(
         handlers::handle_script_history(
             &script_type,
             &payload,
@@ -239,9 +375,9 @@ async fn handle_script_history(
 async fn handle_script_unconfirmed_txs(
     Path((script_type, payload)): Path<(String, String)>,
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::TxHistoryPage>, ReportError> {
+) -> Result<ProtobufOrJson<proto::TxHistoryPage>, ReportError> {
     let indexer = indexer.read().await;
-    Ok(Protobuf(
+    Ok(ProtobufOrJson(
         handlers::handle_script_unconfirmed_txs(
             &script_type,
             &payload,
@@ -254,13 +390,173 @@ async fn handle_script_unconfirmed_txs(
 async fn handle_script_utxos(
     Path((script_type, payload)): Path<(String, String)>,
     Extension(indexer): Extension<ChronikIndexerRef>,
-) -> Result<Protobuf<proto::ScriptUtxos>, ReportError> {
+) -> Result<ProtobufOrJson<proto::ScriptUtxos>, ReportError> {
     let indexer = indexer.read().await;
-    Ok(Protobuf(
+    Ok(ProtobufOrJson(
         handlers::handle_script_utxos(&script_type, &payload, &indexer).await?,
     ))
 }
 
+async fn handle_script_balance(
+    Path((script_type, payload)): Path<(String, String)>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::ScriptBalance>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_script_balance(&script_type, &payload, &indexer)
+            .await?,
+    ))
+}
+
+async fn handle_address_confirmed_txs(
+    Path(address): Path<String>,
+    Query(query_params): Query<HashMap<String, String>>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TxHistoryPage>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_address_confirmed_txs(
+            &address,
+            &query_params,
+            &indexer,
+        )
+        .await?,
+    ))
+}
+
+async fn handle_address_history(
+    Path(address): Path<String>,
+    Query(query_params): Query<HashMap<String, String>>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TxHistoryPage>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_address_history(&address, &query_params, &indexer)
+            .await?,
+    ))
+}
+
+async fn handle_address_unconfirmed_txs(
+    Path(address): Path<String>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TxHistoryPage>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_address_unconfirmed_txs(&address, &indexer).await?,
+    ))
+}
+
+async fn handle_address_utxos(
+    Path(address): Path<String>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::ScriptUtxos>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_address_utxos(&address, &indexer).await?,
+    ))
+}
+
+async fn handle_address_balance(
+    Path(address): Path<String>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::ScriptBalance>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_address_balance(&address, &indexer).await?,
+    ))
+}
+
+async fn handle_scripts_utxos(
+    Extension(indexer): Extension<ChronikIndexerRef>,
+    ProtobufOrJson(request): ProtobufOrJson<proto::ScriptsUtxosRequest>,
+) -> Result<ProtobufOrJson<proto::ScriptsUtxos>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_scripts_utxos(request, &indexer).await?,
+    ))
+}
+
+async fn handle_scripts_history(
+    Extension(indexer): Extension<ChronikIndexerRef>,
+    ProtobufOrJson(request): ProtobufOrJson<proto::ScriptsHistoryRequest>,
+) -> Result<ProtobufOrJson<proto::ScriptsHistory>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_scripts_history(request, &indexer).await?,
+    ))
+}
+
+async fn handle_token_id_history(
+    Path(token_id): Path<String>,
+    Query(query_params): Query<HashMap<String, String>>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TxHistoryPage>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_token_id_history(&token_id, &query_params, &indexer)
+            .await?,
+    ))
+}
+
+async fn handle_token_id_utxos(
+    Path(token_id): Path<String>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TokenIdUtxos>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_token_id_utxos(&token_id, &indexer).await?,
+    ))
+}
+
+async fn handle_mempool(
+    Query(query_params): Query<HashMap<String, String>>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::MempoolTxs>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_mempool(&query_params, &indexer).await?,
+    ))
+}
+
+async fn handle_mempool_info(
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::MempoolInfo>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(indexer.mempool().info()))
+}
+
+async fn handle_fee_estimate(
+    Query(query_params): Query<HashMap<String, String>>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::FeeEstimate>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_fee_estimate(&query_params, &indexer).await?,
+    ))
+}
+
+async fn handle_lokad_id_history(
+    Path(lokad_id): Path<String>,
+    Query(query_params): Query<HashMap<String, String>>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TxHistoryPage>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_lokad_id_history(&lokad_id, &query_params, &indexer)
+            .await?,
+    ))
+}
+
+async fn handle_token_info(
+    Path(token_id): Path<String>,
+    Extension(indexer): Extension<ChronikIndexerRef>,
+) -> Result<ProtobufOrJson<proto::TokenInfo>, ReportError> {
+    let indexer = indexer.read().await;
+    Ok(ProtobufOrJson(
+        handlers::handle_token_info(&token_id, &indexer).await?,
+    ))
+}
+
 async fn handle_ws(
     ws: WebSocketUpgrade,
     Extension(indexer): Extension<ChronikIndexerRef>,
@@ -29,7 +29,8 @@ pub(crate) enum ServerValidationError {
 
 use self::ServerValidationError::*;
 
-/// Verify the `Content-Type` header of a request matches the expected value.
+/// Verify the media type of the `Content-Type` header of a request matches the
+/// expected value, ignoring parameters like `charset`.
 pub(crate) fn check_content_type(
     headers: &HeaderMap,
     expected: &'static str,
@@ -38,7 +39,7 @@ pub(crate) fn check_content_type(
     let content_type = content_type
         .to_str()
         .map_err(|err| BadContentType(err.to_string()))?;
-    if content_type != expected {
+    if !media_type(content_type).eq_ignore_ascii_case(expected) {
         return Err(WrongContentType {
             expected,
             actual: content_type.to_string(),
@@ -47,3 +48,9 @@ pub(crate) fn check_content_type(
     }
     Ok(())
 }
+
+/// Media type of a `Content-Type` or `Accept` entry, without its parameters,
+/// e.g. `application/json` for `application/json; charset=utf-8`.
+pub(crate) fn media_type(value: &str) -> &str {
+    value.split(';').next().unwrap_or("").trim()
+}
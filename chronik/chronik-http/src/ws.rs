@@ -4,11 +4,16 @@
 
 //! Module for [`handle_subscribe_socket`].
 
-use std::collections::HashMap;
+use std::{collections::HashMap, hash::Hash};
 
 use abc_rust_error::Result;
 use axum::extract::ws::{self, WebSocket};
-use bitcoinsuite_core::script::ScriptVariant;
+use bitcoinsuite_core::{
+    error::DataError,
+    script::ScriptVariant,
+    tx::{OutPoint, TxId},
+};
+use chronik_db::groups::LokadId;
 use chronik_indexer::{
     subs::{BlockMsg, BlockMsgType},
     subs_group::{TxMsg, TxMsgType},
@@ -21,12 +26,13 @@ use thiserror::Error;
 use tokio::sync::broadcast;
 
 use crate::{
-    error::report_status_error, parse::parse_script_variant,
+    error::report_status_error,
+    parse::{parse_lokad_id, parse_script_variant},
     server::ChronikIndexerRef,
 };
 
 /// Errors for [`ChronikServer`].
-#[derive(Debug, Eq, Error, PartialEq)]
+#[derive(Debug, Error, PartialEq)]
 pub enum ChronikWsError {
     /// Unexpected [`ws::Message`] type.
     #[error("Unexpected message type {0}")]
@@ -35,6 +41,14 @@ pub enum ChronikWsError {
     /// [`proto::WsSub`] must have the `sub_type` field set.
     #[error("400: Missing sub_type in WsSub message")]
     MissingSubType,
+
+    /// Txid of a subscription is invalid.
+    #[error("400: Invalid txid: {0}")]
+    InvalidTxId(DataError),
+
+    /// [`proto::WsSubOutPoint`] must have the `outpoint` field set.
+    #[error("400: Missing outpoint in WsSubOutPoint message")]
+    MissingOutPoint,
 }
 
 use self::ChronikWsError::*;
@@ -53,23 +67,35 @@ struct WsSub {
 
 enum WsSubType {
     Blocks,
+    Headers,
     Script(ScriptVariant),
+    LokadId(LokadId),
+    TxId(TxId),
+    OutPoint(OutPoint),
 }
 
 type SubRecvBlocks = Option<broadcast::Receiver<BlockMsg>>;
-type SubRecvScripts = HashMap<ScriptVariant, broadcast::Receiver<TxMsg>>;
+type SubRecvTxMsgs<K> = HashMap<K, broadcast::Receiver<TxMsg>>;
 
 #[derive(Default)]
 struct SubRecv {
     blocks: SubRecvBlocks,
-    scripts: SubRecvScripts,
+    headers: SubRecvBlocks,
+    scripts: SubRecvTxMsgs<ScriptVariant>,
+    lokad_ids: SubRecvTxMsgs<LokadId>,
+    txids: SubRecvTxMsgs<TxId>,
+    outpoints: SubRecvTxMsgs<OutPoint>,
 }
 
 impl SubRecv {
     async fn recv_action(&mut self) -> Result<WsAction> {
         tokio::select! {
             action = Self::recv_blocks(&mut self.blocks) => action,
-            action = Self::recv_scripts(&mut self.scripts) => action,
+            action = Self::recv_headers(&mut self.headers) => action,
+            action = Self::recv_tx_msgs(&mut self.scripts) => action,
+            action = Self::recv_tx_msgs(&mut self.lokad_ids) => action,
+            action = Self::recv_tx_msgs(&mut self.txids) => action,
+            action = Self::recv_tx_msgs(&mut self.outpoints) => action,
         }
     }
 
@@ -80,18 +106,25 @@ impl SubRecv {
         }
     }
 
+    async fn recv_headers(headers: &mut SubRecvBlocks) -> Result<WsAction> {
+        match headers {
+            Some(headers) => sub_header_msg_action(headers.recv().await),
+            None => futures::future::pending().await,
+        }
+    }
+
     #[allow(clippy::mutable_key_type)]
-    async fn recv_scripts(scripts: &mut SubRecvScripts) -> Result<WsAction> {
-        if scripts.is_empty() {
+    async fn recv_tx_msgs<K: Eq + Hash>(
+        subs: &mut SubRecvTxMsgs<K>,
+    ) -> Result<WsAction> {
+        if subs.is_empty() {
             futures::future::pending().await
         } else {
-            let script_receivers = select_all(
-                scripts
-                    .values_mut()
-                    .map(|receiver| Box::pin(receiver.recv())),
+            let receivers = select_all(
+                subs.values_mut().map(|receiver| Box::pin(receiver.recv())),
             );
-            let (script_msg, _, _) = script_receivers.await;
-            sub_script_msg_action(script_msg)
+            let (tx_msg, _, _) = receivers.await;
+            sub_tx_msg_action(tx_msg)
         }
     }
 
@@ -111,6 +144,18 @@ impl SubRecv {
                     }
                 }
             }
+            WsSubType::Headers => {
+                if sub.is_unsub {
+                    log_chronik!("WS unsubscribe from headers\n");
+                    self.headers = None;
+                } else {
+                    log_chronik!("WS subscribe to headers\n");
+                    // Silently ignore multiple subs to headers
+                    if self.headers.is_none() {
+                        self.headers = Some(subs.sub_to_block_msgs());
+                    }
+                }
+            }
             WsSubType::Script(script_variant) => {
                 let script = script_variant.to_script();
                 if sub.is_unsub {
@@ -124,11 +169,55 @@ impl SubRecv {
                     self.scripts.insert(script_variant, recv);
                 }
             }
+            WsSubType::LokadId(lokad_id) => {
+                if sub.is_unsub {
+                    log_chronik!(
+                        "WS unsubscribe from LOKAD ID {}\n",
+                        hex::encode(lokad_id),
+                    );
+                    std::mem::drop(self.lokad_ids.remove(&lokad_id));
+                    subs.subs_lokad_id_mut().unsubscribe_from_member(&lokad_id)
+                } else {
+                    log_chronik!(
+                        "WS subscribe to LOKAD ID {}\n",
+                        hex::encode(lokad_id),
+                    );
+                    let recv =
+                        subs.subs_lokad_id_mut().subscribe_to_member(&lokad_id);
+                    self.lokad_ids.insert(lokad_id, recv);
+                }
+            }
+            WsSubType::TxId(txid) => {
+                if sub.is_unsub {
+                    log_chronik!("WS unsubscribe from txid {}\n", txid);
+                    std::mem::drop(self.txids.remove(&txid));
+                    subs.unsubscribe_from_txid(&txid);
+                } else {
+                    log_chronik!("WS subscribe to txid {}\n", txid);
+                    let recv = subs.subscribe_to_txid(txid);
+                    self.txids.insert(txid, recv);
+                }
+            }
+            WsSubType::OutPoint(outpoint) => {
+                if sub.is_unsub {
+                    log_chronik!("WS unsubscribe from {:?}\n", outpoint);
+                    std::mem::drop(self.outpoints.remove(&outpoint));
+                    subs.unsubscribe_from_outpoint(&outpoint);
+                } else {
+                    log_chronik!("WS subscribe to {:?}\n", outpoint);
+                    let recv = subs.subscribe_to_outpoint(outpoint);
+                    self.outpoints.insert(outpoint, recv);
+                }
+            }
         }
     }
 
     async fn cleanup(self, indexer: &ChronikIndexerRef) {
-        if self.scripts.is_empty() {
+        if self.scripts.is_empty()
+            && self.lokad_ids.is_empty()
+            && self.txids.is_empty()
+            && self.outpoints.is_empty()
+        {
             return;
         }
         let indexer = indexer.read().await;
@@ -138,6 +227,18 @@ impl SubRecv {
             subs.subs_script_mut()
                 .unsubscribe_from_member(&&script_variant.to_script());
         }
+        for (lokad_id, receiver) in self.lokad_ids {
+            std::mem::drop(receiver);
+            subs.subs_lokad_id_mut().unsubscribe_from_member(&lokad_id);
+        }
+        for (txid, receiver) in self.txids {
+            std::mem::drop(receiver);
+            subs.unsubscribe_from_txid(&txid);
+        }
+        for (outpoint, receiver) in self.outpoints {
+            std::mem::drop(receiver);
+            subs.unsubscribe_from_outpoint(&outpoint);
+        }
     }
 }
 
@@ -157,12 +258,27 @@ fn sub_client_msg_action(
                 sub_type: match sub.sub_type {
                     None => return Err(MissingSubType.into()),
                     Some(SubType::Blocks(_)) => WsSubType::Blocks,
+                    Some(SubType::Headers(_)) => WsSubType::Headers,
                     Some(SubType::Script(script)) => {
                         WsSubType::Script(parse_script_variant(
                             &script.script_type,
                             &script.payload,
                         )?)
                     }
+                    Some(SubType::LokadId(lokad_id)) => {
+                        WsSubType::LokadId(parse_lokad_id(&lokad_id.lokad_id)?)
+                    }
+                    Some(SubType::Txid(txid)) => {
+                        WsSubType::TxId(parse_txid(&txid.txid)?)
+                    }
+                    Some(SubType::Outpoint(outpoint)) => {
+                        let outpoint =
+                            outpoint.outpoint.ok_or(MissingOutPoint)?;
+                        WsSubType::OutPoint(OutPoint {
+                            txid: parse_txid(&outpoint.txid)?,
+                            out_idx: outpoint.out_idx,
+                        })
+                    }
                 },
             }))
         }
@@ -175,6 +291,10 @@ fn sub_client_msg_action(
     }
 }
 
+fn parse_txid(txid: &[u8]) -> Result<TxId> {
+    Ok(TxId::try_from(txid).map_err(InvalidTxId)?)
+}
+
 fn sub_block_msg_action(
     block_msg: Result<BlockMsg, broadcast::error::RecvError>,
 ) -> Result<WsAction> {
@@ -195,23 +315,49 @@ fn sub_block_msg_action(
     Ok(WsAction::Message(msg))
 }
 
-fn sub_script_msg_action(
-    script_msg: Result<TxMsg, broadcast::error::RecvError>,
+fn sub_header_msg_action(
+    block_msg: Result<BlockMsg, broadcast::error::RecvError>,
+) -> Result<WsAction> {
+    use proto::{ws_msg::MsgType, BlockMsgType::*};
+    let block_msg = match block_msg {
+        Ok(block_msg) => block_msg,
+        Err(_) => return Ok(WsAction::Nothing),
+    };
+    let block_msg_type = match block_msg.msg_type {
+        BlockMsgType::Connected => BlkConnected,
+        BlockMsgType::Disconnected => BlkDisconnected,
+        // Finalization doesn't change the header chain
+        BlockMsgType::Finalized => return Ok(WsAction::Nothing),
+    };
+    let msg_type = Some(MsgType::Header(proto::MsgHeader {
+        msg_type: block_msg_type as _,
+        header: block_msg.header,
+        block_height: block_msg.height,
+    }));
+    let msg_proto = proto::WsMsg { msg_type };
+    let msg = ws::Message::Binary(msg_proto.encode_to_vec());
+    Ok(WsAction::Message(msg))
+}
+
+fn sub_tx_msg_action(
+    tx_msg: Result<TxMsg, broadcast::error::RecvError>,
 ) -> Result<WsAction> {
     use proto::{ws_msg::MsgType, TxMsgType::*};
-    let script_msg = match script_msg {
-        Ok(script_msg) => script_msg,
+    let tx_msg = match tx_msg {
+        Ok(tx_msg) => tx_msg,
         Err(_) => return Ok(WsAction::Nothing),
     };
-    let tx_msg_type = match script_msg.msg_type {
+    let tx_msg_type = match tx_msg.msg_type {
         TxMsgType::AddedToMempool => TxAddedToMempool,
         TxMsgType::RemovedFromMempool => TxRemovedFromMempool,
         TxMsgType::Confirmed => TxConfirmed,
         TxMsgType::Finalized => TxFinalized,
+        TxMsgType::DoubleSpent => TxDoubleSpent,
+        TxMsgType::Conflicted => TxConflicted,
     };
     let msg_type = Some(MsgType::Tx(proto::MsgTx {
         msg_type: tx_msg_type as _,
-        txid: script_msg.txid.to_vec(),
+        txid: tx_msg.txid.to_vec(),
     }));
     let msg_proto = proto::WsMsg { msg_type };
     let msg = ws::Message::Binary(msg_proto.encode_to_vec());
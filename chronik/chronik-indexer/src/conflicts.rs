@@ -0,0 +1,383 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module containing [`Conflicts`], to keep track of mempool txs that have
+//! been removed because a block mined a conflicting tx.
+
+use std::collections::HashMap;
+
+use bitcoinsuite_core::tx::{OutPoint, Tx, TxId};
+use chronik_db::{io::BlockHeight, mem::MempoolTx};
+
+use crate::subs_group::TxMsgType;
+
+/// Number of blocks after which a [`TxConflict`] is forgotten, and after which
+/// a pending conflict that no block resolved is dropped.
+pub const CONFLICT_EXPIRY_BLOCKS: BlockHeight = 10;
+
+/// How a mempool tx conflicts with a mined tx.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConflictType {
+    /// Tx spent an output that has also been spent by a mined tx.
+    DoubleSpent,
+    /// Tx spent an output of a tx that got double-spent or conflicted.
+    Conflicted,
+}
+
+/// Mempool tx that has been removed because it conflicts with a mined tx.
+#[derive(Debug, Eq, PartialEq)]
+pub struct TxConflict {
+    /// The removed mempool tx.
+    pub tx: MempoolTx,
+    /// Whether the tx got double-spent or conflicted.
+    pub conflict_type: ConflictType,
+    /// Mined tx that double-spent this tx or its ancestor.
+    pub conflicting_txid: TxId,
+    /// Height of the block that mined the conflicting tx.
+    pub block_height: BlockHeight,
+}
+
+/// Struct keeping track of conflicts between mempool txs and mined txs.
+///
+/// The node removes conflicting txs from the mempool before it connects the
+/// block that caused the conflict, so those txs are first kept as pending and
+/// only turned into a [`TxConflict`] once the block is connected.
+#[derive(Debug, Default)]
+pub struct Conflicts {
+    pending: HashMap<TxId, PendingConflict>,
+    conflicts: HashMap<TxId, TxConflict>,
+}
+
+/// Changes to the conflicts after a block has been connected.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct BlockConflicts {
+    /// Txids of the new conflicts, double-spent txs first and then their
+    /// descendants.
+    pub new_conflicts: Vec<TxId>,
+    /// Pending txs that no block resolved for [`CONFLICT_EXPIRY_BLOCKS`].
+    pub dropped: Vec<MempoolTx>,
+}
+
+/// Removed mempool tx waiting for the block that conflicts with it.
+#[derive(Debug)]
+struct PendingConflict {
+    tx: MempoolTx,
+    /// Height of the first connected block that didn't resolve the conflict.
+    unresolved_since: Option<BlockHeight>,
+}
+
+impl From<ConflictType> for TxMsgType {
+    fn from(conflict_type: ConflictType) -> Self {
+        match conflict_type {
+            ConflictType::DoubleSpent => TxMsgType::DoubleSpent,
+            ConflictType::Conflicted => TxMsgType::Conflicted,
+        }
+    }
+}
+
+impl Conflicts {
+    /// Add a tx that has been removed from the mempool due to a conflict with
+    /// a block that's about to be connected.
+    pub fn add_pending(&mut self, tx: MempoolTx) {
+        self.pending.insert(
+            tx.tx.txid(),
+            PendingConflict {
+                tx,
+                unresolved_since: None,
+            },
+        );
+    }
+
+    /// Resolve the pending txs conflicting with the txs of the connected
+    /// block, and forget conflicts older than [`CONFLICT_EXPIRY_BLOCKS`].
+    /// Pending txs that no block resolved for [`CONFLICT_EXPIRY_BLOCKS`] are
+    /// dropped and returned, so they don't pile up.
+    pub fn connect_block(
+        &mut self,
+        height: BlockHeight,
+        txs: &[Tx],
+    ) -> BlockConflicts {
+        self.conflicts.retain(|_, conflict| {
+            conflict.block_height > height - CONFLICT_EXPIRY_BLOCKS
+        });
+        let spent_outpoints = txs
+            .iter()
+            .flat_map(|tx| {
+                tx.inputs.iter().map(|input| (input.prev_out, tx.txid()))
+            })
+            .collect::<HashMap<OutPoint, TxId>>();
+        let mut new_conflicts = self.resolve_pending(
+            height,
+            ConflictType::DoubleSpent,
+            |_, outpoint| spent_outpoints.get(outpoint).copied(),
+        );
+        // Resolve descendants one generation at a time
+        loop {
+            let conflicted = self.resolve_pending(
+                height,
+                ConflictType::Conflicted,
+                |conflicts, outpoint| {
+                    let parent = conflicts.get(&outpoint.txid)?;
+                    Some(parent.conflicting_txid)
+                },
+            );
+            if conflicted.is_empty() {
+                break;
+            }
+            new_conflicts.extend(conflicted);
+        }
+        let mut expired = self
+            .pending
+            .iter_mut()
+            .filter_map(|(&txid, pending)| {
+                let unresolved_since =
+                    *pending.unresolved_since.get_or_insert(height);
+                let is_expired =
+                    unresolved_since <= height - CONFLICT_EXPIRY_BLOCKS;
+                is_expired.then_some(txid)
+            })
+            .collect::<Vec<_>>();
+        expired.sort_unstable();
+        let dropped = expired
+            .iter()
+            .map(|txid| self.pending.remove(txid).unwrap().tx)
+            .collect();
+        BlockConflicts {
+            new_conflicts,
+            dropped,
+        }
+    }
+
+    fn resolve_pending(
+        &mut self,
+        height: BlockHeight,
+        conflict_type: ConflictType,
+        conflicting_txid: impl Fn(
+            &HashMap<TxId, TxConflict>,
+            &OutPoint,
+        ) -> Option<TxId>,
+    ) -> Vec<TxId> {
+        let mut resolved = self
+            .pending
+            .iter()
+            .filter_map(|(&txid, pending)| {
+                let conflicting_txid =
+                    pending.tx.tx.inputs.iter().find_map(|input| {
+                        conflicting_txid(&self.conflicts, &input.prev_out)
+                    })?;
+                Some((txid, conflicting_txid))
+            })
+            .collect::<Vec<_>>();
+        resolved.sort_unstable();
+        for &(txid, conflicting_txid) in &resolved {
+            let tx = self.pending.remove(&txid).unwrap().tx;
+            self.conflicts.insert(
+                txid,
+                TxConflict {
+                    tx,
+                    conflict_type,
+                    conflicting_txid,
+                    block_height: height,
+                },
+            );
+        }
+        resolved.into_iter().map(|(txid, _)| txid).collect()
+    }
+
+    /// Forget any conflict of the tx, e.g. when it's been added back to the
+    /// mempool after a reorg.
+    pub fn remove(&mut self, txid: &TxId) {
+        self.pending.remove(txid);
+        self.conflicts.remove(txid);
+    }
+
+    /// Get the conflict of a recently removed tx, or [`None`] if not found.
+    pub fn get(&self, txid: &TxId) -> Option<&TxConflict> {
+        self.conflicts.get(txid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use abc_rust_error::Result;
+    use bitcoinsuite_core::tx::{OutPoint, Tx, TxId, TxInput, TxMut, TxOutput};
+    use chronik_db::{
+        db::Db,
+        groups::{prefix_mock_compress, ScriptGroup},
+        mem::{Mempool, MempoolTx},
+    };
+    use pretty_assertions::assert_eq;
+
+    use crate::conflicts::{
+        BlockConflicts, ConflictType, Conflicts, TxConflict,
+        CONFLICT_EXPIRY_BLOCKS,
+    };
+
+    fn make_tx(txid_num: u8, spent: &[(u8, u32)], num_outputs: usize) -> Tx {
+        Tx::with_txid(
+            TxId::from([txid_num; 32]),
+            TxMut {
+                version: 1,
+                inputs: spent
+                    .iter()
+                    .map(|&(txid_num, out_idx)| TxInput {
+                        prev_out: OutPoint {
+                            txid: TxId::from([txid_num; 32]),
+                            out_idx,
+                        },
+                        ..Default::default()
+                    })
+                    .collect(),
+                outputs: vec![TxOutput::default(); num_outputs],
+                locktime: 0,
+            },
+        )
+    }
+
+    fn mempool_tx(tx: &Tx) -> MempoolTx {
+        MempoolTx {
+            tx: tx.clone(),
+            time_first_seen: 1000,
+        }
+    }
+
+    #[test]
+    fn test_conflicts() -> Result<()> {
+        abc_rust_error::install();
+        let tempdir = tempdir::TempDir::new("chronik-indexer--conflicts")?;
+        let db = Db::open(tempdir.path())?;
+        let mut mempool =
+            Mempool::new(ScriptGroup::new(prefix_mock_compress), false);
+
+        // tx1 spends 0xff:0, tx2 and tx3 spend tx1, tx4 spends tx2 and tx3
+        let tx1 = make_tx(1, &[(0xff, 0)], 2);
+        let tx2 = make_tx(2, &[(1, 0)], 1);
+        let tx3 = make_tx(3, &[(1, 1)], 1);
+        let tx4 = make_tx(4, &[(2, 0), (3, 0)], 1);
+        // tx5 spends 0xff:1 and is mined
+        let tx5 = make_tx(5, &[(0xff, 1)], 1);
+        // tx6 is unrelated
+        let tx6 = make_tx(6, &[(0xff, 2)], 1);
+        for tx in [&tx1, &tx2, &tx3, &tx4, &tx5, &tx6] {
+            mempool.insert(&db, mempool_tx(tx))?;
+        }
+
+        // Block mines tx5 and tx7, which double-spends tx1
+        let tx7 = make_tx(7, &[(0xff, 0)], 1);
+        let block_txs = [tx5.clone(), tx7.clone()];
+        for tx in &block_txs {
+            mempool.remove_mined(&tx.txid())?;
+        }
+        let removed = mempool.remove_conflicts(&block_txs)?;
+        let removed_txids = removed
+            .iter()
+            .map(|tx| tx.tx.txid_ref().as_bytes()[0])
+            .collect::<Vec<_>>();
+        // Descendants are removed before their ancestors
+        assert_eq!(removed_txids.len(), 4);
+        assert_eq!(removed_txids[0], 4);
+        assert_eq!(removed_txids[3], 1);
+        assert_eq!(mempool.txs().len(), 1);
+        assert!(mempool.tx(&tx6.txid()).is_some());
+
+        let mut conflicts = Conflicts::default();
+        // tx8 is conflicted by a later block
+        let tx8 = make_tx(8, &[(0xfe, 0)], 1);
+        conflicts.add_pending(mempool_tx(&tx8));
+        for tx in removed {
+            conflicts.add_pending(tx);
+        }
+        assert_eq!(
+            conflicts.connect_block(100, &block_txs),
+            BlockConflicts {
+                new_conflicts: vec![
+                    tx1.txid(),
+                    tx2.txid(),
+                    tx3.txid(),
+                    tx4.txid(),
+                ],
+                dropped: vec![],
+            },
+        );
+        assert_eq!(
+            conflicts.get(&tx1.txid()),
+            Some(&TxConflict {
+                tx: mempool_tx(&tx1),
+                conflict_type: ConflictType::DoubleSpent,
+                conflicting_txid: tx7.txid(),
+                block_height: 100,
+            }),
+        );
+        for tx in [&tx2, &tx3, &tx4] {
+            assert_eq!(
+                conflicts.get(&tx.txid()),
+                Some(&TxConflict {
+                    tx: mempool_tx(tx),
+                    conflict_type: ConflictType::Conflicted,
+                    conflicting_txid: tx7.txid(),
+                    block_height: 100,
+                }),
+            );
+        }
+        assert_eq!(conflicts.get(&tx8.txid()), None);
+
+        // Next block resolves tx8
+        let tx9 = make_tx(9, &[(0xfe, 0)], 1);
+        assert_eq!(
+            conflicts.connect_block(101, &[tx9]).new_conflicts,
+            vec![tx8.txid()],
+        );
+        assert_eq!(
+            conflicts
+                .get(&tx8.txid())
+                .map(|conflict| conflict.conflict_type),
+            Some(ConflictType::DoubleSpent),
+        );
+
+        // tx4 gets re-added to the mempool
+        conflicts.remove(&tx4.txid());
+        assert_eq!(conflicts.get(&tx4.txid()), None);
+
+        // Conflicts expire after a few blocks
+        assert_eq!(
+            conflicts.connect_block(100 + CONFLICT_EXPIRY_BLOCKS, &[]),
+            BlockConflicts::default(),
+        );
+        assert_eq!(conflicts.get(&tx1.txid()), None);
+        assert!(conflicts.get(&tx8.txid()).is_some());
+
+        // Pending txs that no block resolves are dropped after a few blocks
+        let height = 100 + CONFLICT_EXPIRY_BLOCKS;
+        let tx10 = make_tx(10, &[(0xfd, 0)], 1);
+        let tx11 = make_tx(11, &[(0xfd, 1)], 1);
+        conflicts.add_pending(mempool_tx(&tx10));
+        conflicts.add_pending(mempool_tx(&tx11));
+        for offset in 1..=CONFLICT_EXPIRY_BLOCKS {
+            assert_eq!(
+                conflicts.connect_block(height + offset, &[]),
+                BlockConflicts::default(),
+            );
+        }
+        // Block resolving tx11 is still in time, tx10 is dropped after it
+        // and returned, so subscribers can be notified
+        let tx12 = make_tx(12, &[(0xfd, 1)], 1);
+        assert_eq!(
+            conflicts
+                .connect_block(height + CONFLICT_EXPIRY_BLOCKS + 1, &[tx12]),
+            BlockConflicts {
+                new_conflicts: vec![tx11.txid()],
+                dropped: vec![mempool_tx(&tx10)],
+            },
+        );
+        let tx13 = make_tx(13, &[(0xfd, 0)], 1);
+        assert_eq!(
+            conflicts
+                .connect_block(height + CONFLICT_EXPIRY_BLOCKS + 2, &[tx13]),
+            BlockConflicts::default(),
+        );
+        assert_eq!(conflicts.get(&tx10.txid()), None);
+
+        Ok(())
+    }
+}
@@ -4,7 +4,10 @@
 
 //! Module containing [`ChronikIndexer`] to index blocks and txs.
 
-use std::path::PathBuf;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use abc_rust_error::{Result, WrapErr};
 use bitcoinsuite_core::{
@@ -15,15 +18,18 @@ use chronik_bridge::{ffi, util::expect_unique_ptr};
 use chronik_db::{
     db::{Db, WriteBatch},
     groups::{
-        FnCompressScript, ScriptGroup, ScriptHistoryWriter, ScriptUtxoWriter,
+        FnCompressScript, LokadIdGroup, LokadIdHistoryWriter, ScriptGroup,
+        ScriptHashGroup, ScriptHashHistoryWriter, ScriptHashUtxoWriter,
+        ScriptHistoryWriter, ScriptUtxoWriter, TokenIdGroup, TokenIdGroupAux,
+        TokenIdHistoryWriter, TokenIdUtxoWriter,
     },
     index_tx::prepare_indexed_txs,
     io::{
         BlockHeight, BlockReader, BlockStatsWriter, BlockTxs, BlockWriter,
         DbBlock, MetadataReader, MetadataWriter, SchemaVersion, SpentByWriter,
-        TxEntry, TxWriter,
+        TokenInfoWriter, TokenTxWriter, TxEntry, TxReader, TxWriter,
     },
-    mem::{Mempool, MempoolTx},
+    mem::{Mempool, MempoolSnapshot, MempoolTx},
 };
 use chronik_util::{log, log_chronik};
 use thiserror::Error;
@@ -31,12 +37,16 @@ use tokio::sync::RwLock;
 
 use crate::{
     avalanche::Avalanche,
-    query::{QueryBlocks, QueryGroupHistory, QueryGroupUtxos, QueryTxs},
+    conflicts::Conflicts,
+    query::{
+        QueryBlocks, QueryFeeEstimate, QueryGroupBalance, QueryGroupHistory,
+        QueryGroupUtxos, QueryMempool, QueryTokens, QueryTxGraph, QueryTxs,
+    },
     subs::{BlockMsg, BlockMsgType, Subs},
     subs_group::TxMsgType,
 };
 
-const CURRENT_INDEXER_VERSION: SchemaVersion = 7;
+const CURRENT_INDEXER_VERSION: SchemaVersion = 15;
 
 /// Params for setting up a [`ChronikIndexer`] instance.
 #[derive(Clone)]
@@ -47,6 +57,12 @@ pub struct ChronikIndexerParams {
     pub wipe_db: bool,
     /// Function ptr to compress scripts.
     pub fn_compress_script: FnCompressScript,
+    /// Whether to write a snapshot of the mempool on shutdown and to load it
+    /// again on startup, see [`MempoolSnapshot`].
+    pub persist_mempool: bool,
+    /// Whether to index txs by the Electrum script hash of their scripts.
+    /// Toggling this for an existing DB requires a reindex.
+    pub enable_script_hash_index: bool,
 }
 
 /// Struct for indexing blocks and txs. Maintains db handles and mempool.
@@ -55,8 +71,12 @@ pub struct ChronikIndexer {
     db: Db,
     mempool: Mempool,
     script_group: ScriptGroup,
+    enable_script_hash_index: bool,
     avalanche: Avalanche,
+    conflicts: Conflicts,
     subs: RwLock<Subs>,
+    mempool_snapshot_path: Option<PathBuf>,
+    snapshot_times_first_seen: HashMap<TxId, i64>,
 }
 
 /// Access to the bitcoind node.
@@ -75,6 +95,8 @@ pub struct ChronikBlock {
     pub block_txs: BlockTxs,
     /// Block size in bytes.
     pub size: u64,
+    /// Serialized header of the block.
+    pub header: Vec<u8>,
     /// Txs in the block, with inputs/outputs so we can group them.
     pub txs: Vec<Tx>,
 }
@@ -135,6 +157,25 @@ pub enum ChronikIndexerError {
         CURRENT_INDEXER_VERSION
     )]
     DatabaseOutdated(SchemaVersion),
+
+    /// Corrupted script hash index flag
+    #[error(
+        "Corrupted script hash index flag in the Chronik database, consider \
+         running -reindex/-chronikreindex"
+    )]
+    CorruptedScriptHashIndex,
+
+    /// Script hash index has been toggled for a non-empty database
+    #[error(
+        "Cannot {} the script hash index of a non-empty Chronik database, \
+         -reindex/-chronikreindex to reindex the database.",
+        if *.0 { "enable" } else { "disable" }
+    )]
+    ScriptHashIndexToggled(bool),
+
+    /// Script hash index is not enabled
+    #[error("Script hash index is disabled")]
+    ScriptHashIndexDisabled,
 }
 
 use self::ChronikIndexerError::*;
@@ -156,14 +197,27 @@ impl ChronikIndexer {
         log_chronik!("Opening Chronik at {}\n", db_path.to_string_lossy());
         let db = Db::open(&db_path)?;
         verify_schema_version(&db)?;
+        verify_script_hash_index(&db, params.enable_script_hash_index)?;
+        let mempool_snapshot_path = params
+            .persist_mempool
+            .then(|| indexes_path.join("chronik_mempool.dat"));
+        let snapshot_times_first_seen = match &mempool_snapshot_path {
+            Some(path) => load_mempool_snapshot(&db, path)?,
+            None => HashMap::new(),
+        };
         let script_group = ScriptGroup::new(params.fn_compress_script);
-        let mempool = Mempool::new(script_group.clone());
+        let mempool =
+            Mempool::new(script_group.clone(), params.enable_script_hash_index);
         Ok(ChronikIndexer {
             db,
             mempool,
             script_group: script_group.clone(),
+            enable_script_hash_index: params.enable_script_hash_index,
             avalanche: Avalanche::default(),
+            conflicts: Conflicts::default(),
             subs: RwLock::new(Subs::new(script_group)),
+            mempool_snapshot_path,
+            snapshot_times_first_seen,
         })
     }
 
@@ -280,25 +334,50 @@ impl ChronikIndexer {
     }
 
     /// Add transaction to the indexer's mempool.
+    ///
+    /// If the tx has been in the mempool snapshot loaded on startup, the
+    /// earlier time first seen is kept.
     pub fn handle_tx_added_to_mempool(
         &mut self,
-        mempool_tx: MempoolTx,
+        mut mempool_tx: MempoolTx,
     ) -> Result<()> {
+        let snapshot_time_first_seen = self
+            .snapshot_times_first_seen
+            .remove(mempool_tx.tx.txid_ref());
+        if let Some(time_first_seen) = snapshot_time_first_seen {
+            if mempool_tx.time_first_seen == 0
+                || time_first_seen < mempool_tx.time_first_seen
+            {
+                mempool_tx.time_first_seen = time_first_seen;
+            }
+        }
         self.subs
             .get_mut()
             .handle_tx_event(&mempool_tx.tx, TxMsgType::AddedToMempool);
-        self.mempool.insert(mempool_tx)?;
+        self.conflicts.remove(mempool_tx.tx.txid_ref());
+        self.mempool.insert(&self.db, mempool_tx)?;
         Ok(())
     }
 
     /// Remove tx from the indexer's mempool, e.g. by a conflicting tx, expiry
     /// etc. This is not called when the transaction has been mined (and thus
     /// also removed from the mempool).
-    pub fn handle_tx_removed_from_mempool(&mut self, txid: TxId) -> Result<()> {
+    ///
+    /// If the tx conflicts with a tx of a block that's about to be connected
+    /// (`is_conflict`), subscribers are additionally notified about the
+    /// conflict once the block has been connected, see [`Conflicts`].
+    pub fn handle_tx_removed_from_mempool(
+        &mut self,
+        txid: TxId,
+        is_conflict: bool,
+    ) -> Result<()> {
         let mempool_tx = self.mempool.remove(txid)?;
         self.subs
             .get_mut()
             .handle_tx_event(&mempool_tx.tx, TxMsgType::RemovedFromMempool);
+        if is_conflict {
+            self.conflicts.add_pending(mempool_tx);
+        }
         Ok(())
     }
 
@@ -317,28 +396,75 @@ impl ChronikIndexer {
         let script_utxo_writer =
             ScriptUtxoWriter::new(&self.db, self.script_group.clone())?;
         let spent_by_writer = SpentByWriter::new(&self.db)?;
+        let token_id_history_writer =
+            TokenIdHistoryWriter::new(&self.db, TokenIdGroup)?;
+        let token_id_utxo_writer =
+            TokenIdUtxoWriter::new(&self.db, TokenIdGroup)?;
+        let token_tx_writer = TokenTxWriter::new(&self.db)?;
+        let token_info_writer = TokenInfoWriter::new(&self.db)?;
+        let lokad_id_history_writer =
+            LokadIdHistoryWriter::new(&self.db, LokadIdGroup)?;
         block_writer.insert(&mut batch, &block.db_block)?;
         let first_tx_num = tx_writer.insert(&mut batch, &block.block_txs)?;
         let index_txs =
             prepare_indexed_txs(&self.db, first_tx_num, &block.txs)?;
         block_stats_writer
             .insert(&mut batch, height, block.size, &index_txs)?;
-        script_history_writer.insert(&mut batch, &index_txs)?;
-        script_utxo_writer.insert(&mut batch, &index_txs)?;
+        script_history_writer.insert(&mut batch, &index_txs, &())?;
+        script_utxo_writer.insert(&mut batch, &index_txs, &())?;
         spent_by_writer.insert(&mut batch, &index_txs)?;
+        let tokens = TokenIdGroupAux::from_db(&self.db, &index_txs)?;
+        token_id_history_writer.insert(&mut batch, &index_txs, &tokens)?;
+        token_id_utxo_writer.insert(&mut batch, &index_txs, &tokens)?;
+        token_tx_writer.insert(&mut batch, &index_txs, &tokens)?;
+        token_info_writer.insert(&mut batch, height, &index_txs, &tokens)?;
+        lokad_id_history_writer.insert(&mut batch, &index_txs, &())?;
+        if self.enable_script_hash_index {
+            ScriptHashHistoryWriter::new(&self.db, ScriptHashGroup)?.insert(
+                &mut batch,
+                &index_txs,
+                &(),
+            )?;
+            ScriptHashUtxoWriter::new(&self.db, ScriptHashGroup)?.insert(
+                &mut batch,
+                &index_txs,
+                &(),
+            )?;
+        }
         self.db.write_batch(batch)?;
         for tx in &block.block_txs.txs {
             self.mempool.remove_mined(&tx.txid)?;
         }
+        let conflicted_txs = self.mempool.remove_conflicts(&block.txs)?;
         let subs = self.subs.get_mut();
+        for conflicted_tx in conflicted_txs {
+            subs.handle_tx_event(
+                &conflicted_tx.tx,
+                TxMsgType::RemovedFromMempool,
+            );
+            self.conflicts.add_pending(conflicted_tx);
+        }
+        let block_conflicts = self.conflicts.connect_block(height, &block.txs);
         subs.broadcast_block_msg(BlockMsg {
             msg_type: BlockMsgType::Connected,
             hash: block.db_block.hash,
             height: block.db_block.height,
+            header: block.header.clone(),
         });
         for tx in &block.txs {
             subs.handle_tx_event(tx, TxMsgType::Confirmed);
         }
+        for txid in &block_conflicts.new_conflicts {
+            if let Some(conflict) = self.conflicts.get(txid) {
+                subs.handle_tx_event(
+                    &conflict.tx.tx,
+                    conflict.conflict_type.into(),
+                );
+            }
+        }
+        for dropped_tx in &block_conflicts.dropped {
+            subs.handle_tx_event(&dropped_tx.tx, TxMsgType::RemovedFromMempool);
+        }
         Ok(())
     }
 
@@ -356,14 +482,40 @@ impl ChronikIndexer {
         let script_utxo_writer =
             ScriptUtxoWriter::new(&self.db, self.script_group.clone())?;
         let spent_by_writer = SpentByWriter::new(&self.db)?;
+        let token_id_history_writer =
+            TokenIdHistoryWriter::new(&self.db, TokenIdGroup)?;
+        let token_id_utxo_writer =
+            TokenIdUtxoWriter::new(&self.db, TokenIdGroup)?;
+        let token_tx_writer = TokenTxWriter::new(&self.db)?;
+        let token_info_writer = TokenInfoWriter::new(&self.db)?;
+        let lokad_id_history_writer =
+            LokadIdHistoryWriter::new(&self.db, LokadIdGroup)?;
         block_writer.delete(&mut batch, &block.db_block)?;
         let first_tx_num = tx_writer.delete(&mut batch, &block.block_txs)?;
         let index_txs =
             prepare_indexed_txs(&self.db, first_tx_num, &block.txs)?;
         block_stats_writer.delete(&mut batch, block.db_block.height);
-        script_history_writer.delete(&mut batch, &index_txs)?;
-        script_utxo_writer.delete(&mut batch, &index_txs)?;
+        script_history_writer.delete(&mut batch, &index_txs, &())?;
+        script_utxo_writer.delete(&mut batch, &index_txs, &())?;
         spent_by_writer.delete(&mut batch, &index_txs)?;
+        let tokens = TokenIdGroupAux::from_db(&self.db, &index_txs)?;
+        token_id_history_writer.delete(&mut batch, &index_txs, &tokens)?;
+        token_id_utxo_writer.delete(&mut batch, &index_txs, &tokens)?;
+        token_tx_writer.delete(&mut batch, &index_txs)?;
+        token_info_writer.delete(&mut batch, &index_txs, &tokens)?;
+        lokad_id_history_writer.delete(&mut batch, &index_txs, &())?;
+        if self.enable_script_hash_index {
+            ScriptHashHistoryWriter::new(&self.db, ScriptHashGroup)?.delete(
+                &mut batch,
+                &index_txs,
+                &(),
+            )?;
+            ScriptHashUtxoWriter::new(&self.db, ScriptHashGroup)?.delete(
+                &mut batch,
+                &index_txs,
+                &(),
+            )?;
+        }
         self.avalanche.disconnect_block(block.db_block.height)?;
         self.db.write_batch(batch)?;
         let subs = self.subs.get_mut();
@@ -371,6 +523,7 @@ impl ChronikIndexer {
             msg_type: BlockMsgType::Disconnected,
             hash: block.db_block.hash,
             height: block.db_block.height,
+            header: block.header.clone(),
         });
         Ok(())
     }
@@ -386,6 +539,7 @@ impl ChronikIndexer {
             msg_type: BlockMsgType::Finalized,
             hash: block.db_block.hash,
             height: block.db_block.height,
+            header: block.header.clone(),
         });
         for tx in &block.txs {
             subs.handle_tx_event(tx, TxMsgType::Finalized);
@@ -393,6 +547,22 @@ impl ChronikIndexer {
         Ok(())
     }
 
+    /// Write a snapshot of the mempool to disk, if enabled via
+    /// [`ChronikIndexerParams::persist_mempool`]. Called on shutdown.
+    pub fn save_mempool_snapshot(&self) -> Result<()> {
+        let path = match &self.mempool_snapshot_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let snapshot = MempoolSnapshot::from_mempool(&self.mempool);
+        snapshot.save(path)?;
+        log!(
+            "Chronik: Saved mempool snapshot with {} txs\n",
+            snapshot.txs.len(),
+        );
+        Ok(())
+    }
+
     /// Return [`QueryBlocks`] to read blocks from the DB.
     pub fn blocks(&self) -> QueryBlocks<'_> {
         QueryBlocks {
@@ -408,6 +578,32 @@ impl ChronikIndexer {
             db: &self.db,
             avalanche: &self.avalanche,
             mempool: &self.mempool,
+            conflicts: &self.conflicts,
+        }
+    }
+
+    /// Return [`QueryTxGraph`] to query the ancestors and descendants of txs.
+    pub fn tx_graph(&self) -> QueryTxGraph<'_> {
+        QueryTxGraph {
+            db: &self.db,
+            avalanche: &self.avalanche,
+            mempool: &self.mempool,
+        }
+    }
+
+    /// Return [`QueryMempool`] to query the txs in the mempool.
+    pub fn mempool(&self) -> QueryMempool<'_> {
+        QueryMempool {
+            mempool: &self.mempool,
+        }
+    }
+
+    /// Return [`QueryFeeEstimate`] to estimate the fee rate required for a tx
+    /// to be mined.
+    pub fn fee_estimate(&self) -> QueryFeeEstimate<'_> {
+        QueryFeeEstimate {
+            db: &self.db,
+            mempool: &self.mempool,
         }
     }
 
@@ -434,6 +630,115 @@ impl ChronikIndexer {
         })
     }
 
+    /// Return [`QueryGroupBalance`] for scripts to query the balance of
+    /// scripts.
+    pub fn script_balance(&self) -> Result<QueryGroupBalance<'_, ScriptGroup>> {
+        Ok(QueryGroupBalance {
+            db: &self.db,
+            mempool: &self.mempool,
+            mempool_history: self.mempool.script_history(),
+            group: self.script_group.clone(),
+        })
+    }
+
+    /// Return [`QueryGroupHistory`] for token IDs to query the tx history of
+    /// tokens.
+    pub fn token_id_history(
+        &self,
+    ) -> Result<QueryGroupHistory<'_, TokenIdGroup>> {
+        Ok(QueryGroupHistory {
+            db: &self.db,
+            avalanche: &self.avalanche,
+            mempool: &self.mempool,
+            mempool_history: self.mempool.token_id_history(),
+            group: TokenIdGroup,
+        })
+    }
+
+    /// Return [`QueryGroupUtxos`] for token IDs to query the utxos of tokens.
+    pub fn token_id_utxos(&self) -> Result<QueryGroupUtxos<'_, TokenIdGroup>> {
+        Ok(QueryGroupUtxos {
+            db: &self.db,
+            avalanche: &self.avalanche,
+            mempool: &self.mempool,
+            mempool_utxos: self.mempool.token_id_utxos(),
+            group: TokenIdGroup,
+        })
+    }
+
+    /// Return [`QueryGroupHistory`] for LOKAD IDs to query the tx history of
+    /// OP_RETURN protocols.
+    pub fn lokad_id_history(
+        &self,
+    ) -> Result<QueryGroupHistory<'_, LokadIdGroup>> {
+        Ok(QueryGroupHistory {
+            db: &self.db,
+            avalanche: &self.avalanche,
+            mempool: &self.mempool,
+            mempool_history: self.mempool.lokad_id_history(),
+            group: LokadIdGroup,
+        })
+    }
+
+    /// Return [`QueryGroupHistory`] for script hashes to query the tx history
+    /// of scripts by their Electrum script hash.
+    pub fn script_hash_history(
+        &self,
+    ) -> Result<QueryGroupHistory<'_, ScriptHashGroup>> {
+        Ok(QueryGroupHistory {
+            db: &self.db,
+            avalanche: &self.avalanche,
+            mempool: &self.mempool,
+            mempool_history: self
+                .mempool
+                .script_hash_history()
+                .ok_or(ScriptHashIndexDisabled)?,
+            group: ScriptHashGroup,
+        })
+    }
+
+    /// Return [`QueryGroupUtxos`] for script hashes to query the utxos of
+    /// scripts by their Electrum script hash.
+    pub fn script_hash_utxos(
+        &self,
+    ) -> Result<QueryGroupUtxos<'_, ScriptHashGroup>> {
+        Ok(QueryGroupUtxos {
+            db: &self.db,
+            avalanche: &self.avalanche,
+            mempool: &self.mempool,
+            mempool_utxos: self
+                .mempool
+                .script_hash_utxos()
+                .ok_or(ScriptHashIndexDisabled)?,
+            group: ScriptHashGroup,
+        })
+    }
+
+    /// Return [`QueryGroupBalance`] for script hashes to query the balance of
+    /// scripts by their Electrum script hash.
+    pub fn script_hash_balance(
+        &self,
+    ) -> Result<QueryGroupBalance<'_, ScriptHashGroup>> {
+        Ok(QueryGroupBalance {
+            db: &self.db,
+            mempool: &self.mempool,
+            mempool_history: self
+                .mempool
+                .script_hash_history()
+                .ok_or(ScriptHashIndexDisabled)?,
+            group: ScriptHashGroup,
+        })
+    }
+
+    /// Return [`QueryTokens`] to query info about tokens.
+    pub fn tokens(&self) -> QueryTokens<'_> {
+        QueryTokens {
+            db: &self.db,
+            avalanche: &self.avalanche,
+            mempool: &self.mempool,
+        }
+    }
+
     /// Subscribers, behind read/write lock
     pub fn subs(&self) -> &RwLock<Subs> {
         &self.subs
@@ -484,11 +789,38 @@ impl ChronikIndexer {
             db_block,
             block_txs,
             size: block.size,
+            header: block.header,
             txs,
         })
     }
 }
 
+/// Load the first-seen times from the mempool snapshot at `path`, skipping txs
+/// that have been mined in the meantime. A snapshot that fails to load is
+/// ignored, as it only affects the first-seen times.
+fn load_mempool_snapshot(db: &Db, path: &Path) -> Result<HashMap<TxId, i64>> {
+    let snapshot = match MempoolSnapshot::load(path) {
+        Ok(Some(snapshot)) => snapshot,
+        Ok(None) => return Ok(HashMap::new()),
+        Err(report) => {
+            log!("Chronik: Ignoring mempool snapshot: {report}\n");
+            return Ok(HashMap::new());
+        }
+    };
+    let tx_reader = TxReader::new(db)?;
+    let mut times_first_seen = snapshot.times_first_seen();
+    for txid in times_first_seen.keys().copied().collect::<Vec<_>>() {
+        if tx_reader.tx_num_by_txid(&txid)?.is_some() {
+            times_first_seen.remove(&txid);
+        }
+    }
+    log!(
+        "Chronik: Loaded mempool snapshot with {} txs\n",
+        times_first_seen.len(),
+    );
+    Ok(times_first_seen)
+}
+
 fn verify_schema_version(db: &Db) -> Result<()> {
     let metadata_reader = MetadataReader::new(db)?;
     let metadata_writer = MetadataWriter::new(db)?;
@@ -520,12 +852,36 @@ fn verify_schema_version(db: &Db) -> Result<()> {
     Ok(())
 }
 
+fn verify_script_hash_index(db: &Db, enable: bool) -> Result<()> {
+    let metadata_reader = MetadataReader::new(db)?;
+    let metadata_writer = MetadataWriter::new(db)?;
+    let has_blocks = BlockReader::new(db)?.tip()?.is_some();
+    let is_script_hash_index = metadata_reader
+        .is_script_hash_index()
+        .wrap_err(CorruptedScriptHashIndex)?;
+    if has_blocks {
+        // DBs that predate the flag never had the index enabled
+        if is_script_hash_index.unwrap_or(false) != enable {
+            return Err(ScriptHashIndexToggled(enable).into());
+        }
+        if is_script_hash_index.is_some() {
+            return Ok(());
+        }
+    }
+    let mut batch = WriteBatch::default();
+    metadata_writer.update_is_script_hash_index(&mut batch, enable)?;
+    db.write_batch(batch)?;
+    Ok(())
+}
+
 impl std::fmt::Debug for ChronikIndexerParams {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ChronikIndexerParams")
             .field("datadir_net", &self.datadir_net)
             .field("wipe_db", &self.wipe_db)
             .field("fn_compress_script", &"..")
+            .field("persist_mempool", &self.persist_mempool)
+            .field("enable_script_hash_index", &self.enable_script_hash_index)
             .finish()
     }
 }
@@ -533,11 +889,15 @@ impl std::fmt::Debug for ChronikIndexerParams {
 #[cfg(test)]
 mod tests {
     use abc_rust_error::Result;
-    use bitcoinsuite_core::block::BlockHash;
+    use bitcoinsuite_core::{
+        block::BlockHash,
+        tx::{Tx, TxId, TxMut},
+    };
     use chronik_db::{
         db::{Db, WriteBatch, CF_META},
         groups::prefix_mock_compress,
         io::{BlockReader, BlockTxs, DbBlock, MetadataReader, MetadataWriter},
+        mem::MempoolTx,
     };
     use pretty_assertions::assert_eq;
 
@@ -554,6 +914,8 @@ mod tests {
             datadir_net: datadir_net.clone(),
             wipe_db: false,
             fn_compress_script: prefix_mock_compress,
+            persist_mempool: false,
+            enable_script_hash_index: false,
         };
         // regtest folder doesn't exist yet -> error
         assert_eq!(
@@ -589,6 +951,7 @@ mod tests {
                 txs: vec![],
             },
             size: 285,
+            header: vec![],
             txs: vec![],
         };
 
@@ -615,6 +978,59 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_persist_mempool() -> Result<()> {
+        let tempdir = tempdir::TempDir::new("chronik-indexer--mempool")?;
+        let params = ChronikIndexerParams {
+            datadir_net: tempdir.path().to_path_buf(),
+            wipe_db: false,
+            fn_compress_script: prefix_mock_compress,
+            persist_mempool: true,
+            enable_script_hash_index: false,
+        };
+        let snapshot_path =
+            tempdir.path().join("indexes").join("chronik_mempool.dat");
+        let mempool_tx = |txid_num: u8, time_first_seen: i64| MempoolTx {
+            tx: Tx::with_txid(TxId::from([txid_num; 32]), TxMut::default()),
+            time_first_seen,
+        };
+
+        let mut indexer = ChronikIndexer::setup(params.clone())?;
+        indexer.handle_tx_added_to_mempool(mempool_tx(1, 1000))?;
+        indexer.handle_tx_added_to_mempool(mempool_tx(2, 1000))?;
+        indexer.save_mempool_snapshot()?;
+        assert!(snapshot_path.exists());
+        std::mem::drop(indexer);
+
+        // Snapshot is consumed on startup
+        let mut indexer = ChronikIndexer::setup(params.clone())?;
+        assert!(!snapshot_path.exists());
+        // Node reports later time -> keep the time from the snapshot
+        indexer.handle_tx_added_to_mempool(mempool_tx(1, 2000))?;
+        // Node reports earlier time -> keep the node's time
+        indexer.handle_tx_added_to_mempool(mempool_tx(2, 500))?;
+        // Tx not in the snapshot
+        indexer.handle_tx_added_to_mempool(mempool_tx(3, 3000))?;
+        let time_first_seen = |txid_num: u8| {
+            let txid = TxId::from([txid_num; 32]);
+            indexer.mempool.tx(&txid).unwrap().time_first_seen
+        };
+        assert_eq!(time_first_seen(1), 1000);
+        assert_eq!(time_first_seen(2), 500);
+        assert_eq!(time_first_seen(3), 3000);
+        std::mem::drop(indexer);
+
+        // Disabled -> no snapshot written
+        let indexer = ChronikIndexer::setup(ChronikIndexerParams {
+            persist_mempool: false,
+            ..params
+        })?;
+        indexer.save_mempool_snapshot()?;
+        assert!(!snapshot_path.exists());
+
+        Ok(())
+    }
+
     #[test]
     fn test_schema_version() -> Result<()> {
         let dir = tempdir::TempDir::new("chronik-indexer--schema_version")?;
@@ -623,6 +1039,8 @@ mod tests {
             datadir_net: dir.path().to_path_buf(),
             wipe_db: false,
             fn_compress_script: prefix_mock_compress,
+            persist_mempool: false,
+            enable_script_hash_index: false,
         };
 
         // Setting up DB first time sets the schema version
@@ -716,4 +1134,97 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_script_hash_index_flag() -> Result<()> {
+        let dir = tempdir::TempDir::new("chronik-indexer--script_hash_index")?;
+        let chronik_path = dir.path().join("indexes").join("chronik");
+        let params = ChronikIndexerParams {
+            datadir_net: dir.path().to_path_buf(),
+            wipe_db: false,
+            fn_compress_script: prefix_mock_compress,
+            persist_mempool: false,
+            enable_script_hash_index: true,
+        };
+        let disabled_params = ChronikIndexerParams {
+            enable_script_hash_index: false,
+            ..params.clone()
+        };
+        let block = ChronikBlock {
+            db_block: DbBlock {
+                hash: BlockHash::from([4; 32]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Empty DB can be toggled freely, the latest setup is stored
+        ChronikIndexer::setup(params.clone())?;
+        let indexer = ChronikIndexer::setup(disabled_params.clone())?;
+        assert_eq!(
+            indexer
+                .script_hash_history()
+                .unwrap_err()
+                .downcast::<ChronikIndexerError>()?,
+            ChronikIndexerError::ScriptHashIndexDisabled,
+        );
+        std::mem::drop(indexer);
+        let mut indexer = ChronikIndexer::setup(params.clone())?;
+        indexer.script_hash_history()?;
+        indexer.handle_block_connected(block)?;
+        std::mem::drop(indexer);
+        {
+            let db = Db::open(&chronik_path)?;
+            assert_eq!(
+                MetadataReader::new(&db)?.is_script_hash_index()?,
+                Some(true),
+            );
+        }
+
+        // Non-empty DB can't be toggled anymore
+        ChronikIndexer::setup(params.clone())?;
+        assert_eq!(
+            ChronikIndexer::setup(disabled_params.clone())
+                .unwrap_err()
+                .downcast::<ChronikIndexerError>()?,
+            ChronikIndexerError::ScriptHashIndexToggled(false),
+        );
+
+        // Missing flag (DB predates it) -> index not enabled
+        {
+            let db = Db::open(&chronik_path)?;
+            let mut batch = WriteBatch::default();
+            batch.delete_cf(db.cf(CF_META)?, b"SCRIPT_HASH_INDEX");
+            db.write_batch(batch)?;
+        }
+        assert_eq!(
+            ChronikIndexer::setup(params.clone())
+                .unwrap_err()
+                .downcast::<ChronikIndexerError>()?,
+            ChronikIndexerError::ScriptHashIndexToggled(true),
+        );
+        ChronikIndexer::setup(disabled_params.clone())?;
+
+        // Corrupt flag
+        {
+            let db = Db::open(&chronik_path)?;
+            let mut batch = WriteBatch::default();
+            batch.put_cf(db.cf(CF_META)?, b"SCRIPT_HASH_INDEX", [0xff]);
+            db.write_batch(batch)?;
+        }
+        assert_eq!(
+            ChronikIndexer::setup(params.clone())
+                .unwrap_err()
+                .downcast::<ChronikIndexerError>()?,
+            ChronikIndexerError::CorruptedScriptHashIndex,
+        );
+
+        // with wipe it works
+        ChronikIndexer::setup(ChronikIndexerParams {
+            wipe_db: true,
+            ..params
+        })?;
+
+        Ok(())
+    }
 }
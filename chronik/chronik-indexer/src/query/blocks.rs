@@ -14,7 +14,7 @@ use chronik_db::{
     db::Db,
     io::{
         BlockHeight, BlockReader, BlockStats, BlockStatsReader, DbBlock,
-        SpentByReader, TxNum, TxReader,
+        SpentByReader, TokenTxReader, TxNum, TxReader,
     },
     mem::Mempool,
 };
@@ -23,10 +23,14 @@ use thiserror::Error;
 
 use crate::{
     avalanche::Avalanche,
+    indexer::Node,
     query::{make_tx_proto, HashOrHeight, OutputsSpent},
 };
 
 const MAX_BLOCKS_PAGE_SIZE: usize = 500;
+/// Headers are much smaller than [`proto::BlockInfo`], so we allow more of
+/// them per request, to cover a full difficulty period.
+const MAX_BLOCK_HEADERS_PAGE_SIZE: usize = 2016;
 
 /// Smallest allowed page size
 pub const MIN_BLOCK_TXS_PAGE_SIZE: usize = 1;
@@ -66,6 +70,18 @@ pub enum QueryBlockError {
     )]
     BlocksPageSizeTooLarge(usize),
 
+    /// Block headers page size too large
+    #[error(
+        "400: Block headers page size too large, may not be above {} but got \
+         {0}",
+        MAX_BLOCK_HEADERS_PAGE_SIZE
+    )]
+    BlockHeadersPageSizeTooLarge(usize),
+
+    /// Block in the DB not in the node's block index
+    #[error("500: Inconsistent DB: Block {0} not in the node's block index")]
+    MissingBlockIndex(BlockHeight),
+
     /// DB is missing block stats
     #[error("500: Inconsistent DB: Missing block stats for height {0}")]
     MissingBlockStats(BlockHeight),
@@ -158,6 +174,51 @@ impl<'a> QueryBlocks<'a> {
         Ok(proto::Blocks { blocks })
     }
 
+    /// Query the serialized headers of a range of heights. Start and end
+    /// height are inclusive; heights above the tip are left out.
+    ///
+    /// Headers are taken from the node's in-memory block index, along the
+    /// chain of the indexed tip, so no block files are read.
+    pub fn headers_by_range(
+        &self,
+        node: &Node,
+        start_height: BlockHeight,
+        end_height: BlockHeight,
+    ) -> Result<proto::BlockHeaders> {
+        if start_height < 0 {
+            return Err(InvalidStartHeight(start_height).into());
+        }
+        if end_height < start_height {
+            return Err(InvalidEndHeight(end_height).into());
+        }
+        let num_headers = end_height as usize - start_height as usize + 1;
+        if num_headers > MAX_BLOCK_HEADERS_PAGE_SIZE {
+            return Err(BlockHeadersPageSizeTooLarge(num_headers).into());
+        }
+        let block_reader = BlockReader::new(self.db)?;
+        let tip = match block_reader.tip()? {
+            Some(tip) => tip,
+            None => return Ok(proto::BlockHeaders::default()),
+        };
+        let end_height = end_height.min(tip.height);
+        if start_height > end_height {
+            return Ok(proto::BlockHeaders::default());
+        }
+        let tip_index = node
+            .bridge
+            .lookup_block_index(tip.hash.to_bytes())
+            .wrap_err(MissingBlockIndex(tip.height))?;
+        let headers = (start_height..=end_height)
+            .map(|block_height| {
+                let block_index =
+                    ffi::get_block_ancestor(tip_index, block_height)
+                        .wrap_err(MissingBlockIndex(block_height))?;
+                Ok(ffi::get_block_header(block_index))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(proto::BlockHeaders { headers })
+    }
+
     /// Query the txs of a block, paginated.
     pub fn block_txs(
         &self,
@@ -174,6 +235,7 @@ impl<'a> QueryBlocks<'a> {
         let block_reader = BlockReader::new(self.db)?;
         let tx_reader = TxReader::new(self.db)?;
         let spent_by_reader = SpentByReader::new(self.db)?;
+        let token_tx_reader = TokenTxReader::new(self.db)?;
         let db_block = match hash_or_height.parse::<HashOrHeight>()? {
             HashOrHeight::Hash(hash) => block_reader.by_hash(&hash)?,
             HashOrHeight::Height(height) => block_reader.by_height(height)?,
@@ -207,6 +269,7 @@ impl<'a> QueryBlocks<'a> {
                 self.mempool.spent_by().outputs_spent(&db_tx.entry.txid),
                 tx_num,
             )?;
+            let token_tx = token_tx_reader.by_tx_num(tx_num)?;
             txs.push(make_tx_proto(
                 &Tx::from(tx),
                 &outputs_spent,
@@ -214,6 +277,7 @@ impl<'a> QueryBlocks<'a> {
                 db_tx.entry.is_coinbase,
                 Some(&db_block),
                 self.avalanche,
+                token_tx.as_ref(),
             ));
         }
         let total_num_txs = (tx_range.end - tx_range.start) as usize;
@@ -223,6 +287,7 @@ impl<'a> QueryBlocks<'a> {
             txs,
             num_pages: total_num_pages as u32,
             num_txs: total_num_txs as u32,
+            ..Default::default()
         })
     }
 
@@ -261,6 +326,10 @@ impl<'a> QueryBlocks<'a> {
             sum_coinbase_output_sats: block_stats.sum_coinbase_output_sats,
             sum_normal_output_sats: block_stats.sum_normal_output_sats,
             sum_burned_sats: block_stats.sum_burned_sats,
+            sum_fees: block_stats.sum_fees,
+            min_fee_rate: block_stats.min_fee_rate,
+            median_fee_rate: block_stats.median_fee_rate,
+            max_fee_rate: block_stats.max_fee_rate,
         }
     }
 }
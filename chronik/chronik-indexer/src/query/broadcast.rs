@@ -0,0 +1,318 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`QueryBroadcast`], to broadcast txs to the network.
+
+use abc_rust_error::Result;
+use bitcoinsuite_core::{
+    error::DataError,
+    ser::BitcoinSer,
+    tx::{Tx, TxId},
+};
+use bytes::Bytes;
+use thiserror::Error;
+
+use crate::indexer::Node;
+
+/// Maximum fee rate (in sats/kB) of txs we broadcast, same as the node's
+/// `DEFAULT_MAX_RAW_TX_FEE_RATE` used by `sendrawtransaction`.
+pub const DEFAULT_MAX_RAW_TX_FEE_RATE_PER_KB: i64 = 10_000_000;
+
+/// Submits raw txs to the node's mempool and relays them to peers.
+///
+/// Implemented by [`Node`]; tests can provide a stand-in instead.
+pub trait TxBroadcaster: std::fmt::Debug + Send + Sync {
+    /// Add the serialized tx to the mempool and broadcast it to all peers,
+    /// or return the reason the node rejected it.
+    ///
+    /// `max_fee` is absolute, not a fee rate.
+    fn broadcast_tx(
+        &self,
+        raw_tx: &[u8],
+        max_fee: i64,
+    ) -> std::result::Result<TxId, String>;
+}
+
+/// Struct for broadcasting txs to the network.
+#[derive(Debug)]
+pub struct QueryBroadcast<'a> {
+    /// Where to send the txs to
+    pub broadcaster: &'a dyn TxBroadcaster,
+}
+
+/// Errors indicating something went wrong with broadcasting txs.
+#[derive(Debug, Error, PartialEq)]
+pub enum QueryBroadcastError {
+    /// Tx couldn't be parsed
+    #[error("400: Parsing tx failed: {0}")]
+    ParsingFailed(DataError),
+
+    /// Node rejected the tx
+    #[error("400: Broadcast failed: {0}")]
+    BroadcastFailed(String),
+
+    /// A tx in a batch couldn't be parsed, so nothing was broadcast
+    #[error("400: Parsing tx {idx} failed, no txs have been broadcast: {err}")]
+    BatchParsingFailed {
+        /// Index of the tx in the batch
+        idx: usize,
+        /// Why parsing failed
+        err: DataError,
+    },
+
+    /// Node rejected a tx in a batch, the txs before it have been broadcast
+    #[error(
+        "400: Broadcast of tx {idx} failed, txs before it have been \
+         broadcast: {msg}"
+    )]
+    BatchBroadcastFailed {
+        /// Index of the tx in the batch
+        idx: usize,
+        /// Rejection reason of the node
+        msg: String,
+    },
+}
+
+use self::QueryBroadcastError::*;
+
+impl TxBroadcaster for Node {
+    fn broadcast_tx(
+        &self,
+        raw_tx: &[u8],
+        max_fee: i64,
+    ) -> std::result::Result<TxId, String> {
+        self.bridge
+            .broadcast_tx(raw_tx, max_fee)
+            .map(TxId::from)
+            .map_err(|err| err.what().to_string())
+    }
+}
+
+impl<'a> QueryBroadcast<'a> {
+    /// Parse the tx, then add it to the mempool and broadcast it.
+    pub fn broadcast_tx(&self, raw_tx: &[u8]) -> Result<TxId> {
+        parse_raw_tx(raw_tx).map_err(ParsingFailed)?;
+        let txid = self
+            .broadcaster
+            .broadcast_tx(raw_tx, max_fee(raw_tx))
+            .map_err(BroadcastFailed)?;
+        Ok(txid)
+    }
+
+    /// Parse all txs, and only if all of them are valid, broadcast them one
+    /// after another. Stops at the first tx rejected by the node.
+    pub fn broadcast_txs(&self, raw_txs: &[Vec<u8>]) -> Result<Vec<TxId>> {
+        for (idx, raw_tx) in raw_txs.iter().enumerate() {
+            parse_raw_tx(raw_tx)
+                .map_err(|err| BatchParsingFailed { idx, err })?;
+        }
+        let mut txids = Vec::with_capacity(raw_txs.len());
+        for (idx, raw_tx) in raw_txs.iter().enumerate() {
+            let txid = self
+                .broadcaster
+                .broadcast_tx(raw_tx, max_fee(raw_tx))
+                .map_err(|msg| BatchBroadcastFailed { idx, msg })?;
+            txids.push(txid);
+        }
+        Ok(txids)
+    }
+}
+
+/// Deserialize the tx, and make sure there's no leftover data.
+fn parse_raw_tx(raw_tx: &[u8]) -> std::result::Result<Tx, DataError> {
+    let mut bytes = Bytes::copy_from_slice(raw_tx);
+    let tx = Tx::deser(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(DataError::InvalidLength {
+            expected: raw_tx.len() - bytes.len(),
+            actual: raw_tx.len(),
+        });
+    }
+    Ok(tx)
+}
+
+fn max_fee(raw_tx: &[u8]) -> i64 {
+    raw_tx.len() as i64 * DEFAULT_MAX_RAW_TX_FEE_RATE_PER_KB / 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use abc_rust_error::Result;
+    use bitcoinsuite_core::{
+        error::DataError,
+        ser::BitcoinSer,
+        tx::{Tx, TxId, TxMut, TxOutput},
+    };
+    use pretty_assertions::assert_eq;
+
+    use crate::query::{
+        QueryBroadcast, QueryBroadcastError, TxBroadcaster,
+        DEFAULT_MAX_RAW_TX_FEE_RATE_PER_KB,
+    };
+
+    /// Stand-in for the node, accepts every tx except the rejected ones.
+    #[derive(Debug, Default)]
+    struct MockBroadcaster {
+        rejected: Vec<Vec<u8>>,
+        broadcast: Mutex<Vec<(Vec<u8>, i64)>>,
+    }
+
+    impl MockBroadcaster {
+        fn take_broadcast(&self) -> Vec<(Vec<u8>, i64)> {
+            std::mem::take(&mut self.broadcast.lock().unwrap())
+        }
+    }
+
+    impl TxBroadcaster for MockBroadcaster {
+        fn broadcast_tx(
+            &self,
+            raw_tx: &[u8],
+            max_fee: i64,
+        ) -> std::result::Result<TxId, String> {
+            if self.rejected.iter().any(|rejected| rejected == raw_tx) {
+                return Err("Transaction rejected by mempool: bad-txns".into());
+            }
+            self.broadcast.lock().unwrap().push((raw_tx.to_vec(), max_fee));
+            Ok(Tx::deser(&mut raw_tx.to_vec().into()).unwrap().txid())
+        }
+    }
+
+    fn make_tx(value: i64) -> Tx {
+        let tx = TxMut {
+            version: 1,
+            inputs: vec![],
+            outputs: vec![TxOutput {
+                value,
+                ..Default::default()
+            }],
+            locktime: 0,
+        };
+        Tx::with_txid(TxId::from_tx(&tx), tx)
+    }
+
+    #[test]
+    fn test_broadcast_tx() -> Result<()> {
+        abc_rust_error::install();
+        let tx1 = make_tx(1000);
+        let raw_tx1 = tx1.ser().to_vec();
+        let broadcaster = MockBroadcaster {
+            rejected: vec![make_tx(2000).ser().to_vec()],
+            ..Default::default()
+        };
+        let query = QueryBroadcast {
+            broadcaster: &broadcaster,
+        };
+
+        // Success
+        assert_eq!(query.broadcast_tx(&raw_tx1)?, tx1.txid());
+        let max_fee =
+            raw_tx1.len() as i64 * DEFAULT_MAX_RAW_TX_FEE_RATE_PER_KB / 1000;
+        assert_eq!(
+            broadcaster.take_broadcast(),
+            vec![(raw_tx1.clone(), max_fee)],
+        );
+
+        // Truncated tx
+        assert_eq!(
+            query
+                .broadcast_tx(&raw_tx1[..raw_tx1.len() - 1])
+                .unwrap_err()
+                .downcast::<QueryBroadcastError>()?,
+            QueryBroadcastError::ParsingFailed(DataError::InvalidLength {
+                expected: 4,
+                actual: 3,
+            }),
+        );
+
+        // Extra bytes after the tx
+        let mut extra_tx = raw_tx1.clone();
+        extra_tx.extend([1, 2]);
+        assert_eq!(
+            query
+                .broadcast_tx(&extra_tx)
+                .unwrap_err()
+                .downcast::<QueryBroadcastError>()?,
+            QueryBroadcastError::ParsingFailed(DataError::InvalidLength {
+                expected: raw_tx1.len(),
+                actual: raw_tx1.len() + 2,
+            }),
+        );
+
+        // Rejected by the node
+        assert_eq!(
+            query
+                .broadcast_tx(&make_tx(2000).ser())
+                .unwrap_err()
+                .downcast::<QueryBroadcastError>()?,
+            QueryBroadcastError::BroadcastFailed(
+                "Transaction rejected by mempool: bad-txns".to_string(),
+            ),
+        );
+        assert!(broadcaster.take_broadcast().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_broadcast_txs() -> Result<()> {
+        abc_rust_error::install();
+        let tx1 = make_tx(1000);
+        let tx2 = make_tx(3000);
+        let raw_tx1 = tx1.ser().to_vec();
+        let raw_tx2 = tx2.ser().to_vec();
+        let raw_rejected = make_tx(2000).ser().to_vec();
+        let broadcaster = MockBroadcaster {
+            rejected: vec![raw_rejected.clone()],
+            ..Default::default()
+        };
+        let query = QueryBroadcast {
+            broadcaster: &broadcaster,
+        };
+
+        // Empty batch
+        assert_eq!(query.broadcast_txs(&[])?, vec![]);
+
+        // Success
+        assert_eq!(
+            query.broadcast_txs(&[raw_tx1.clone(), raw_tx2.clone()])?,
+            vec![tx1.txid(), tx2.txid()],
+        );
+        assert_eq!(broadcaster.take_broadcast().len(), 2);
+
+        // Invalid tx: nothing broadcast
+        assert_eq!(
+            query
+                .broadcast_txs(&[raw_tx1.clone(), vec![]])
+                .unwrap_err()
+                .downcast::<QueryBroadcastError>()?,
+            QueryBroadcastError::BatchParsingFailed {
+                idx: 1,
+                err: DataError::InvalidLength {
+                    expected: 4,
+                    actual: 0,
+                },
+            },
+        );
+        assert!(broadcaster.take_broadcast().is_empty());
+
+        // Rejected tx: txs before it are broadcast
+        assert_eq!(
+            query
+                .broadcast_txs(&[raw_tx1.clone(), raw_rejected, raw_tx2])
+                .unwrap_err()
+                .downcast::<QueryBroadcastError>()?,
+            QueryBroadcastError::BatchBroadcastFailed {
+                idx: 1,
+                msg: "Transaction rejected by mempool: bad-txns".to_string(),
+            },
+        );
+        let broadcast = broadcaster.take_broadcast();
+        assert_eq!(broadcast.len(), 1);
+        assert_eq!(broadcast[0].0, raw_tx1);
+
+        Ok(())
+    }
+}
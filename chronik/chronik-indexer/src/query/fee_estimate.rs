@@ -0,0 +1,214 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`QueryFeeEstimate`], to estimate the fee rate required for a
+//! tx to be mined.
+
+use abc_rust_error::Result;
+use chronik_db::{
+    db::Db,
+    io::{BlockReader, BlockStats, BlockStatsReader},
+    mem::Mempool,
+};
+use chronik_proto::proto;
+use thiserror::Error;
+
+use crate::query::QueryMempool;
+
+/// Smallest allowed number of blocks to estimate the fee rate for.
+pub const MIN_FEE_ESTIMATE_BLOCKS: u32 = 1;
+/// Largest allowed number of blocks to estimate the fee rate for.
+pub const MAX_FEE_ESTIMATE_BLOCKS: u32 = 144;
+
+/// Number of recent blocks considered for the estimate.
+pub const NUM_RECENT_BLOCKS: i32 = 6;
+/// Smallest fee rate ever estimated, the node's default min relay fee rate
+/// (sats/kB).
+pub const MIN_FEE_RATE: i64 = 1000;
+/// Smallest number of bytes assumed to fit into a block, the node's default
+/// max. generated block size.
+pub const MIN_BLOCK_CAPACITY: u64 = 2_000_000;
+
+/// Struct for estimating fee rates from the mempool and recent blocks.
+#[derive(Debug)]
+pub struct QueryFeeEstimate<'a> {
+    /// Database
+    pub db: &'a Db,
+    /// Mempool
+    pub mempool: &'a Mempool,
+}
+
+/// Errors indicating something went wrong with estimating fees.
+#[derive(Debug, Error, PartialEq)]
+pub enum QueryFeeEstimateError {
+    /// Requested number of blocks out of range.
+    #[error(
+        "400: Invalid number of blocks {0}, must be between {} and {}",
+        MIN_FEE_ESTIMATE_BLOCKS,
+        MAX_FEE_ESTIMATE_BLOCKS
+    )]
+    InvalidNumBlocks(u32),
+}
+
+use self::QueryFeeEstimateError::*;
+
+impl<'a> QueryFeeEstimate<'a> {
+    /// Estimate the fee rate in sats/kB a tx has to pay to be mined within
+    /// the next `num_blocks` blocks, see [`estimate_fee_rate`].
+    pub fn fee_estimate(&self, num_blocks: u32) -> Result<proto::FeeEstimate> {
+        if !(MIN_FEE_ESTIMATE_BLOCKS..=MAX_FEE_ESTIMATE_BLOCKS)
+            .contains(&num_blocks)
+        {
+            return Err(InvalidNumBlocks(num_blocks).into());
+        }
+        let block_reader = BlockReader::new(self.db)?;
+        let block_stats_reader = BlockStatsReader::new(self.db)?;
+        let tip_height = block_reader.height()?;
+        let mut recent_blocks = Vec::new();
+        for height in (tip_height - NUM_RECENT_BLOCKS + 1).max(0)..=tip_height {
+            if let Some(stats) = block_stats_reader.by_height(height)? {
+                recent_blocks.push(stats);
+            }
+        }
+        let mempool_info = QueryMempool {
+            mempool: self.mempool,
+        }
+        .info();
+        Ok(proto::FeeEstimate {
+            fee_rate: estimate_fee_rate(
+                num_blocks,
+                &mempool_info.fee_rate_histogram,
+                &recent_blocks,
+            ),
+            num_blocks,
+        })
+    }
+}
+
+/// Estimate the fee rate in sats/kB a tx has to pay to be mined within the
+/// next `num_blocks` blocks.
+///
+/// The estimate is the largest of:
+/// 1. [`MIN_FEE_RATE`], below which txs aren't relayed.
+/// 2. The mempool backlog: Going through the buckets of the mempool fee rate
+///    histogram from the highest fee rate downwards, we sum up the size of the
+///    txs until they'd fill `num_blocks` blocks. A new tx then has to pay more
+///    than the bucket in which that happens, i.e. the lower bound of the next
+///    higher bucket. Blocks are assumed to fit as many bytes as the largest of
+///    the `recent_blocks`, but at least [`MIN_BLOCK_CAPACITY`].
+/// 3. The median of the smallest fee rates that `recent_blocks` with any
+///    non-coinbase txs included, i.e. what miners currently accept.
+pub fn estimate_fee_rate(
+    num_blocks: u32,
+    mempool_histogram: &[proto::FeeRateBucket],
+    recent_blocks: &[BlockStats],
+) -> i64 {
+    let block_capacity = recent_blocks
+        .iter()
+        .map(|stats| stats.block_size)
+        .max()
+        .unwrap_or_default()
+        .max(MIN_BLOCK_CAPACITY);
+    let capacity = block_capacity.saturating_mul(num_blocks.into());
+
+    let mut backlog_fee_rate = 0;
+    let mut backlog_bytes = 0u64;
+    for (idx, bucket) in mempool_histogram.iter().enumerate().rev() {
+        backlog_bytes += bucket.num_bytes;
+        if backlog_bytes >= capacity {
+            backlog_fee_rate = match mempool_histogram.get(idx + 1) {
+                Some(next_bucket) => next_bucket.min_fee_rate,
+                None => bucket.min_fee_rate,
+            };
+            break;
+        }
+    }
+
+    let mut min_fee_rates = recent_blocks
+        .iter()
+        .filter(|stats| stats.num_txs > 1)
+        .map(|stats| stats.min_fee_rate)
+        .collect::<Vec<_>>();
+    min_fee_rates.sort_unstable();
+    let recent_fee_rate = min_fee_rates
+        .get(min_fee_rates.len().saturating_sub(1) / 2)
+        .copied()
+        .unwrap_or_default();
+
+    MIN_FEE_RATE.max(backlog_fee_rate).max(recent_fee_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use chronik_db::io::BlockStats;
+    use chronik_proto::proto;
+
+    use crate::query::{
+        estimate_fee_rate, FEE_RATE_BUCKETS, MIN_BLOCK_CAPACITY, MIN_FEE_RATE,
+    };
+
+    fn empty_histogram() -> Vec<proto::FeeRateBucket> {
+        FEE_RATE_BUCKETS
+            .iter()
+            .map(|&min_fee_rate| proto::FeeRateBucket {
+                min_fee_rate,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_estimate_fee_rate() {
+        // Empty mempool and no blocks
+        assert_eq!(estimate_fee_rate(1, &empty_histogram(), &[]), MIN_FEE_RATE);
+
+        // Mempool backlog fills 2 blocks in the 5000 sats/kB bucket (idx 4)
+        let mut histogram = empty_histogram();
+        histogram[2].num_bytes = 5 * MIN_BLOCK_CAPACITY;
+        histogram[4].num_bytes = MIN_BLOCK_CAPACITY + 1;
+        histogram[7].num_bytes = MIN_BLOCK_CAPACITY;
+        histogram[9].num_bytes = 1000;
+        // Buckets from 50_000 sats/kB (idx 7) upwards already fill 1 block
+        assert_eq!(estimate_fee_rate(1, &histogram, &[]), 100_000);
+        assert_eq!(estimate_fee_rate(2, &histogram, &[]), 10_000);
+        assert_eq!(estimate_fee_rate(3, &histogram, &[]), 3000);
+        assert_eq!(estimate_fee_rate(7, &histogram, &[]), 3000);
+        // Whole mempool fits into 8 blocks
+        assert_eq!(estimate_fee_rate(8, &histogram, &[]), MIN_FEE_RATE);
+
+        // Large recent blocks increase the capacity
+        let large_block = BlockStats {
+            block_size: 2 * MIN_BLOCK_CAPACITY,
+            num_txs: 1,
+            ..Default::default()
+        };
+        assert_eq!(estimate_fee_rate(1, &histogram, &[large_block]), 10_000);
+
+        // Median of the min fee rates of recent blocks with non-coinbase txs
+        let block = |min_fee_rate: i64| BlockStats {
+            num_txs: 2,
+            min_fee_rate,
+            ..Default::default()
+        };
+        let recent_blocks = [block(1000), block(8000), block(2000)];
+        assert_eq!(
+            estimate_fee_rate(9, &empty_histogram(), &recent_blocks),
+            2000,
+        );
+        // Coinbase-only blocks are ignored
+        let recent_blocks = [
+            block(8000),
+            BlockStats {
+                num_txs: 1,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            estimate_fee_rate(1, &empty_histogram(), &recent_blocks),
+            8000,
+        );
+        // Backlog larger than recent blocks
+        assert_eq!(estimate_fee_rate(2, &histogram, &recent_blocks), 10_000);
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`QueryGroupBalance`], to query the balance of a group member.
+
+use abc_rust_error::Result;
+use bitcoinsuite_core::tx::TxId;
+use chronik_db::{
+    db::Db,
+    group::{GroupQuery, UtxoGroup},
+    io::{GroupHistoryReader, GroupUtxoReader},
+    mem::{Mempool, MempoolGroupHistory},
+};
+use chronik_proto::proto;
+use thiserror::Error;
+
+/// Query the balance of a member of a group, i.e. the sum of its UTXOs.
+#[derive(Debug)]
+pub struct QueryGroupBalance<'a, G: UtxoGroup> {
+    /// Database
+    pub db: &'a Db,
+    /// Mempool
+    pub mempool: &'a Mempool,
+    /// The part of the mempool we search for this group's history.
+    pub mempool_history: &'a MempoolGroupHistory<G>,
+    /// Group to query the balance by
+    pub group: G,
+}
+
+/// Errors indicating something went wrong with [`QueryGroupBalance`].
+#[derive(Debug, Error, PartialEq)]
+pub enum QueryGroupBalanceError {
+    /// Transaction not in mempool.
+    #[error("500: Inconsistent mempool: Transaction {0} not in mempool")]
+    MissingMempoolTx(TxId),
+}
+
+use self::QueryGroupBalanceError::*;
+
+impl<'a, G: UtxoGroup<Aux = ()>> QueryGroupBalance<'a, G> {
+    /// Return the balance of the given member.
+    ///
+    /// The confirmed balance is read from the balance column of the DB, so
+    /// this doesn't have to read all the UTXOs of the member. The mempool
+    /// txs of the member are added on top as the unconfirmed delta.
+    ///
+    /// `script` is left empty and has to be filled in by the caller.
+    pub fn balance(
+        &self,
+        member: G::Member<'_>,
+    ) -> Result<proto::ScriptBalance> {
+        let utxo_reader = GroupUtxoReader::<G>::new(self.db)?;
+        let history_reader = GroupHistoryReader::<G>::new(self.db)?;
+        let member_ser = self.group.ser_member(&member);
+        let is_member = |item: &G::Member<'_>| {
+            self.group.ser_member(item).as_ref() == member_ser.as_ref()
+        };
+
+        let db_balance = utxo_reader
+            .balance(member_ser.as_ref())?
+            .unwrap_or_default();
+        let (_, num_db_txs) =
+            history_reader.member_num_pages_and_txs(member_ser.as_ref())?;
+        let mempool_history =
+            self.mempool_history.member_history(member_ser.as_ref());
+
+        let mut unconfirmed_delta = 0;
+        let mut num_utxos = db_balance.num_utxos as i64;
+        for (_, txid) in mempool_history.into_iter().flatten() {
+            let mempool_tx =
+                self.mempool.tx(txid).ok_or(MissingMempoolTx(*txid))?;
+            let query = GroupQuery {
+                is_coinbase: false,
+                tx: &mempool_tx.tx,
+            };
+            for item in self.group.output_members(query, &()) {
+                if is_member(&item.member) {
+                    unconfirmed_delta += mempool_tx.tx.outputs[item.idx].value;
+                    num_utxos += 1;
+                }
+            }
+            for item in self.group.input_members(query, &()) {
+                if is_member(&item.member) {
+                    let input = &mempool_tx.tx.inputs[item.idx];
+                    unconfirmed_delta -= input
+                        .coin
+                        .as_ref()
+                        .map(|coin| coin.output.value)
+                        .unwrap_or_default();
+                    num_utxos -= 1;
+                }
+            }
+        }
+
+        Ok(proto::ScriptBalance {
+            script: vec![],
+            confirmed_value: db_balance.value,
+            unconfirmed_delta,
+            num_utxos: num_utxos.max(0) as u64,
+            num_txs: (num_db_txs
+                + mempool_history.map(|history| history.len()).unwrap_or(0))
+                as u64,
+        })
+    }
+}
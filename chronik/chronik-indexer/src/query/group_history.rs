@@ -4,7 +4,7 @@
 
 //! Module for [`QueryGroupHistory`], to query the tx history of a group.
 
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, ops::Bound, str::FromStr};
 
 use abc_rust_error::Result;
 use bitcoinsuite_core::tx::{Tx, TxId};
@@ -12,7 +12,10 @@ use chronik_bridge::ffi;
 use chronik_db::{
     db::Db,
     group::Group,
-    io::{BlockReader, GroupHistoryReader, SpentByReader, TxNum, TxReader},
+    io::{
+        BlockHeight, BlockReader, GroupHistoryReader, SpentByReader,
+        TokenTxReader, TxNum, TxReader,
+    },
     mem::{Mempool, MempoolGroupHistory},
 };
 use chronik_proto::proto;
@@ -21,7 +24,7 @@ use thiserror::Error;
 
 use crate::{
     avalanche::Avalanche,
-    query::{make_tx_proto, OutputsSpent},
+    query::{make_tx_proto, mempool_token_tx, OutputsSpent},
 };
 
 /// Smallest allowed page size
@@ -31,6 +34,33 @@ pub const MAX_HISTORY_PAGE_SIZE: usize = 200;
 
 static EMPTY_MEMBER_TX_HISTORY: BTreeSet<(i64, TxId)> = BTreeSet::new();
 
+/// Position of a tx in the history of a group member, used as an opaque cursor
+/// to page through the history deterministically, even if new txs arrive.
+///
+/// Cursors are ordered the same way as the history: DB txs by tx num first,
+/// followed by mempool txs in first-seen order (then by txid). They are
+/// encoded as hex for the API.
+///
+/// Mempool cursors also remember how many txs the DB had when they were
+/// issued, so txs mined since can be returned when resuming after them.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum HistoryCursor {
+    /// Tx in the DB, by its tx num.
+    Db(TxNum),
+    /// Tx in the mempool, by the number of txs in the DB at the time, its time
+    /// first seen and txid.
+    Mempool(TxNum, i64, TxId),
+}
+
+/// Which txs to return relative to a [`HistoryCursor`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HistoryCursorRequest {
+    /// Return the txs right after (i.e. newer than) the cursor.
+    After(HistoryCursor),
+    /// Return the txs right before (i.e. older than) the cursor.
+    Before(HistoryCursor),
+}
+
 /// Query pages of the tx history of a group
 #[derive(Debug)]
 pub struct QueryGroupHistory<'a, G: Group> {
@@ -74,10 +104,65 @@ pub enum QueryGroupHistoryError {
         MIN_HISTORY_PAGE_SIZE
     )]
     RequestPageSizeTooSmall(usize),
+
+    /// Cursor is not one returned by the API.
+    #[error("400: Invalid cursor: {0}")]
+    InvalidCursor(String),
 }
 
 use self::QueryGroupHistoryError::*;
 
+const CURSOR_PREFIX_DB: u8 = 0;
+const CURSOR_PREFIX_MEMPOOL: u8 = 1;
+
+impl std::fmt::Display for HistoryCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bytes = match self {
+            HistoryCursor::Db(tx_num) => {
+                [[CURSOR_PREFIX_DB].as_ref(), &tx_num.to_be_bytes()].concat()
+            }
+            HistoryCursor::Mempool(num_db_txs, time_first_seen, txid) => [
+                [CURSOR_PREFIX_MEMPOOL].as_ref(),
+                &num_db_txs.to_be_bytes(),
+                &time_first_seen.to_be_bytes(),
+                txid.as_bytes(),
+            ]
+            .concat(),
+        };
+        f.write_str(&hex::encode(bytes))
+    }
+}
+
+impl FromStr for HistoryCursor {
+    type Err = QueryGroupHistoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidCursor(s.to_string());
+        let bytes = hex::decode(s).map_err(|_| invalid())?;
+        match bytes.split_first() {
+            Some((&CURSOR_PREFIX_DB, tx_num)) => Ok(HistoryCursor::Db(
+                TxNum::from_be_bytes(tx_num.try_into().map_err(|_| invalid())?),
+            )),
+            Some((&CURSOR_PREFIX_MEMPOOL, rest))
+                if rest.len() == 8 + 8 + 32 =>
+            {
+                let (num_db_txs, rest) = rest.split_at(8);
+                let (time_first_seen, txid) = rest.split_at(8);
+                Ok(HistoryCursor::Mempool(
+                    TxNum::from_be_bytes(num_db_txs.try_into().unwrap()),
+                    i64::from_be_bytes(time_first_seen.try_into().unwrap()),
+                    TxId::from(<[u8; 32]>::try_from(txid).unwrap()),
+                ))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+fn cursor_string(cursor: Option<&HistoryCursor>) -> String {
+    cursor.map(|cursor| cursor.to_string()).unwrap_or_default()
+}
+
 impl<'a, G: Group> QueryGroupHistory<'a, G> {
     /// Return the confirmed txs of the group in the order as txs occur on the
     /// blockchain, i.e.:
@@ -103,7 +188,7 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
         let num_request_pages =
             (num_db_txs + request_page_size - 1) / request_page_size;
 
-        let make_result = |txs: Vec<proto::Tx>| {
+        let make_result = |txs: Vec<proto::Tx>, tx_nums: &[TxNum]| {
             if txs.len() != txs.capacity() {
                 // We should've predicted exactly how many txs we'll return.
                 log!("WARNING: Allocated more txs than needed\n");
@@ -112,6 +197,12 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
                 txs,
                 num_pages: num_request_pages as u32,
                 num_txs: num_db_txs as u32,
+                oldest_cursor: cursor_string(
+                    tx_nums.first().map(|&n| HistoryCursor::Db(n)).as_ref(),
+                ),
+                newest_cursor: cursor_string(
+                    tx_nums.last().map(|&n| HistoryCursor::Db(n)).as_ref(),
+                ),
             }
         };
 
@@ -126,10 +217,11 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
         let num_returned_txs =
             request_page_size.min(num_db_txs.saturating_sub(first_tx_idx));
         let mut page_txs = Vec::with_capacity(num_returned_txs);
+        let mut page_tx_nums = Vec::with_capacity(num_returned_txs);
 
         // Short-circuit so we don't fetch DB again if no results.
         if num_returned_txs == 0 {
-            return Ok(make_result(vec![]));
+            return Ok(make_result(vec![], &[]));
         }
 
         // First DB page to start reading from.
@@ -146,16 +238,17 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
                 .unwrap_or_default();
             for &tx_num in db_page_tx_nums.iter().skip(first_inner_idx) {
                 page_txs.push(self.read_block_tx(tx_num)?);
+                page_tx_nums.push(tx_num);
                 // We filled up the requested page size -> return
                 if page_txs.len() == request_page_size {
-                    return Ok(make_result(page_txs));
+                    return Ok(make_result(page_txs, &page_tx_nums));
                 }
             }
             first_inner_idx = 0;
         }
 
         // Couldn't fill requested page size completely
-        Ok(make_result(page_txs))
+        Ok(make_result(page_txs, &page_tx_nums))
     }
 
     /// Return the group history in reverse chronological order, i.e. the latest
@@ -215,12 +308,14 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
         let total_num_txs = mempool_txs.len() + num_db_txs;
         let total_num_pages =
             (total_num_txs + request_page_size - 1) / request_page_size;
-        let make_result = |txs: Vec<proto::Tx>| {
+        let make_result = |txs: Vec<proto::Tx>, cursors: &[HistoryCursor]| {
             assert_eq!(txs.len(), txs.capacity());
             proto::TxHistoryPage {
                 txs,
                 num_pages: total_num_pages as u32,
                 num_txs: total_num_txs as u32,
+                oldest_cursor: cursor_string(cursors.iter().min()),
+                newest_cursor: cursor_string(cursors.iter().max()),
             }
         };
 
@@ -250,6 +345,7 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
         // Allocate sufficient space for the txs on the page.
         let mut page_txs =
             Vec::with_capacity(num_page_mempool_txs + num_page_db_txs);
+        let mut page_cursors = Vec::with_capacity(page_txs.capacity());
 
         // Add the requested mempool txs, we skip over the requested offset, and
         // take only as many as we can put on the page.
@@ -258,24 +354,22 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
             .rev()
             .skip(request_tx_offset)
             .take(request_page_size);
-        for (_, txid) in page_mempool_txs_iter {
-            let entry = self.mempool.tx(txid).ok_or(MissingMempoolTx(*txid))?;
-            page_txs.push(make_tx_proto(
-                &entry.tx,
-                &OutputsSpent::new_mempool(
-                    self.mempool.spent_by().outputs_spent(txid),
-                ),
-                entry.time_first_seen,
-                false,
-                None,
-                self.avalanche,
-            ));
+        if num_page_mempool_txs > 0 {
+            let num_all_db_txs = self.num_all_db_txs()?;
+            for &(time_first_seen, txid) in page_mempool_txs_iter {
+                page_txs.push(self.read_mempool_tx(&txid)?);
+                page_cursors.push(HistoryCursor::Mempool(
+                    num_all_db_txs,
+                    time_first_seen,
+                    txid,
+                ));
+            }
         }
 
         // If we filled up the page with mempool txs, or there's no DB txs on
         // this page, we can return early to avoid reading the DB.
         if num_page_mempool_txs == request_page_size || num_page_db_txs == 0 {
-            return Ok(make_result(page_txs));
+            return Ok(make_result(page_txs, &page_cursors));
         }
 
         // Initial index to start reading from in the list of all DB txs of this
@@ -297,6 +391,7 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
             for inner_idx in (0..=first_inner_idx).rev() {
                 let tx_num = db_page_tx_nums[inner_idx];
                 page_txs.push(self.read_block_tx(tx_num)?);
+                page_cursors.push(HistoryCursor::Db(tx_num));
                 // Filled up page: break out of outer loop.
                 if page_txs.len() == request_page_size {
                     break 'outer;
@@ -319,7 +414,45 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
             }
         });
 
-        Ok(make_result(page_txs))
+        Ok(make_result(page_txs, &page_cursors))
+    }
+
+    /// Return the confirmed txs of the group right after or before the given
+    /// cursor, in the order as txs occur on the blockchain, like
+    /// [`QueryGroupHistory::confirmed_txs`].
+    ///
+    /// Unlike page numbers, cursors don't shift when new txs arrive, so
+    /// clients can resume reading the history without skipping any txs.
+    pub fn confirmed_txs_by_cursor(
+        &self,
+        member: G::Member<'_>,
+        request: HistoryCursorRequest,
+        request_page_size: usize,
+    ) -> Result<proto::TxHistoryPage> {
+        self.history_by_cursor(member, request, request_page_size, false)
+    }
+
+    /// Return the group history right after or before the given cursor,
+    /// including mempool txs, latest first.
+    ///
+    /// Unlike [`QueryGroupHistory::rev_history`], txs are strictly ordered by
+    /// their cursor, i.e. block txs by their position in the blockchain and
+    /// mempool txs by time first seen, so the order never depends on the page
+    /// size.
+    ///
+    /// Resuming after a mempool cursor first returns the txs mined since the
+    /// cursor was issued, which can include txs the client already saw while
+    /// they were in the mempool, but never skips any.
+    pub fn rev_history_by_cursor(
+        &self,
+        member: G::Member<'_>,
+        request: HistoryCursorRequest,
+        request_page_size: usize,
+    ) -> Result<proto::TxHistoryPage> {
+        let mut page =
+            self.history_by_cursor(member, request, request_page_size, true)?;
+        page.txs.reverse();
+        Ok(page)
     }
 
     /// Return the unconfirmed txs (i.e. all txs in the mempool) in first-seen
@@ -335,34 +468,243 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
         member: G::Member<'_>,
     ) -> Result<proto::TxHistoryPage> {
         let member_ser = self.group.ser_member(&member);
-        let txs = match self.mempool_history.member_history(member_ser.as_ref())
-        {
-            Some(mempool_txs) => mempool_txs
-                .iter()
-                .map(|(_, txid)| -> Result<_> {
-                    let entry =
-                        self.mempool.tx(txid).ok_or(MissingMempoolTx(*txid))?;
-                    Ok(make_tx_proto(
-                        &entry.tx,
-                        &OutputsSpent::new_mempool(
-                            self.mempool.spent_by().outputs_spent(txid),
-                        ),
-                        entry.time_first_seen,
-                        false,
-                        None,
-                        self.avalanche,
-                    ))
-                })
-                .collect::<Result<Vec<_>>>()?,
-            None => vec![],
+        let mempool_txs = self
+            .mempool_history
+            .member_history(member_ser.as_ref())
+            .unwrap_or(&EMPTY_MEMBER_TX_HISTORY);
+        let txs = mempool_txs
+            .iter()
+            .map(|(_, txid)| self.read_mempool_tx(txid))
+            .collect::<Result<Vec<_>>>()?;
+        let num_all_db_txs = match mempool_txs.is_empty() {
+            true => 0,
+            false => self.num_all_db_txs()?,
+        };
+        let mempool_cursor = |&(time_first_seen, txid): &(i64, TxId)| {
+            HistoryCursor::Mempool(num_all_db_txs, time_first_seen, txid)
         };
         Ok(proto::TxHistoryPage {
             num_pages: if txs.is_empty() { 0 } else { 1 },
             num_txs: txs.len() as u32,
             txs,
+            oldest_cursor: cursor_string(
+                mempool_txs.first().map(mempool_cursor).as_ref(),
+            ),
+            newest_cursor: cursor_string(
+                mempool_txs.last().map(mempool_cursor).as_ref(),
+            ),
         })
     }
 
+    /// Number of confirmed txs of the group member.
+    pub fn num_confirmed_txs(&self, member: G::Member<'_>) -> Result<usize> {
+        let db_reader = GroupHistoryReader::<G>::new(self.db)?;
+        let member_ser = self.group.ser_member(&member);
+        let (_, num_db_txs) =
+            db_reader.member_num_pages_and_txs(member_ser.as_ref())?;
+        Ok(num_db_txs)
+    }
+
+    /// TxIds and block heights of all the confirmed txs of the group member,
+    /// in the same order as [`QueryGroupHistory::confirmed_txs`].
+    ///
+    /// Unlike the other queries, this only reads the index and doesn't load
+    /// the txs from disk, so it's cheap enough to run on every update.
+    pub fn confirmed_txids(
+        &self,
+        member: G::Member<'_>,
+    ) -> Result<Vec<(TxId, BlockHeight)>> {
+        let db_reader = GroupHistoryReader::<G>::new(self.db)?;
+        let tx_reader = TxReader::new(self.db)?;
+        let member_ser = self.group.ser_member(&member);
+        let (num_db_pages, num_db_txs) =
+            db_reader.member_num_pages_and_txs(member_ser.as_ref())?;
+        let mut txids = Vec::with_capacity(num_db_txs);
+        for page_num in 0..num_db_pages {
+            let page_tx_nums = db_reader
+                .page_txs(member_ser.as_ref(), page_num as u32)?
+                .unwrap_or_default();
+            for tx_num in page_tx_nums {
+                let block_tx = tx_reader
+                    .tx_by_tx_num(tx_num)?
+                    .ok_or(MissingDbTx(tx_num))?;
+                txids.push((block_tx.entry.txid, block_tx.block_height));
+            }
+        }
+        Ok(txids)
+    }
+
+    /// TxIds of the unconfirmed txs of the group member, in the same order as
+    /// [`QueryGroupHistory::unconfirmed_txs`].
+    pub fn unconfirmed_txids(&self, member: G::Member<'_>) -> Vec<TxId> {
+        let member_ser = self.group.ser_member(&member);
+        self.mempool_history
+            .member_history(member_ser.as_ref())
+            .unwrap_or(&EMPTY_MEMBER_TX_HISTORY)
+            .iter()
+            .map(|&(_, txid)| txid)
+            .collect()
+    }
+
+    fn history_by_cursor(
+        &self,
+        member: G::Member<'_>,
+        request: HistoryCursorRequest,
+        request_page_size: usize,
+        include_mempool: bool,
+    ) -> Result<proto::TxHistoryPage> {
+        if request_page_size < MIN_HISTORY_PAGE_SIZE {
+            return Err(RequestPageSizeTooSmall(request_page_size).into());
+        }
+        if request_page_size > MAX_HISTORY_PAGE_SIZE {
+            return Err(RequestPageSizeTooBig(request_page_size).into());
+        }
+        let member_ser = self.group.ser_member(&member);
+        let (cursors, total_num_txs) = self.page_cursors(
+            member_ser.as_ref(),
+            request,
+            request_page_size,
+            include_mempool,
+        )?;
+        let txs = cursors
+            .iter()
+            .map(|cursor| match cursor {
+                HistoryCursor::Db(tx_num) => self.read_block_tx(*tx_num),
+                HistoryCursor::Mempool(_, _, txid) => {
+                    self.read_mempool_tx(txid)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(proto::TxHistoryPage {
+            txs,
+            num_pages: ((total_num_txs + request_page_size - 1)
+                / request_page_size) as u32,
+            num_txs: total_num_txs as u32,
+            oldest_cursor: cursor_string(cursors.first()),
+            newest_cursor: cursor_string(cursors.last()),
+        })
+    }
+
+    /// Cursors of the txs on the requested page, in history order, and the
+    /// total number of txs of the member.
+    fn page_cursors(
+        &self,
+        member_ser: &[u8],
+        request: HistoryCursorRequest,
+        request_page_size: usize,
+        include_mempool: bool,
+    ) -> Result<(Vec<HistoryCursor>, usize)> {
+        let db_reader = GroupHistoryReader::<G>::new(self.db)?;
+        let (num_db_pages, num_db_txs) =
+            db_reader.member_num_pages_and_txs(member_ser)?;
+        let mempool_txs = match include_mempool {
+            true => self
+                .mempool_history
+                .member_history(member_ser)
+                .unwrap_or(&EMPTY_MEMBER_TX_HISTORY),
+            false => &EMPTY_MEMBER_TX_HISTORY,
+        };
+        let db_lower_bound = |tx_num: TxNum| {
+            db_tx_lower_bound(
+                &db_reader,
+                member_ser,
+                num_db_pages,
+                num_db_txs,
+                tx_num,
+            )
+        };
+        let num_all_db_txs = match mempool_txs.is_empty() {
+            true => 0,
+            false => self.num_all_db_txs()?,
+        };
+        let mempool_cursor = |&(time_first_seen, txid): &(i64, TxId)| {
+            HistoryCursor::Mempool(num_all_db_txs, time_first_seen, txid)
+        };
+
+        let mut cursors = Vec::with_capacity(request_page_size);
+        match request {
+            HistoryCursorRequest::After(cursor) => {
+                // DB txs after the cursor; for mempool cursors, these are the
+                // txs mined since the cursor has been issued.
+                let (db_start, mempool_start) = match cursor {
+                    HistoryCursor::Db(tx_num) => {
+                        (tx_num.saturating_add(1), Bound::Unbounded)
+                    }
+                    HistoryCursor::Mempool(
+                        cursor_num_db_txs,
+                        time_first_seen,
+                        txid,
+                    ) => (
+                        cursor_num_db_txs,
+                        Bound::Excluded((time_first_seen, txid)),
+                    ),
+                };
+                let start = db_lower_bound(db_start)?;
+                let end = num_db_txs.min(start + request_page_size);
+                let tx_nums = db_tx_nums(&db_reader, member_ser, start..end)?;
+                cursors.extend(tx_nums.into_iter().map(HistoryCursor::Db));
+                let num_mempool_txs = request_page_size - cursors.len();
+                cursors.extend(
+                    mempool_txs
+                        .range((mempool_start, Bound::Unbounded))
+                        .take(num_mempool_txs)
+                        .map(mempool_cursor),
+                );
+            }
+            HistoryCursorRequest::Before(cursor) => {
+                let db_end = match cursor {
+                    HistoryCursor::Db(tx_num) => db_lower_bound(tx_num)?,
+                    HistoryCursor::Mempool(_, time_first_seen, txid) => {
+                        let mempool_end =
+                            Bound::Excluded((time_first_seen, txid));
+                        cursors.extend(
+                            mempool_txs
+                                .range((Bound::Unbounded, mempool_end))
+                                .rev()
+                                .take(request_page_size)
+                                .map(mempool_cursor),
+                        );
+                        cursors.reverse();
+                        // Txs mined since the cursor has been issued could've
+                        // been before the cursor in the mempool, so include
+                        // all DB txs.
+                        num_db_txs
+                    }
+                };
+                let num_db_page_txs = request_page_size - cursors.len();
+                let tx_nums = db_tx_nums(
+                    &db_reader,
+                    member_ser,
+                    db_end.saturating_sub(num_db_page_txs)..db_end,
+                )?;
+                cursors
+                    .splice(0..0, tx_nums.into_iter().map(HistoryCursor::Db));
+            }
+        }
+        Ok((cursors, num_db_txs + mempool_txs.len()))
+    }
+
+    /// Number of txs in the whole DB, i.e. the tx num the next mined tx gets.
+    fn num_all_db_txs(&self) -> Result<TxNum> {
+        let tx_reader = TxReader::new(self.db)?;
+        Ok(tx_reader.last_tx_num()?.map_or(0, |tx_num| tx_num + 1))
+    }
+
+    fn read_mempool_tx(&self, txid: &TxId) -> Result<proto::Tx> {
+        let entry = self.mempool.tx(txid).ok_or(MissingMempoolTx(*txid))?;
+        Ok(make_tx_proto(
+            &entry.tx,
+            &OutputsSpent::new_mempool(
+                self.mempool.spent_by().outputs_spent(txid),
+            ),
+            entry.time_first_seen,
+            false,
+            None,
+            self.avalanche,
+            mempool_token_tx(self.mempool, txid).as_ref(),
+        ))
+    }
+
     fn read_block_tx(&self, tx_num: TxNum) -> Result<proto::Tx> {
         let tx_reader = TxReader::new(self.db)?;
         let block_reader = BlockReader::new(self.db)?;
@@ -383,6 +725,8 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
             self.mempool.spent_by().outputs_spent(&block_tx.entry.txid),
             tx_num,
         )?;
+        let token_tx_reader = TokenTxReader::new(self.db)?;
+        let token_tx = token_tx_reader.by_tx_num(tx_num)?;
         Ok(make_tx_proto(
             &Tx::from(tx),
             &outputs_spent,
@@ -390,6 +734,342 @@ impl<'a, G: Group> QueryGroupHistory<'a, G> {
             block_tx.entry.is_coinbase,
             Some(&block),
             self.avalanche,
+            token_tx.as_ref(),
         ))
     }
 }
+
+/// Index of the first tx of the member in the DB with a tx num of at least
+/// `tx_num`, or `num_db_txs` if there's none. Tx nums are sorted within and
+/// across pages, so we can binary search the pages.
+fn db_tx_lower_bound<G: Group>(
+    db_reader: &GroupHistoryReader<'_, G>,
+    member_ser: &[u8],
+    num_db_pages: usize,
+    num_db_txs: usize,
+    tx_num: TxNum,
+) -> Result<usize> {
+    let (mut low, mut high) = (0, num_db_pages);
+    while low < high {
+        let mid = (low + high) / 2;
+        let page_tx_nums = db_reader
+            .page_txs(member_ser, mid as u32)?
+            .unwrap_or_default();
+        match page_tx_nums.last() {
+            Some(&last_tx_num) if last_tx_num >= tx_num => high = mid,
+            _ => low = mid + 1,
+        }
+    }
+    if low == num_db_pages {
+        return Ok(num_db_txs);
+    }
+    let page_tx_nums = db_reader
+        .page_txs(member_ser, low as u32)?
+        .unwrap_or_default();
+    Ok(low * db_reader.page_size()
+        + page_tx_nums.partition_point(|&page_tx_num| page_tx_num < tx_num))
+}
+
+/// Tx nums of the member in the DB in the given range of indices.
+fn db_tx_nums<G: Group>(
+    db_reader: &GroupHistoryReader<'_, G>,
+    member_ser: &[u8],
+    range: std::ops::Range<usize>,
+) -> Result<Vec<TxNum>> {
+    let mut tx_nums = Vec::with_capacity(range.len());
+    if range.is_empty() {
+        return Ok(tx_nums);
+    }
+    let page_size = db_reader.page_size();
+    for page_num in range.start / page_size..=(range.end - 1) / page_size {
+        let page_tx_nums = db_reader
+            .page_txs(member_ser, page_num as u32)?
+            .unwrap_or_default();
+        let page_start = page_num * page_size;
+        tx_nums.extend(page_tx_nums.iter().enumerate().filter_map(
+            |(inner_idx, &tx_num)| {
+                range.contains(&(page_start + inner_idx)).then_some(tx_num)
+            },
+        ));
+    }
+    Ok(tx_nums)
+}
+
+#[cfg(test)]
+mod tests {
+    use abc_rust_error::Result;
+    use bitcoinsuite_core::{
+        script::Script,
+        tx::{Tx, TxId, TxMut, TxOutput},
+    };
+    use chronik_db::{
+        db::{Db, WriteBatch},
+        group::Group,
+        groups::{prefix_mock_compress, ScriptGroup, ScriptHistoryWriter},
+        index_tx::prepare_indexed_txs,
+        io::{BlockTxs, TxEntry, TxWriter},
+        mem::{Mempool, MempoolTx},
+    };
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        avalanche::Avalanche,
+        query::{
+            HistoryCursor, HistoryCursorRequest, QueryGroupHistory,
+            QueryGroupHistoryError,
+        },
+    };
+
+    #[test]
+    fn test_history_cursor() {
+        let db_cursor = HistoryCursor::Db(0x0102);
+        let mempool_cursor =
+            HistoryCursor::Mempool(0x0304, 0x1000, TxId::from([0xab; 32]));
+        assert_eq!(db_cursor.to_string(), "000000000000000102");
+        assert_eq!(
+            mempool_cursor.to_string(),
+            format!("0100000000000003040000000000001000{}", "ab".repeat(32)),
+        );
+        for cursor in [db_cursor, mempool_cursor] {
+            assert_eq!(cursor.to_string().parse(), Ok(cursor));
+        }
+
+        // DB txs come before mempool txs
+        assert!(HistoryCursor::Db(u64::MAX) < mempool_cursor);
+        assert!(
+            HistoryCursor::Mempool(0x0304, 0x0fff, TxId::from([0xff; 32]))
+                < mempool_cursor
+        );
+
+        for invalid in ["", "xy", "00", "0000000000000001020304", "0201", "01"]
+        {
+            assert_eq!(
+                invalid.parse::<HistoryCursor>(),
+                Err(QueryGroupHistoryError::InvalidCursor(invalid.to_string())),
+            );
+        }
+        // Mempool cursors must have the number of DB txs
+        let invalid = format!("010000000000001000{}", "ab".repeat(32));
+        assert_eq!(
+            invalid.parse::<HistoryCursor>(),
+            Err(QueryGroupHistoryError::InvalidCursor(invalid)),
+        );
+    }
+
+    fn make_tx(txid_num: u32, script: &Script) -> Tx {
+        let mut txid = [0; 32];
+        txid[..4].copy_from_slice(&txid_num.to_le_bytes());
+        Tx::with_txid(
+            TxId::from(txid),
+            TxMut {
+                outputs: vec![TxOutput {
+                    value: 1000,
+                    script: script.clone(),
+                }],
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_page_cursors() -> Result<()> {
+        use HistoryCursorRequest::*;
+        abc_rust_error::install();
+        let tempdir = tempdir::TempDir::new("chronik-indexer--history")?;
+        let db = Db::open(tempdir.path())?;
+        let group = ScriptGroup::new(prefix_mock_compress);
+        let mut mempool = Mempool::new(group.clone(), false);
+        let script = Script::new(vec![0x51].into());
+        let other_script = Script::new(vec![0x52].into());
+        let member_ser = group.ser_member(&&script);
+
+        let connect_block = |block_height: i32, txs: &[Tx]| -> Result<()> {
+            let tx_writer = TxWriter::new(&db)?;
+            let history_writer = ScriptHistoryWriter::new(&db, group.clone())?;
+            let mut batch = WriteBatch::default();
+            let block_txs = BlockTxs {
+                txs: txs
+                    .iter()
+                    .map(|tx| TxEntry {
+                        txid: tx.txid(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                block_height,
+            };
+            let first_tx_num = tx_writer.insert(&mut batch, &block_txs)?;
+            let index_txs = prepare_indexed_txs(&db, first_tx_num, txs)?;
+            history_writer.insert(&mut batch, &index_txs, &())?;
+            db.write_batch(batch)
+        };
+
+        // Every even tx of the block goes to the script, so the member has
+        // 1250 txs in the DB, spread over 2 DB pages, with tx nums 0, 2, ...
+        let block_txs = (0..2500)
+            .map(|num| match num % 2 {
+                0 => make_tx(num, &script),
+                _ => make_tx(num, &other_script),
+            })
+            .collect::<Vec<_>>();
+        connect_block(0, &block_txs)?;
+
+        // 3 txs in the mempool
+        let mempool_txs = [(3000, 1000), (3001, 1000), (3002, 1001)].map(
+            |(num, time_first_seen)| MempoolTx {
+                tx: make_tx(num, &script),
+                time_first_seen,
+            },
+        );
+        for mempool_tx in &mempool_txs {
+            mempool.insert(
+                &db,
+                MempoolTx {
+                    tx: mempool_tx.tx.clone(),
+                    time_first_seen: mempool_tx.time_first_seen,
+                },
+            )?;
+        }
+        let mempool_cursor = |num_db_txs: u64, idx: usize| {
+            let tx = &mempool_txs[idx];
+            HistoryCursor::Mempool(num_db_txs, tx.time_first_seen, tx.tx.txid())
+        };
+
+        let avalanche = Avalanche::default();
+        let page_cursors = |mempool: &Mempool,
+                            request: HistoryCursorRequest,
+                            page_size: usize,
+                            include_mempool: bool| {
+            let query = QueryGroupHistory {
+                db: &db,
+                avalanche: &avalanche,
+                mempool,
+                mempool_history: mempool.script_history(),
+                group: group.clone(),
+            };
+            query.page_cursors(&member_ser, request, page_size, include_mempool)
+        };
+        let db_cursors =
+            |tx_nums: std::ops::RangeInclusive<u64>| -> Vec<HistoryCursor> {
+                tx_nums.step_by(2).map(HistoryCursor::Db).collect()
+            };
+
+        // Confirmed only
+        assert_eq!(
+            page_cursors(&mempool, After(HistoryCursor::Db(0)), 200, false)?,
+            (db_cursors(2..=400), 1250),
+        );
+        // Crosses the DB page boundary
+        assert_eq!(
+            page_cursors(&mempool, After(HistoryCursor::Db(1995)), 5, false)?,
+            (db_cursors(1996..=2004), 1250),
+        );
+        assert_eq!(
+            page_cursors(&mempool, After(HistoryCursor::Db(2496)), 5, false)?,
+            (db_cursors(2498..=2498), 1250),
+        );
+        assert_eq!(
+            page_cursors(&mempool, Before(HistoryCursor::Db(2001)), 3, false)?,
+            (db_cursors(1996..=2000), 1250),
+        );
+        assert_eq!(
+            page_cursors(&mempool, Before(HistoryCursor::Db(1)), 3, false)?,
+            (vec![HistoryCursor::Db(0)], 1250),
+        );
+        assert_eq!(
+            page_cursors(&mempool, Before(HistoryCursor::Db(0)), 3, false)?,
+            (vec![], 1250),
+        );
+        // Before a mempool cursor without mempool txs: the last DB txs
+        assert_eq!(
+            page_cursors(&mempool, Before(mempool_cursor(2500, 1)), 2, false)?,
+            (db_cursors(2496..=2498), 1250),
+        );
+
+        // Including the mempool, DB txs are followed by mempool txs
+        assert_eq!(
+            page_cursors(&mempool, After(HistoryCursor::Db(2496)), 5, true)?,
+            (
+                vec![
+                    HistoryCursor::Db(2498),
+                    mempool_cursor(2500, 0),
+                    mempool_cursor(2500, 1),
+                    mempool_cursor(2500, 2),
+                ],
+                1253,
+            ),
+        );
+        assert_eq!(
+            page_cursors(&mempool, After(mempool_cursor(2500, 0)), 1, true)?,
+            (vec![mempool_cursor(2500, 1)], 1253),
+        );
+        assert_eq!(
+            page_cursors(&mempool, After(mempool_cursor(2500, 2)), 5, true)?,
+            (vec![], 1253),
+        );
+        assert_eq!(
+            page_cursors(&mempool, Before(mempool_cursor(2500, 2)), 4, true)?,
+            (
+                vec![
+                    HistoryCursor::Db(2496),
+                    HistoryCursor::Db(2498),
+                    mempool_cursor(2500, 0),
+                    mempool_cursor(2500, 1),
+                ],
+                1253,
+            ),
+        );
+        assert_eq!(
+            page_cursors(&mempool, Before(HistoryCursor::Db(2)), 4, true)?,
+            (vec![HistoryCursor::Db(0)], 1253),
+        );
+
+        // Block mines the first mempool tx and a tx that never was in the
+        // mempool, which get tx nums 2500 and 2501.
+        let block_txs = [
+            mempool_txs[0].tx.clone(),
+            make_tx(4000, &script),
+            make_tx(4001, &other_script),
+        ];
+        for tx in &block_txs {
+            mempool.remove_mined(&tx.txid())?;
+        }
+        connect_block(1, &block_txs)?;
+
+        // Resuming after a mempool cursor from before the block returns the
+        // mined txs first, so none are skipped
+        assert_eq!(
+            page_cursors(&mempool, After(mempool_cursor(2500, 1)), 5, true)?,
+            (
+                vec![
+                    HistoryCursor::Db(2500),
+                    HistoryCursor::Db(2501),
+                    mempool_cursor(2503, 2)
+                ],
+                1254
+            ),
+        );
+        assert_eq!(
+            page_cursors(&mempool, After(mempool_cursor(2500, 2)), 1, true)?,
+            (vec![HistoryCursor::Db(2500)], 1254),
+        );
+        // Cursors issued after the block don't return them again
+        assert_eq!(
+            page_cursors(&mempool, After(mempool_cursor(2503, 1)), 5, true)?,
+            (vec![mempool_cursor(2503, 2)], 1254),
+        );
+        // Going backwards from a mempool cursor includes all DB txs
+        assert_eq!(
+            page_cursors(&mempool, Before(mempool_cursor(2500, 2)), 3, true)?,
+            (
+                vec![
+                    HistoryCursor::Db(2500),
+                    HistoryCursor::Db(2501),
+                    mempool_cursor(2503, 1)
+                ],
+                1254
+            ),
+        );
+
+        Ok(())
+    }
+}
@@ -10,20 +10,23 @@ use abc_rust_error::Result;
 use bitcoinsuite_core::tx::{OutPoint, TxId};
 use chronik_db::{
     db::Db,
-    group::Group,
-    io::{GroupUtxoReader, TxNum, TxReader},
+    group::UtxoGroup,
+    io::{DbTokenTx, GroupUtxoReader, TokenTxReader, TxNum, TxReader},
     mem::{Mempool, MempoolGroupUtxos},
 };
 use chronik_proto::proto;
 use thiserror::Error;
 
-use crate::{avalanche::Avalanche, query::make_outpoint_proto};
+use crate::{
+    avalanche::Avalanche,
+    query::{make_outpoint_proto, make_token_proto, mempool_token_tx},
+};
 
 static EMPTY_MEMBER_UTXOS: BTreeSet<OutPoint> = BTreeSet::new();
 
 /// Query pages of the tx history of a group
 #[derive(Debug)]
-pub struct QueryGroupUtxos<'a, G: Group> {
+pub struct QueryGroupUtxos<'a, G: UtxoGroup> {
     /// Database
     pub db: &'a Db,
     /// Avalanche
@@ -57,7 +60,7 @@ pub enum QueryGroupUtxosError {
 
 use self::QueryGroupUtxosError::*;
 
-impl<'a, G: Group> QueryGroupUtxos<'a, G> {
+impl<'a, G: UtxoGroup> QueryGroupUtxos<'a, G> {
     /// Return the UTXOs of the given member, from both DB and mempool.
     ///
     /// UTXOs are sorted this way:
@@ -71,6 +74,7 @@ impl<'a, G: Group> QueryGroupUtxos<'a, G> {
     ) -> Result<Vec<proto::ScriptUtxo>> {
         let tx_reader = TxReader::new(self.db)?;
         let utxo_reader = GroupUtxoReader::<G>::new(self.db)?;
+        let token_tx_reader = TokenTxReader::new(self.db)?;
         let member_ser = self.group.ser_member(&member);
 
         // Read UTXO entries from DB and mempool
@@ -108,12 +112,14 @@ impl<'a, G: Group> QueryGroupUtxos<'a, G> {
             }
 
             let outpoint = OutPoint { txid, out_idx };
+            let token_tx = token_tx_reader.by_tx_num(tx_num)?;
             utxos.push(proto::ScriptUtxo {
                 outpoint: Some(make_outpoint_proto(&outpoint)),
                 block_height: db_tx.block_height,
                 is_coinbase: db_tx.entry.is_coinbase,
                 value: db_utxo.value,
                 is_final: self.avalanche.is_final_height(db_tx.block_height),
+                token: make_utxo_token_proto(token_tx.as_ref(), out_idx),
             });
         }
 
@@ -128,15 +134,34 @@ impl<'a, G: Group> QueryGroupUtxos<'a, G> {
                 .outputs
                 .get(mempool_outpoint.out_idx as usize)
                 .ok_or(MempoolTxOutputsOutOfBounds(mempool_outpoint))?;
+            let token_tx =
+                mempool_token_tx(self.mempool, &mempool_outpoint.txid);
             utxos.push(proto::ScriptUtxo {
                 outpoint: Some(make_outpoint_proto(&mempool_outpoint)),
                 block_height: -1,
                 is_coinbase: false,
                 value: output.value,
                 is_final: false,
+                token: make_utxo_token_proto(
+                    token_tx.as_ref(),
+                    mempool_outpoint.out_idx,
+                ),
             });
         }
 
         Ok(utxos)
     }
 }
+
+/// UTXOs are not part of a tx, so they don't reference a token entry.
+fn make_utxo_token_proto(
+    token_tx: Option<&DbTokenTx>,
+    out_idx: u32,
+) -> Option<proto::Token> {
+    let token_tx = token_tx?;
+    let db_token = token_tx.outputs.get(out_idx as usize)?.as_ref()?;
+    Some(proto::Token {
+        entry_idx: -1,
+        ..make_token_proto(token_tx, db_token)
+    })
+}
@@ -0,0 +1,287 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`QueryMempool`], to query the txs in the mempool.
+
+use abc_rust_error::Result;
+use bitcoinsuite_core::tx::TxId;
+use chronik_db::{fee::fee_rate, mem::Mempool};
+use chronik_proto::proto;
+use thiserror::Error;
+
+/// Smallest allowed page size
+pub const MIN_MEMPOOL_PAGE_SIZE: usize = 1;
+/// Largest allowed page size
+pub const MAX_MEMPOOL_PAGE_SIZE: usize = 200;
+
+/// Lower bounds of the fee rate buckets of the fee rate histogram in
+/// [`QueryMempool::info`], in sats/kB.
+pub const FEE_RATE_BUCKETS: &[i64] = &[
+    0, 1000, 2000, 3000, 5000, 10_000, 20_000, 50_000, 100_000, 1_000_000,
+];
+
+/// Struct for querying the txs in the mempool.
+#[derive(Debug)]
+pub struct QueryMempool<'a> {
+    /// Mempool
+    pub mempool: &'a Mempool,
+}
+
+/// Errors indicating something went wrong with querying the mempool.
+#[derive(Debug, Error, PartialEq)]
+pub enum QueryMempoolError {
+    /// Can only request page sizes below a certain maximum.
+    #[error(
+        "400: Requested page size {0} is too big, maximum is {}",
+        MAX_MEMPOOL_PAGE_SIZE
+    )]
+    RequestPageSizeTooBig(usize),
+
+    /// Can only request page sizes above a certain minimum.
+    #[error(
+        "400: Requested page size {0} is too small, minimum is {}",
+        MIN_MEMPOOL_PAGE_SIZE
+    )]
+    RequestPageSizeTooSmall(usize),
+
+    /// Transaction not in mempool.
+    #[error("500: Inconsistent mempool: Transaction {0} not in mempool")]
+    MissingMempoolTx(TxId),
+}
+
+use self::QueryMempoolError::*;
+
+impl<'a> QueryMempool<'a> {
+    /// Return a page of the txs in the mempool, ordered by the time they have
+    /// been first seen, latest first. Txs first seen at the same time are
+    /// ordered by txid.
+    pub fn txs(
+        &self,
+        request_page_num: usize,
+        request_page_size: usize,
+    ) -> Result<proto::MempoolTxs> {
+        if request_page_size < MIN_MEMPOOL_PAGE_SIZE {
+            return Err(RequestPageSizeTooSmall(request_page_size).into());
+        }
+        if request_page_size > MAX_MEMPOOL_PAGE_SIZE {
+            return Err(RequestPageSizeTooBig(request_page_size).into());
+        }
+        let txids = self.mempool.txs_by_time_first_seen();
+        let num_txs = txids.len();
+        let num_pages = (num_txs + request_page_size - 1) / request_page_size;
+        let txs = txids
+            .iter()
+            .skip(request_page_num.saturating_mul(request_page_size))
+            .take(request_page_size)
+            .map(|(_, txid)| {
+                let tx =
+                    self.mempool.tx(txid).ok_or(MissingMempoolTx(*txid))?;
+                Ok(proto::MempoolTxInfo {
+                    txid: txid.to_vec(),
+                    time_first_seen: tx.time_first_seen,
+                    size: tx.size() as u32,
+                    fee: tx.fee(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(proto::MempoolTxs {
+            txs,
+            num_pages: num_pages as u32,
+            num_txs: num_txs as u32,
+        })
+    }
+
+    /// Summary of the mempool: Number of txs, their total size and fees, and
+    /// a histogram of their fee rates, bucketed by [`FEE_RATE_BUCKETS`].
+    pub fn info(&self) -> proto::MempoolInfo {
+        let mut info = proto::MempoolInfo {
+            fee_rate_histogram: FEE_RATE_BUCKETS
+                .iter()
+                .map(|&min_fee_rate| proto::FeeRateBucket {
+                    min_fee_rate,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        for tx in self.mempool.txs().values() {
+            let size = tx.size();
+            let fee = tx.fee();
+            let fee_rate = fee_rate(fee, size);
+            info.num_txs += 1;
+            info.num_bytes += size as u64;
+            info.sum_fees += fee;
+            let bucket_idx = FEE_RATE_BUCKETS
+                .partition_point(|&min_fee_rate| min_fee_rate <= fee_rate)
+                .saturating_sub(1);
+            let bucket = &mut info.fee_rate_histogram[bucket_idx];
+            bucket.num_txs += 1;
+            bucket.num_bytes += size as u64;
+        }
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use abc_rust_error::Result;
+    use bitcoinsuite_core::tx::{
+        Coin, OutPoint, Tx, TxId, TxInput, TxMut, TxOutput,
+    };
+    use chronik_db::{
+        db::Db,
+        groups::{prefix_mock_compress, ScriptGroup},
+        mem::{Mempool, MempoolTx},
+    };
+    use chronik_proto::proto;
+    use pretty_assertions::assert_eq;
+
+    use crate::query::{QueryMempool, QueryMempoolError, FEE_RATE_BUCKETS};
+
+    fn make_mempool_tx(
+        txid_num: u8,
+        fee: i64,
+        time_first_seen: i64,
+    ) -> MempoolTx {
+        MempoolTx {
+            tx: Tx::with_txid(
+                TxId::from([txid_num; 32]),
+                TxMut {
+                    version: 1,
+                    inputs: vec![TxInput {
+                        prev_out: OutPoint {
+                            txid: TxId::from([0xff; 32]),
+                            out_idx: txid_num.into(),
+                        },
+                        coin: Some(Coin {
+                            output: TxOutput {
+                                value: 10_000 + fee,
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }],
+                    outputs: vec![TxOutput {
+                        value: 10_000,
+                        ..Default::default()
+                    }],
+                    locktime: 0,
+                },
+            ),
+            time_first_seen,
+        }
+    }
+
+    #[test]
+    fn test_query_mempool() -> Result<()> {
+        abc_rust_error::install();
+        let tempdir = tempdir::TempDir::new("chronik-indexer--mempool")?;
+        let db = Db::open(tempdir.path())?;
+        let mut mempool =
+            Mempool::new(ScriptGroup::new(prefix_mock_compress), false);
+        let query = QueryMempool { mempool: &mempool };
+        assert_eq!(
+            query.txs(0, 25)?,
+            proto::MempoolTxs {
+                txs: vec![],
+                num_pages: 0,
+                num_txs: 0,
+            },
+        );
+        assert_eq!(
+            query
+                .txs(0, 0)
+                .unwrap_err()
+                .downcast::<QueryMempoolError>()?,
+            QueryMempoolError::RequestPageSizeTooSmall(0),
+        );
+        assert_eq!(
+            query
+                .txs(0, 201)
+                .unwrap_err()
+                .downcast::<QueryMempoolError>()?,
+            QueryMempoolError::RequestPageSizeTooBig(201),
+        );
+
+        let tx1 = make_mempool_tx(1, 100, 1000);
+        let tx2 = make_mempool_tx(2, 0, 1001);
+        let tx3 = make_mempool_tx(3, 100_000, 1000);
+        // All txs have the same size
+        let size = tx1.size() as u32;
+        let tx_info = |tx: &MempoolTx| proto::MempoolTxInfo {
+            txid: tx.tx.txid().to_vec(),
+            time_first_seen: tx.time_first_seen,
+            size,
+            fee: tx.fee(),
+        };
+        let expected_txs = [tx_info(&tx2), tx_info(&tx1), tx_info(&tx3)];
+        mempool.insert(&db, tx1)?;
+        mempool.insert(&db, tx2)?;
+        mempool.insert(&db, tx3)?;
+        let query = QueryMempool { mempool: &mempool };
+
+        // Latest first, then ordered by txid
+        assert_eq!(
+            query.txs(0, 25)?,
+            proto::MempoolTxs {
+                txs: expected_txs.to_vec(),
+                num_pages: 1,
+                num_txs: 3,
+            },
+        );
+        assert_eq!(
+            query.txs(1, 2)?,
+            proto::MempoolTxs {
+                txs: expected_txs[2..].to_vec(),
+                num_pages: 2,
+                num_txs: 3,
+            },
+        );
+        assert_eq!(query.txs(2, 2)?.txs, vec![]);
+
+        let info = query.info();
+        assert_eq!(info.num_txs, 3);
+        assert_eq!(info.num_bytes, 3 * size as u64);
+        assert_eq!(info.sum_fees, 100_100);
+        assert_eq!(info.fee_rate_histogram.len(), FEE_RATE_BUCKETS.len());
+        let bucket_of = |fee: i64| {
+            let fee_rate = fee * 1000 / size as i64;
+            FEE_RATE_BUCKETS
+                .iter()
+                .rposition(|&min_fee_rate| min_fee_rate <= fee_rate)
+                .unwrap()
+        };
+        for (idx, bucket) in info.fee_rate_histogram.iter().enumerate() {
+            let num_txs = [0, 100, 100_000]
+                .into_iter()
+                .filter(|&fee| bucket_of(fee) == idx)
+                .count() as u64;
+            assert_eq!(
+                *bucket,
+                proto::FeeRateBucket {
+                    min_fee_rate: FEE_RATE_BUCKETS[idx],
+                    num_txs,
+                    num_bytes: num_txs * size as u64,
+                },
+            );
+        }
+
+        // Removed txs are gone from the pages
+        let tx3_info = expected_txs[2].clone();
+        mempool.remove(TxId::from([2; 32]))?;
+        mempool.remove_mined(&TxId::from([1; 32]))?;
+        let query = QueryMempool { mempool: &mempool };
+        assert_eq!(
+            query.txs(0, 25)?,
+            proto::MempoolTxs {
+                txs: vec![tx3_info],
+                num_pages: 1,
+                num_txs: 1,
+            },
+        );
+
+        Ok(())
+    }
+}
@@ -5,13 +5,25 @@
 //! Module for structs helping to query the indexer.
 
 mod blocks;
+mod broadcast;
+mod fee_estimate;
+mod group_balance;
 mod group_history;
 mod group_utxos;
+mod mempool;
+mod tokens;
+mod tx_graph;
 mod txs;
 mod util;
 
 pub use self::blocks::*;
+pub use self::broadcast::*;
+pub use self::fee_estimate::*;
+pub use self::group_balance::*;
 pub use self::group_history::*;
 pub use self::group_utxos::*;
+pub use self::mempool::*;
+pub use self::tokens::*;
+pub use self::tx_graph::*;
 pub use self::txs::*;
 pub use self::util::*;
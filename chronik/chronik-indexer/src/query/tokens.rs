@@ -0,0 +1,338 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`QueryTokens`], to query info about tokens.
+
+use abc_rust_error::Result;
+use bitcoinsuite_slp::{structs::TxType, token_id::TokenId};
+use chronik_db::{
+    db::Db,
+    io::{BlockReader, DbGenesisInfo, TokenInfoReader},
+    mem::Mempool,
+};
+use chronik_proto::proto;
+use thiserror::Error;
+
+use crate::{avalanche::Avalanche, query::make_token_type_proto};
+
+/// Struct for querying info about tokens from the DB/mempool.
+#[derive(Debug)]
+pub struct QueryTokens<'a> {
+    /// Database
+    pub db: &'a Db,
+    /// Avalanche
+    pub avalanche: &'a Avalanche,
+    /// Mempool
+    pub mempool: &'a Mempool,
+}
+
+/// Errors indicating something went wrong with querying tokens.
+#[derive(Debug, Error, PartialEq)]
+pub enum QueryTokenError {
+    /// Token has no valid GENESIS tx in the mempool nor the DB.
+    #[error("404: Token {0} not found in the index")]
+    TokenNotFound(TokenId),
+
+    /// Token in DB with a block that doesn't exist.
+    #[error("500: Inconsistent DB: Token {0} has no block")]
+    DbTokenHasNoBlock(TokenId),
+}
+
+use self::QueryTokenError::*;
+
+impl<'a> QueryTokens<'a> {
+    /// Query the info of a token by its token ID, from the DB or, if the
+    /// GENESIS tx is unconfirmed, from the mempool.
+    pub fn token_info(&self, token_id: &TokenId) -> Result<proto::TokenInfo> {
+        let token_info_reader = TokenInfoReader::new(self.db)?;
+        if let Some(info) = token_info_reader.by_token_id(token_id)? {
+            let block_reader = BlockReader::new(self.db)?;
+            let block = block_reader
+                .by_height(info.block_height)?
+                .ok_or(DbTokenHasNoBlock(*token_id))?;
+            return Ok(proto::TokenInfo {
+                token_id: token_id.txid().to_vec(),
+                token_type: Some(make_token_type_proto(info.token_type)),
+                genesis_info: Some(make_genesis_info_proto(&info.genesis_info)),
+                block: Some(proto::BlockMetadata {
+                    hash: block.hash.to_vec(),
+                    height: block.height,
+                    timestamp: block.timestamp,
+                    is_final: self.avalanche.is_final_height(block.height),
+                }),
+                circulating_supply: info.circulating_supply.to_string(),
+            });
+        }
+        let tx_tokens = self
+            .mempool
+            .tokens()
+            .txs
+            .get(token_id.txid())
+            .ok_or(TokenNotFound(*token_id))?;
+        let entry = tx_tokens
+            .colored
+            .entries
+            .iter()
+            .find(|entry| {
+                entry.token_id == *token_id
+                    && entry.tx_type == Some(TxType::Genesis)
+                    && entry.is_valid()
+            })
+            .ok_or(TokenNotFound(*token_id))?;
+        let genesis_info = entry
+            .genesis_info
+            .as_ref()
+            .ok_or(TokenNotFound(*token_id))?;
+        let circulating_supply = tx_tokens
+            .supply_deltas()
+            .get(token_id)
+            .copied()
+            .unwrap_or_default();
+        Ok(proto::TokenInfo {
+            token_id: token_id.txid().to_vec(),
+            token_type: Some(make_token_type_proto(entry.token_type.into())),
+            genesis_info: Some(make_genesis_info_proto(&genesis_info.into())),
+            block: None,
+            circulating_supply: circulating_supply.to_string(),
+        })
+    }
+}
+
+fn make_genesis_info_proto(info: &DbGenesisInfo) -> proto::GenesisInfo {
+    proto::GenesisInfo {
+        token_ticker: info.token_ticker.clone(),
+        token_name: info.token_name.clone(),
+        url: info.url.clone(),
+        hash: info.hash.map(|hash| hash.to_vec()).unwrap_or_default(),
+        data: info.data.clone().unwrap_or_default(),
+        auth_pubkey: info.auth_pubkey.clone().unwrap_or_default(),
+        decimals: info.decimals.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use abc_rust_error::Result;
+    use bitcoinsuite_core::{
+        block::BlockHash,
+        tx::{Coin, OutPoint, Tx, TxId, TxInput, TxMut, TxOutput},
+    };
+    use bitcoinsuite_slp::{
+        alp::{
+            sections_script, AlpBurn, AlpGenesis, AlpMint, AlpMintData,
+            AlpSection, AlpTokenType, AlpTxType,
+        },
+        structs::{GenesisInfo, Token, TokenType, TokenVariant},
+        token_id::TokenId,
+    };
+    use chronik_db::{
+        db::{Db, WriteBatch},
+        groups::{
+            prefix_mock_compress, ScriptGroup, TokenIdGroupAux, TxTokens,
+        },
+        index_tx::IndexTx,
+        io::{BlockWriter, DbBlock, TokenInfoWriter},
+        mem::{Mempool, MempoolTx},
+    };
+    use chronik_proto::proto;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        avalanche::Avalanche,
+        query::{QueryTokenError, QueryTokens},
+    };
+
+    fn make_tx(txid_num: u8, tx_type: AlpTxType) -> Tx {
+        let mut outputs = vec![TxOutput::default(); 4];
+        outputs[0].script = sections_script(&[AlpSection {
+            token_type: AlpTokenType::Standard,
+            tx_type,
+        }])
+        .unwrap();
+        Tx::with_txid(
+            TxId::from([txid_num; 32]),
+            TxMut {
+                inputs: vec![TxInput {
+                    prev_out: OutPoint {
+                        txid: TxId::from([0xff; 32]),
+                        out_idx: txid_num.into(),
+                    },
+                    coin: Some(Coin::default()),
+                    ..Default::default()
+                }],
+                outputs,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_query_token_info() -> Result<()> {
+        abc_rust_error::install();
+        let tempdir = tempdir::TempDir::new("chronik-indexer--tokens")?;
+        let db = Db::open(tempdir.path())?;
+        let mut mempool =
+            Mempool::new(ScriptGroup::new(prefix_mock_compress), false);
+        let avalanche = Avalanche::default();
+
+        let token_id = TokenId::new(TxId::from([1; 32]));
+        let token = |variant| {
+            Some(Token {
+                token_id,
+                token_type: TokenType::Alp(AlpTokenType::Standard),
+                variant,
+            })
+        };
+        let genesis_tx = make_tx(
+            1,
+            AlpTxType::Genesis(Box::new(AlpGenesis {
+                info: GenesisInfo {
+                    token_ticker: b"TKN".as_ref().into(),
+                    decimals: 4,
+                    ..Default::default()
+                },
+                mint_data: AlpMintData {
+                    amounts: vec![100, 20],
+                    num_batons: 1,
+                },
+            })),
+        );
+        let mint_tx = make_tx(
+            2,
+            AlpTxType::Mint(AlpMint {
+                token_id,
+                mint_data: AlpMintData {
+                    amounts: vec![30],
+                    num_batons: 0,
+                },
+            }),
+        );
+        let burn_tx = make_tx(
+            3,
+            AlpTxType::Burn(AlpBurn {
+                token_id,
+                burn_amount: 20,
+            }),
+        );
+
+        let connect = |height: i32,
+                       txs: &[(&Tx, Vec<Option<Token>>)]|
+         -> Result<()> {
+            let index_txs = txs
+                .iter()
+                .map(|&(tx, _)| IndexTx {
+                    tx,
+                    tx_num: 0,
+                    is_coinbase: false,
+                    input_nums: vec![],
+                })
+                .collect::<Vec<_>>();
+            let aux = TokenIdGroupAux {
+                txs: txs
+                    .iter()
+                    .map(|(tx, spent_tokens)| {
+                        (tx.txid(), TxTokens::color(tx, spent_tokens.clone()))
+                    })
+                    .collect::<HashMap<_, _>>(),
+            };
+            let mut batch = WriteBatch::default();
+            BlockWriter::new(&db)?.insert(
+                &mut batch,
+                &DbBlock {
+                    hash: BlockHash::from([height as u8; 32]),
+                    height,
+                    timestamp: 1_300_000_000 + i64::from(height),
+                    ..Default::default()
+                },
+            )?;
+            TokenInfoWriter::new(&db)?
+                .insert(&mut batch, height, &index_txs, &aux)?;
+            db.write_batch(batch)?;
+            Ok(())
+        };
+        let token_info = |mempool: &Mempool| {
+            QueryTokens {
+                db: &db,
+                avalanche: &avalanche,
+                mempool,
+            }
+            .token_info(&token_id)
+        };
+        let expected_info =
+            |block: Option<(u8, i32)>, supply: &str| proto::TokenInfo {
+                token_id: token_id.txid().to_vec(),
+                token_type: Some(proto::TokenType {
+                    token_type: Some(proto::token_type::TokenType::Alp(
+                        proto::AlpTokenType::Standard as _,
+                    )),
+                }),
+                genesis_info: Some(proto::GenesisInfo {
+                    token_ticker: b"TKN".to_vec(),
+                    decimals: 4,
+                    ..Default::default()
+                }),
+                block: block.map(|(hash_num, height)| proto::BlockMetadata {
+                    hash: vec![hash_num; 32],
+                    height,
+                    timestamp: 1_300_000_000 + i64::from(height),
+                    is_final: false,
+                }),
+                circulating_supply: supply.to_string(),
+            };
+
+        // Token unknown
+        assert_eq!(
+            token_info(&mempool)
+                .unwrap_err()
+                .downcast::<QueryTokenError>()?,
+            QueryTokenError::TokenNotFound(token_id),
+        );
+
+        // GENESIS in the mempool
+        mempool.insert(
+            &db,
+            MempoolTx {
+                tx: genesis_tx.clone(),
+                time_first_seen: 1_300_000_000,
+            },
+        )?;
+        assert_eq!(token_info(&mempool)?, expected_info(None, "120"));
+
+        // Mempool txs other than the GENESIS don't affect the supply
+        mempool.insert(
+            &db,
+            MempoolTx {
+                tx: burn_tx.clone(),
+                time_first_seen: 1_300_000_001,
+            },
+        )?;
+        assert_eq!(token_info(&mempool)?, expected_info(None, "120"));
+
+        // Block without any tokens
+        connect(0, &[])?;
+        assert_eq!(token_info(&mempool)?, expected_info(None, "120"));
+
+        // GENESIS mined, the DB takes precedence over the mempool
+        connect(1, &[(&genesis_tx, vec![None])])?;
+        assert_eq!(token_info(&mempool)?, expected_info(Some((1, 1)), "120"));
+        let empty_mempool =
+            Mempool::new(ScriptGroup::new(prefix_mock_compress), false);
+        assert_eq!(
+            token_info(&empty_mempool)?,
+            expected_info(Some((1, 1)), "120"),
+        );
+
+        // MINT of 30 tokens
+        connect(2, &[(&mint_tx, vec![token(TokenVariant::MintBaton)])])?;
+        assert_eq!(token_info(&mempool)?, expected_info(Some((1, 1)), "150"));
+
+        // BURN of 20 tokens
+        connect(3, &[(&burn_tx, vec![token(TokenVariant::Amount(20))])])?;
+        assert_eq!(token_info(&mempool)?, expected_info(Some((1, 1)), "130"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Module for [`QueryTxGraph`], to query the ancestors and descendants of a tx.
+
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+use abc_rust_error::{Result, WrapErr};
+use bitcoinsuite_core::{
+    ser::BitcoinSer,
+    tx::{Tx, TxId},
+};
+use chronik_bridge::ffi;
+use chronik_db::{
+    db::Db,
+    fee::tx_fee,
+    io::{BlockReader, DbBlock, SpentByReader, TxNum, TxReader},
+    mem::Mempool,
+};
+use chronik_proto::proto;
+use thiserror::Error;
+
+use crate::avalanche::Avalanche;
+
+/// Depth used if none is requested.
+pub const DEFAULT_TX_GRAPH_DEPTH: usize = 10;
+/// Largest allowed depth
+pub const MAX_TX_GRAPH_DEPTH: usize = 100;
+/// Maximum number of txs included in the graph, to cap the work done per
+/// request. If the graph has more txs, it will be truncated.
+pub const MAX_TX_GRAPH_TXS: usize = 1000;
+
+/// Struct for querying the ancestors and descendants of a tx, in the mempool
+/// and in the DB.
+#[derive(Debug)]
+pub struct QueryTxGraph<'a> {
+    /// Database
+    pub db: &'a Db,
+    /// Avalanche
+    pub avalanche: &'a Avalanche,
+    /// Mempool
+    pub mempool: &'a Mempool,
+}
+
+/// Errors indicating something went wrong with querying the tx graph.
+#[derive(Debug, Error, PartialEq)]
+pub enum QueryTxGraphError {
+    /// Transaction not in mempool nor DB.
+    #[error("404: Transaction {0} not found in the index")]
+    TxNotFound(TxId),
+
+    /// Can only request depths up to a certain maximum.
+    #[error(
+        "400: Requested depth {0} is too big, maximum is {}",
+        MAX_TX_GRAPH_DEPTH
+    )]
+    RequestDepthTooBig(usize),
+
+    /// Transaction in DB without block
+    #[error("500: Inconsistent DB: {0} has no block")]
+    DbTxHasNoBlock(TxId),
+
+    /// DB contains a spent-by entry whose referenced tx_num cannot be found.
+    #[error("500: Inconsistent DB: Spending tx num {0} not found")]
+    SpendingTxNotFound(TxNum),
+
+    /// Reading failed, likely corrupted block data
+    #[error("500: Reading {0} failed")]
+    ReadFailure(TxId),
+}
+
+use self::QueryTxGraphError::*;
+
+/// Direction in which the graph is walked.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Direction {
+    /// Walk the inputs of txs
+    Ancestors,
+    /// Walk the spenders of the outputs of txs
+    Descendants,
+}
+
+/// A tx loaded from the mempool or the DB.
+struct GraphTx {
+    tx: Tx,
+    tx_num: Option<TxNum>,
+    block: Option<DbBlock>,
+    is_coinbase: bool,
+}
+
+impl<'a> QueryTxGraph<'a> {
+    /// Query the ancestors of the tx, i.e. the txs it spends from,
+    /// recursively up to the given depth.
+    pub fn ancestors(
+        &self,
+        txid: &TxId,
+        depth: usize,
+    ) -> Result<proto::TxGraph> {
+        self.walk(txid, depth, Direction::Ancestors)
+    }
+
+    /// Query the descendants of the tx, i.e. the txs spending its outputs,
+    /// recursively up to the given depth.
+    pub fn descendants(
+        &self,
+        txid: &TxId,
+        depth: usize,
+    ) -> Result<proto::TxGraph> {
+        self.walk(txid, depth, Direction::Descendants)
+    }
+
+    /// Walk the graph breadth-first, so the queried tx comes first and txs
+    /// closer to it come before txs further away.
+    fn walk(
+        &self,
+        txid: &TxId,
+        max_depth: usize,
+        direction: Direction,
+    ) -> Result<proto::TxGraph> {
+        if max_depth > MAX_TX_GRAPH_DEPTH {
+            return Err(RequestDepthTooBig(max_depth).into());
+        }
+        let tx_reader = TxReader::new(self.db)?;
+        let block_reader = BlockReader::new(self.db)?;
+        let spent_by_reader = SpentByReader::new(self.db)?;
+
+        let mut nodes = Vec::new();
+        let mut visited = HashSet::from([*txid]);
+        let mut is_truncated = false;
+        let mut queue = VecDeque::from([(*txid, 0)]);
+        while let Some((txid, depth)) = queue.pop_front() {
+            let graph_tx =
+                match self.load_tx(&tx_reader, &block_reader, &txid)? {
+                    Some(graph_tx) => graph_tx,
+                    // Only the queried tx has to exist, others might be
+                    // pruned from the mempool concurrently
+                    None if depth == 0 => return Err(TxNotFound(txid).into()),
+                    None => continue,
+                };
+            let linked_txids = match direction {
+                // Coinbase txs don't spend any tx
+                Direction::Ancestors if graph_tx.is_coinbase => BTreeSet::new(),
+                Direction::Ancestors => graph_tx
+                    .tx
+                    .inputs
+                    .iter()
+                    .map(|input| input.prev_out.txid)
+                    .collect(),
+                Direction::Descendants => self.spender_txids(
+                    &tx_reader,
+                    &spent_by_reader,
+                    &txid,
+                    graph_tx.tx_num,
+                )?,
+            };
+            if depth < max_depth {
+                for &linked_txid in &linked_txids {
+                    if visited.contains(&linked_txid) {
+                        continue;
+                    }
+                    if visited.len() >= MAX_TX_GRAPH_TXS {
+                        is_truncated = true;
+                        break;
+                    }
+                    visited.insert(linked_txid);
+                    queue.push_back((linked_txid, depth + 1));
+                }
+            }
+            nodes.push(self.make_node_proto(graph_tx, depth, linked_txids));
+        }
+
+        Ok(proto::TxGraph {
+            txs: nodes,
+            is_truncated,
+        })
+    }
+
+    fn load_tx(
+        &self,
+        tx_reader: &TxReader<'_>,
+        block_reader: &BlockReader<'_>,
+        txid: &TxId,
+    ) -> Result<Option<GraphTx>> {
+        if let Some(mempool_tx) = self.mempool.tx(txid) {
+            return Ok(Some(GraphTx {
+                tx: mempool_tx.tx.clone(),
+                tx_num: None,
+                block: None,
+                is_coinbase: false,
+            }));
+        }
+        let (tx_num, block_tx) = match tx_reader.tx_and_num_by_txid(txid)? {
+            Some(tx_and_num) => tx_and_num,
+            None => return Ok(None),
+        };
+        let block = block_reader
+            .by_height(block_tx.block_height)?
+            .ok_or(DbTxHasNoBlock(*txid))?;
+        let tx = ffi::load_tx(
+            block.file_num,
+            block_tx.entry.data_pos,
+            block_tx.entry.undo_pos,
+        )
+        .wrap_err(ReadFailure(*txid))?;
+        Ok(Some(GraphTx {
+            tx: Tx::from(tx),
+            tx_num: Some(tx_num),
+            block: Some(block),
+            is_coinbase: block_tx.entry.is_coinbase,
+        }))
+    }
+
+    fn spender_txids(
+        &self,
+        tx_reader: &TxReader<'_>,
+        spent_by_reader: &SpentByReader<'_>,
+        txid: &TxId,
+        tx_num: Option<TxNum>,
+    ) -> Result<BTreeSet<TxId>> {
+        let mut spender_txids = BTreeSet::new();
+        if let Some(spent_by) = self.mempool.spent_by().outputs_spent(txid) {
+            spender_txids.extend(spent_by.values().map(|spent| spent.txid));
+        }
+        if let Some(tx_num) = tx_num {
+            let entries =
+                spent_by_reader.by_tx_num(tx_num)?.unwrap_or_default();
+            for entry in entries {
+                let spender_txid = tx_reader
+                    .txid_by_tx_num(entry.tx_num)?
+                    .ok_or(SpendingTxNotFound(entry.tx_num))?;
+                spender_txids.insert(spender_txid);
+            }
+        }
+        Ok(spender_txids)
+    }
+
+    fn make_node_proto(
+        &self,
+        graph_tx: GraphTx,
+        depth: usize,
+        linked_txids: BTreeSet<TxId>,
+    ) -> proto::TxGraphNode {
+        proto::TxGraphNode {
+            txid: graph_tx.tx.txid().to_vec(),
+            size: graph_tx.tx.ser_len() as u32,
+            fee: match graph_tx.is_coinbase {
+                true => 0,
+                false => tx_fee(&graph_tx.tx).unwrap_or_default(),
+            },
+            block: graph_tx.block.map(|block| proto::BlockMetadata {
+                height: block.height,
+                hash: block.hash.to_vec(),
+                timestamp: block.timestamp,
+                is_final: self.avalanche.is_final_height(block.height),
+            }),
+            depth: depth as u32,
+            linked_txids: linked_txids
+                .into_iter()
+                .map(|txid| txid.to_vec())
+                .collect(),
+        }
+    }
+}
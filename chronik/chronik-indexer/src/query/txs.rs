@@ -6,13 +6,16 @@
 
 use abc_rust_error::{Result, WrapErr};
 use bitcoinsuite_core::{
+    block::BlockHeader,
+    hash::Hashed,
+    merkle::{merkle_branch, verify_merkle_proof},
     ser::BitcoinSer,
-    tx::{Tx, TxId},
+    tx::{OutPoint, SpentBy, Tx, TxId},
 };
 use chronik_bridge::ffi;
 use chronik_db::{
     db::Db,
-    io::{BlockReader, SpentByReader, TxReader},
+    io::{BlockReader, SpentByReader, TokenTxReader, TxReader},
     mem::Mempool,
 };
 use chronik_proto::proto;
@@ -20,7 +23,11 @@ use thiserror::Error;
 
 use crate::{
     avalanche::Avalanche,
-    query::{make_tx_proto, OutputsSpent},
+    conflicts::{ConflictType, Conflicts},
+    query::{
+        make_outpoint_proto, make_spent_by_proto, make_tx_proto,
+        mempool_token_tx, OutputsSpent,
+    },
 };
 
 /// Struct for querying txs from the db/mempool.
@@ -32,6 +39,8 @@ pub struct QueryTxs<'a> {
     pub avalanche: &'a Avalanche,
     /// Mempool
     pub mempool: &'a Mempool,
+    /// Recent conflicts of mempool txs with mined txs
+    pub conflicts: &'a Conflicts,
 }
 
 /// Errors indicating something went wrong with reading txs.
@@ -48,12 +57,33 @@ pub enum QueryTxError {
     /// Reading failed, likely corrupted block data
     #[error("500: Reading {0} failed")]
     ReadFailure(TxId),
+
+    /// Transaction is in the mempool, so it has no merkle proof yet.
+    #[error("400: Transaction {0} not mined yet")]
+    TxNotMined(TxId),
+
+    /// Block of a tx has no txs in the DB.
+    #[error("500: Inconsistent DB: Block {0} has no txs")]
+    BlockHasNoTxs(i32),
+
+    /// Reading the header failed, likely corrupted block data
+    #[error("500: Reading header of block {0} failed")]
+    ReadHeaderFailure(i32),
+
+    /// Merkle root of the txs doesn't match the header.
+    #[error("500: Inconsistent DB: Merkle root mismatch for block {0}")]
+    MerkleRootMismatch(i32),
+
+    /// DB contains a spent-by entry whose spending tx cannot be found.
+    #[error("500: Inconsistent DB: Spending tx of {0:?} not found")]
+    SpendingTxNotFound(OutPoint),
 }
 
 use self::QueryTxError::*;
 
 impl<'a> QueryTxs<'a> {
-    /// Query a tx by txid from the mempool or DB.
+    /// Query a tx by txid from the mempool or DB, or from the recently
+    /// conflicted txs.
     pub fn tx_by_id(&self, txid: TxId) -> Result<proto::Tx> {
         match self.mempool.tx(&txid) {
             Some(tx) => Ok(make_tx_proto(
@@ -65,12 +95,15 @@ impl<'a> QueryTxs<'a> {
                 false,
                 None,
                 self.avalanche,
+                mempool_token_tx(self.mempool, &txid).as_ref(),
             )),
             None => {
                 let tx_reader = TxReader::new(self.db)?;
-                let (tx_num, block_tx) = tx_reader
-                    .tx_and_num_by_txid(&txid)?
-                    .ok_or(TxNotFound(txid))?;
+                let (tx_num, block_tx) =
+                    match tx_reader.tx_and_num_by_txid(&txid)? {
+                        Some(tx_and_num) => tx_and_num,
+                        None => return self.conflicted_tx_by_id(txid),
+                    };
                 let tx_entry = block_tx.entry;
                 let block_reader = BlockReader::new(self.db)?;
                 let spent_by_reader = SpentByReader::new(self.db)?;
@@ -89,6 +122,8 @@ impl<'a> QueryTxs<'a> {
                     self.mempool.spent_by().outputs_spent(&txid),
                     tx_num,
                 )?;
+                let token_tx_reader = TokenTxReader::new(self.db)?;
+                let token_tx = token_tx_reader.by_tx_num(tx_num)?;
                 Ok(make_tx_proto(
                     &Tx::from(tx),
                     &outputs_spent,
@@ -96,11 +131,32 @@ impl<'a> QueryTxs<'a> {
                     tx_entry.is_coinbase,
                     Some(&block),
                     self.avalanche,
+                    token_tx.as_ref(),
                 ))
             }
         }
     }
 
+    fn conflicted_tx_by_id(&self, txid: TxId) -> Result<proto::Tx> {
+        let conflict = self.conflicts.get(&txid).ok_or(TxNotFound(txid))?;
+        let mut tx = make_tx_proto(
+            &conflict.tx.tx,
+            &OutputsSpent::new_mempool(None),
+            conflict.tx.time_first_seen,
+            false,
+            None,
+            self.avalanche,
+            None,
+        );
+        tx.conflict = Some(proto::TxConflict {
+            is_double_spend: conflict.conflict_type
+                == ConflictType::DoubleSpent,
+            conflicting_txid: conflict.conflicting_txid.to_vec(),
+            block_height: conflict.block_height,
+        });
+        Ok(tx)
+    }
+
     /// Query the raw serialized tx by txid.
     ///
     /// Serializes the tx if it's in the mempool, or reads the tx data from the
@@ -122,4 +178,157 @@ impl<'a> QueryTxs<'a> {
         };
         Ok(proto::RawTx { raw_tx })
     }
+
+    /// Query a single output, i.e. its value and script, and whether and by
+    /// which input it has been spent.
+    ///
+    /// Unlike [`QueryTxs::tx_by_id`], this doesn't query the spent coins of
+    /// the inputs or any token data. Unknown outputs result in
+    /// [`proto::OutpointState::NotFound`] instead of an error.
+    pub fn outpoint(&self, outpoint: &OutPoint) -> Result<proto::OutpointInfo> {
+        let mut info = proto::OutpointInfo {
+            outpoint: Some(make_outpoint_proto(outpoint)),
+            state: proto::OutpointState::NotFound as _,
+            spent_by_height: -1,
+            ..Default::default()
+        };
+        let spent_by_mempool = self
+            .mempool
+            .spent_by()
+            .outputs_spent(&outpoint.txid)
+            .and_then(|spent_by| spent_by.get(&outpoint.out_idx));
+        if let Some(mempool_tx) = self.mempool.tx(&outpoint.txid) {
+            let output =
+                match mempool_tx.tx.outputs.get(outpoint.out_idx as usize) {
+                    Some(output) => output,
+                    None => return Ok(info),
+                };
+            info.value = output.value;
+            info.output_script = output.script.to_vec();
+            Self::set_spent_by(&mut info, spent_by_mempool, None);
+            return Ok(info);
+        }
+
+        let tx_reader = TxReader::new(self.db)?;
+        let (tx_num, block_tx) =
+            match tx_reader.tx_and_num_by_txid(&outpoint.txid)? {
+                Some(tx_and_num) => tx_and_num,
+                None => return Ok(info),
+            };
+        let block_reader = BlockReader::new(self.db)?;
+        let block = block_reader
+            .by_height(block_tx.block_height)?
+            .ok_or(DbTxHasNoBlock(outpoint.txid))?;
+        let raw_tx = ffi::load_raw_tx(block.file_num, block_tx.entry.data_pos)
+            .wrap_err(ReadFailure(outpoint.txid))?;
+        let tx = Tx::deser(&mut raw_tx.into())
+            .wrap_err(ReadFailure(outpoint.txid))?;
+        let output = match tx.outputs.get(outpoint.out_idx as usize) {
+            Some(output) => output,
+            None => return Ok(info),
+        };
+        info.value = output.value;
+        info.output_script = output.script.to_vec();
+        info.block = Some(proto::BlockMetadata {
+            height: block.height,
+            hash: block.hash.to_vec(),
+            timestamp: block.timestamp,
+            is_final: self.avalanche.is_final_height(block.height),
+        });
+        if spent_by_mempool.is_some() {
+            Self::set_spent_by(&mut info, spent_by_mempool, None);
+            return Ok(info);
+        }
+
+        let spent_by_reader = SpentByReader::new(self.db)?;
+        let spent_by_blocks =
+            spent_by_reader.by_tx_num(tx_num)?.unwrap_or_default();
+        let spent_by_entry = spent_by_blocks
+            .iter()
+            .find(|entry| entry.out_idx == outpoint.out_idx);
+        match spent_by_entry {
+            Some(entry) => {
+                let spending_tx = tx_reader
+                    .tx_by_tx_num(entry.tx_num)?
+                    .ok_or(SpendingTxNotFound(*outpoint))?;
+                let spent_by = SpentBy {
+                    txid: spending_tx.entry.txid,
+                    input_idx: entry.input_idx,
+                };
+                Self::set_spent_by(
+                    &mut info,
+                    Some(&spent_by),
+                    Some(spending_tx.block_height),
+                );
+            }
+            None => Self::set_spent_by(&mut info, None, None),
+        }
+        Ok(info)
+    }
+
+    fn set_spent_by(
+        info: &mut proto::OutpointInfo,
+        spent_by: Option<&SpentBy>,
+        spent_by_height: Option<i32>,
+    ) {
+        info.state = match (spent_by, spent_by_height) {
+            (None, _) => proto::OutpointState::Unspent,
+            (Some(_), None) => proto::OutpointState::SpentInMempool,
+            (Some(_), Some(_)) => proto::OutpointState::SpentInBlock,
+        } as _;
+        info.spent_by = spent_by.map(make_spent_by_proto);
+        info.spent_by_height = spent_by_height.unwrap_or(-1);
+    }
+
+    /// Query the merkle proof of a mined tx, i.e. the header of its block,
+    /// its index in the block and its merkle branch.
+    ///
+    /// Txs of the block are read from the DB and the merkle root is checked
+    /// against the header before returning the proof.
+    pub fn merkle_proof(&self, txid: &TxId) -> Result<proto::TxMerkleProof> {
+        if self.mempool.tx(txid).is_some() {
+            return Err(TxNotMined(*txid).into());
+        }
+        let tx_reader = TxReader::new(self.db)?;
+        let block_reader = BlockReader::new(self.db)?;
+        let (tx_num, block_tx) = tx_reader
+            .tx_and_num_by_txid(txid)?
+            .ok_or(TxNotFound(*txid))?;
+        let block_height = block_tx.block_height;
+        let block = block_reader
+            .by_height(block_height)?
+            .ok_or(DbTxHasNoBlock(*txid))?;
+        let tx_num_range = tx_reader
+            .block_tx_num_range(block_height)?
+            .ok_or(BlockHasNoTxs(block_height))?;
+        let tx_idx = (tx_num - tx_num_range.start) as usize;
+        let txids = tx_num_range
+            .map(|tx_num| {
+                tx_reader
+                    .txid_by_tx_num(tx_num)?
+                    .ok_or_else(|| BlockHasNoTxs(block_height).into())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let raw_header =
+            ffi::load_raw_block_header(block.file_num, block.data_pos)
+                .wrap_err(ReadHeaderFailure(block_height))?;
+        let header = BlockHeader::deser(&mut raw_header.clone().into())
+            .wrap_err(ReadHeaderFailure(block_height))?;
+        let branch =
+            merkle_branch(&txids, tx_idx).ok_or(BlockHasNoTxs(block_height))?;
+        if !verify_merkle_proof(txid, tx_idx, &branch, &header) {
+            return Err(MerkleRootMismatch(block_height).into());
+        }
+
+        Ok(proto::TxMerkleProof {
+            block_header: raw_header,
+            block_height,
+            tx_idx: tx_idx as u32,
+            branch: branch
+                .iter()
+                .map(|hash| hash.as_le_bytes().to_vec())
+                .collect(),
+        })
+    }
 }
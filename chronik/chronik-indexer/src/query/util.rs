@@ -13,8 +13,13 @@ use bitcoinsuite_core::{
     ser::BitcoinSer,
     tx::{OutPoint, SpentBy, Tx, TxId},
 };
-use chronik_db::io::{
-    BlockHeight, DbBlock, SpentByEntry, SpentByReader, TxNum, TxReader,
+use chronik_db::{
+    fee::{fee_rate, tx_fee},
+    io::{
+        BlockHeight, DbBlock, DbToken, DbTokenEntry, DbTokenTx, DbTokenType,
+        DbTxType, SpentByEntry, SpentByReader, TxNum, TxReader,
+    },
+    mem::Mempool,
 };
 use chronik_proto::proto;
 use thiserror::Error;
@@ -51,14 +56,21 @@ pub(crate) fn make_tx_proto(
     is_coinbase: bool,
     block: Option<&DbBlock>,
     avalanche: &Avalanche,
+    token_tx: Option<&DbTokenTx>,
 ) -> proto::Tx {
+    let size = tx.ser_len();
+    let fee = match is_coinbase {
+        true => 0,
+        false => tx_fee(tx).unwrap_or_default(),
+    };
     proto::Tx {
         txid: tx.txid().to_vec(),
         version: tx.version,
         inputs: tx
             .inputs
             .iter()
-            .map(|input| {
+            .enumerate()
+            .map(|(input_idx, input)| {
                 let coin = input.coin.as_ref();
                 let (output_script, value) = coin
                     .map(|coin| {
@@ -71,6 +83,10 @@ pub(crate) fn make_tx_proto(
                     output_script,
                     value,
                     sequence_no: input.sequence,
+                    token: token_tx.and_then(|token_tx| {
+                        let token = token_tx.inputs.get(input_idx)?.as_ref()?;
+                        Some(make_token_proto(token_tx, token))
+                    }),
                 }
             })
             .collect(),
@@ -84,6 +100,10 @@ pub(crate) fn make_tx_proto(
                 spent_by: outputs_spent
                     .spent_by(output_idx as u32)
                     .map(|spent_by| make_spent_by_proto(&spent_by)),
+                token: token_tx.and_then(|token_tx| {
+                    let token = token_tx.outputs.get(output_idx)?.as_ref()?;
+                    Some(make_token_proto(token_tx, token))
+                }),
             })
             .collect(),
         lock_time: tx.locktime,
@@ -94,8 +114,93 @@ pub(crate) fn make_tx_proto(
             is_final: avalanche.is_final_height(block.height),
         }),
         time_first_seen,
-        size: tx.ser_len() as u32,
+        size: size as u32,
         is_coinbase,
+        token_entries: token_tx
+            .map(|token_tx| {
+                token_tx
+                    .entries
+                    .iter()
+                    .map(make_token_entry_proto)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        token_failed_parsings: token_tx
+            .map(|token_tx| token_tx.failed_parsings.clone())
+            .unwrap_or_default(),
+        conflict: None,
+        fee,
+        fee_rate: fee_rate(fee, size),
+    }
+}
+
+/// Token data of a mempool tx, in the same format as stored in the DB, or
+/// [`None`] if the tx doesn't involve any tokens.
+pub(crate) fn mempool_token_tx(
+    mempool: &Mempool,
+    txid: &TxId,
+) -> Option<DbTokenTx> {
+    let tx_tokens = mempool.tokens().txs.get(txid)?;
+    Some(DbTokenTx::from_colored(
+        &tx_tokens.colored,
+        &tx_tokens.spent_tokens,
+    ))
+}
+
+/// Make a [`proto::Token`] for a token of an input or output of the tx.
+pub(crate) fn make_token_proto(
+    token_tx: &DbTokenTx,
+    db_token: &DbToken,
+) -> proto::Token {
+    let entry = &token_tx.entries[db_token.entry_idx as usize];
+    proto::Token {
+        token_id: entry.token_id.to_vec(),
+        token_type: Some(make_token_type_proto(entry.token_type)),
+        entry_idx: db_token.entry_idx as i32,
+        amount: db_token.amount.unwrap_or_default(),
+        is_mint_baton: db_token.amount.is_none(),
+    }
+}
+
+fn make_token_entry_proto(entry: &DbTokenEntry) -> proto::TokenEntry {
+    proto::TokenEntry {
+        token_id: entry.token_id.to_vec(),
+        token_type: Some(make_token_type_proto(entry.token_type)),
+        tx_type: match entry.tx_type {
+            None => proto::TokenTxType::None,
+            Some(DbTxType::Genesis) => proto::TokenTxType::Genesis,
+            Some(DbTxType::Mint) => proto::TokenTxType::Mint,
+            Some(DbTxType::Send) => proto::TokenTxType::Send,
+            Some(DbTxType::Burn) => proto::TokenTxType::Burn,
+        } as _,
+        is_invalid: !entry.failures.is_empty(),
+        failures: entry.failures.clone(),
+        intentional_burn: entry.intentional_burn.to_string(),
+        actual_burn: entry.actual_burn.to_string(),
+        burns_mint_batons: entry.burns_mint_batons,
+    }
+}
+
+pub(crate) fn make_token_type_proto(
+    token_type: DbTokenType,
+) -> proto::TokenType {
+    use proto::token_type::TokenType;
+    let token_type = match token_type {
+        DbTokenType::SlpFungible => {
+            TokenType::Slp(proto::SlpTokenType::Fungible as _)
+        }
+        DbTokenType::SlpNft1Group => {
+            TokenType::Slp(proto::SlpTokenType::Nft1Group as _)
+        }
+        DbTokenType::SlpNft1Child => {
+            TokenType::Slp(proto::SlpTokenType::Nft1Child as _)
+        }
+        DbTokenType::AlpStandard => {
+            TokenType::Alp(proto::AlpTokenType::Standard as _)
+        }
+    };
+    proto::TokenType {
+        token_type: Some(token_type),
     }
 }
 
@@ -106,7 +211,7 @@ pub(crate) fn make_outpoint_proto(outpoint: &OutPoint) -> proto::OutPoint {
     }
 }
 
-fn make_spent_by_proto(spent_by: &SpentBy) -> proto::SpentBy {
+pub(crate) fn make_spent_by_proto(spent_by: &SpentBy) -> proto::SpentBy {
     proto::SpentBy {
         txid: spent_by.txid.to_vec(),
         input_idx: spent_by.input_idx,
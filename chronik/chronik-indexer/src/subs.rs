@@ -4,12 +4,20 @@
 
 //! Module containing [`Subs`].
 
-use bitcoinsuite_core::{block::BlockHash, tx::Tx};
-use chronik_db::{groups::ScriptGroup, io::BlockHeight};
+use std::{collections::HashMap, hash::Hash};
+
+use bitcoinsuite_core::{
+    block::BlockHash,
+    tx::{OutPoint, Tx, TxId},
+};
+use chronik_db::{
+    groups::{LokadIdGroup, ScriptGroup, ScriptHashGroup},
+    io::BlockHeight,
+};
 use chronik_util::log;
 use tokio::sync::broadcast;
 
-use crate::subs_group::{SubsGroup, TxMsgType};
+use crate::subs_group::{SubsGroup, TxMsg, TxMsgType};
 
 /// Block update message.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,6 +28,8 @@ pub struct BlockMsg {
     pub hash: BlockHash,
     /// Height of the block which we got an update for.
     pub height: BlockHeight,
+    /// Serialized header of the block which we got an update for.
+    pub header: Vec<u8>,
 }
 
 /// Type of message for the block.
@@ -34,12 +44,17 @@ pub enum BlockMsgType {
 }
 
 const BLOCK_CHANNEL_CAPACITY: usize = 16;
+const TX_CHANNEL_CAPACITY: usize = 16;
 
 /// Struct for managing subscriptions to e.g. block updates.
 #[derive(Debug)]
 pub struct Subs {
     subs_block: broadcast::Sender<BlockMsg>,
     subs_script: SubsGroup<ScriptGroup>,
+    subs_lokad_id: SubsGroup<LokadIdGroup>,
+    subs_script_hash: SubsGroup<ScriptHashGroup>,
+    subs_txid: HashMap<TxId, broadcast::Sender<TxMsg>>,
+    subs_outpoint: HashMap<OutPoint, broadcast::Sender<TxMsg>>,
 }
 
 impl Subs {
@@ -48,6 +63,10 @@ impl Subs {
         Subs {
             subs_block: broadcast::channel(BLOCK_CHANNEL_CAPACITY).0,
             subs_script: SubsGroup::new(script_group),
+            subs_lokad_id: SubsGroup::new(LokadIdGroup),
+            subs_script_hash: SubsGroup::new(ScriptHashGroup),
+            subs_txid: HashMap::new(),
+            subs_outpoint: HashMap::new(),
         }
     }
 
@@ -61,9 +80,60 @@ impl Subs {
         &mut self.subs_script
     }
 
+    /// Mutable reference to the LOKAD ID subscribers.
+    pub fn subs_lokad_id_mut(&mut self) -> &mut SubsGroup<LokadIdGroup> {
+        &mut self.subs_lokad_id
+    }
+
+    /// Mutable reference to the script hash subscribers.
+    pub fn subs_script_hash_mut(&mut self) -> &mut SubsGroup<ScriptHashGroup> {
+        &mut self.subs_script_hash
+    }
+
+    /// Subscribe to updates about the tx with the given txid.
+    pub fn subscribe_to_txid(
+        &mut self,
+        txid: TxId,
+    ) -> broadcast::Receiver<TxMsg> {
+        subscribe_to_key(&mut self.subs_txid, txid)
+    }
+
+    /// Cleanly unsubscribe from a txid. This will try to deallocate the memory
+    /// used by a subscriber.
+    pub fn unsubscribe_from_txid(&mut self, txid: &TxId) {
+        unsubscribe_from_key(&mut self.subs_txid, txid)
+    }
+
+    /// Subscribe to updates about txs spending the given outpoint. Subscribers
+    /// will receive messages with the txid of the spending tx.
+    pub fn subscribe_to_outpoint(
+        &mut self,
+        outpoint: OutPoint,
+    ) -> broadcast::Receiver<TxMsg> {
+        subscribe_to_key(&mut self.subs_outpoint, outpoint)
+    }
+
+    /// Cleanly unsubscribe from an outpoint. This will try to deallocate the
+    /// memory used by a subscriber.
+    pub fn unsubscribe_from_outpoint(&mut self, outpoint: &OutPoint) {
+        unsubscribe_from_key(&mut self.subs_outpoint, outpoint)
+    }
+
     /// Send out updates to subscribers for this tx and msg_type.
     pub fn handle_tx_event(&mut self, tx: &Tx, msg_type: TxMsgType) {
-        self.subs_script.handle_tx_event(tx, msg_type);
+        self.subs_script.handle_tx_event(tx, msg_type.clone(), &());
+        self.subs_lokad_id
+            .handle_tx_event(tx, msg_type.clone(), &());
+        self.subs_script_hash
+            .handle_tx_event(tx, msg_type.clone(), &());
+        let msg = TxMsg {
+            msg_type,
+            txid: tx.txid(),
+        };
+        send_to_key(&mut self.subs_txid, &msg.txid, &msg);
+        for input in &tx.inputs {
+            send_to_key(&mut self.subs_outpoint, &input.prev_out, &msg);
+        }
     }
 
     pub(crate) fn broadcast_block_msg(&self, msg: BlockMsg) {
@@ -74,3 +144,93 @@ impl Subs {
         }
     }
 }
+
+fn subscribe_to_key<K: Eq + Hash>(
+    subs: &mut HashMap<K, broadcast::Sender<TxMsg>>,
+    key: K,
+) -> broadcast::Receiver<TxMsg> {
+    subs.entry(key)
+        .or_insert_with(|| broadcast::channel(TX_CHANNEL_CAPACITY).0)
+        .subscribe()
+}
+
+fn unsubscribe_from_key<K: Eq + Hash>(
+    subs: &mut HashMap<K, broadcast::Sender<TxMsg>>,
+    key: &K,
+) {
+    if let Some(sender) = subs.get(key) {
+        if sender.receiver_count() == 0 {
+            subs.remove(key);
+        }
+    }
+}
+
+fn send_to_key<K: Eq + Hash>(
+    subs: &mut HashMap<K, broadcast::Sender<TxMsg>>,
+    key: &K,
+    msg: &TxMsg,
+) {
+    if let Some(sender) = subs.get(key) {
+        // Unclean unsubscribe
+        if sender.send(msg.clone()).is_err() {
+            subs.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoinsuite_core::tx::{OutPoint, Tx, TxId, TxInput, TxMut};
+    use chronik_db::groups::{prefix_mock_compress, ScriptGroup};
+
+    use crate::{
+        subs::Subs,
+        subs_group::{TxMsg, TxMsgType},
+    };
+
+    #[test]
+    fn test_subs_txid_outpoint() {
+        let mut subs = Subs::new(ScriptGroup::new(prefix_mock_compress));
+        let outpoint = OutPoint {
+            txid: TxId::from([1; 32]),
+            out_idx: 2,
+        };
+        let tx = Tx::with_txid(
+            TxId::from([3; 32]),
+            TxMut {
+                inputs: vec![TxInput {
+                    prev_out: outpoint,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+        let mut recv_txid = subs.subscribe_to_txid(tx.txid());
+        let mut recv_outpoint = subs.subscribe_to_outpoint(outpoint);
+        let mut recv_other = subs.subscribe_to_outpoint(OutPoint {
+            txid: TxId::from([1; 32]),
+            out_idx: 3,
+        });
+
+        subs.handle_tx_event(&tx, TxMsgType::AddedToMempool);
+        let expected_msg = TxMsg {
+            msg_type: TxMsgType::AddedToMempool,
+            txid: tx.txid(),
+        };
+        assert_eq!(recv_txid.try_recv(), Ok(expected_msg.clone()));
+        assert_eq!(recv_outpoint.try_recv(), Ok(expected_msg));
+        assert!(recv_other.try_recv().is_err());
+
+        // Unsubscribing only deallocates once all receivers are dropped
+        subs.unsubscribe_from_txid(&tx.txid());
+        assert!(subs.subs_txid.contains_key(&tx.txid()));
+        drop(recv_txid);
+        subs.unsubscribe_from_txid(&tx.txid());
+        assert!(!subs.subs_txid.contains_key(&tx.txid()));
+
+        // Dropped receivers are cleaned up when sending the next message
+        drop(recv_outpoint);
+        subs.handle_tx_event(&tx, TxMsgType::Confirmed);
+        assert!(!subs.subs_outpoint.contains_key(&outpoint));
+    }
+}
@@ -30,6 +30,12 @@ pub enum TxMsgType {
     Confirmed,
     /// Tx was finalized by Avalanche.
     Finalized,
+    /// Tx was removed from the mempool because a mined tx spent one of the
+    /// same outputs.
+    DoubleSpent,
+    /// Tx was removed from the mempool because one of its ancestors got
+    /// double-spent.
+    Conflicted,
 }
 
 const GROUP_CHANNEL_CAPACITY: usize = 16;
@@ -83,7 +89,17 @@ impl<G: Group> SubsGroup<G> {
     }
 
     /// Send out updates to subscribers for this tx and msg_type.
-    pub fn handle_tx_event(&mut self, tx: &Tx, msg_type: TxMsgType) {
+    pub fn handle_tx_event(
+        &mut self,
+        tx: &Tx,
+        msg_type: TxMsgType,
+        aux: &G::Aux,
+    ) {
+        // Skip computing the members, e.g. hashing every script, if there's
+        // nobody to notify anyway
+        if self.subs.is_empty() {
+            return;
+        }
         let query = GroupQuery {
             is_coinbase: false,
             tx,
@@ -93,7 +109,7 @@ impl<G: Group> SubsGroup<G> {
             txid: tx.txid(),
         };
         let mut already_notified = HashSet::new();
-        for member in tx_members_for_group(&self.group, query) {
+        for member in tx_members_for_group(&self.group, query, aux) {
             if !already_notified.contains(&member) {
                 let member_ser = self.group.ser_member(&member);
                 if let Some(sender) = self.subs.get(member_ser.as_ref()) {
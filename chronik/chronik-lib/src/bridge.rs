@@ -16,6 +16,7 @@ use bitcoinsuite_core::{
 };
 use chronik_bridge::{ffi::init_error, util::expect_unique_ptr};
 use chronik_db::mem::MempoolTx;
+use chronik_electrum::server::{ElectrumServer, ElectrumServerParams};
 use chronik_http::server::{ChronikServer, ChronikServerParams};
 use chronik_indexer::indexer::{ChronikIndexer, ChronikIndexerParams, Node};
 use chronik_util::{log, log_chronik};
@@ -64,12 +65,22 @@ fn try_setup_chronik(
         .map(|host| parse_socket_addr(host, params.default_port))
         .collect::<Result<Vec<_>>>()?;
     log!("Starting Chronik bound to {:?}\n", hosts);
+    let electrum_hosts = params
+        .electrum_hosts
+        .into_iter()
+        .map(|host| parse_socket_addr(host, params.electrum_default_port))
+        .collect::<Result<Vec<_>>>()?;
+    if !electrum_hosts.is_empty() {
+        log!("Starting Chronik Electrum bound to {:?}\n", electrum_hosts);
+    }
     let bridge = chronik_bridge::ffi::make_bridge(config, node_context);
     let bridge_ref = expect_unique_ptr("make_bridge", &bridge);
     let mut indexer = ChronikIndexer::setup(ChronikIndexerParams {
         datadir_net: params.datadir_net.into(),
         wipe_db: params.wipe_db,
         fn_compress_script: compress_script,
+        persist_mempool: params.persist_mempool,
+        enable_script_hash_index: !electrum_hosts.is_empty(),
     })?;
     indexer.resync_indexer(bridge_ref)?;
     if chronik_bridge::ffi::shutdown_requested() {
@@ -96,6 +107,25 @@ fn try_setup_chronik(
     runtime.spawn(async move {
         ok_or_abort_node("ChronikServer::serve", server.serve().await);
     });
+    if !electrum_hosts.is_empty() {
+        let electrum_server = runtime.block_on({
+            let indexer = Arc::clone(&indexer);
+            let node = Arc::clone(&node);
+            async move {
+                ElectrumServer::setup(ElectrumServerParams {
+                    hosts: electrum_hosts,
+                    indexer,
+                    node,
+                })
+            }
+        })?;
+        runtime.spawn(async move {
+            ok_or_abort_node(
+                "ElectrumServer::serve",
+                electrum_server.serve().await,
+            );
+        });
+    }
     let chronik = Box::new(Chronik {
         node: Arc::clone(&node),
         indexer,
@@ -105,6 +135,15 @@ fn try_setup_chronik(
     Ok(())
 }
 
+impl Drop for Chronik {
+    fn drop(&mut self) {
+        let indexer = self.indexer.blocking_read();
+        if let Err(report) = indexer.save_mempool_snapshot() {
+            log!("Chronik: Failed saving mempool snapshot: {report}\n");
+        }
+    }
+}
+
 fn parse_socket_addr(host: String, default_port: u16) -> Result<SocketAddr> {
     if let Ok(addr) = host.parse::<SocketAddr>() {
         return Ok(addr);
@@ -143,13 +182,18 @@ impl Chronik {
         );
     }
 
-    /// Tx removed from the bitcoind mempool
-    pub fn handle_tx_removed_from_mempool(&self, txid: [u8; 32]) {
+    /// Tx removed from the bitcoind mempool, `is_conflict` if a tx of a block
+    /// that's about to be connected conflicts with it.
+    pub fn handle_tx_removed_from_mempool(
+        &self,
+        txid: [u8; 32],
+        is_conflict: bool,
+    ) {
         let mut indexer = self.indexer.blocking_write();
         let txid = TxId::from(txid);
         ok_or_abort_node(
             "handle_tx_removed_from_mempool",
-            indexer.handle_tx_removed_from_mempool(txid),
+            indexer.handle_tx_removed_from_mempool(txid, is_conflict),
         );
         log_chronik!("Chronik: transaction {} removed from mempool\n", txid);
     }
@@ -20,8 +20,15 @@ mod ffi_inner {
         pub hosts: Vec<String>,
         /// Default port for `hosts` if only an IP address is given
         pub default_port: u16,
+        /// Host addresses where the Electrum protocol will be served, none if
+        /// empty
+        pub electrum_hosts: Vec<String>,
+        /// Default port for `electrum_hosts` if only an IP address is given
+        pub electrum_default_port: u16,
         /// Whether to clear the DB before proceeding, e.g. when reindexing
         pub wipe_db: bool,
+        /// Whether to persist the mempool index across restarts
+        pub persist_mempool: bool,
     }
 
     extern "Rust" {
@@ -37,7 +44,11 @@ mod ffi_inner {
             ptx: &CTransaction,
             time_first_seen: i64,
         );
-        fn handle_tx_removed_from_mempool(&self, txid: [u8; 32]);
+        fn handle_tx_removed_from_mempool(
+            &self,
+            txid: [u8; 32],
+            is_conflict: bool,
+        );
         fn handle_block_connected(&self, block: &CBlock, bindex: &CBlockIndex);
         fn handle_block_disconnected(
             &self,
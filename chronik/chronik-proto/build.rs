@@ -2,8 +2,238 @@
 // Distributed under the MIT software license, see the accompanying
 // file COPYING or http://www.opensource.org/licenses/mit-license.php.
 
+use std::path::PathBuf;
+
+use prost::Message;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    FileDescriptorSet,
+};
+
+/// Hashes (txids, block hashes, token IDs), encoded as hex in big-endian
+/// display order in JSON.
+const HASH_FIELDS: &[&str] = &[
+    ".chronik.BlockchainInfo.tip_hash",
+    ".chronik.BlockInfo.hash",
+    ".chronik.BlockInfo.prev_hash",
+    ".chronik.Tx.txid",
+    ".chronik.OutPoint.txid",
+    ".chronik.SpentBy.txid",
+    ".chronik.Token.token_id",
+    ".chronik.TokenEntry.token_id",
+    ".chronik.TokenInfo.token_id",
+    ".chronik.BlockMetadata.hash",
+    ".chronik.TokenIdUtxos.token_id",
+    ".chronik.BroadcastTxResponse.txid",
+    ".chronik.MsgBlock.block_hash",
+    ".chronik.MsgTx.txid",
+    ".chronik.MempoolTxInfo.txid",
+    ".chronik.WsSubTxId.txid",
+    ".chronik.TxConflict.conflicting_txid",
+    ".chronik.TxGraphNode.txid",
+];
+
+/// Repeated hashes, encoded like [`HASH_FIELDS`].
+const HASH_VEC_FIELDS: &[&str] = &[
+    ".chronik.BroadcastTxsResponse.txids",
+    ".chronik.TxMerkleProof.branch",
+    ".chronik.TxGraphNode.linked_txids",
+];
+
+/// Other byte strings (scripts, raw txs, etc.), encoded as hex in JSON.
+const BYTES_FIELDS: &[&str] = &[
+    ".chronik.TxInput.input_script",
+    ".chronik.TxInput.output_script",
+    ".chronik.TxOutput.output_script",
+    ".chronik.GenesisInfo.token_ticker",
+    ".chronik.GenesisInfo.token_name",
+    ".chronik.GenesisInfo.url",
+    ".chronik.GenesisInfo.hash",
+    ".chronik.GenesisInfo.data",
+    ".chronik.GenesisInfo.auth_pubkey",
+    ".chronik.ScriptUtxos.script",
+    ".chronik.ScriptBalance.script",
+    ".chronik.RawTx.raw_tx",
+    ".chronik.TxMerkleProof.block_header",
+    ".chronik.OutpointInfo.output_script",
+    ".chronik.MsgHeader.header",
+    ".chronik.BroadcastTxRequest.raw_tx",
+    ".chronik.WsSubScript.payload",
+    ".chronik.ScriptRequest.payload",
+    ".chronik.WsSubLokadId.lokad_id",
+];
+
+/// Repeated byte strings, encoded like [`BYTES_FIELDS`].
+const BYTES_VEC_FIELDS: &[&str] = &[
+    ".chronik.BroadcastTxsRequest.raw_txs",
+    ".chronik.BlockHeaders.headers",
+];
+
+/// 64-bit integers, encoded as decimal strings in JSON, as JavaScript can't
+/// represent them losslessly.
+const INT64_FIELDS: &[&str] = &[
+    ".chronik.BlockInfo.timestamp",
+    ".chronik.BlockInfo.block_size",
+    ".chronik.BlockInfo.num_txs",
+    ".chronik.BlockInfo.num_inputs",
+    ".chronik.BlockInfo.num_outputs",
+    ".chronik.BlockInfo.sum_input_sats",
+    ".chronik.BlockInfo.sum_coinbase_output_sats",
+    ".chronik.BlockInfo.sum_normal_output_sats",
+    ".chronik.BlockInfo.sum_burned_sats",
+    ".chronik.BlockInfo.sum_fees",
+    ".chronik.BlockInfo.min_fee_rate",
+    ".chronik.BlockInfo.median_fee_rate",
+    ".chronik.BlockInfo.max_fee_rate",
+    ".chronik.Tx.time_first_seen",
+    ".chronik.Tx.fee",
+    ".chronik.Tx.fee_rate",
+    ".chronik.ScriptUtxo.value",
+    ".chronik.OutpointInfo.value",
+    ".chronik.TxGraphNode.fee",
+    ".chronik.ScriptBalance.confirmed_value",
+    ".chronik.ScriptBalance.unconfirmed_delta",
+    ".chronik.ScriptBalance.num_utxos",
+    ".chronik.ScriptBalance.num_txs",
+    ".chronik.TxInput.value",
+    ".chronik.TxOutput.value",
+    ".chronik.Token.amount",
+    ".chronik.BlockMetadata.timestamp",
+    ".chronik.MempoolTxInfo.time_first_seen",
+    ".chronik.MempoolTxInfo.fee",
+    ".chronik.MempoolInfo.num_txs",
+    ".chronik.MempoolInfo.num_bytes",
+    ".chronik.MempoolInfo.sum_fees",
+    ".chronik.FeeRateBucket.min_fee_rate",
+    ".chronik.FeeRateBucket.num_txs",
+    ".chronik.FeeRateBucket.num_bytes",
+    ".chronik.FeeEstimate.fee_rate",
+];
+
+/// Enum fields, encoded by the name of the variant in JSON, with the module
+/// in `crate::json` en-/decoding them.
+const ENUM_FIELDS: &[(&str, &str)] = &[
+    (".chronik.TokenType.token_type.slp", "slp_token_type"),
+    (".chronik.TokenType.token_type.alp", "alp_token_type"),
+    (".chronik.TokenEntry.tx_type", "token_tx_type"),
+    (".chronik.MsgBlock.msg_type", "block_msg_type"),
+    (".chronik.MsgHeader.msg_type", "block_msg_type"),
+    (".chronik.MsgTx.msg_type", "tx_msg_type"),
+    (".chronik.OutpointInfo.state", "outpoint_state"),
+];
+
 fn main() -> std::io::Result<()> {
-    prost_build::compile_protos(&["proto/chronik.proto"], &["proto"])?;
+    let mut config = prost_build::Config::new();
+    config
+        .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]");
+    config.type_attribute(".", "#[serde(rename_all = \"camelCase\")]");
+    config.message_attribute(".", "#[serde(default)]");
+    let with_fields = [
+        (HASH_FIELDS, "hash"),
+        (HASH_VEC_FIELDS, "hash_vec"),
+        (BYTES_FIELDS, "bytes"),
+        (BYTES_VEC_FIELDS, "bytes_vec"),
+        (INT64_FIELDS, "int64"),
+    ];
+    for (fields, module) in with_fields {
+        for field in fields {
+            config.field_attribute(
+                field,
+                format!("#[serde(with = \"crate::json::{module}\")]"),
+            );
+        }
+    }
+    for (field, module) in ENUM_FIELDS {
+        config.field_attribute(
+            field,
+            format!("#[serde(with = \"crate::json::{module}\")]"),
+        );
+    }
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    let descriptor_path = out_dir.join("chronik_descriptor.bin");
+    config.file_descriptor_set_path(&descriptor_path);
+    config.compile_protos(&["proto/chronik.proto"], &["proto"])?;
+    check_json_fields(&std::fs::read(&descriptor_path)?);
+
+    // Oneof fields are flattened into their message in JSON. This can't be
+    // done with field_attribute, as it would also apply to the variants.
+    let out_path = out_dir.join("chronik.rs");
+    let code = std::fs::read_to_string(&out_path)?;
+    let code =
+        code.replace("#[prost(oneof = ", "#[serde(flatten)]\n#[prost(oneof = ");
+    std::fs::write(&out_path, code)?;
     println!("cargo:rerun-if-changed=proto/chronik.proto");
     Ok(())
 }
+
+/// Check that every bytes and 64-bit integer field of the *.proto file is in
+/// exactly the field list matching its type, and that every listed field
+/// exists. Otherwise, a newly added field would silently be encoded as an
+/// array of numbers or as a lossy JSON number.
+fn check_json_fields(descriptor: &[u8]) {
+    let descriptor_set = FileDescriptorSet::decode(descriptor)
+        .expect("Invalid file descriptor set");
+    let mut expected_fields = Vec::new();
+    for file in &descriptor_set.file {
+        let package = file.package();
+        for message in &file.message_type {
+            for field in &message.field {
+                let is_repeated = field.label() == Label::Repeated;
+                let is_int64 = matches!(
+                    field.r#type(),
+                    Type::Int64
+                        | Type::Uint64
+                        | Type::Sint64
+                        | Type::Fixed64
+                        | Type::Sfixed64,
+                );
+                let lists: &[&[&str]] = match field.r#type() {
+                    Type::Bytes if is_repeated => {
+                        &[HASH_VEC_FIELDS, BYTES_VEC_FIELDS]
+                    }
+                    Type::Bytes => &[HASH_FIELDS, BYTES_FIELDS],
+                    // There's no JSON encoding for repeated 64-bit ints yet
+                    _ if is_int64 && is_repeated => &[],
+                    _ if is_int64 => &[INT64_FIELDS],
+                    _ => continue,
+                };
+                // Fields of a oneof are prefixed by the oneof name
+                let prefix = match field.oneof_index {
+                    Some(idx) => format!(
+                        ".{package}.{}.{}",
+                        message.name(),
+                        message.oneof_decl[idx as usize].name(),
+                    ),
+                    None => format!(".{package}.{}", message.name()),
+                };
+                let path = format!("{prefix}.{}", field.name());
+                let num_lists = lists
+                    .iter()
+                    .filter(|list| list.contains(&path.as_str()))
+                    .count();
+                if num_lists != 1 {
+                    panic!(
+                        "Field {path} must be in exactly one of the JSON \
+                         field lists for its type in build.rs"
+                    );
+                }
+                expected_fields.push(path);
+            }
+        }
+    }
+    let all_lists = [
+        HASH_FIELDS,
+        HASH_VEC_FIELDS,
+        BYTES_FIELDS,
+        BYTES_VEC_FIELDS,
+        INT64_FIELDS,
+    ];
+    for field in all_lists.iter().flat_map(|list| list.iter()) {
+        if !expected_fields.iter().any(|expected| expected == field) {
+            panic!(
+                "Field {field} listed in build.rs isn't a bytes or 64-bit \
+                 integer field in the *.proto file"
+            );
+        }
+    }
+}
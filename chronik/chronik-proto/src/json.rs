@@ -0,0 +1,190 @@
+// Copyright (c) 2023 The Bitcoin developers
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Serde helpers to en-/decode protobuf fields as JSON.
+//!
+//! Which fields use which module is configured in build.rs.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+fn decode_hex<'de, D: Deserializer<'de>>(
+    hex: &str,
+) -> Result<Vec<u8>, D::Error> {
+    hex::decode(hex).map_err(D::Error::custom)
+}
+
+fn rev(mut bytes: Vec<u8>) -> Vec<u8> {
+    bytes.reverse();
+    bytes
+}
+
+/// Byte strings as hex.
+pub(crate) mod bytes {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        bytes: &[u8],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        decode_hex::<D>(&String::deserialize(deserializer)?)
+    }
+}
+
+/// Repeated byte strings as hex.
+pub(crate) mod bytes_vec {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        bytes_vec: &[Vec<u8>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(bytes_vec.iter().map(hex::encode))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<u8>>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|hex| decode_hex::<D>(hex))
+            .collect()
+    }
+}
+
+/// Hashes as hex, in big-endian display order.
+pub(crate) mod hash {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        hash: &[u8],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(rev(hash.to_vec())))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        Ok(rev(decode_hex::<D>(&String::deserialize(deserializer)?)?))
+    }
+}
+
+/// Repeated hashes as hex, in big-endian display order.
+pub(crate) mod hash_vec {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        hashes: &[Vec<u8>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            hashes.iter().map(|hash| hex::encode(rev(hash.clone()))),
+        )
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<u8>>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|hex| Ok(rev(decode_hex::<D>(hex)?)))
+            .collect()
+    }
+}
+
+/// 64-bit integers as decimal strings. Numbers are also accepted when
+/// decoding.
+pub(crate) mod int64 {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber<T> {
+        String(String),
+        Number(T),
+    }
+
+    pub(crate) fn serialize<S: Serializer, T: Display>(
+        int: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(int)
+    }
+
+    pub(crate) fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + FromStr,
+        T::Err: Display,
+    {
+        match StringOrNumber::<T>::deserialize(deserializer)? {
+            StringOrNumber::String(string) => {
+                string.parse().map_err(D::Error::custom)
+            }
+            StringOrNumber::Number(int) => Ok(int),
+        }
+    }
+}
+
+/// Define a module to en-/decode fields of a protobuf enum by the name of the
+/// variant, e.g. "TX_CONFIRMED". Numbers are also accepted when decoding.
+macro_rules! enum_module {
+    ($module:ident, $enum:ident) => {
+        #[doc = concat!("[`crate::proto::", stringify!($enum), "`] by name.")]
+        pub(crate) mod $module {
+            use super::*;
+            use crate::proto::$enum;
+
+            #[derive(Deserialize)]
+            #[serde(untagged)]
+            enum NameOrNumber {
+                Name(String),
+                Number(i32),
+            }
+
+            pub(crate) fn serialize<S: Serializer>(
+                value: &i32,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                match $enum::from_i32(*value) {
+                    Some(variant) => {
+                        serializer.serialize_str(variant.as_str_name())
+                    }
+                    None => serializer.serialize_i32(*value),
+                }
+            }
+
+            pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<i32, D::Error> {
+                match NameOrNumber::deserialize(deserializer)? {
+                    NameOrNumber::Name(name) => $enum::from_str_name(&name)
+                        .map(|variant| variant as i32)
+                        .ok_or_else(|| {
+                            D::Error::custom(format!(
+                                "Unknown {}: {name}",
+                                stringify!($enum),
+                            ))
+                        }),
+                    NameOrNumber::Number(number) => Ok(number),
+                }
+            }
+        }
+    };
+}
+
+enum_module!(slp_token_type, SlpTokenType);
+enum_module!(alp_token_type, AlpTokenType);
+enum_module!(token_tx_type, TokenTxType);
+enum_module!(block_msg_type, BlockMsgType);
+enum_module!(tx_msg_type, TxMsgType);
+enum_module!(outpoint_state, OutpointState);
@@ -5,6 +5,8 @@
 //! Protobuf structs/enums for Chronik.
 
 abc_rust_lint::lint! {
+    mod json;
+
     pub mod proto {
         //! Module with protobuf structs/enums for the Chronik HTTP server.
         include!(concat!(env!("OUT_DIR"), "/chronik.rs"));
@@ -142,6 +142,20 @@ BOOST_FIXTURE_TEST_CASE(test_get_block_ancestor, TestChain100Setup) {
                       chronik_bridge::block_index_not_found);
 }
 
+BOOST_FIXTURE_TEST_CASE(test_get_block_header, TestChain100Setup) {
+    const CChainParams &params = GetConfig().GetChainParams();
+    const chronik_bridge::ChronikBridge bridge(params.GetConsensus(), m_node);
+    ChainstateManager &chainman = *Assert(m_node.chainman);
+    const CBlockIndex &tip = *chainman.ActiveTip();
+
+    for (const CBlockIndex *pindex : {tip.GetAncestor(0), &tip}) {
+        CDataStream expected(SER_NETWORK, PROTOCOL_VERSION);
+        expected << bridge.load_block(*pindex)->GetBlockHeader();
+        BOOST_CHECK_EQUAL(HexStr(chronik_bridge::get_block_header(*pindex)),
+                          HexStr(expected));
+    }
+}
+
 BOOST_FIXTURE_TEST_CASE(test_get_block_info, TestChain100Setup) {
     const CChainParams &params = GetConfig().GetChainParams();
     const chronik_bridge::ChronikBridge bridge(params.GetConsensus(), m_node);
@@ -635,10 +635,27 @@ void SetupServerArgs(NodeContext &node) {
             regtestBaseParams->ChronikPort()),
         ArgsManager::ALLOW_STRING | ArgsManager::NETWORK_ONLY,
         OptionsCategory::CHRONIK);
+    argsman.AddArg(
+        "-chronikelectrumbind=<addr>[:port]",
+        strprintf("Also serve the Chronik index over the Electrum protocol "
+                  "(TCP, newline-delimited JSON-RPC) at the given address, so "
+                  "Electrum wallets can use the node directly. Disabled unless "
+                  "set. This option can be specified multiple times (default "
+                  "port: %u)",
+                  chronik::DEFAULT_ELECTRUM_PORT),
+        ArgsManager::ALLOW_STRING | ArgsManager::NETWORK_ONLY,
+        OptionsCategory::CHRONIK);
     argsman.AddArg("-chronikreindex",
                    "Reindex the Chronik indexer from genesis, but leave the "
                    "other indexes untouched",
                    ArgsManager::ALLOW_BOOL, OptionsCategory::CHRONIK);
+    argsman.AddArg(
+        "-chronikpersistmempool",
+        strprintf("Save the Chronik mempool index on shutdown and load it on "
+                  "restart, so the time txs have first been seen is kept "
+                  "(default: %d)",
+                  chronik::DEFAULT_PERSIST_MEMPOOL),
+        ArgsManager::ALLOW_BOOL, OptionsCategory::CHRONIK);
 #endif
     argsman.AddArg(
         "-blockfilterindex=<type>",
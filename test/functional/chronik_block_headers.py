@@ -0,0 +1,112 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's /block-headers/:start/:end endpoint and the WsSubHeaders
+WebSocket subscription.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+)
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.util import assert_equal
+
+
+class ChronikBlockHeadersTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient, pb
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+
+        assert_equal(
+            chronik.block_headers(-1, 0).err(400).msg,
+            "400: Invalid block start height: -1",
+        )
+        assert_equal(
+            chronik.block_headers(2, 1).err(400).msg,
+            "400: Invalid block end height: 1",
+        )
+        assert_equal(
+            chronik.block_headers(1, 2017).err(400).msg,
+            "400: Block headers page size too large, may not be above 2016 but "
+            "got 2017",
+        )
+
+        def raw_header(height):
+            return bytes.fromhex(node.getblockheader(node.getblockhash(height), False))
+
+        assert_equal(
+            chronik.block_headers(0, 100).ok(), pb.BlockHeaders(headers=[raw_header(0)])
+        )
+
+        self.generatetoaddress(node, 10, ADDRESS_ECREG_UNSPENDABLE)
+        assert_equal(
+            chronik.block_headers(0, 2015).ok(),
+            pb.BlockHeaders(headers=[raw_header(height) for height in range(11)]),
+        )
+        assert_equal(
+            chronik.block_headers(5, 7).ok(),
+            pb.BlockHeaders(headers=[raw_header(height) for height in range(5, 8)]),
+        )
+        assert_equal(chronik.block_headers(11, 20).ok(), pb.BlockHeaders())
+
+        # Subscribe to headers, we get the raw header for every block update
+        ws = chronik.ws(timeout=30)
+        ws.sub_to_headers()
+
+        tip = self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)[-1]
+        assert_equal(
+            ws.recv(),
+            pb.WsMsg(
+                header=pb.MsgHeader(
+                    msg_type=pb.BLK_CONNECTED,
+                    header=raw_header(11),
+                    block_height=11,
+                )
+            ),
+        )
+
+        header = raw_header(11)
+        node.invalidateblock(tip)
+        assert_equal(
+            ws.recv(),
+            pb.WsMsg(
+                header=pb.MsgHeader(
+                    msg_type=pb.BLK_DISCONNECTED,
+                    header=header,
+                    block_height=11,
+                )
+            ),
+        )
+
+        # After unsubscribing, we don't get any more headers, but still get
+        # block messages if subscribed to blocks
+        ws.sub_to_headers(is_unsub=True)
+        ws.sub_to_blocks()
+        tip = self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)[-1]
+        assert_equal(
+            ws.recv(),
+            pb.WsMsg(
+                block=pb.MsgBlock(
+                    msg_type=pb.BLK_CONNECTED,
+                    block_hash=bytes.fromhex(tip)[::-1],
+                    block_height=11,
+                )
+            ),
+        )
+
+
+if __name__ == "__main__":
+    ChronikBlockHeadersTest().main()
@@ -81,6 +81,10 @@ class ChronikBlockInfoTest(BitcoinTestFramework):
                     sum_coinbase_output_sats=coinvalue + 9000,
                     sum_normal_output_sats=coinvalue - 9000,
                     sum_burned_sats=1000,
+                    sum_fees=9000,
+                    min_fee_rate=9000 * 1000 // len(tx.serialize()),
+                    median_fee_rate=9000 * 1000 // len(tx.serialize()),
+                    max_fee_rate=9000 * 1000 // len(tx.serialize()),
                 ),
             ),
         )
@@ -107,6 +111,10 @@ class ChronikBlockInfoTest(BitcoinTestFramework):
         block.hashMerkleRoot = block.calc_merkle_root()
         block.solve()
         peer.send_blocks_and_test([block], node)
+        fee_rates = [
+            9000 * 1000 // len(tx.serialize()),
+            2000 * 1000 // len(tx2.serialize()),
+        ]
 
         assert_equal(
             chronik.block(block.hash).ok(),
@@ -125,6 +133,10 @@ class ChronikBlockInfoTest(BitcoinTestFramework):
                     sum_coinbase_output_sats=coinvalue,
                     sum_normal_output_sats=coinvalue * 2 - 21000,
                     sum_burned_sats=9000,
+                    sum_fees=11000,
+                    min_fee_rate=min(fee_rates),
+                    median_fee_rate=min(fee_rates),
+                    max_fee_rate=max(fee_rates),
                 ),
             ),
         )
@@ -190,6 +190,8 @@ class ChronikBlockTxsTest(BitcoinTestFramework):
             lock_time=0,
             size=len(tx1.serialize()),
             block=block_metadata,
+            fee=9000,
+            fee_rate=9000 * 1000 // len(tx1.serialize()),
         )
 
         proto_tx2 = pb.Tx(
@@ -217,6 +219,8 @@ class ChronikBlockTxsTest(BitcoinTestFramework):
             lock_time=0,
             size=len(tx2.serialize()),
             block=block_metadata,
+            fee=7000,
+            fee_rate=7000 * 1000 // len(tx2.serialize()),
         )
 
         sorted_tx1, sorted_tx2 = sorted(
@@ -0,0 +1,210 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's optional Electrum protocol server (-chronikelectrumbind).
+"""
+
+import json
+import socket
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+)
+from test_framework.messages import sha256
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.util import assert_equal
+
+
+class ElectrumClient:
+    def __init__(self, port, timeout):
+        self.sock = socket.create_connection(("127.0.0.1", port), timeout=timeout)
+        self.reader = self.sock.makefile("rb")
+        self.next_id = 0
+
+    def send(self, msg):
+        self.sock.sendall(json.dumps(msg).encode() + b"\n")
+
+    def recv(self):
+        return json.loads(self.reader.readline())
+
+    def request(self, method, *params):
+        self.next_id += 1
+        self.send(
+            {
+                "jsonrpc": "2.0",
+                "id": self.next_id,
+                "method": method,
+                "params": params,
+            }
+        )
+        response = self.recv()
+        assert_equal(response["id"], self.next_id)
+        return response
+
+    def call(self, method, *params):
+        return self.request(method, *params)["result"]
+
+    def error(self, method, *params):
+        return self.request(method, *params)["error"]
+
+    def close(self):
+        self.reader.close()
+        self.sock.close()
+
+
+class ChronikElectrumTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def setup_network(self):
+        # Let the OS pick a free port for the Electrum server
+        with socket.socket() as sock:
+            sock.bind(("127.0.0.1", 0))
+            self.electrum_port = sock.getsockname()[1]
+        self.extra_args = [
+            ["-chronik", f"-chronikelectrumbind=127.0.0.1:{self.electrum_port}"]
+        ]
+        super().setup_network()
+
+    def run_test(self):
+        node = self.nodes[0]
+        client = ElectrumClient(self.electrum_port, timeout=30)
+
+        def raw_header(height):
+            return node.getblockheader(node.getblockhash(height), False)
+
+        version = client.call("server.version", "test", "1.4")
+        assert_equal(version[1], "1.4")
+        assert_equal(client.call("server.ping"), None)
+        assert_equal(client.call("blockchain.block.header", 0), raw_header(0))
+
+        assert_equal(
+            client.error("foo.bar"),
+            {"code": -32601, "message": "Unknown method: foo.bar"},
+        )
+        assert_equal(
+            client.error("blockchain.block.header"),
+            {"code": -32602, "message": "Invalid params: Missing height"},
+        )
+        assert_equal(
+            client.error("blockchain.block.header", 1),
+            {"code": 1, "message": "404: Block not found: 1"},
+        )
+        assert_equal(
+            client.error("blockchain.scripthash.get_history", "abc"),
+            {"code": 1, "message": "400: Invalid script hash: abc"},
+        )
+
+        # Malformed JSON gets a parse error with a null id
+        client.sock.sendall(b"{]\n")
+        response = client.recv()
+        assert_equal(response["id"], None)
+        assert_equal(response["error"]["code"], -32700)
+
+        # Batches get a batch response
+        client.send(
+            [
+                {"jsonrpc": "2.0", "id": "a", "method": "server.ping"},
+                {"jsonrpc": "2.0", "method": "server.ping"},
+                {"jsonrpc": "2.0", "id": "b", "method": "server.ping"},
+            ]
+        )
+        assert_equal(
+            client.recv(),
+            [
+                {"jsonrpc": "2.0", "id": "a", "result": None},
+                {"jsonrpc": "2.0", "id": "b", "result": None},
+            ],
+        )
+
+        # Script hashes are the reversed SHA-256 of the script
+        script_hash = sha256(P2SH_OP_TRUE)[::-1].hex()
+        assert_equal(client.call("blockchain.scripthash.get_history", script_hash), [])
+        assert_equal(client.call("blockchain.scripthash.subscribe", script_hash), None)
+        assert_equal(
+            client.call("blockchain.headers.subscribe"),
+            {"hex": raw_header(0), "height": 0},
+        )
+
+        # Mining to the script notifies both the new tip and the status change
+        blockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)[0]
+        coinbase_txid = node.getblock(blockhash)["tx"][0]
+        status = sha256(f"{coinbase_txid}:1:".encode())[::-1].hex()
+        notifications = sorted(
+            [client.recv(), client.recv()], key=lambda msg: msg["method"]
+        )
+        assert_equal(
+            notifications,
+            [
+                {
+                    "jsonrpc": "2.0",
+                    "method": "blockchain.headers.subscribe",
+                    "params": [{"hex": raw_header(1), "height": 1}],
+                },
+                {
+                    "jsonrpc": "2.0",
+                    "method": "blockchain.scripthash.subscribe",
+                    "params": [script_hash, status],
+                },
+            ],
+        )
+
+        assert_equal(
+            client.call("blockchain.scripthash.get_history", script_hash),
+            [{"tx_hash": coinbase_txid, "height": 1}],
+        )
+        assert_equal(
+            client.call("blockchain.scripthash.get_balance", script_hash),
+            {"confirmed": 5000000000, "unconfirmed": 0},
+        )
+        assert_equal(
+            client.call("blockchain.scripthash.listunspent", script_hash),
+            [
+                {
+                    "tx_hash": coinbase_txid,
+                    "tx_pos": 0,
+                    "height": 1,
+                    "value": 5000000000,
+                }
+            ],
+        )
+        assert_equal(
+            client.call("blockchain.transaction.get", coinbase_txid),
+            node.getrawtransaction(coinbase_txid),
+        )
+        assert_equal(
+            client.call("blockchain.scripthash.subscribe", script_hash), status
+        )
+
+        # After unsubscribing, only headers are notified
+        assert_equal(
+            client.call("blockchain.scripthash.unsubscribe", script_hash), True
+        )
+        assert_equal(
+            client.call("blockchain.scripthash.unsubscribe", script_hash), False
+        )
+        self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)
+        assert_equal(
+            client.recv(),
+            {
+                "jsonrpc": "2.0",
+                "method": "blockchain.headers.subscribe",
+                "params": [{"hex": raw_header(2), "height": 2}],
+            },
+        )
+        self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)
+        assert_equal(client.recv()["params"][0]["height"], 3)
+
+        client.close()
+
+
+if __name__ == "__main__":
+    ChronikElectrumTest().main()
@@ -0,0 +1,101 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's /fee-estimate endpoint.
+
+The mempool backlog part of the estimate requires blocks worth of txs in the
+mempool and is covered by the unit tests of estimate_fee_rate instead.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+    SCRIPTSIG_OP_TRUE,
+)
+from test_framework.messages import COutPoint, CTransaction, CTxIn, CTxOut
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.txtools import pad_tx
+from test_framework.util import assert_equal
+
+MIN_FEE_RATE = 1000
+NUM_RECENT_BLOCKS = 6
+
+
+class ChronikFeeEstimateTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient, pb
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+
+        coinblockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)[0]
+        cointxid = node.getblock(coinblockhash)["tx"][0]
+        self.generatetoaddress(node, 100, ADDRESS_ECREG_UNSPENDABLE)
+
+        def fee_estimate(blocks=None):
+            return chronik.fee_estimate(blocks).ok()
+
+        # Only coinbase txs so far, so the min fee rate is estimated
+        assert_equal(
+            fee_estimate(), pb.FeeEstimate(fee_rate=MIN_FEE_RATE, num_blocks=1)
+        )
+        assert_equal(
+            fee_estimate(144), pb.FeeEstimate(fee_rate=MIN_FEE_RATE, num_blocks=144)
+        )
+
+        for blocks in [0, 145]:
+            assert_equal(
+                chronik.fee_estimate(blocks).err(400).msg,
+                f"400: Invalid number of blocks {blocks}, must be between 1 and 144",
+            )
+        assert_equal(
+            chronik.fee_estimate("abc").err(400).msg,
+            "400: Invalid param blocks: abc, invalid digit found in string",
+        )
+
+        utxo = (cointxid, 0, 5000000000)
+
+        def mine_tx(fee_rate):
+            # Mine a block with one tx paying exactly the given fee rate
+            nonlocal utxo
+            txid, out_idx, value = utxo
+            tx = CTransaction()
+            tx.vin = [CTxIn(COutPoint(int(txid, 16), out_idx), SCRIPTSIG_OP_TRUE)]
+            tx.vout = [CTxOut(value, P2SH_OP_TRUE)]
+            pad_tx(tx)
+            fee = len(tx.serialize()) * fee_rate // 1000
+            tx.vout[0].nValue -= fee
+            txid = node.sendrawtransaction(tx.serialize().hex())
+            utxo = (txid, 0, value - fee)
+            self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)
+
+        # Median of the min fee rates of recent blocks with non-coinbase txs,
+        # the lower one for an even number of blocks
+        mine_tx(5000)
+        assert_equal(fee_estimate().fee_rate, 5000)
+        mine_tx(3000)
+        assert_equal(fee_estimate().fee_rate, 3000)
+        mine_tx(8000)
+        assert_equal(fee_estimate().fee_rate, 5000)
+        assert_equal(fee_estimate(6).fee_rate, 5000)
+
+        # Coinbase-only blocks don't count, but push older blocks out
+        self.generatetoaddress(node, NUM_RECENT_BLOCKS - 1, ADDRESS_ECREG_UNSPENDABLE)
+        assert_equal(fee_estimate().fee_rate, 8000)
+        self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)
+        assert_equal(fee_estimate().fee_rate, MIN_FEE_RATE)
+
+
+if __name__ == "__main__":
+    ChronikFeeEstimateTest().main()
@@ -0,0 +1,132 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's /lokad-id/:lokad_id/history endpoint and LOKAD ID WebSocket
+subscriptions.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+    SCRIPTSIG_OP_TRUE,
+)
+from test_framework.messages import COutPoint, CTransaction, CTxIn, CTxOut
+from test_framework.script import OP_RESERVED, OP_RETURN, CScript
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.util import assert_equal
+
+FEE = 10000
+
+
+class ChronikLokadIdTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient, pb
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+        mocktime = 1300000000
+        node.setmocktime(mocktime)
+
+        coinblockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)[0]
+        cointxid = node.getblock(coinblockhash)["tx"][0]
+        self.generatetoaddress(node, 100, ADDRESS_ECREG_UNSPENDABLE)
+
+        utxo = (cointxid, 0, 5000000000)
+
+        # Txs aren't padded, as only one OP_RETURN output is standard, but all
+        # of them are at least 100 bytes anyway
+        def send_op_return(op_return_script):
+            # Separate times first seen, so the mempool order is deterministic
+            nonlocal mocktime, utxo
+            mocktime += 1
+            node.setmocktime(mocktime)
+            txid, out_idx, value = utxo
+            tx = CTransaction()
+            tx.vin = [CTxIn(COutPoint(int(txid, 16), out_idx), SCRIPTSIG_OP_TRUE)]
+            tx.vout = [
+                CTxOut(0, op_return_script),
+                CTxOut(value - FEE, P2SH_OP_TRUE),
+            ]
+            txid = node.sendrawtransaction(tx.serialize().hex())
+            utxo = (txid, 1, value - FEE)
+            return txid
+
+        def history_txids(lokad_id, page=None, page_size=None):
+            page = chronik.lokad_id(lokad_id).history(page, page_size).ok()
+            return [tx.txid[::-1].hex() for tx in page.txs]
+
+        def tx_msg(msg_type, txid):
+            return pb.WsMsg(
+                tx=pb.MsgTx(msg_type=msg_type, txid=bytes.fromhex(txid)[::-1])
+            )
+
+        assert_equal(
+            chronik.lokad_id("abc").history().err(400).msg,
+            "400: Invalid hex: Odd number of digits",
+        )
+        assert_equal(
+            chronik.lokad_id("0102").history().err(400).msg,
+            "400: Invalid LOKAD ID length: expected 4 bytes but got 2",
+        )
+
+        ws = chronik.ws(timeout=30)
+        ws.sub_lokad_id(b"TEST")
+
+        test_txid = send_op_return(CScript([OP_RETURN, b"TEST", b"data"]))
+        # Pushdata with other than 4 bytes has no LOKAD ID
+        send_op_return(CScript([OP_RETURN, b"TES", b"data"]))
+        other_txid = send_op_return(CScript([OP_RETURN, b"ABCD"]))
+        # eMPP: Each pushdata starts with a LOKAD ID
+        empp_txid = send_op_return(
+            CScript([OP_RETURN, OP_RESERVED, b"ABCDfoo", b"TESTbar"])
+        )
+
+        assert_equal(ws.recv(), tx_msg(pb.TX_ADDED_TO_MEMPOOL, test_txid))
+        assert_equal(ws.recv(), tx_msg(pb.TX_ADDED_TO_MEMPOOL, empp_txid))
+
+        # Txs in the mempool, latest first
+        assert_equal(history_txids("54455354"), [empp_txid, test_txid])
+        assert_equal(history_txids("41424344"), [empp_txid, other_txid])
+        assert_equal(history_txids("41424344", 1, 1), [other_txid])
+        assert_equal(history_txids("00000000"), [])
+
+        # Mined txs are ordered as in the block, latest first
+        blockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)[0]
+        block_txids = node.getblock(blockhash)["tx"]
+        for txid in sorted([test_txid, empp_txid]):
+            assert_equal(ws.recv(), tx_msg(pb.TX_CONFIRMED, txid))
+        assert_equal(
+            history_txids("54455354"),
+            [txid for txid in block_txids if txid in [test_txid, empp_txid]][::-1],
+        )
+        assert_equal(
+            history_txids("41424344"),
+            [txid for txid in block_txids if txid in [other_txid, empp_txid]][::-1],
+        )
+
+        # New mempool txs come first
+        test2_txid = send_op_return(CScript([OP_RETURN, b"TEST"]))
+        assert_equal(ws.recv(), tx_msg(pb.TX_ADDED_TO_MEMPOOL, test2_txid))
+        assert_equal(history_txids("54455354")[0], test2_txid)
+
+        # After unsubscribing, only msgs of the other LOKAD ID are sent
+        ws.sub_lokad_id(b"TEST", is_unsub=True)
+        ws.sub_lokad_id(b"ABCD")
+        send_op_return(CScript([OP_RETURN, b"TEST"]))
+        other2_txid = send_op_return(CScript([OP_RETURN, b"ABCD"]))
+        assert_equal(ws.recv(), tx_msg(pb.TX_ADDED_TO_MEMPOOL, other2_txid))
+
+
+if __name__ == "__main__":
+    ChronikLokadIdTest().main()
@@ -0,0 +1,132 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's /mempool and /mempool/info endpoints.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+    SCRIPTSIG_OP_TRUE,
+)
+from test_framework.messages import COutPoint, CTransaction, CTxIn, CTxOut
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.txtools import pad_tx
+from test_framework.util import assert_equal
+
+# Lower bounds of the fee rate buckets, in sats/kB
+FEE_RATE_BUCKETS = [0, 1000, 2000, 3000, 5000, 10000, 20000, 50000, 100000, 1000000]
+
+
+class ChronikMempoolTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient, pb
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+        mocktime = 1300000000
+        node.setmocktime(mocktime)
+
+        coinblockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)[0]
+        cointxid = node.getblock(coinblockhash)["tx"][0]
+        self.generatetoaddress(node, 100, ADDRESS_ECREG_UNSPENDABLE)
+
+        def check_info(txs):
+            histogram = [
+                pb.FeeRateBucket(min_fee_rate=min_fee_rate)
+                for min_fee_rate in FEE_RATE_BUCKETS
+            ]
+            for tx in txs:
+                fee_rate = tx.fee * 1000 // tx.size
+                bucket_idx = max(
+                    idx
+                    for idx, min_fee_rate in enumerate(FEE_RATE_BUCKETS)
+                    if min_fee_rate <= fee_rate
+                )
+                histogram[bucket_idx].num_txs += 1
+                histogram[bucket_idx].num_bytes += tx.size
+            assert_equal(
+                chronik.mempool_info().ok(),
+                pb.MempoolInfo(
+                    num_txs=len(txs),
+                    num_bytes=sum(tx.size for tx in txs),
+                    sum_fees=sum(tx.fee for tx in txs),
+                    fee_rate_histogram=histogram,
+                ),
+            )
+
+        # Empty mempool
+        assert_equal(chronik.mempool().ok(), pb.MempoolTxs())
+        check_info([])
+
+        assert_equal(
+            chronik.mempool(page_size=0).err(400).msg,
+            "400: Requested page size 0 is too small, minimum is 1",
+        )
+        assert_equal(
+            chronik.mempool(page_size=201).err(400).msg,
+            "400: Requested page size 201 is too big, maximum is 200",
+        )
+
+        # Chain of txs, each paying a different fee
+        expected_txs = []
+        utxo = (cointxid, 0, 5000000000)
+        for fee in [200, 5000, 20000, 150000]:
+            # Separate times first seen, so the mempool order is deterministic
+            mocktime += 1
+            node.setmocktime(mocktime)
+            txid, out_idx, value = utxo
+            tx = CTransaction()
+            tx.vin = [CTxIn(COutPoint(int(txid, 16), out_idx), SCRIPTSIG_OP_TRUE)]
+            tx.vout = [CTxOut(value - fee, P2SH_OP_TRUE)]
+            pad_tx(tx)
+            txid = node.sendrawtransaction(tx.serialize().hex())
+            utxo = (txid, 0, value - fee)
+            expected_txs.insert(
+                0,
+                pb.MempoolTxInfo(
+                    txid=bytes.fromhex(txid)[::-1],
+                    time_first_seen=mocktime,
+                    size=len(tx.serialize()),
+                    fee=fee,
+                ),
+            )
+
+        # Txs in the mempool, latest first
+        assert_equal(
+            chronik.mempool().ok(),
+            pb.MempoolTxs(txs=expected_txs, num_pages=1, num_txs=4),
+        )
+        assert_equal(
+            chronik.mempool(0, 3).ok(),
+            pb.MempoolTxs(txs=expected_txs[:3], num_pages=2, num_txs=4),
+        )
+        assert_equal(
+            chronik.mempool(1, 3).ok(),
+            pb.MempoolTxs(txs=expected_txs[3:], num_pages=2, num_txs=4),
+        )
+        assert_equal(
+            chronik.mempool(2, 3).ok(),
+            pb.MempoolTxs(txs=[], num_pages=2, num_txs=4),
+        )
+        check_info(expected_txs)
+
+        # Mined txs leave the mempool
+        self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)
+        assert_equal(chronik.mempool().ok(), pb.MempoolTxs())
+        check_info([])
+
+
+if __name__ == "__main__":
+    ChronikMempoolTest().main()
@@ -0,0 +1,90 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's /tx/:txid/merkle-proof endpoint.
+"""
+
+from test_framework.blocktools import GENESIS_BLOCK_HASH, GENESIS_CB_TXID
+from test_framework.messages import hash256
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.util import assert_equal
+from test_framework.wallet import MiniWallet
+
+
+def merkle_root_from_branch(txid: str, tx_idx: int, branch) -> bytes:
+    node_hash = bytes.fromhex(txid)[::-1]
+    for sibling in branch:
+        if tx_idx % 2 == 0:
+            node_hash = hash256(node_hash + sibling)
+        else:
+            node_hash = hash256(sibling + node_hash)
+        tx_idx //= 2
+    return node_hash
+
+
+class ChronikMerkleProofTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+        self.rpc_timeout = 240
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+
+        def check_proof(txid: str, blockhash: str, height: int, tx_idx: int):
+            proof = chronik.merkle_proof(txid).ok()
+            assert_equal(
+                proof.block_header.hex(), node.getblockheader(blockhash, False)
+            )
+            assert_equal(proof.block_height, height)
+            assert_equal(proof.tx_idx, tx_idx)
+            # Merkle root is at bytes 36..68 of the header
+            assert_equal(
+                merkle_root_from_branch(txid, tx_idx, proof.branch),
+                proof.block_header[36:68],
+            )
+            assert_equal(hash256(proof.block_header)[::-1].hex(), blockhash)
+
+        assert_equal(chronik.merkle_proof("0").err(400).msg, "400: Not a txid: 0")
+        assert_equal(
+            chronik.merkle_proof("00" * 32).err(404).msg,
+            f'404: Transaction {"00"*32} not found in the index',
+        )
+
+        # Genesis has just one tx, so the branch is empty
+        check_proof(GENESIS_CB_TXID, GENESIS_BLOCK_HASH, 0, 0)
+        assert_equal(list(chronik.merkle_proof(GENESIS_CB_TXID).ok().branch), [])
+
+        wallet = MiniWallet(node)
+        self.generate(wallet, 4)
+        self.generate(node, 100)
+
+        # Coinbase plus 4 txs, so the merkle tree has levels with an odd number
+        # of nodes, which duplicate their last node
+        txids = [wallet.send_self_transfer(from_node=node)["txid"] for _ in range(4)]
+
+        # Mempool txs have no proof yet
+        assert_equal(
+            chronik.merkle_proof(txids[0]).err(400).msg,
+            f"400: Transaction {txids[0]} not mined yet",
+        )
+
+        blockhash = self.generate(node, 1)[0]
+        block = node.getblock(blockhash)
+        assert_equal(len(block["tx"]), 5)
+        for tx_idx, txid in enumerate(block["tx"]):
+            check_proof(txid, blockhash, 105, tx_idx)
+            assert_equal(len(chronik.merkle_proof(txid).ok().branch), 3)
+
+
+if __name__ == "__main__":
+    ChronikMerkleProofTest().main()
@@ -123,7 +123,13 @@ class ChronikScriptHistoryTest(BitcoinTestFramework):
         genesis_db_script_history = chronik.script("p2pk", GENESIS_CB_PK).history().ok()
         assert_equal(
             genesis_db_script_history,
-            pb.TxHistoryPage(txs=[genesis_cb_tx()], num_pages=1, num_txs=1),
+            pb.TxHistoryPage(
+                txs=[genesis_cb_tx()],
+                num_pages=1,
+                num_txs=1,
+                oldest_cursor="000000000000000000",
+                newest_cursor="000000000000000000",
+            ),
         )
 
         script_type = "p2sh"
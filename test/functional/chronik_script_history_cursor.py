@@ -0,0 +1,128 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test paging through Chronik's /script/:type/:payload/confirmed-txs and
+/script/:type/:payload/history endpoints with the before/after cursors, while
+new blocks and txs arrive.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+    SCRIPTSIG_OP_TRUE,
+)
+from test_framework.messages import COutPoint, CTransaction, CTxIn, CTxOut
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.txtools import pad_tx
+from test_framework.util import assert_equal
+
+
+class ChronikScriptHistoryCursorTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+        self.rpc_timeout = 240
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+        script = chronik.script("p2sh", P2SH_OP_TRUE[2:-1].hex())
+
+        def txids(page):
+            return [tx.txid[::-1].hex() for tx in page.txs]
+
+        def mine_coinbase_txids(num_blocks):
+            blockhashes = self.generatetoaddress(
+                node, num_blocks, ADDRESS_ECREG_P2SH_OP_TRUE
+            )
+            return [node.getblock(blockhash)["tx"][0] for blockhash in blockhashes]
+
+        coinbase_txids = mine_coinbase_txids(110)
+
+        assert_equal(script.history(after="00").err(400).msg, "400: Invalid cursor: 00")
+        cursor = "000000000000000000"
+        assert_equal(
+            script.history(before=cursor, after=cursor).err(400).msg,
+            "400: Only one of the params before and after can be given",
+        )
+
+        # Page forwards through the confirmed txs, while new blocks arrive
+        page = script.confirmed_txs(page_size=25).ok()
+        seen_txids = txids(page)
+        for _ in range(3):
+            coinbase_txids += mine_coinbase_txids(2)
+            page = script.confirmed_txs(page_size=25, after=page.newest_cursor).ok()
+            seen_txids += txids(page)
+        while page.txs:
+            page = script.confirmed_txs(page_size=25, after=page.newest_cursor).ok()
+            seen_txids += txids(page)
+        assert_equal(seen_txids, coinbase_txids)
+
+        # Page backwards through the history, while new blocks arrive. Unlike
+        # with page numbers, the new txs don't shift the pages.
+        expected_txids = coinbase_txids[::-1]
+        page = script.history(page_size=30).ok()
+        seen_txids = txids(page)
+        while page.txs:
+            mine_coinbase_txids(1)
+            page = script.history(page_size=30, before=page.oldest_cursor).ok()
+            seen_txids += txids(page)
+        assert_equal(seen_txids, expected_txids)
+
+        def spend_coinbase(cointxid):
+            tx = CTransaction()
+            tx.vin = [CTxIn(COutPoint(int(cointxid, 16), 0), SCRIPTSIG_OP_TRUE)]
+            tx.vout = [CTxOut(5000000000 - 10000, P2SH_OP_TRUE)]
+            pad_tx(tx)
+            tx.rehash()
+            return tx
+
+        # Catch up with the latest history, newest cursor is of a mempool tx
+        tx1 = spend_coinbase(coinbase_txids[0])
+        node.sendrawtransaction(tx1.serialize().hex())
+        page = script.history(page_size=5).ok()
+        assert_equal(txids(page)[0], tx1.hash)
+        newest_cursor = page.newest_cursor
+        assert_equal(newest_cursor[:2], "01")
+
+        # Block mines tx1 and tx2, which never has been in the mempool
+        tx2 = spend_coinbase(coinbase_txids[1])
+        blockhash = self.generateblock(
+            node, ADDRESS_ECREG_UNSPENDABLE, [tx1.hash, tx2.serialize().hex()]
+        )["hash"]
+        block_txids = node.getblock(blockhash)["tx"][1:]
+        assert_equal(sorted(block_txids), sorted([tx1.hash, tx2.hash]))
+
+        # Resuming after the mempool cursor returns both mined txs, latest first
+        page = script.history(page_size=5, after=newest_cursor).ok()
+        assert_equal(txids(page), block_txids[::-1])
+        assert_equal(page.newest_cursor[:2], "00")
+
+        # New mempool tx is returned after the mined ones
+        tx3 = spend_coinbase(coinbase_txids[2])
+        node.sendrawtransaction(tx3.serialize().hex())
+        page = script.history(page_size=5, after=page.newest_cursor).ok()
+        assert_equal(txids(page), [tx3.hash])
+        page = script.history(page_size=5, after=page.newest_cursor).ok()
+        assert_equal(txids(page), [])
+
+        # Resuming from an old mempool cursor with a small page size
+        page = script.history(page_size=1, after=newest_cursor).ok()
+        assert_equal(txids(page), block_txids[:1])
+        page = script.history(page_size=1, after=page.newest_cursor).ok()
+        assert_equal(txids(page), block_txids[1:])
+        page = script.history(page_size=1, after=page.newest_cursor).ok()
+        assert_equal(txids(page), [tx3.hash])
+
+
+if __name__ == "__main__":
+    ChronikScriptHistoryCursorTest().main()
@@ -173,6 +173,8 @@ class ChronikScriptUnconfirmedTxsTest(BitcoinTestFramework):
                     lock_time=1,
                     size=len(tx.serialize()),
                     time_first_seen=time_first_seen,
+                    fee=1000,
+                    fee_rate=1000 * 1000 // len(tx.serialize()),
                 )
             )
 
@@ -180,11 +182,36 @@ class ChronikScriptUnconfirmedTxsTest(BitcoinTestFramework):
         def sorted_txs(txs):
             return sorted(txs, key=lambda tx: (tx.time_first_seen, tx.txid[::-1]))
 
+        # Number of txs in the DB, i.e. in all blocks including genesis
+        def num_db_txs():
+            return sum(
+                node.getblockheader(node.getblockhash(height))["nTx"]
+                for height in range(node.getblockcount() + 1)
+            )
+
+        # Cursor of a mempool tx: prefix 01, number of txs in the DB,
+        # time_first_seen and txid
+        def mempool_cursor(tx, num_db_txs):
+            return (
+                "01"
+                + num_db_txs.to_bytes(8, "big").hex()
+                + tx.time_first_seen.to_bytes(8, "big").hex()
+                + tx.txid.hex()
+            )
+
+        def unconfirmed_page(txs):
+            txs = sorted_txs(txs)
+            return pb.TxHistoryPage(
+                txs=txs,
+                num_pages=1,
+                num_txs=len(txs),
+                oldest_cursor=mempool_cursor(txs[0], num_db_txs()),
+                newest_cursor=mempool_cursor(txs[-1], num_db_txs()),
+            )
+
         assert_equal(
             chronik.script(script_type, payload_hex).unconfirmed_txs().ok(),
-            pb.TxHistoryPage(
-                txs=sorted_txs(mempool_proto_txs), num_pages=1, num_txs=len(mempool_txs)
-            ),
+            unconfirmed_page(mempool_proto_txs),
         )
 
         # Mine 5 transactions, with 2 conflicts, leave 5 others unconfirmed
@@ -214,9 +241,7 @@ class ChronikScriptUnconfirmedTxsTest(BitcoinTestFramework):
         # Only unconfirmed txs remain, conflict txs are removed
         assert_equal(
             chronik.script(script_type, payload_hex).unconfirmed_txs().ok(),
-            pb.TxHistoryPage(
-                txs=sorted_txs(mempool_proto_txs[5:]), num_pages=1, num_txs=5
-            ),
+            unconfirmed_page(mempool_proto_txs[5:]),
         )
 
 
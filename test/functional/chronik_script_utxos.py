@@ -195,6 +195,29 @@ class ChronikScriptUtxosTest(BitcoinTestFramework):
             pb.ScriptUtxos(script=bytes(P2SH_OP_TRUE), utxos=expected_utxos),
         )
 
+        # Same UTXOs when queried by address
+        assert_equal(
+            chronik.address(ADDRESS_ECREG_P2SH_OP_TRUE).utxos().ok(),
+            pb.ScriptUtxos(script=bytes(P2SH_OP_TRUE), utxos=expected_utxos),
+        )
+        assert_equal(
+            chronik.address("ecregtest:invalid").utxos().err(400).msg,
+            "400: Invalid address ecregtest:invalid: Invalid character 'i' in "
+            + "address",
+        )
+
+        # Balance of the DB UTXOs, with tx2 as the unconfirmed delta
+        assert_equal(
+            chronik.script(script_type, payload_hex).balance().ok(),
+            pb.ScriptBalance(
+                script=bytes(P2SH_OP_TRUE),
+                confirmed_value=sum(send_values),
+                unconfirmed_delta=2500 - 3000,
+                num_utxos=4,
+                num_txs=3,
+            ),
+        )
+
         # Make tx spending a DB UTXO and a mempool UTXO
         tx3 = CTransaction()
         tx3.vin = [
@@ -0,0 +1,130 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's POST /scripts/utxos and /scripts/history batch endpoints.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+)
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.util import assert_equal
+
+
+class ChronikScriptsBatchTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+
+        self.generatetoaddress(node, 3, ADDRESS_ECREG_P2SH_OP_TRUE)
+        self.generatetoaddress(node, 2, ADDRESS_ECREG_UNSPENDABLE)
+
+        p2sh = ("p2sh", bytes(P2SH_OP_TRUE[2:-1]))
+        p2pkh = ("p2pkh", bytes(20))
+        unused = ("p2sh", bytes([0x11] * 20))
+
+        def single(script):
+            script_type, payload = script
+            return chronik.script(script_type, payload.hex())
+
+        # Results are in the order of the request and match the single queries
+        for scripts in [[p2sh, p2pkh, unused], [unused, p2pkh, p2sh], [p2sh, p2sh]]:
+            batch_utxos = chronik.scripts_utxos(scripts).ok()
+            assert_equal(
+                list(batch_utxos.scripts),
+                [single(script).utxos().ok() for script in scripts],
+            )
+            batch_history = chronik.scripts_history(scripts, page_size=2).ok()
+            assert_equal(
+                list(batch_history.pages),
+                [single(script).history(0, 2).ok() for script in scripts],
+            )
+            batch_history = chronik.scripts_history(scripts, page=1, page_size=2).ok()
+            assert_equal(
+                list(batch_history.pages),
+                [single(script).history(1, 2).ok() for script in scripts],
+            )
+
+        batch_utxos = chronik.scripts_utxos([p2sh, p2pkh, unused]).ok()
+        assert_equal(
+            [len(script_utxos.utxos) for script_utxos in batch_utxos.scripts],
+            [3, 2, 0],
+        )
+        batch_history = chronik.scripts_history([p2sh, p2pkh], page_size=25).ok()
+        assert_equal([page.num_txs for page in batch_history.pages], [3, 2])
+
+        # Empty batches are fine
+        assert_equal(list(chronik.scripts_utxos([]).ok().scripts), [])
+        assert_equal(list(chronik.scripts_history([], page_size=25).ok().pages), [])
+
+        # At most 100 scripts per batch
+        assert_equal(len(chronik.scripts_utxos([unused] * 100).ok().scripts), 100)
+        assert_equal(
+            len(chronik.scripts_history([unused] * 100, page_size=20).ok().pages),
+            100,
+        )
+        assert_equal(
+            chronik.scripts_utxos([unused] * 101).err(400).msg,
+            "400: Too many scripts requested: 101, maximum is 100",
+        )
+        assert_equal(
+            chronik.scripts_history([unused] * 101, page_size=1).err(400).msg,
+            "400: Too many scripts requested: 101, maximum is 100",
+        )
+
+        # At most 2000 txs in total per batch
+        assert_equal(
+            len(chronik.scripts_history([unused] * 10, page_size=200).ok().pages),
+            10,
+        )
+        assert_equal(
+            chronik.scripts_history([unused] * 11, page_size=200).err(400).msg,
+            "400: Too many txs requested: 11 scripts with page size 200, maximum "
+            "is 2000 txs in total",
+        )
+        assert_equal(
+            chronik.scripts_history([unused] * 100, page_size=21).err(400).msg,
+            "400: Too many txs requested: 100 scripts with page size 21, maximum "
+            "is 2000 txs in total",
+        )
+
+        # Page size must be given and within bounds
+        assert_equal(
+            chronik.scripts_history([p2sh]).err(400).msg,
+            "400: Requested page size 0 is too small, minimum is 1",
+        )
+        assert_equal(
+            chronik.scripts_history([p2sh], page_size=201).err(400).msg,
+            "400: Requested page size 201 is too big, maximum is 200",
+        )
+
+        # Any invalid script fails the whole batch
+        assert_equal(
+            chronik.scripts_utxos([p2sh, ("foo", b"")]).err(400).msg,
+            "400: Unknown script type: foo",
+        )
+        assert_equal(
+            chronik.scripts_history([p2sh, ("p2sh", b"\x00")], page_size=25)
+            .err(400)
+            .msg,
+            "400: Invalid payload for P2SH: Invalid length, "
+            + "expected 20 bytes but got 1 bytes",
+        )
+
+
+if __name__ == "__main__":
+    ChronikScriptsBatchTest().main()
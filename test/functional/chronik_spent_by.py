@@ -3,7 +3,8 @@
 # Distributed under the MIT software license, see the accompanying
 # file COPYING or http://www.opensource.org/licenses/mit-license.php.
 """
-Test if the `Tx.spent_by` field is set correctly in Chronik.
+Test if the `Tx.spent_by` field and the /outpoint/:txid/:out_idx endpoint are
+set correctly in Chronik.
 """
 
 from test_framework.address import (
@@ -92,6 +93,42 @@ class ChronikSpentByTest(BitcoinTestFramework):
                     tx_outputs_spent(tx),
                     expected_outpoints,
                 )
+            mempool = node.getrawmempool()
+            for out_idx, spent_by in enumerate(expected_outpoints):
+                info = chronik.outpoint(txid, out_idx).ok()
+                assert_equal(info.value, send_values[out_idx])
+                assert_equal(info.output_script, bytes(send_scripts[out_idx]))
+                assert_equal(info.block.height != 0, has_been_mined)
+                assert_equal(info.spent_by, spent_by)
+                spent_txid = spent_by.txid[::-1].hex()
+                if spent_by == pb.SpentBy():
+                    assert_equal(info.state, pb.OUTPOINT_STATE_UNSPENT)
+                    assert_equal(info.spent_by_height, -1)
+                elif spent_txid in mempool:
+                    assert_equal(info.state, pb.OUTPOINT_STATE_SPENT_IN_MEMPOOL)
+                    assert_equal(info.spent_by_height, -1)
+                else:
+                    assert_equal(info.state, pb.OUTPOINT_STATE_SPENT_IN_BLOCK)
+                    assert_equal(
+                        info.spent_by_height,
+                        chronik.tx(spent_txid).ok().block.height,
+                    )
+            # Output index out of range
+            info = chronik.outpoint(txid, len(send_values)).ok()
+            assert_equal(info.state, pb.OUTPOINT_STATE_NOT_FOUND)
+
+        assert_equal(
+            chronik.outpoint(txid, "x").err(400).msg,
+            "400: Not an output index: x",
+        )
+        assert_equal(
+            chronik.outpoint("00" * 32, 0).ok(),
+            pb.OutpointInfo(
+                outpoint=pb.OutPoint(txid=bytes(32), out_idx=0),
+                state=pb.OUTPOINT_STATE_NOT_FOUND,
+                spent_by_height=-1,
+            ),
+        )
 
         # Initially, none of the outputs are spent
         check_outputs_spent([pb.SpentBy()] * len(send_values), has_been_mined=False)
@@ -0,0 +1,175 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's /token-id/:token_id/history and /token-id/:token_id/utxos
+endpoints.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+    SCRIPTSIG_OP_TRUE,
+)
+from test_framework.chronik.slp import slp_burn, slp_genesis, slp_send
+from test_framework.messages import COutPoint, CTransaction, CTxIn, CTxOut
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.txtools import pad_tx
+from test_framework.util import assert_equal
+
+DUST = 546
+FEE = 10000
+
+
+class ChronikTokenIdTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient, pb
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+        mocktime = 1300000000
+        node.setmocktime(mocktime)
+
+        coinblockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)[0]
+        cointxid = node.getblock(coinblockhash)["tx"][0]
+        self.generatetoaddress(node, 100, ADDRESS_ECREG_UNSPENDABLE)
+
+        def send_tx(inputs, op_return_script, num_dust_outputs):
+            # Separate times first seen, so the mempool order is deterministic
+            nonlocal mocktime
+            mocktime += 1
+            node.setmocktime(mocktime)
+            tx = CTransaction()
+            tx.vin = [
+                CTxIn(COutPoint(int(txid, 16), out_idx), SCRIPTSIG_OP_TRUE)
+                for txid, out_idx, _ in inputs
+            ]
+            input_sum = sum(value for _, _, value in inputs)
+            change = input_sum - num_dust_outputs * DUST - FEE
+            tx.vout = [CTxOut(DUST, P2SH_OP_TRUE)] * num_dust_outputs + [
+                CTxOut(change, P2SH_OP_TRUE)
+            ]
+            if op_return_script is not None:
+                tx.vout.insert(0, CTxOut(0, op_return_script))
+            change_idx = len(tx.vout) - 1
+            pad_tx(tx)
+            txid = node.sendrawtransaction(tx.serialize().hex())
+            return txid, (txid, change_idx, change)
+
+        def history_txids(page):
+            return [tx.txid[::-1].hex() for tx in page.txs]
+
+        def utxos(token_id):
+            token_utxos = chronik.token_id(token_id).utxos().ok()
+            assert_equal(token_utxos.token_id, bytes.fromhex(token_id)[::-1])
+            return sorted(
+                (
+                    utxo.outpoint.txid[::-1].hex(),
+                    utxo.outpoint.out_idx,
+                    utxo.block_height,
+                    utxo.token.amount,
+                    utxo.token.is_mint_baton,
+                )
+                for utxo in token_utxos.utxos
+            )
+
+        assert_equal(
+            chronik.token_id("abc").history().err(400).msg,
+            "400: Not a token ID: abc",
+        )
+        assert_equal(
+            chronik.token_id("abc").utxos().err(400).msg,
+            "400: Not a token ID: abc",
+        )
+
+        genesis_txid, change = send_tx(
+            [(cointxid, 0, 5000000000)],
+            slp_genesis(mint_baton_vout=2, initial_mint_amount=5000),
+            2,
+        )
+        send_txid, change = send_tx(
+            [(genesis_txid, 1, DUST), change], slp_send(genesis_txid, [3000, 2000]), 2
+        )
+        # Tx without any tokens isn't part of the history
+        _, change = send_tx([change], None, 0)
+        burn_txid, change = send_tx(
+            [(send_txid, 2, DUST), change], slp_burn(genesis_txid, 2000), 0
+        )
+
+        # Txs in the mempool, latest first
+        token = chronik.token_id(genesis_txid)
+        page = token.history().ok()
+        assert_equal(history_txids(page), [burn_txid, send_txid, genesis_txid])
+        assert_equal((page.num_pages, page.num_txs), (1, 3))
+        page = token.history(0, 2).ok()
+        assert_equal(history_txids(page), [burn_txid, send_txid])
+        assert_equal((page.num_pages, page.num_txs), (2, 3))
+        page = token.history(1, 2).ok()
+        assert_equal(history_txids(page), [genesis_txid])
+        assert_equal(
+            utxos(genesis_txid),
+            sorted([(genesis_txid, 2, -1, 0, True), (send_txid, 1, -1, 3000, False)]),
+        )
+        # Token UTXOs reference the token, but not a tx entry
+        token_utxo = token.utxos().ok().utxos[0]
+        assert_equal(token_utxo.token.token_id, bytes.fromhex(genesis_txid)[::-1])
+        assert_equal(
+            token_utxo.token.token_type, pb.TokenType(slp=pb.SLP_TOKEN_TYPE_FUNGIBLE)
+        )
+        assert_equal(token_utxo.token.entry_idx, -1)
+
+        # Unknown token ID has no history or UTXOs
+        unknown_token_id = "00" * 32
+        page = chronik.token_id(unknown_token_id).history().ok()
+        assert_equal((list(page.txs), page.num_pages, page.num_txs), ([], 0, 0))
+        assert_equal(utxos(unknown_token_id), [])
+
+        # Mined txs are ordered as in the block, latest first
+        blockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)[0]
+        block = node.getblock(blockhash)
+        height = block["height"]
+        token_txids = [burn_txid, send_txid, genesis_txid]
+        block_token_txids = [txid for txid in block["tx"] if txid in token_txids]
+        assert_equal(history_txids(token.history().ok()), block_token_txids[::-1])
+        assert_equal(
+            utxos(genesis_txid),
+            sorted(
+                [
+                    (genesis_txid, 2, height, 0, True),
+                    (send_txid, 1, height, 3000, False),
+                ]
+            ),
+        )
+
+        # New mempool txs come first, spent DB UTXOs are gone
+        send2_txid, change = send_tx(
+            [(send_txid, 1, DUST), change], slp_send(genesis_txid, [1000, 2000]), 2
+        )
+        assert_equal(
+            history_txids(token.history().ok()),
+            [send2_txid] + block_token_txids[::-1],
+        )
+        assert_equal(
+            utxos(genesis_txid),
+            sorted(
+                [
+                    (genesis_txid, 2, height, 0, True),
+                    (send2_txid, 1, -1, 1000, False),
+                    (send2_txid, 2, -1, 2000, False),
+                ]
+            ),
+        )
+
+
+if __name__ == "__main__":
+    ChronikTokenIdTest().main()
@@ -0,0 +1,164 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's /token/:token_id endpoint.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+    SCRIPTSIG_OP_TRUE,
+)
+from test_framework.chronik.slp import slp_burn, slp_genesis, slp_mint, slp_send
+from test_framework.messages import COutPoint, CTransaction, CTxIn, CTxOut
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.txtools import pad_tx
+from test_framework.util import assert_equal
+
+DUST = 546
+FEE = 10000
+
+
+class ChronikTokenInfoTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient, pb
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+
+        coinblockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)[0]
+        cointxid = node.getblock(coinblockhash)["tx"][0]
+        self.generatetoaddress(node, 100, ADDRESS_ECREG_UNSPENDABLE)
+
+        def send_tx(inputs, op_return_script, num_dust_outputs):
+            tx = CTransaction()
+            tx.vin = [
+                CTxIn(COutPoint(int(txid, 16), out_idx), SCRIPTSIG_OP_TRUE)
+                for txid, out_idx, _ in inputs
+            ]
+            input_sum = sum(value for _, _, value in inputs)
+            change = input_sum - num_dust_outputs * DUST - FEE
+            tx.vout = (
+                [CTxOut(0, op_return_script)]
+                + [CTxOut(DUST, P2SH_OP_TRUE)] * num_dust_outputs
+                + [CTxOut(change, P2SH_OP_TRUE)]
+            )
+            change_idx = len(tx.vout) - 1
+            pad_tx(tx)
+            txid = node.sendrawtransaction(tx.serialize().hex())
+            return txid, (txid, change_idx, change)
+
+        assert_equal(
+            chronik.token("abc").err(400).msg,
+            "400: Not a token ID: abc",
+        )
+        assert_equal(
+            chronik.token("00" * 32).err(404).msg,
+            f"404: Token {'00' * 32} not found in the index",
+        )
+
+        doc_hash = bytes(range(32))
+        genesis_txid, change = send_tx(
+            [(cointxid, 0, 5000000000)],
+            slp_genesis(
+                token_ticker=b"SLPINFO",
+                token_name=b"Token Info Test",
+                url=b"https://example.com",
+                doc_hash=doc_hash,
+                decimals=4,
+                mint_baton_vout=2,
+                initial_mint_amount=5000,
+            ),
+            2,
+        )
+        token_id = bytes.fromhex(genesis_txid)[::-1]
+
+        def expected_info(block, circulating_supply):
+            return pb.TokenInfo(
+                token_id=token_id,
+                token_type=pb.TokenType(slp=pb.SLP_TOKEN_TYPE_FUNGIBLE),
+                genesis_info=pb.GenesisInfo(
+                    token_ticker=b"SLPINFO",
+                    token_name=b"Token Info Test",
+                    url=b"https://example.com",
+                    hash=doc_hash,
+                    decimals=4,
+                ),
+                block=block,
+                circulating_supply=circulating_supply,
+            )
+
+        def block_metadata(blockhash):
+            block = node.getblock(blockhash)
+            return pb.BlockMetadata(
+                hash=bytes.fromhex(blockhash)[::-1],
+                height=block["height"],
+                timestamp=block["time"],
+                is_final=False,
+            )
+
+        # GENESIS in the mempool: no block, supply is the initial mint amount
+        assert_equal(chronik.token(genesis_txid).ok(), expected_info(None, "5000"))
+
+        # GENESIS mined
+        genesis_blockhash = self.generatetoaddress(
+            node, 1, ADDRESS_ECREG_UNSPENDABLE
+        )[0]
+        genesis_block = block_metadata(genesis_blockhash)
+        assert_equal(
+            chronik.token(genesis_txid).ok(), expected_info(genesis_block, "5000")
+        )
+
+        # MINT 1000 and BURN 2000 tokens in the mempool
+        _, change = send_tx(
+            [(genesis_txid, 2, DUST), change],
+            slp_mint(genesis_txid, mint_baton_vout=2, mint_amount=1000),
+            2,
+        )
+        send_txid, change = send_tx(
+            [(genesis_txid, 1, DUST), change],
+            slp_send(genesis_txid, [3000, 2000]),
+            2,
+        )
+        send_tx(
+            [(send_txid, 2, DUST), change],
+            slp_burn(genesis_txid, 2000),
+            0,
+        )
+
+        # Supply only includes confirmed txs
+        assert_equal(
+            chronik.token(genesis_txid).ok(), expected_info(genesis_block, "5000")
+        )
+
+        # MINT and BURN mined
+        self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)
+        assert_equal(
+            chronik.token(genesis_txid).ok(), expected_info(genesis_block, "4000")
+        )
+
+        # Tokens of an invalid GENESIS are not found
+        invalid_genesis_txid, _ = send_tx(
+            [change],
+            slp_genesis(mint_baton_vout=1, initial_mint_amount=100),
+            1,
+        )
+        assert_equal(
+            chronik.token(invalid_genesis_txid).err(404).msg,
+            f"404: Token {invalid_genesis_txid} not found in the index",
+        )
+
+
+if __name__ == "__main__":
+    ChronikTokenInfoTest().main()
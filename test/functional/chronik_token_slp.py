@@ -0,0 +1,194 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test that Chronik returns the SLP tokens of txs, their inputs and outputs and of
+UTXOs, for valid and invalid SLP txs, both in the mempool and after mining.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+    SCRIPTSIG_OP_TRUE,
+)
+from test_framework.chronik.slp import slp_genesis, slp_script, slp_send
+from test_framework.messages import COutPoint, CTransaction, CTxIn, CTxOut
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.util import assert_equal
+
+DUST = 546
+FEE = 10000
+
+
+class ChronikTokenSlpTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient, pb
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+
+        coinblockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)[0]
+        cointxid = node.getblock(coinblockhash)["tx"][0]
+        self.generatetoaddress(node, 100, ADDRESS_ECREG_UNSPENDABLE)
+
+        def send_tx(inputs, op_return_script, num_dust_outputs):
+            tx = CTransaction()
+            tx.vin = [
+                CTxIn(COutPoint(int(txid, 16), out_idx), SCRIPTSIG_OP_TRUE)
+                for txid, out_idx, _ in inputs
+            ]
+            input_sum = sum(value for _, _, value in inputs)
+            change = input_sum - num_dust_outputs * DUST - FEE
+            tx.vout = (
+                [CTxOut(0, op_return_script)]
+                + [CTxOut(DUST, P2SH_OP_TRUE)] * num_dust_outputs
+                + [CTxOut(change, P2SH_OP_TRUE)]
+            )
+            txid = node.sendrawtransaction(tx.serialize().hex())
+            return txid, (txid, len(tx.vout) - 1, change)
+
+        genesis_txid, change = send_tx(
+            [(cointxid, 0, 5000000000)],
+            slp_genesis(
+                token_ticker=b"SLPTEST",
+                token_name=b"Test SLP Token",
+                url=b"https://example.com",
+                decimals=4,
+                mint_baton_vout=2,
+                initial_mint_amount=5000,
+            ),
+            2,
+        )
+        token_id = bytes.fromhex(genesis_txid)[::-1]
+
+        send_txid, change = send_tx(
+            [(genesis_txid, 1, DUST), change],
+            slp_send(genesis_txid, [3000, 2000]),
+            2,
+        )
+
+        # SEND of more tokens than the inputs have burns the spent tokens
+        invalid_send_txid, change = send_tx(
+            [(send_txid, 1, DUST), change],
+            slp_send(genesis_txid, [4000]),
+            1,
+        )
+
+        # OP_RETURN with an unknown tx type fails parsing and burns the tokens
+        failed_parsing_txid, change = send_tx(
+            [(send_txid, 2, DUST), change],
+            slp_script([b"SLP\0", b"\x01", b"FOO"]),
+            1,
+        )
+
+        token_type = pb.TokenType(slp=pb.SLP_TOKEN_TYPE_FUNGIBLE)
+
+        def token(entry_idx, amount, is_mint_baton=False):
+            return pb.Token(
+                token_id=token_id,
+                token_type=token_type,
+                entry_idx=entry_idx,
+                amount=amount,
+                is_mint_baton=is_mint_baton,
+            )
+
+        def token_entry(tx_type, actual_burn="0", failures=()):
+            return pb.TokenEntry(
+                token_id=token_id,
+                token_type=token_type,
+                tx_type=tx_type,
+                is_invalid=len(failures) > 0,
+                failures=failures,
+                intentional_burn="0",
+                actual_burn=actual_burn,
+            )
+
+        def tokens(msgs):
+            return [msg.token if msg.HasField("token") else None for msg in msgs]
+
+        expected_txs = {
+            genesis_txid: {
+                "token_entries": [token_entry(pb.TOKEN_TX_TYPE_GENESIS)],
+                "token_failed_parsings": [],
+                "inputs": [None],
+                "outputs": [None, token(0, 5000), token(0, 0, True), None],
+            },
+            send_txid: {
+                "token_entries": [token_entry(pb.TOKEN_TX_TYPE_SEND)],
+                "token_failed_parsings": [],
+                "inputs": [token(0, 5000), None],
+                "outputs": [None, token(0, 3000), token(0, 2000), None],
+            },
+            invalid_send_txid: {
+                "token_entries": [
+                    token_entry(
+                        pb.TOKEN_TX_TYPE_SEND,
+                        actual_burn="3000",
+                        failures=[
+                            "Insufficient input sum, required 4000 but got 3000"
+                        ],
+                    )
+                ],
+                "token_failed_parsings": [],
+                "inputs": [token(0, 3000), None],
+                "outputs": [None, None, None],
+            },
+            failed_parsing_txid: {
+                "token_entries": [
+                    token_entry(pb.TOKEN_TX_TYPE_NONE, actual_burn="2000")
+                ],
+                "token_failed_parsings": ["Invalid SLP: Unknown tx type: 464f4f"],
+                "inputs": [token(0, 2000), None],
+                "outputs": [None, None, None],
+            },
+        }
+
+        # UTXOs don't reference a token entry of a tx
+        expected_utxo_tokens = {
+            (genesis_txid, 2): token(-1, 0, True),
+            (invalid_send_txid, 1): None,
+            (failed_parsing_txid, 1): None,
+            change[:2]: None,
+        }
+
+        def check_tokens():
+            for txid, expected in expected_txs.items():
+                proto_tx = chronik.tx(txid).ok()
+                assert_equal(list(proto_tx.token_entries), expected["token_entries"])
+                assert_equal(
+                    list(proto_tx.token_failed_parsings),
+                    expected["token_failed_parsings"],
+                )
+                assert_equal(tokens(proto_tx.inputs), expected["inputs"])
+                assert_equal(tokens(proto_tx.outputs), expected["outputs"])
+
+            script_utxos = chronik.script("p2sh", P2SH_OP_TRUE[2:-1].hex()).utxos().ok()
+            utxo_tokens = {
+                (utxo.outpoint.txid[::-1].hex(), utxo.outpoint.out_idx): (
+                    utxo.token if utxo.HasField("token") else None
+                )
+                for utxo in script_utxos.utxos
+            }
+            assert_equal(utxo_tokens, expected_utxo_tokens)
+
+        # Txs are in the mempool
+        check_tokens()
+
+        # Txs are mined
+        self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)
+        assert_equal(node.getmempoolinfo()["size"], 0)
+        check_tokens()
+
+
+if __name__ == "__main__":
+    ChronikTokenSlpTest().main()
@@ -111,6 +111,8 @@ class ChronikTxTest(BitcoinTestFramework):
             time_first_seen=1333333337,
             size=len(tx.serialize()),
             is_coinbase=False,
+            fee=4000,
+            fee_rate=4000 * 1000 // len(tx.serialize()),
         )
 
         assert_equal(chronik.tx(txid).ok(), proto_tx)
@@ -169,6 +171,8 @@ class ChronikTxTest(BitcoinTestFramework):
             time_first_seen=1333333338,
             size=len(tx2.serialize()),
             is_coinbase=False,
+            fee=16000,
+            fee_rate=16000 * 1000 // len(tx2.serialize()),
         )
 
         assert_equal(chronik.tx(txid2).ok(), proto_tx2)
@@ -0,0 +1,136 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""
+Test Chronik's /tx/:txid/ancestors and /tx/:txid/descendants endpoints.
+"""
+
+from test_framework.address import (
+    ADDRESS_ECREG_P2SH_OP_TRUE,
+    ADDRESS_ECREG_UNSPENDABLE,
+    P2SH_OP_TRUE,
+    SCRIPTSIG_OP_TRUE,
+)
+from test_framework.messages import COutPoint, CTransaction, CTxIn, CTxOut
+from test_framework.test_framework import BitcoinTestFramework
+from test_framework.txtools import pad_tx
+from test_framework.util import assert_equal
+
+
+class ChronikTxGraphTest(BitcoinTestFramework):
+    def set_test_params(self):
+        self.setup_clean_chain = True
+        self.num_nodes = 1
+        self.extra_args = [["-chronik"]]
+        self.rpc_timeout = 240
+
+    def skip_test_if_missing_module(self):
+        self.skip_if_no_chronik()
+
+    def run_test(self):
+        from test_framework.chronik.client import ChronikClient
+
+        node = self.nodes[0]
+        chronik = ChronikClient("127.0.0.1", node.chronik_port)
+
+        coinblockhash = self.generatetoaddress(node, 1, ADDRESS_ECREG_P2SH_OP_TRUE)[0]
+        cointxid = node.getblock(coinblockhash)["tx"][0]
+        self.generatetoaddress(node, 100, ADDRESS_ECREG_UNSPENDABLE)
+
+        def send_tx(outpoints, values):
+            tx = CTransaction()
+            tx.vin = [
+                CTxIn(COutPoint(int(txid, 16), out_idx), SCRIPTSIG_OP_TRUE)
+                for txid, out_idx in outpoints
+            ]
+            tx.vout = [CTxOut(value, P2SH_OP_TRUE) for value in values]
+            pad_tx(tx)
+            txid = node.sendrawtransaction(tx.serialize().hex())
+            return txid, len(tx.serialize())
+
+        coinvalue = 5000000000
+        # tx1 spends the coinbase and is mined
+        txid1, size1 = send_tx(
+            [(cointxid, 0)], [coinvalue // 2, coinvalue // 2 - 10000]
+        )
+        self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)
+        # tx2 spends tx1, tx3 spends tx1 and tx2, both stay in the mempool
+        txid2, size2 = send_tx([(txid1, 0)], [coinvalue // 2 - 10000])
+        txid3, size3 = send_tx([(txid1, 1), (txid2, 0)], [coinvalue - 40000])
+
+        def graph_summary(graph):
+            return [
+                (
+                    tx.txid[::-1].hex(),
+                    tx.depth,
+                    sorted(txid[::-1].hex() for txid in tx.linked_txids),
+                    tx.block.height,
+                )
+                for tx in graph.txs
+            ]
+
+        # Ancestors of tx3: tx1 and tx2, then the coinbase
+        graph = chronik.tx_ancestors(txid3).ok()
+        assert_equal(graph.is_truncated, False)
+        assert_equal(graph_summary(graph)[0], (txid3, 0, sorted([txid1, txid2]), 0))
+        assert_equal(
+            sorted(graph_summary(graph)[1:3]),
+            sorted(
+                [
+                    (txid1, 1, [cointxid], 102),
+                    (txid2, 1, [txid1], 0),
+                ]
+            ),
+        )
+        assert_equal(graph_summary(graph)[3], (cointxid, 2, [], 1))
+        assert_equal(len(graph.txs), 4)
+        assert_equal(graph.txs[0].size, size3)
+        assert_equal(graph.txs[0].fee, 20000)
+        assert_equal(graph.txs[3].fee, 0)
+
+        # Depth limits the graph, but linked txids are still listed
+        graph = chronik.tx_ancestors(txid3, depth=0).ok()
+        assert_equal(graph_summary(graph), [(txid3, 0, sorted([txid1, txid2]), 0)])
+
+        # Descendants of the coinbase: tx1, then tx2 and tx3
+        graph = chronik.tx_descendants(cointxid).ok()
+        assert_equal(
+            graph_summary(graph),
+            [
+                (cointxid, 0, [txid1], 1),
+                (txid1, 1, sorted([txid2, txid3]), 102),
+            ]
+            + sorted(
+                [
+                    (txid2, 2, [txid3], 0),
+                    (txid3, 2, [], 0),
+                ]
+            ),
+        )
+        assert_equal(graph.txs[1].size, size1)
+        assert_equal(graph.txs[1].fee, 10000)
+
+        graph = chronik.tx_descendants(txid2).ok()
+        assert_equal(graph_summary(graph), [(txid2, 0, [txid3], 0), (txid3, 1, [], 0)])
+        assert_equal(graph.txs[0].size, size2)
+
+        # Mining tx2 and tx3 keeps the graph
+        self.generatetoaddress(node, 1, ADDRESS_ECREG_UNSPENDABLE)
+        graph = chronik.tx_descendants(txid2).ok()
+        assert_equal(
+            graph_summary(graph), [(txid2, 0, [txid3], 103), (txid3, 1, [], 103)]
+        )
+
+        assert_equal(
+            chronik.tx_ancestors("00" * 32).err(404).msg,
+            f'404: Transaction {"00"*32} not found in the index',
+        )
+        assert_equal(
+            chronik.tx_ancestors(txid3, depth=101).err(400).msg,
+            "400: Requested depth 101 is too big, maximum is 100",
+        )
+
+
+if __name__ == "__main__":
+    ChronikTxGraphTest().main()
@@ -191,6 +191,19 @@ class ChronikWsScriptTest(BitcoinTestFramework):
         # For ws1, this sends a REMOVED_FROM_MEMPOOL for tx3, and two CONFIRMED
         check_tx_msgs(ws1, pb.TX_REMOVED_FROM_MEMPOOL, [tx3.hash])
         check_tx_msgs(ws1, pb.TX_CONFIRMED, sorted([txid, tx3_conflict.hash]))
+        # Once the block is connected, it also sends a DOUBLE_SPENT for tx3
+        check_tx_msgs(ws1, pb.TX_DOUBLE_SPENT, [tx3.hash])
+
+        # tx3 can still be queried for a while, with the conflict
+        proto_tx3 = chronik.tx(tx3.hash).ok()
+        assert_equal(
+            proto_tx3.conflict,
+            pb.TxConflict(
+                is_double_spend=True,
+                conflicting_txid=bytes.fromhex(tx3_conflict.hash)[::-1],
+                block_height=height,
+            ),
+        )
 
         # For ws2, this only sends the CONFIRMED msgs
         check_tx_msgs(ws2, pb.TX_CONFIRMED, sorted([txid, txid2]))
@@ -45,36 +45,66 @@ class ChronikResponse:
 
 
 class ChronikScriptClient:
-    def __init__(
-        self, client: "ChronikClient", script_type: str, script_payload: str
-    ) -> None:
+    def __init__(self, client: "ChronikClient", path: str) -> None:
         self.client = client
-        self.script_type = script_type
-        self.script_payload = script_payload
+        self.path = path
 
-    def confirmed_txs(self, page=None, page_size=None):
-        query = _page_query_params(page, page_size)
+    def confirmed_txs(self, page=None, page_size=None, *, before=None, after=None):
+        query = _page_query_params(page, page_size, before=before, after=after)
         return self.client._request_get(
-            f"/script/{self.script_type}/{self.script_payload}/confirmed-txs{query}",
+            f"{self.path}/confirmed-txs{query}",
             pb.TxHistoryPage,
         )
 
-    def history(self, page=None, page_size=None):
-        query = _page_query_params(page, page_size)
+    def history(self, page=None, page_size=None, *, before=None, after=None):
+        query = _page_query_params(page, page_size, before=before, after=after)
         return self.client._request_get(
-            f"/script/{self.script_type}/{self.script_payload}/history{query}",
+            f"{self.path}/history{query}",
             pb.TxHistoryPage,
         )
 
     def unconfirmed_txs(self):
         return self.client._request_get(
-            f"/script/{self.script_type}/{self.script_payload}/unconfirmed-txs",
+            f"{self.path}/unconfirmed-txs",
             pb.TxHistoryPage,
         )
 
     def utxos(self):
+        return self.client._request_get(f"{self.path}/utxos", pb.ScriptUtxos)
+
+    def balance(self):
+        return self.client._request_get(
+            f"{self.path}/balance",
+            pb.ScriptBalance,
+        )
+
+
+class ChronikTokenIdClient:
+    def __init__(self, client: "ChronikClient", token_id: str) -> None:
+        self.client = client
+        self.path = f"/token-id/{token_id}"
+
+    def history(self, page=None, page_size=None, *, before=None, after=None):
+        query = _page_query_params(page, page_size, before=before, after=after)
         return self.client._request_get(
-            f"/script/{self.script_type}/{self.script_payload}/utxos", pb.ScriptUtxos
+            f"{self.path}/history{query}",
+            pb.TxHistoryPage,
+        )
+
+    def utxos(self):
+        return self.client._request_get(f"{self.path}/utxos", pb.TokenIdUtxos)
+
+
+class ChronikLokadIdClient:
+    def __init__(self, client: "ChronikClient", lokad_id: str) -> None:
+        self.client = client
+        self.path = f"/lokad-id/{lokad_id}"
+
+    def history(self, page=None, page_size=None, *, before=None, after=None):
+        query = _page_query_params(page, page_size, before=before, after=after)
+        return self.client._request_get(
+            f"{self.path}/history{query}",
+            pb.TxHistoryPage,
         )
 
 
@@ -95,6 +125,10 @@ class ChronikWs:
         sub = pb.WsSub(is_unsub=is_unsub, blocks=pb.WsSubBlocks())
         self.send_bytes(sub.SerializeToString())
 
+    def sub_to_headers(self, *, is_unsub=False) -> None:
+        sub = pb.WsSub(is_unsub=is_unsub, headers=pb.WsSubHeaders())
+        self.send_bytes(sub.SerializeToString())
+
     def sub_script(self, script_type: str, payload: bytes, *, is_unsub=False) -> None:
         sub = pb.WsSub(
             is_unsub=is_unsub,
@@ -102,6 +136,13 @@ class ChronikWs:
         )
         self.send_bytes(sub.SerializeToString())
 
+    def sub_lokad_id(self, lokad_id: bytes, *, is_unsub=False) -> None:
+        sub = pb.WsSub(
+            is_unsub=is_unsub,
+            lokad_id=pb.WsSubLokadId(lokad_id=lokad_id),
+        )
+        self.send_bytes(sub.SerializeToString())
+
 
 class ChronikClient:
     CONTENT_TYPE = "application/x-protobuf"
@@ -112,11 +153,23 @@ class ChronikClient:
         self.timeout = timeout
 
     def _request_get(self, path: str, pb_type):
+        return self._request("GET", path, None, pb_type)
+
+    def _request_post(self, path: str, pb_msg, pb_type):
+        return self._request(
+            "POST",
+            path,
+            pb_msg.SerializeToString(),
+            pb_type,
+            headers={"Content-Type": self.CONTENT_TYPE},
+        )
+
+    def _request(self, method: str, path: str, body, pb_type, headers=None):
         kwargs = {}
         if self.timeout is not None:
             kwargs["timeout"] = self.timeout
         client = http.client.HTTPConnection(self.host, self.port, **kwargs)
-        client.request("GET", path)
+        client.request(method, path, body, headers or {})
         response = client.getresponse()
         content_type = response.getheader("Content-Type")
         body = response.read()
@@ -153,14 +206,76 @@ class ChronikClient:
     def blocks(self, start_height: int, end_height: int) -> ChronikResponse:
         return self._request_get(f"/blocks/{start_height}/{end_height}", pb.Blocks)
 
+    def block_headers(self, start_height: int, end_height: int) -> ChronikResponse:
+        return self._request_get(
+            f"/block-headers/{start_height}/{end_height}", pb.BlockHeaders
+        )
+
     def tx(self, txid: str) -> ChronikResponse:
         return self._request_get(f"/tx/{txid}", pb.Tx)
 
     def raw_tx(self, txid: str) -> bytes:
         return self._request_get(f"/raw-tx/{txid}", pb.RawTx)
 
+    def outpoint(self, txid: str, out_idx: Union[str, int]) -> ChronikResponse:
+        return self._request_get(f"/outpoint/{txid}/{out_idx}", pb.OutpointInfo)
+
+    def tx_ancestors(self, txid: str, depth=None) -> ChronikResponse:
+        query = f"?depth={depth}" if depth is not None else ""
+        return self._request_get(f"/tx/{txid}/ancestors{query}", pb.TxGraph)
+
+    def tx_descendants(self, txid: str, depth=None) -> ChronikResponse:
+        query = f"?depth={depth}" if depth is not None else ""
+        return self._request_get(f"/tx/{txid}/descendants{query}", pb.TxGraph)
+
+    def merkle_proof(self, txid: str) -> ChronikResponse:
+        return self._request_get(f"/tx/{txid}/merkle-proof", pb.TxMerkleProof)
+
     def script(self, script_type: str, script_payload: str) -> ChronikScriptClient:
-        return ChronikScriptClient(self, script_type, script_payload)
+        return ChronikScriptClient(self, f"/script/{script_type}/{script_payload}")
+
+    def address(self, address: str) -> ChronikScriptClient:
+        return ChronikScriptClient(self, f"/address/{address}")
+
+    def token_id(self, token_id: str) -> ChronikTokenIdClient:
+        return ChronikTokenIdClient(self, token_id)
+
+    def lokad_id(self, lokad_id: str) -> ChronikLokadIdClient:
+        return ChronikLokadIdClient(self, lokad_id)
+
+    def token(self, token_id: str) -> ChronikResponse:
+        return self._request_get(f"/token/{token_id}", pb.TokenInfo)
+
+    def scripts_utxos(self, scripts) -> ChronikResponse:
+        request = pb.ScriptsUtxosRequest(
+            scripts=[
+                pb.ScriptRequest(script_type=script_type, payload=payload)
+                for script_type, payload in scripts
+            ]
+        )
+        return self._request_post("/scripts/utxos", request, pb.ScriptsUtxos)
+
+    def scripts_history(self, scripts, page=0, page_size=0) -> ChronikResponse:
+        request = pb.ScriptsHistoryRequest(
+            scripts=[
+                pb.ScriptRequest(script_type=script_type, payload=payload)
+                for script_type, payload in scripts
+            ],
+            page=page,
+            page_size=page_size,
+        )
+        return self._request_post("/scripts/history", request, pb.ScriptsHistory)
+
+    def mempool(self, page=None, page_size=None) -> ChronikResponse:
+        query = _page_query_params(page, page_size)
+        return self._request_get(f"/mempool{query}", pb.MempoolTxs)
+
+    def mempool_info(self) -> ChronikResponse:
+        return self._request_get("/mempool/info", pb.MempoolInfo)
+
+    def fee_estimate(self, blocks=None) -> ChronikResponse:
+        query = f"?blocks={blocks}" if blocks is not None else ""
+        return self._request_get(f"/fee-estimate{query}", pb.FeeEstimate)
 
     def ws(self, *, timeout=None) -> ChronikWs:
         ws = websocket.WebSocket()
@@ -168,12 +283,17 @@ class ChronikClient:
         return ChronikWs(ws)
 
 
-def _page_query_params(page=None, page_size=None) -> str:
-    if page is not None and page_size is not None:
-        return f"?page={page}&page_size={page_size}"
-    elif page is not None:
-        return f"?page={page}"
-    elif page_size is not None:
-        return f"?page_size={page_size}"
-    else:
+def _page_query_params(page=None, page_size=None, *, before=None, after=None) -> str:
+    params = [
+        (name, value)
+        for name, value in [
+            ("page", page),
+            ("page_size", page_size),
+            ("before", before),
+            ("after", after),
+        ]
+        if value is not None
+    ]
+    if not params:
         return ""
+    return "?" + "&".join(f"{name}={value}" for name, value in params)
@@ -0,0 +1,86 @@
+#!/usr/bin/env python3
+# Copyright (c) 2023 The Bitcoin developers
+# Distributed under the MIT software license, see the accompanying
+# file COPYING or http://www.opensource.org/licenses/mit-license.php.
+"""Build SLP OP_RETURN scripts for testing Chronik's token indexing."""
+
+from test_framework.script import OP_RETURN, CScript
+
+SLP_LOKAD_ID = b"SLP\0"
+SLP_FUNGIBLE = 1
+
+
+def slp_script(pushes) -> CScript:
+    # SLP doesn't allow single-byte push opcodes, e.g. OP_0 for empty pushes
+    script = bytes([OP_RETURN])
+    for push in pushes:
+        if len(push) == 0:
+            script += b"\x4c\x00"
+        else:
+            script += bytes([len(push)]) + push
+    return CScript(script)
+
+
+def slp_amount(amount: int) -> bytes:
+    return amount.to_bytes(8, "big")
+
+
+def slp_genesis(
+    *,
+    token_ticker=b"",
+    token_name=b"",
+    url=b"",
+    doc_hash=b"",
+    decimals=0,
+    mint_baton_vout=None,
+    initial_mint_amount=0,
+    token_type=SLP_FUNGIBLE,
+) -> CScript:
+    return slp_script(
+        [
+            SLP_LOKAD_ID,
+            bytes([token_type]),
+            b"GENESIS",
+            token_ticker,
+            token_name,
+            url,
+            doc_hash,
+            bytes([decimals]),
+            bytes([mint_baton_vout]) if mint_baton_vout is not None else b"",
+            slp_amount(initial_mint_amount),
+        ]
+    )
+
+
+def slp_mint(
+    token_id: str, mint_baton_vout, mint_amount: int, token_type=SLP_FUNGIBLE
+) -> CScript:
+    return slp_script(
+        [
+            SLP_LOKAD_ID,
+            bytes([token_type]),
+            b"MINT",
+            bytes.fromhex(token_id),
+            bytes([mint_baton_vout]) if mint_baton_vout is not None else b"",
+            slp_amount(mint_amount),
+        ]
+    )
+
+
+def slp_send(token_id: str, amounts, token_type=SLP_FUNGIBLE) -> CScript:
+    return slp_script(
+        [SLP_LOKAD_ID, bytes([token_type]), b"SEND", bytes.fromhex(token_id)]
+        + [slp_amount(amount) for amount in amounts]
+    )
+
+
+def slp_burn(token_id: str, burn_amount: int, token_type=SLP_FUNGIBLE) -> CScript:
+    return slp_script(
+        [
+            SLP_LOKAD_ID,
+            bytes([token_type]),
+            b"BURN",
+            bytes.fromhex(token_id),
+            slp_amount(burn_amount),
+        ]
+    )